@@ -28,7 +28,7 @@ fn main() {
     // Run the async code
     runtime.block_on(async {
         // Create a new controller
-        let mut controller: Controller<'_, 16, 64> = Controller::new(
+        let controller: Controller<'_, 16, 64> = Controller::new(
             &args.interface,
             Duration::from_millis(20),
             &PDU_STORAGE,
@@ -38,23 +38,26 @@ fn main() {
         .expect("Failed to initialize controller");
 
         // Iterate over the connected devices
-        for (index, sub_device) in controller.device_iter().enumerate() {
+        for index in 0..controller.device_count() {
+            let info = controller
+                .device_info(index)
+                .await
+                .expect("Failed to read device information");
+
             // Display the device number and address
-            println!("Device number: {index}");
-            println!("Alias address: {}", sub_device.alias_address());
-            println!("Configured address: {}", sub_device.configured_address());
+            println!("Device number: {}", info.number);
+            println!("Alias address: {}", info.alias_address);
+            println!("Configured address: {}", info.configured_address);
 
-            // Try to retrieve the full name of the device
-            if let Ok(Some(description)) = sub_device.description().await {
-                // On success, display the full name
+            // Display the full name if available, the partial name otherwise
+            if let Some(description) = info.description {
                 println!("Device type: {description}",);
             } else {
-                // Otherwise, display the partial name
-                println!("Model name: {}", sub_device.name());
+                println!("Model name: {}", info.name);
             }
 
             // Display the identity of the device (vendor, product, revision, serial numbers)
-            println!("Device identity: ({})", sub_device.identity());
+            println!("Device identity: ({})", info.identity);
 
             // Use an empty line between devices
             println!();
@@ -4,13 +4,10 @@
 use clap::Parser;
 use ethercrab::PduStorage;
 use festo_robotcontroller::{
-    controller::Controller,
-    device::servo::{MovementMode, Servo},
-};
-use std::{
-    sync::Arc,
-    time::{Duration, Instant},
+    controller::{Controller, DeviceQuery},
+    device::servo::{group::ServoGroup, homing::HomingPolicy, motion::MoveOptions, Servo},
 };
+use std::{sync::Arc, time::Duration};
 
 /// This will store the PDU messages until sent
 static PDU_STORAGE: PduStorage<16, 1100> = PduStorage::new();
@@ -20,6 +17,10 @@ static PDU_STORAGE: PduStorage<16, 1100> = PduStorage::new();
 pub struct Args {
     /// The interface to communicate over
     interface: String,
+
+    /// Select the motors by alias address instead of their position on the bus
+    #[arg(long)]
+    by_alias: bool,
 }
 
 fn main() {
@@ -44,62 +45,77 @@ fn main() {
         .await
         .expect("Failed to initialize controller");
 
-        // Find the first device matching the requested properties
+        // Cycle in the background, so the servos can be operated at the same time
         let controller = Arc::new(controller);
+        controller.start_cycling();
 
-        for (number, name) in [
-            (0, "horizontal_rotation"),
-            (1, "bender"),
-            (2, "vertical_rotation"),
-            (3, "object_rotation"),
+        // Attach to the motors, they're reset and enabled together below
+        let mut servos = Vec::new();
+        for (number, alias, name) in [
+            (0, 1, "horizontal_rotation"),
+            (1, 2, "bender"),
+            (2, 3, "vertical_rotation"),
+            (3, 4, "object_rotation"),
         ] {
-            // Treat the found device as a servo
-            let mut servo = Servo::new(&controller, number)
-                .await
-                .unwrap_or_else(|error| panic!("Failed to connect to {name} motor: {error:?}"));
-            // Move the motor to the home position
-            eprintln!("Homing");
-            servo.home(true).await.unwrap();
-
-            // Move the motor in the positive direction
-            eprintln!("Moving in positive direction");
-            servo
-                .move_position_velocity_acceleration(190_000, 100, 10, 10, MovementMode::Absolute)
-                .await
-                .unwrap();
-
-            // Move the motor in the negative direction
-            eprintln!("Moving in negative direction");
-            servo
-                .move_position(-1_000, MovementMode::Absolute)
-                .await
-                .unwrap();
+            let number = if args.by_alias {
+                controller
+                    .find_device(DeviceQuery::Alias(alias))
+                    .unwrap_or_else(|error| panic!("Failed to find {name} motor: {error:?}"))
+            } else {
+                number
+            };
+            servos
+                .push(Servo::attach(&controller, number).unwrap_or_else(|error| {
+                    panic!("Failed to connect to {name} motor: {error:?}")
+                }));
+        }
+        let mut group = ServoGroup::new(servos);
 
-            // Move the motor back to 0
-            eprintln!("Moving to 0 position");
-            servo
-                .move_position(0, MovementMode::Absolute)
-                .await
-                .unwrap();
+        // Enable all motors
+        eprintln!("Enabling");
+        for result in group.enable_all().await {
+            result.unwrap();
+        }
 
-            // Jog in positive direction
-            eprintln!("Jogging in positive direction");
-            servo.jog_positive().await.unwrap();
-            let mut jog_start = Instant::now();
-            while jog_start.elapsed().as_secs() < 4 {
-                controller.cycle().await;
-            }
+        // Move all motors to the home position
+        eprintln!("Homing");
+        for result in group.home_all(HomingPolicy::Always).await {
+            result.unwrap();
+        }
 
-            // Jog back
-            jog_start = Instant::now();
-            eprintln!("Jogging in negative direction");
-            servo.jog_negative().await.unwrap();
-            while jog_start.elapsed().as_secs() < 4 {
-                controller.cycle().await;
+        // Move all motors in the positive direction, then in the negative direction and back
+        // to 0
+        let positive = MoveOptions::new()
+            .with_velocity(100)
+            .with_acceleration(10)
+            .with_deceleration(10);
+        for (description, target, options) in [
+            ("Moving in positive direction", 190_000, positive),
+            ("Moving in negative direction", -1_000, MoveOptions::new()),
+            ("Moving to 0 position", 0, MoveOptions::new()),
+        ] {
+            eprintln!("{description}");
+            let moves: Vec<_> = (0..group.len())
+                .map(|index| (index, target, options.clone()))
+                .collect();
+            for result in group.move_all(&moves).await {
+                result.unwrap();
             }
-            servo.jog_stop().await;
+        }
 
-            servo.disable().await.unwrap();
+        // Disable all motors
+        for (index, result) in group
+            .disable_all_with_timeout(Duration::from_secs(5))
+            .await
+            .into_iter()
+            .enumerate()
+        {
+            let report = result.unwrap();
+            eprintln!(
+                "Motor {index} disabled in {:?}, final state {:?}",
+                report.elapsed, report.state
+            );
         }
+        controller.stop_cycling();
     });
 }
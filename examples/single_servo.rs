@@ -5,12 +5,9 @@ use clap::Parser;
 use ethercrab::PduStorage;
 use festo_robotcontroller::{
     controller::Controller,
-    device::servo::{MovementMode, Servo},
-};
-use std::{
-    sync::Arc,
-    time::{Duration, Instant},
+    device::servo::{homing::HomingPolicy, JoggingDirection, MovementMode, Servo},
 };
+use std::{sync::Arc, time::Duration};
 
 /// This will store the PDU messages until sent
 static PDU_STORAGE: PduStorage<128, 1100> = PduStorage::new();
@@ -125,7 +122,7 @@ fn main() {
 
         // Move the motor to the home position
         eprintln!("Homing");
-        servo.home(true).await.unwrap();
+        servo.home(HomingPolicy::Always).await.unwrap();
 
         // Move the motor in the positive direction
         eprintln!("Moving in positive direction");
@@ -150,21 +147,25 @@ fn main() {
 
         // Jog in positive direction
         eprintln!("Jogging in positive direction");
-        servo.jog_positive().await.unwrap();
-        let mut jog_start = Instant::now();
-        while jog_start.elapsed().as_secs() < 4 {
-            controller.cycle().await;
-        }
+        servo
+            .jog_for(JoggingDirection::Positive, 100, Duration::from_secs(4))
+            .await
+            .unwrap();
 
         // Jog back
-        jog_start = Instant::now();
         eprintln!("Jogging in negative direction");
-        servo.jog_negative().await.unwrap();
-        while jog_start.elapsed().as_secs() < 4 {
-            controller.cycle().await;
-        }
-        servo.jog_stop().await;
+        servo
+            .jog_for(JoggingDirection::Negative, 100, Duration::from_secs(4))
+            .await
+            .unwrap();
 
-        servo.disable().await.unwrap();
+        let report = servo
+            .disable_with_timeout(Duration::from_secs(5))
+            .await
+            .unwrap();
+        eprintln!(
+            "Disabled in {:?}, final state {:?}",
+            report.elapsed, report.state
+        );
     });
 }
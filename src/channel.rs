@@ -0,0 +1,140 @@
+//! This module contains a small bounded channel used to hand events from the cycling task to
+//! async receivers. When the channel is full the oldest event is dropped, so a slow receiver
+//! can never block the cycling task.
+
+use core::{future::poll_fn, task::Poll, task::Waker};
+use std::{
+    collections::VecDeque,
+    sync::{Arc, Mutex, PoisonError},
+};
+
+/// The state shared between the sender and receiver
+struct Shared<T> {
+    /// The events that haven't been received yet
+    queue: VecDeque<T>,
+
+    /// The maximum number of queued events
+    capacity: usize,
+
+    /// The number of events dropped because the channel was full
+    dropped: u64,
+
+    /// The waker of the receiver waiting for an event
+    waker: Option<Waker>,
+
+    /// Whether the sender has been dropped
+    closed: bool,
+
+    /// Whether the receiver has been dropped
+    disconnected: bool,
+}
+
+/// Sends events to the receiver without ever blocking
+pub struct Sender<T> {
+    /// The state shared with the receiver
+    shared: Arc<Mutex<Shared<T>>>,
+}
+
+/// Receives events sent by the sender
+pub struct Receiver<T> {
+    /// The state shared with the sender
+    shared: Arc<Mutex<Shared<T>>>,
+}
+
+/// Creates a channel holding at most `capacity` events, with a minimum of 1
+pub fn bounded<T>(capacity: usize) -> (Sender<T>, Receiver<T>) {
+    let capacity = capacity.max(1);
+    let shared = Arc::new(Mutex::new(Shared {
+        queue: VecDeque::with_capacity(capacity),
+        capacity,
+        dropped: 0,
+        waker: None,
+        closed: false,
+        disconnected: false,
+    }));
+    (
+        Sender {
+            shared: Arc::clone(&shared),
+        },
+        Receiver { shared },
+    )
+}
+
+impl<T> Sender<T> {
+    /// Queues an event, dropping the oldest event if the channel is full
+    pub fn send(&self, event: T) {
+        let mut shared = self.shared.lock().unwrap_or_else(PoisonError::into_inner);
+        if shared.queue.len() >= shared.capacity {
+            shared.queue.pop_front();
+            shared.dropped += 1;
+        }
+        shared.queue.push_back(event);
+        if let Some(waker) = shared.waker.take() {
+            waker.wake();
+        }
+    }
+
+    /// Returns whether the receiver has been dropped, so events are no longer received
+    pub fn is_disconnected(&self) -> bool {
+        self.shared
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .disconnected
+    }
+}
+
+impl<T> Drop for Sender<T> {
+    fn drop(&mut self) {
+        let mut shared = self.shared.lock().unwrap_or_else(PoisonError::into_inner);
+        shared.closed = true;
+        if let Some(waker) = shared.waker.take() {
+            waker.wake();
+        }
+    }
+}
+
+impl<T> Receiver<T> {
+    /// Waits for the next event.
+    ///
+    /// # Returns
+    /// The next event or `None` if the sender has been dropped and all events have been received
+    pub async fn recv(&self) -> Option<T> {
+        poll_fn(|context| {
+            let mut shared = self.shared.lock().unwrap_or_else(PoisonError::into_inner);
+            match shared.queue.pop_front() {
+                Some(event) => Poll::Ready(Some(event)),
+                None if shared.closed => Poll::Ready(None),
+                None => {
+                    shared.waker = Some(context.waker().clone());
+                    Poll::Pending
+                }
+            }
+        })
+        .await
+    }
+
+    /// Returns the next event if one is queued, without waiting
+    pub fn try_recv(&self) -> Option<T> {
+        self.shared
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .queue
+            .pop_front()
+    }
+
+    /// Returns the number of events dropped because the channel was full
+    pub fn dropped(&self) -> u64 {
+        self.shared
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .dropped
+    }
+}
+
+impl<T> Drop for Receiver<T> {
+    fn drop(&mut self) {
+        let mut shared = self.shared.lock().unwrap_or_else(PoisonError::into_inner);
+        shared.disconnected = true;
+        shared.queue.clear();
+    }
+}
@@ -5,10 +5,36 @@ use core::{
     fmt::{self, Debug, Formatter},
     time::Duration,
 };
-use std::{io, time::Instant};
+use std::{
+    io,
+    sync::{
+        atomic::{AtomicBool, AtomicU32, AtomicU64, AtomicU8, Ordering},
+        Arc, Mutex, PoisonError,
+    },
+    time::Instant,
+};
 
+use crate::{
+    channel::{self, Sender},
+    cycle::CycleGate,
+    device::{
+        self,
+        fault::{notify_fault_handlers, FaultEvent, FaultHandler},
+        festo::{self, VendorObjects},
+        objects::{self, Object},
+        servo::{
+            events::{status_events, MotionEvent, MotionEventKind},
+            fault_callback::{call_fault_callbacks, FaultCallback},
+            fault_details::FaultDetails,
+            status::ServoStatus,
+            telemetry::Sampler,
+        },
+        DeviceError, DeviceInfo, DriveType, StatusWord, StatusWordBit,
+    },
+    pdo::{self, PdoValue},
+};
 use ethercrab::{
-    error::Error as EthercrabError,
+    error::{Error as EthercrabError, MailboxError},
     std::{ethercat_now, tx_rx_task},
     MainDevice, MainDeviceConfig, PduStorage, SubDeviceGroup,
 };
@@ -61,6 +87,150 @@ impl Debug for ControllerError {
     }
 }
 
+/// An address identifying a device independent of its position on the bus
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum DeviceQuery {
+    /// The alias address stored in the EEPROM of the device
+    Alias(u16),
+
+    /// The address configured by the main device
+    ConfiguredAddress(u16),
+}
+
+impl DeviceQuery {
+    /// Checks whether the addresses of a device match the query
+    const fn matches(self, alias_address: u16, configured_address: u16) -> bool {
+        match self {
+            Self::Alias(alias) => alias == alias_address,
+            Self::ConfiguredAddress(address) => address == configured_address,
+        }
+    }
+}
+
+/// An error returned while searching a device by address
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub enum FindDeviceError {
+    /// No device matches the query
+    NotFound(DeviceQuery),
+
+    /// Multiple devices match the query
+    Ambiguous {
+        /// The query the devices matched
+        query: DeviceQuery,
+
+        /// The number of matching devices
+        count: usize,
+    },
+}
+
+impl Debug for FindDeviceError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::NotFound(query) => write!(f, "No device matches {query:?}"),
+            Self::Ambiguous { query, count } => {
+                write!(f, "{count} devices match {query:?}, expected one")
+            }
+        }
+    }
+}
+
+/// An error returned while identifying a device
+pub enum IdentifyError {
+    /// The drive doesn't support blinking its LED
+    Unsupported(usize),
+
+    /// Communication with the drive failed
+    Ethercat(usize, EthercrabError),
+}
+
+impl Debug for IdentifyError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Unsupported(device) => {
+                write!(f, "Device {device} doesn't support identification")
+            }
+            Self::Ethercat(device, error) => {
+                write!(f, "Failed to identify device {device}: {error:?}")
+            }
+        }
+    }
+}
+
+/// The state shared between all users of a device
+pub(crate) struct DeviceState {
+    /// Whether a handle to the device exists
+    claimed: AtomicBool,
+
+    /// Whether the device has been emergency stopped
+    emergency_stop: AtomicBool,
+
+    /// Whether the running positioning move of the device has to be aborted
+    abort_requested: AtomicBool,
+
+    /// Whether fault handlers have been registered, so the device has to be scanned
+    fault_watched: AtomicBool,
+
+    /// The error status found during the previous scan
+    last_error: AtomicU8,
+
+    /// The handlers called when the device reports a new fault or warning
+    fault_handlers: Mutex<Vec<FaultHandler>>,
+
+    /// Whether telemetry streams have been created, so the device has to be sampled
+    sampled: AtomicBool,
+
+    /// The samplers of the telemetry streams of the device
+    samplers: Mutex<Vec<Sampler>>,
+
+    /// Whether motion event streams have been created, so the device has to be scanned
+    evented: AtomicBool,
+
+    /// The status word found during the previous motion event scan, `NO_STATUS` before the
+    /// first scan
+    last_event_status: AtomicU32,
+
+    /// The senders of the motion event streams of the device
+    event_senders: Mutex<Vec<Sender<MotionEvent>>>,
+
+    /// Whether fault callbacks have been registered, so the device has to be scanned
+    fault_called: AtomicBool,
+
+    /// Whether the fault bit was set during the previous fault callback scan
+    callback_fault: AtomicBool,
+
+    /// The fault callbacks of the device with their identifiers
+    fault_callbacks: Mutex<Vec<(u64, FaultCallback)>>,
+}
+
+/// Marks that a device hasn't been scanned for motion events yet
+const NO_STATUS: u32 = u32::MAX;
+
+/// The number of faults waiting for their error code to be read, older faults are dropped
+const FAULT_REPORT_CAPACITY: usize = 64;
+
+impl DeviceState {
+    /// Creates the state of a device that hasn't been used yet
+    const fn new() -> Self {
+        Self {
+            claimed: AtomicBool::new(false),
+            emergency_stop: AtomicBool::new(false),
+            abort_requested: AtomicBool::new(false),
+            fault_watched: AtomicBool::new(false),
+            last_error: AtomicU8::new(DeviceError::Ok as u8),
+            fault_handlers: Mutex::new(Vec::new()),
+            sampled: AtomicBool::new(false),
+            samplers: Mutex::new(Vec::new()),
+            evented: AtomicBool::new(false),
+            last_event_status: AtomicU32::new(NO_STATUS),
+            event_senders: Mutex::new(Vec::new()),
+            fault_called: AtomicBool::new(false),
+            callback_fault: AtomicBool::new(false),
+            fault_callbacks: Mutex::new(Vec::new()),
+        }
+    }
+}
+
 /// The controller struct, only 1 is allowed to exist at any time.
 /// Recommended constants:
 /// - `MAX_DEVICES`: 16
@@ -77,6 +247,20 @@ pub struct Controller<'main_device, const MAX_DEVICES: usize, const PDI_LENGTH:
 
     /// The group of connected devices
     group: SubDeviceGroup<MAX_DEVICES, PDI_LENGTH, ethercrab::subdevice_group::Op>,
+
+    /// The shared state of each device
+    devices: [DeviceState; MAX_DEVICES],
+
+    /// The number of completed cycles, the tasks waiting for the next cycle and the state of the
+    /// background cycling task
+    cycles: CycleGate,
+
+    /// The identifier of the next registered fault callback
+    next_callback_id: AtomicU64,
+
+    /// Queues the faults for the fault reporting task while cycling in the background, which
+    /// reads their error codes and calls the fault callbacks
+    fault_reports: Mutex<Option<Sender<FaultDetails>>>,
 }
 
 impl<const MAX_DEVICES: usize, const PDI_LENGTH: usize> Controller<'_, MAX_DEVICES, PDI_LENGTH> {
@@ -91,6 +275,12 @@ impl<const MAX_DEVICES: usize, const PDI_LENGTH: usize> Controller<'_, MAX_DEVIC
         self.verbose
     }
 
+    /// Returns the number of completed cycles.
+    /// Values read from the inputs of the devices only change when this number changes.
+    pub fn cycle_count(&self) -> u64 {
+        self.cycles.count()
+    }
+
     /// Returns a reference to the main device, used for communicating with slaves
     pub const fn main_device(&self) -> &MainDevice {
         &self.main_device
@@ -117,6 +307,466 @@ impl<const MAX_DEVICES: usize, const PDI_LENGTH: usize> Controller<'_, MAX_DEVIC
         self.group.iter(&self.main_device)
     }
 
+    /// Stops the requested device as fast as the drive allows.
+    /// Can be called from any task, every operation waiting on the device will be aborted.
+    /// The device can only move again after clearing the emergency stop and recovering it.
+    pub fn emergency_stop(&self, device_number: usize) {
+        if let Some(state) = self.devices.get(device_number) {
+            state.emergency_stop.store(true, Ordering::SeqCst);
+        }
+        log::warn!("Emergency stop of device {device_number}");
+
+        // Write the quick stop pattern into the control word
+        if let Ok(mut sub_device) = self.group.subdevice(&self.main_device, device_number) {
+            let outputs = &mut sub_device.outputs_raw_mut()[pdo::output::CONTROL_WORD..];
+            let control_word = u16::read(outputs);
+            device::emergency_control_word(control_word).write(outputs);
+        }
+    }
+
+    /// Returns whether the requested device has been emergency stopped
+    pub fn emergency_stopped(&self, device_number: usize) -> bool {
+        self.devices
+            .get(device_number)
+            .is_some_and(|state| state.emergency_stop.load(Ordering::SeqCst))
+    }
+
+    /// Clears the emergency stop of the requested device.
+    /// The device stays in quick stop until it's recovered.
+    pub fn clear_emergency(&self, device_number: usize) {
+        if let Some(state) = self.devices.get(device_number) {
+            state.emergency_stop.store(false, Ordering::SeqCst);
+        }
+    }
+
+    /// Requests the running positioning move of the requested device to be aborted.
+    /// Can be called from any task, the task waiting on the move halts the servo and returns an
+    /// error once the servo stands still.
+    pub fn abort_move(&self, device_number: usize) {
+        if let Some(state) = self.devices.get(device_number) {
+            state.abort_requested.store(true, Ordering::SeqCst);
+        }
+        if self.verbose {
+            log::info!("Requested abort of the movement of device {device_number}");
+        }
+    }
+
+    /// Takes the abort request of the requested device, so it's only handled once
+    ///
+    /// # Returns
+    /// Whether an abort was requested
+    pub(crate) fn take_abort(&self, device_number: usize) -> bool {
+        self.devices
+            .get(device_number)
+            .is_some_and(|state| state.abort_requested.swap(false, Ordering::SeqCst))
+    }
+
+    /// Claims the requested device, so only one handle to it can exist.
+    ///
+    /// # Returns
+    /// Whether the device was claimed, false if it's already claimed or doesn't exist
+    pub(crate) fn claim(&self, device_number: usize) -> bool {
+        self.devices.get(device_number).is_some_and(|state| {
+            state
+                .claimed
+                .compare_exchange(false, true, Ordering::AcqRel, Ordering::Acquire)
+                .is_ok()
+        })
+    }
+
+    /// Releases the claim on the requested device
+    pub(crate) fn release(&self, device_number: usize) {
+        if let Some(state) = self.devices.get(device_number) {
+            state.claimed.store(false, Ordering::Release);
+        }
+    }
+
+    /// Registers a handler called once every time the requested device reports a new fault
+    pub(crate) fn register_fault_handler(&self, device_number: usize, handler: FaultHandler) {
+        if let Some(state) = self.devices.get(device_number) {
+            state
+                .fault_handlers
+                .lock()
+                .unwrap_or_else(PoisonError::into_inner)
+                .push(handler);
+            state.fault_watched.store(true, Ordering::Release);
+        }
+    }
+
+    /// Registers a sampler of a telemetry stream of the requested device
+    pub(crate) fn register_sampler(&self, device_number: usize, sampler: Sampler) {
+        if let Some(state) = self.devices.get(device_number) {
+            state
+                .samplers
+                .lock()
+                .unwrap_or_else(PoisonError::into_inner)
+                .push(sampler);
+            state.sampled.store(true, Ordering::Release);
+        }
+    }
+
+    /// Registers a callback called once every time the fault bit of the requested device is
+    /// raised. A fault that is active while registering the first callback is reported too.
+    ///
+    /// # Returns
+    /// The identifier to remove the callback with, `None` if the device doesn't exist
+    pub(crate) fn register_fault_callback(
+        &self,
+        device_number: usize,
+        callback: FaultCallback,
+    ) -> Option<u64> {
+        let state = self.devices.get(device_number)?;
+        let id = self.next_callback_id.fetch_add(1, Ordering::Relaxed);
+        let mut callbacks = state
+            .fault_callbacks
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner);
+        if callbacks.is_empty() {
+            state.callback_fault.store(false, Ordering::Release);
+        }
+        callbacks.push((id, callback));
+        drop(callbacks);
+        state.fault_called.store(true, Ordering::Release);
+        Some(id)
+    }
+
+    /// Removes a fault callback of the requested device
+    pub(crate) fn remove_fault_callback(&self, device_number: usize, id: u64) {
+        if let Some(state) = self.devices.get(device_number) {
+            let mut callbacks = state
+                .fault_callbacks
+                .lock()
+                .unwrap_or_else(PoisonError::into_inner);
+            callbacks.retain(|(callback_id, _)| *callback_id != id);
+            if callbacks.is_empty() {
+                state.fault_called.store(false, Ordering::Release);
+            }
+        }
+    }
+
+    /// Checks the fault bit of every device with fault callbacks.
+    /// Queues the details of the fault for the fault reporting task when the fault bit is raised,
+    /// or calls the callbacks without the error code if the controller isn't cycling in the
+    /// background.
+    fn scan_fault_callbacks(&self) {
+        for (device_number, state) in self.devices.iter().enumerate() {
+            if !state.fault_called.load(Ordering::Acquire) {
+                continue;
+            }
+
+            // Decode the process image, skip devices that are in use
+            let Ok(sub_device) = self.group.subdevice(&self.main_device, device_number) else {
+                continue;
+            };
+            let status = ServoStatus::from_process_image(
+                sub_device.inputs_raw(),
+                sub_device.outputs_raw(),
+                self.cycle_count(),
+            );
+            drop(sub_device);
+
+            // Only call the callbacks when the fault bit is raised
+            let fault = status.status.is_set(StatusWordBit::Fault);
+            let previous = state.callback_fault.swap(fault, Ordering::AcqRel);
+            if !fault || previous {
+                continue;
+            }
+            let details = FaultDetails::from_status(device_number, &status);
+            let queued = self
+                .fault_reports
+                .lock()
+                .unwrap_or_else(PoisonError::into_inner)
+                .as_ref()
+                .map(|reports| reports.send(details))
+                .is_some();
+            if !queued {
+                call_fault_callbacks(&state.fault_callbacks, details);
+            }
+        }
+    }
+
+    /// Reads the error code (0x603F) of a fault found by `scan_fault_callbacks` and calls the
+    /// fault callbacks of the device. The error code is left out if it couldn't be read.
+    async fn report_fault(&self, mut details: FaultDetails) {
+        let Some(state) = self.devices.get(details.device) else {
+            return;
+        };
+        let error_code = async {
+            self.group
+                .subdevice(&self.main_device, details.device)?
+                .sdo_read(objects::ERROR_CODE.index, objects::ERROR_CODE.sub_index)
+                .await
+        };
+        details.error_code = error_code.await.ok();
+        call_fault_callbacks(&state.fault_callbacks, details);
+    }
+
+    /// Registers the sender of a motion event stream of the requested device
+    pub(crate) fn register_event_sender(&self, device_number: usize, sender: Sender<MotionEvent>) {
+        if let Some(state) = self.devices.get(device_number) {
+            let mut senders = state
+                .event_senders
+                .lock()
+                .unwrap_or_else(PoisonError::into_inner);
+            if senders.is_empty() {
+                state.last_event_status.store(NO_STATUS, Ordering::Release);
+            }
+            senders.push(sender);
+            drop(senders);
+            state.evented.store(true, Ordering::Release);
+        }
+    }
+
+    /// Sends a motion event of the requested device to its motion event streams, tagged with
+    /// the current cycle. Removes the senders of dropped streams.
+    pub(crate) fn emit_motion_event(&self, device_number: usize, kind: MotionEventKind) {
+        let Some(state) = self.devices.get(device_number) else {
+            return;
+        };
+        if !state.evented.load(Ordering::Acquire) {
+            return;
+        }
+        let mut senders = state
+            .event_senders
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner);
+        senders.retain(|sender| !sender.is_disconnected());
+        if senders.is_empty() {
+            state.evented.store(false, Ordering::Release);
+            return;
+        }
+        let event = MotionEvent {
+            cycle: self.cycle_count(),
+            kind,
+        };
+        senders.iter().for_each(|sender| sender.send(event));
+    }
+
+    /// Checks the status word of every device with motion event streams.
+    /// Emits an event when the target is reached or the drive faults.
+    fn scan_motion_events(&self) {
+        for (device_number, state) in self.devices.iter().enumerate() {
+            if !state.evented.load(Ordering::Acquire) {
+                continue;
+            }
+
+            // Decode the process image, skip devices that are in use
+            let Ok(sub_device) = self.group.subdevice(&self.main_device, device_number) else {
+                continue;
+            };
+            let status = ServoStatus::from_process_image(
+                sub_device.inputs_raw(),
+                sub_device.outputs_raw(),
+                self.cycle_count(),
+            );
+            drop(sub_device);
+
+            // Only emit events for bits that have been raised since the previous scan
+            let previous = state
+                .last_event_status
+                .swap(u32::from(status.status.raw()), Ordering::AcqRel);
+            let Ok(previous) = u16::try_from(previous).map(StatusWord::new) else {
+                continue;
+            };
+            for kind in status_events(device_number, previous, &status) {
+                self.emit_motion_event(device_number, kind);
+            }
+        }
+    }
+
+    /// Sends a status sample to the telemetry streams that are due this cycle.
+    /// Removes the samplers of dropped streams.
+    fn sample_devices(&self) {
+        let cycle = self.cycle_count();
+        for (device_number, state) in self.devices.iter().enumerate() {
+            if !state.sampled.load(Ordering::Acquire) {
+                continue;
+            }
+            let mut samplers = state
+                .samplers
+                .lock()
+                .unwrap_or_else(PoisonError::into_inner);
+            samplers.retain(|sampler| !sampler.is_disconnected());
+            if samplers.is_empty() {
+                state.sampled.store(false, Ordering::Release);
+                continue;
+            }
+            if !samplers.iter().any(|sampler| sampler.is_due(cycle)) {
+                continue;
+            }
+
+            // Decode the process image once for all due samplers, skip devices that are in use
+            let Ok(sub_device) = self.group.subdevice(&self.main_device, device_number) else {
+                continue;
+            };
+            let status = ServoStatus::from_process_image(
+                sub_device.inputs_raw(),
+                sub_device.outputs_raw(),
+                cycle,
+            );
+            drop(sub_device);
+            samplers
+                .iter()
+                .filter(|sampler| sampler.is_due(cycle))
+                .for_each(|sampler| sampler.send(status));
+        }
+    }
+
+    /// Checks the fault and warning bits of every watched device.
+    /// Calls the fault handlers of devices that reported a new fault or warning.
+    fn scan_faults(&self) {
+        let diagnosis_offset = pdo::input_offset(festo::DIAGNOSIS_MESSAGE);
+        for (device_number, state) in self.devices.iter().enumerate() {
+            if !state.fault_watched.load(Ordering::Acquire) {
+                continue;
+            }
+
+            // Read the status word, skip devices that are in use
+            let Ok(sub_device) = self.group.subdevice(&self.main_device, device_number) else {
+                continue;
+            };
+            let inputs = sub_device.inputs_raw();
+            let status = StatusWord::new(u16::read(&inputs[pdo::input::STATUS_WORD..]));
+
+            // Only notify the handlers when the error status changes to a fault or warning,
+            // after releasing the device so the handlers can access it
+            let event = FaultEvent::detect(&state.last_error, device_number, status, || {
+                diagnosis_offset.map(|offset| u32::read(&inputs[offset..]))
+            });
+            drop(sub_device);
+            if let Some(event) = event {
+                notify_fault_handlers(&state.fault_handlers, event);
+            }
+        }
+    }
+
+    /// Returns the number of devices connected to the network
+    pub fn device_count(&self) -> usize {
+        self.group.len()
+    }
+
+    /// Searches the device matching the query, so devices can be addressed independent of the
+    /// order in which they are wired.
+    ///
+    /// # Errors
+    /// Returns an error if no device or multiple devices match the query
+    ///
+    /// # Returns
+    /// The device number of the matching device
+    pub fn find_device(&self, query: DeviceQuery) -> Result<usize, FindDeviceError> {
+        // Compare the addresses of every device, skipping devices that are in use right now
+        let mut found = None;
+        let mut count = 0;
+        for device_number in 0..self.device_count() {
+            let Ok(sub_device) = self.group.subdevice(&self.main_device, device_number) else {
+                continue;
+            };
+            if query.matches(sub_device.alias_address(), sub_device.configured_address()) {
+                found = Some(device_number);
+                count += 1;
+            }
+        }
+        match (found, count) {
+            (Some(device_number), 1) => Ok(device_number),
+            (None, _) => Err(FindDeviceError::NotFound(query)),
+            _ => Err(FindDeviceError::Ambiguous { query, count }),
+        }
+    }
+
+    /// Reads the identifying information of the requested device.
+    /// This doesn't reset or enable the device.
+    ///
+    /// # Errors
+    /// Returns an error if the device doesn't exist or another reference to it exists
+    pub async fn device_info(&self, device_number: usize) -> Result<DeviceInfo, EthercrabError> {
+        // Select the device
+        let sub_device = self.group.subdevice(&self.main_device, device_number)?;
+
+        // Try to read the full name of the device
+        let description = sub_device
+            .description()
+            .await
+            .ok()
+            .flatten()
+            .map(|description| description.as_str().to_owned());
+
+        Ok(DeviceInfo {
+            number: device_number,
+            name: sub_device.name().to_owned(),
+            identity: sub_device.identity(),
+            alias_address: sub_device.alias_address(),
+            configured_address: sub_device.configured_address(),
+            description,
+        })
+    }
+
+    /// Blinks the LED of a Festo CMMT drive for the duration, so it can be found in the
+    /// cabinet. This doesn't need a `Device`, so it works before any device is created.
+    ///
+    /// # Errors
+    /// Returns an error if the drive doesn't support identification or communication failed
+    pub async fn identify_device(
+        &self,
+        device_number: usize,
+        duration: Duration,
+    ) -> Result<(), IdentifyError> {
+        self.identify_device_with(device_number, &festo::CMMT, duration)
+            .await
+    }
+
+    /// Blinks the LED of a drive for the duration, using the identification object of the
+    /// vendor objects. The LED returns to normal afterwards, unless the future is dropped
+    /// before it completed.
+    ///
+    /// # Errors
+    /// Returns an error if the drive doesn't support identification or communication failed
+    pub async fn identify_device_with(
+        &self,
+        device_number: usize,
+        vendor_objects: &VendorObjects,
+        duration: Duration,
+    ) -> Result<(), IdentifyError> {
+        let object = vendor_objects
+            .identify
+            .ok_or(IdentifyError::Unsupported(device_number))?;
+        if self.verbose() {
+            log::info!("Identifying device {device_number} for {duration:?}");
+        }
+
+        // Blink while cycling, so the process data keeps being exchanged
+        self.write_identify(device_number, object, 1).await?;
+        let start = Instant::now();
+        while start.elapsed() < duration {
+            self.next_cycle().await;
+        }
+        self.write_identify(device_number, object, 0).await
+    }
+
+    /// Writes the identification object of a device.
+    /// Aborted transfers mean the drive doesn't support the object.
+    ///
+    /// # Errors
+    /// Returns an error if the object couldn't be written
+    async fn write_identify(
+        &self,
+        device_number: usize,
+        object: Object,
+        value: u8,
+    ) -> Result<(), IdentifyError> {
+        let write = async {
+            self.group
+                .subdevice(&self.main_device, device_number)?
+                .sdo_write(object.index, object.sub_index, value)
+                .await
+        };
+        write.await.map_err(|error| match error {
+            EthercrabError::Mailbox(MailboxError::Aborted { .. }) => {
+                IdentifyError::Unsupported(device_number)
+            }
+            error => IdentifyError::Ethercat(device_number, error),
+        })
+    }
+
     /// Configures the subdevices
     async fn configure_devices(
         group: &mut SubDeviceGroup<MAX_DEVICES, PDI_LENGTH>,
@@ -124,41 +774,11 @@ impl<const MAX_DEVICES: usize, const PDI_LENGTH: usize> Controller<'_, MAX_DEVIC
         cycle_time: Duration,
         verbose: bool,
     ) -> Result<(), ControllerError> {
-        /// The index to write the output PDO's to
-        const PDO_OUTPUT_INDEX: u16 = 0x1600;
-
-        /// The values for the output PDO's
-        const PDO_OUTPUT: [u32; 9] = [
-            0x6040_0010,
-            0x6060_0008,
-            0x607a_0020,
-            0x6081_0020,
-            0x60ff_0020,
-            0x6071_0010,
-            0x60b1_0020,
-            0x60b2_0010,
-            0x0000_0008,
-        ];
-
-        /// The index to write the input PDO's to
-        const PDO_INPUT_INDEX: u16 = 0x1A00;
-
-        /// The values for the input PDO's
-        const PDO_INPUT: [u32; 7] = [
-            0x6041_0010,
-            0x6061_0008,
-            0x6064_0020,
-            0x606c_0020,
-            0x6077_0010,
-            0x2194_0520,
-            0x0000_0008,
-        ];
-
         for sub_device in group.iter(main_device) {
             log::info!("Configuring device {}", sub_device.identity());
             // Check if name or eeprom-id is correct for all types of CMMT
-            if !matches!(sub_device.name(), "CMMT-AS" | "CMMT-ST")
-                && !matches!(sub_device.identity().product_id, 0x7B_5A25 | 0x7B_1A95)
+            if DriveType::detect(sub_device.name(), sub_device.identity().product_id)
+                == DriveType::Unknown
             {
                 continue;
             }
@@ -177,23 +797,23 @@ impl<const MAX_DEVICES: usize, const PDI_LENGTH: usize> Controller<'_, MAX_DEVIC
 
             // Set output PDOs
             sub_device
-                .sdo_write_array(PDO_OUTPUT_INDEX, &PDO_OUTPUT)
+                .sdo_write_array(pdo::OUTPUT_INDEX, &pdo::OUTPUTS)
                 .await
                 .map_err(ControllerError::OutputPdo)?;
 
             // Set input PDOs
             sub_device
-                .sdo_write_array(PDO_INPUT_INDEX, &PDO_INPUT)
+                .sdo_write_array(pdo::INPUT_INDEX, &pdo::INPUTS)
                 .await
                 .map_err(ControllerError::InputPdo)?;
 
             // Configure the servo controller
             sub_device
-                .sdo_write(0x1C12, 1, 0x1600u16)
+                .sdo_write(0x1C12, 1, pdo::OUTPUT_INDEX)
                 .await
                 .map_err(ControllerError::Ethercat)?;
             sub_device
-                .sdo_write(0x1C13, 1, 0x1A00u16)
+                .sdo_write(0x1C13, 1, pdo::INPUT_INDEX)
                 .await
                 .map_err(ControllerError::Ethercat)?;
             sub_device
@@ -285,12 +905,21 @@ impl<const MAX_DEVICES: usize, const PDI_LENGTH: usize> Controller<'_, MAX_DEVIC
             verbose,
             main_device,
             group,
+            devices: [const { DeviceState::new() }; MAX_DEVICES],
+            cycles: CycleGate::new(),
+            next_callback_id: AtomicU64::new(0),
+            fault_reports: Mutex::new(None),
         })
     }
 
     /// Updates the device state, takes at least the at construction specified cycle time.
+    /// Calls the fault handlers of devices reporting a new fault or warning and samples the
+    /// devices with telemetry streams.
     /// If the update takes shorter than the specified time, the thread will sleep.
     /// If the update takes longer, a warning message will be displayed.
+    ///
+    /// Operations waiting on devices use `next_cycle`, prefer it over calling this directly
+    /// while other operations are running, so the bus isn't updated twice per cycle.
     pub async fn cycle(&self) {
         // Start measuring time
         let start = Instant::now();
@@ -298,6 +927,21 @@ impl<const MAX_DEVICES: usize, const PDI_LENGTH: usize> Controller<'_, MAX_DEVIC
         // Synchronize with the other devices
         let _ = self.group.tx_rx_sync_system_time(&self.main_device).await;
 
+        // Notify the fault handlers of devices with a new fault
+        self.scan_faults();
+
+        // Wake the tasks waiting for this cycle
+        self.cycles.complete();
+
+        // Emit the motion events reported by the status words
+        self.scan_motion_events();
+
+        // Call the fault callbacks of devices with a new fault
+        self.scan_fault_callbacks();
+
+        // Sample the devices with telemetry streams
+        self.sample_devices();
+
         // Store the time spend on updating the task
         let delta = start.elapsed();
 
@@ -316,4 +960,79 @@ impl<const MAX_DEVICES: usize, const PDI_LENGTH: usize> Controller<'_, MAX_DEVIC
             smol::unblock(move || std::thread::sleep(sleep_duration)).await;
         }
     }
+
+    /// Returns whether a background task is cycling the controller
+    pub fn is_cycling(&self) -> bool {
+        self.cycles.is_cycling()
+    }
+
+    /// Stops the background task started by `start_cycling` after its current cycle
+    pub fn stop_cycling(&self) {
+        self.cycles.stop();
+    }
+
+    /// Waits until the next cycle has completed.
+    /// Without a background task, one of the waiting tasks performs the cycle and the others
+    /// wait for it, so concurrent operations observe every cycle exactly once instead of each
+    /// performing their own cycle.
+    pub async fn next_cycle(&self) {
+        self.cycles.next_cycle(|| self.cycle()).await;
+    }
+}
+
+impl<const MAX_DEVICES: usize, const PDI_LENGTH: usize>
+    Controller<'static, MAX_DEVICES, PDI_LENGTH>
+{
+    /// Starts a background task cycling the controller until `stop_cycling` is called.
+    /// Required by the cyclic synchronous modes, which need a setpoint every cycle.
+    /// Does nothing if the controller is already cycling. If the task of a previous call is
+    /// still finishing its last cycle after `stop_cycling`, that task keeps cycling instead.
+    pub fn start_cycling(self: &Arc<Self>) {
+        if !self.cycles.start() {
+            return;
+        }
+        if self.verbose {
+            log::info!("Starting background cycling");
+        }
+
+        // Read the error codes of faults and call the fault callbacks on another task, so the
+        // SDO transfers don't stall the cycle
+        let (reports, receiver) = channel::bounded(FAULT_REPORT_CAPACITY);
+        *self
+            .fault_reports
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner) = Some(reports);
+        let controller = Arc::clone(self);
+        let report_task = async move {
+            while let Some(details) = receiver.recv().await {
+                controller.report_fault(details).await;
+            }
+        };
+
+        // Cycle on another task until cycling is stopped, then stop the fault reporting task
+        // once it reported the queued faults
+        let controller = Arc::clone(self);
+        let cycle_task = async move {
+            let stop_reports = || {
+                controller
+                    .fault_reports
+                    .lock()
+                    .unwrap_or_else(PoisonError::into_inner)
+                    .take();
+            };
+            while controller.cycles.keep_cycling(stop_reports) {
+                controller.cycle().await;
+            }
+        };
+        #[cfg(feature = "tokio")]
+        {
+            tokio::spawn(report_task);
+            tokio::spawn(cycle_task);
+        }
+        #[cfg(feature = "smol")]
+        {
+            smol::spawn(report_task).detach();
+            smol::spawn(cycle_task).detach();
+        }
+    }
 }
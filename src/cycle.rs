@@ -0,0 +1,340 @@
+//! This module contains the synchronization of the cycles of the controller.
+//!
+//! A cycle is either performed by the background cycling task, or by one of the tasks waiting
+//! for the next cycle while the others wait for it, so concurrent operations observe every cycle
+//! exactly once. The background cycling task is tracked until it actually exits, so stopping and
+//! restarting background cycling never runs two cycling tasks at the same time.
+
+use core::{
+    future::{poll_fn, Future},
+    task::{Poll, Waker},
+};
+use std::sync::{
+    atomic::{AtomicBool, AtomicU64, Ordering},
+    Mutex, PoisonError,
+};
+
+/// The cycle count and the tasks waiting for the next cycle
+pub struct CycleGate {
+    /// The number of completed cycles
+    count: AtomicU64,
+
+    /// Whether background cycling has been requested
+    cycling: AtomicBool,
+
+    /// Whether a task waiting in `next_cycle` is performing the cycle for all waiting tasks
+    driving: AtomicBool,
+
+    /// The tasks waiting for the next cycle
+    wakers: Mutex<Vec<Waker>>,
+
+    /// Whether the background cycling task is running, it finishes its current cycle after
+    /// background cycling has been stopped
+    task_running: Mutex<bool>,
+}
+
+impl CycleGate {
+    /// Creates the gate of a controller that hasn't cycled yet
+    pub const fn new() -> Self {
+        Self {
+            count: AtomicU64::new(0),
+            cycling: AtomicBool::new(false),
+            driving: AtomicBool::new(false),
+            wakers: Mutex::new(Vec::new()),
+            task_running: Mutex::new(false),
+        }
+    }
+
+    /// Returns the number of completed cycles
+    pub fn count(&self) -> u64 {
+        self.count.load(Ordering::Acquire)
+    }
+
+    /// Returns whether background cycling has been requested
+    pub fn is_cycling(&self) -> bool {
+        self.cycling.load(Ordering::Acquire)
+    }
+
+    /// Counts a completed cycle and wakes the tasks waiting for it
+    pub fn complete(&self) {
+        let mut wakers = self.wakers.lock().unwrap_or_else(PoisonError::into_inner);
+        self.count.fetch_add(1, Ordering::AcqRel);
+        wakers.drain(..).for_each(Waker::wake);
+    }
+
+    /// Requests background cycling.
+    ///
+    /// # Returns
+    /// Whether a new cycling task has to be started, false if the previous task is still running,
+    /// in which case it keeps cycling
+    pub fn start(&self) -> bool {
+        let mut running = self
+            .task_running
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner);
+        self.cycling.store(true, Ordering::Release);
+        !core::mem::replace(&mut *running, true)
+    }
+
+    /// Requests the background cycling task to stop after its current cycle
+    pub fn stop(&self) {
+        self.cycling.store(false, Ordering::Release);
+    }
+
+    /// Checks whether the background cycling task has to perform another cycle. If not, `on_exit`
+    /// is called and the task is marked as stopped in the same step, so `start` either keeps this
+    /// task cycling or starts a new task after `on_exit` has been called. The task has to exit once
+    /// this returns false.
+    pub fn keep_cycling(&self, on_exit: impl FnOnce()) -> bool {
+        let mut running = self
+            .task_running
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner);
+        if self.is_cycling() {
+            return true;
+        }
+        on_exit();
+        *running = false;
+        drop(running);
+
+        // Wake the tasks still waiting, so they can cycle themselves
+        self.wake_waiters();
+        false
+    }
+
+    /// Waits until the next cycle has completed.
+    /// Without background cycling, one of the waiting tasks performs the cycle and the others
+    /// wait for it.
+    ///
+    /// # Parameters
+    /// `cycle`: Performs a cycle, which has to call `CycleGate::complete`
+    pub async fn next_cycle<F: Future<Output = ()>>(&self, cycle: impl FnOnce() -> F) {
+        let start = self.count();
+        let mut cycle = Some(cycle);
+        loop {
+            // Perform the cycle if nobody else is cycling the controller
+            if !self.is_cycling()
+                && self
+                    .driving
+                    .compare_exchange(false, true, Ordering::AcqRel, Ordering::Acquire)
+                    .is_ok()
+            {
+                let _driver = CycleDriver(self);
+                if let Some(cycle) = cycle.take() {
+                    cycle().await;
+                }
+                return;
+            }
+
+            // Wait until the cycle count changes, the lock prevents missing a wake up
+            poll_fn(|context| {
+                let mut wakers = self.wakers.lock().unwrap_or_else(PoisonError::into_inner);
+                if self.count() != start
+                    || !(self.is_cycling() || self.driving.load(Ordering::Acquire))
+                {
+                    Poll::Ready(())
+                } else {
+                    wakers.push(context.waker().clone());
+                    Poll::Pending
+                }
+            })
+            .await;
+
+            // Return once the cycle completed, otherwise the cycling task stopped or the driving
+            // task was dropped mid-cycle and this task takes over
+            if self.count() != start {
+                return;
+            }
+        }
+    }
+
+    /// Wakes all tasks waiting in `next_cycle`
+    pub fn wake_waiters(&self) {
+        self.wakers
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .drain(..)
+            .for_each(Waker::wake);
+    }
+}
+
+/// Releases the cycle performed in `next_cycle` when dropped, even if the waiting future is
+/// dropped in the middle of the cycle, so the other waiting tasks can take over
+struct CycleDriver<'gate>(&'gate CycleGate);
+
+impl Drop for CycleDriver<'_> {
+    fn drop(&mut self) {
+        self.0.driving.store(false, Ordering::Release);
+        self.0.wake_waiters();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    //! Tests of the cycle synchronization with a simulated process image, driven by a minimal
+    //! executor so they don't depend on the async runtime
+
+    use super::*;
+    use core::{pin::pin, task::Context};
+    use std::{
+        sync::Arc,
+        task::Wake,
+        thread::{self, Thread},
+    };
+
+    /// Wakes the thread blocked in `block_on`
+    struct ThreadWaker(Thread);
+
+    impl Wake for ThreadWaker {
+        fn wake(self: Arc<Self>) {
+            self.0.unpark();
+        }
+    }
+
+    /// Runs the future to completion on the current thread
+    fn block_on<F: Future>(future: F) -> F::Output {
+        let waker = Waker::from(Arc::new(ThreadWaker(thread::current())));
+        let mut context = Context::from_waker(&waker);
+        let mut future = pin!(future);
+        loop {
+            if let Poll::Ready(output) = future.as_mut().poll(&mut context) {
+                return output;
+            }
+            thread::park();
+        }
+    }
+
+    /// Polls both futures until both completed, the first one first
+    async fn join<A: Future, B: Future>(first: A, second: B) -> (A::Output, B::Output) {
+        let (mut first, mut second) = (pin!(first), pin!(second));
+        let (mut first_output, mut second_output) = (None, None);
+        poll_fn(|context| {
+            if first_output.is_none() {
+                if let Poll::Ready(output) = first.as_mut().poll(context) {
+                    first_output = Some(output);
+                }
+            }
+            if second_output.is_none() {
+                if let Poll::Ready(output) = second.as_mut().poll(context) {
+                    second_output = Some(output);
+                }
+            }
+            if first_output.is_some() && second_output.is_some() {
+                Poll::Ready(())
+            } else {
+                Poll::Pending
+            }
+        })
+        .await;
+        (first_output.unwrap(), second_output.unwrap())
+    }
+
+    /// Lets the other futures run once, like the sleep at the end of a cycle
+    async fn yield_now() {
+        let mut yielded = false;
+        poll_fn(|context| {
+            if yielded {
+                Poll::Ready(())
+            } else {
+                yielded = true;
+                context.waker().wake_by_ref();
+                Poll::Pending
+            }
+        })
+        .await;
+    }
+
+    /// A simulated cycle: sends the target in the output image, completes the cycle and sleeps
+    async fn cycle(gate: &CycleGate, output: &Mutex<i32>, sent: &Mutex<Vec<i32>>) {
+        sent.lock().unwrap().push(*output.lock().unwrap());
+        gate.complete();
+        yield_now().await;
+    }
+
+    /// Streaming a ramp of CSP targets with background cycling sends every target exactly once,
+    /// in order
+    #[test]
+    fn background_cycling_sends_every_target() {
+        let gate = CycleGate::new();
+        let output = Mutex::new(0);
+        let sent = Mutex::new(Vec::new());
+        let ramp: Vec<i32> = (1..=100).map(|step| step * 25).collect();
+        assert!(gate.start());
+
+        let background = async {
+            while gate.keep_cycling(|| ()) {
+                cycle(&gate, &output, &sent).await;
+            }
+        };
+        let stream = async {
+            for target in &ramp {
+                *output.lock().unwrap() = *target;
+                gate.next_cycle(|| async {
+                    unreachable!("Background cycling performs the cycles")
+                })
+                .await;
+            }
+            gate.stop();
+        };
+        block_on(join(background, stream));
+
+        // The first cycle sends the output image from before the stream started
+        let sent = sent.into_inner().unwrap();
+        assert_eq!(sent[0], 0);
+        assert_eq!(sent[1..], ramp);
+        assert!(!*gate.task_running.lock().unwrap());
+    }
+
+    /// Two operations waiting for cycles at the same time without background cycling share the
+    /// cycles, every call waits for exactly the next cycle
+    #[test]
+    fn concurrent_waiters_see_every_cycle() {
+        const CALLS: u64 = 50;
+        let gate = CycleGate::new();
+        let output = Mutex::new(0);
+        let sent = Mutex::new(Vec::new());
+
+        // Returns the cycle count before and after every call
+        let waiter = || async {
+            let mut counts = Vec::new();
+            for _ in 0..CALLS {
+                let before = gate.count();
+                gate.next_cycle(|| cycle(&gate, &output, &sent)).await;
+                counts.push((before, gate.count()));
+            }
+            counts
+        };
+        let (first, second) = block_on(join(waiter(), waiter()));
+
+        for (before, after) in first.iter().chain(&second) {
+            assert_eq!(after - before, 1);
+        }
+
+        // Every completed cycle has been performed by one of the waiters, which shared them
+        let cycles = sent.into_inner().unwrap().len() as u64;
+        assert_eq!(cycles, gate.count());
+        assert!(cycles < 2 * CALLS);
+        assert!(!gate.driving.load(Ordering::Acquire));
+    }
+
+    /// Restarting background cycling before the task noticed the stop keeps the running task,
+    /// instead of starting a second one
+    #[test]
+    fn restart_keeps_running_task() {
+        let gate = CycleGate::new();
+        assert!(gate.start());
+        assert!(!gate.start());
+
+        // Stopping and restarting right away keeps the same task cycling
+        gate.stop();
+        assert!(!gate.start());
+        assert!(gate.keep_cycling(|| panic!("The task keeps cycling")));
+
+        // Once the task exited, a new task is needed
+        gate.stop();
+        let mut exited = false;
+        assert!(!gate.keep_cycling(|| exited = true));
+        assert!(exited);
+        assert!(gate.start());
+    }
+}
@@ -2,22 +2,45 @@
 //! The `Device` type is responsible for communicating with the device.
 //! It's also the base struct for most types of subdevices.
 
-use crate::controller::Controller;
-use core::fmt::{self, Debug, Formatter};
-use ethercrab::error::Error as EthercrabError;
-
+use crate::{
+    controller::Controller,
+    pdo::{self, PdoValue},
+};
+use core::{
+    fmt::{self, Debug, Formatter},
+    future::Future,
+    time::Duration,
+};
+use ethercrab::{
+    error::{Error as EthercrabError, MailboxError},
+    EtherCrabWireReadSized, EtherCrabWireWrite, SubDeviceIdentity,
+};
+use objects::Object;
+use servo::events::MotionEventKind;
+use std::time::Instant;
+
+pub mod analog_io;
+pub mod digital_io;
+pub mod drive_io;
+pub mod fault;
+pub mod festo;
+pub mod monitor;
+pub mod objects;
 pub mod servo;
+#[cfg(test)]
+mod simulation;
+pub mod stepper;
 
 /// An error returned while resetting the device
 pub enum ResetError {
-    /// Failed to reset the device
-    ResetFailed(usize),
+    /// Both the fault and warning bits were still set when the reset timed out
+    ResetFailed(WaitTimeout),
 
-    /// Failed to reset with a warning
-    ResetFailedWarning(usize),
+    /// The warning bit was still set when the reset timed out
+    ResetFailedWarning(WaitTimeout),
 
-    /// Failed to reset with a fault
-    ResetFailedFault(usize),
+    /// The fault bit was still set when the reset timed out
+    ResetFailedFault(WaitTimeout),
 
     /// There is an existing reference to the requisted device.
     /// When returned by the enable error, the device doesn't exist.
@@ -29,10 +52,10 @@ impl ResetError {
     /// The device returns the slave contained in the error message
     pub const fn device(&self) -> usize {
         match self {
-            Self::ResetFailed(device)
-            | Self::ResetFailedWarning(device)
-            | Self::ResetFailedFault(device)
-            | Self::DeviceInUse(device, _) => *device,
+            Self::ResetFailed(timeout)
+            | Self::ResetFailedWarning(timeout)
+            | Self::ResetFailedFault(timeout) => timeout.device(),
+            Self::DeviceInUse(device, _) => *device,
         }
     }
 }
@@ -40,16 +63,18 @@ impl ResetError {
 impl Debug for ResetError {
     fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
         match self {
-            Self::ResetFailed(device_number) => {
-                write!(f, "Resetting device number {device_number} failed")
+            Self::ResetFailed(timeout) => {
+                write!(f, "Resetting device number {} failed:\n{timeout:?}", timeout.device())
             }
-            Self::ResetFailedFault(device_number) => write!(
+            Self::ResetFailedFault(timeout) => write!(
                 f,
-                "Resetting device number {device_number} failed with only the fault bit being set"
+                "Resetting device number {} failed with only the fault bit being set:\n{timeout:?}",
+                timeout.device()
             ),
-            Self::ResetFailedWarning(device_number) => write!(
+            Self::ResetFailedWarning(timeout) => write!(
                 f,
-                "Resetting device number {device_number} failed with only the warning bit being set"
+                "Resetting device number {} failed with only the warning bit being set:\n{timeout:?}",
+                timeout.device()
             ),
             Self::DeviceInUse(device, error) => {
                 write!(f, "Resetting device {device} failed:\n{error:?}")
@@ -68,6 +93,12 @@ pub enum EnableError {
 
     /// Initialization failed with an unknown error
     Failed(usize),
+
+    /// The device is emergency stopped, the emergency stop has to be cleared first
+    EmergencyStopped(usize),
+
+    /// Another handle to the device already exists
+    AlreadyClaimed(usize),
 }
 
 impl Debug for EnableError {
@@ -80,14 +111,43 @@ impl Debug for EnableError {
             ),
             Self::Timeout(device) => write!(f, "Timeout: Enable drive {device} unsuccessful"),
             Self::Failed(device) => write!(f, "Enable drive {device} unsuccessful"),
+            Self::EmergencyStopped(device) => write!(
+                f,
+                "Enable drive {device} not possible, the emergency stop has to be cleared first"
+            ),
+            Self::AlreadyClaimed(device) => {
+                write!(f, "Drive {device} is already controlled by another handle")
+            }
+        }
+    }
+}
+
+/// An error returned while attaching to a device
+pub enum AttachError {
+    /// The device doesn't exist or couldn't be accessed
+    NotFound(usize, EthercrabError),
+
+    /// Another handle to the device already exists
+    AlreadyClaimed(usize),
+}
+
+impl Debug for AttachError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::NotFound(device, error) => {
+                write!(f, "Failed to attach to drive {device}: {error}")
+            }
+            Self::AlreadyClaimed(device) => {
+                write!(f, "Drive {device} is already controlled by another handle")
+            }
         }
     }
 }
 
 /// The operation mode byte value
-#[derive(Debug, Clone, Copy)]
-#[expect(dead_code)]
-enum OperationMode {
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum OperationMode {
     /// Not operating
     None,
 
@@ -122,17 +182,70 @@ enum OperationMode {
     Jog = 253,
 }
 
+impl OperationMode {
+    /// Decodes the operation mode byte reported by the device
+    ///
+    /// # Returns
+    /// The operation mode or `None` if the byte isn't a known mode
+    pub const fn from_raw(raw: u8) -> Option<Self> {
+        Some(match raw {
+            0 => Self::None,
+            1 => Self::ProfilePosition,
+            2 => Self::Velocity,
+            3 => Self::ProfileVelocity,
+            4 => Self::Torque,
+            6 => Self::Homing,
+            7 => Self::InterpolatedPosition,
+            8 => Self::CyclingSyncPosition,
+            9 => Self::CyclingSyncVelocity,
+            10 => Self::CyclingSyncTorque,
+            253 => Self::Jog,
+            _ => return None,
+        })
+    }
+}
+
+impl TryFrom<u8> for OperationMode {
+    type Error = UnknownMode;
+
+    fn try_from(value: u8) -> Result<Self, Self::Error> {
+        Self::from_raw(value).ok_or(UnknownMode(value))
+    }
+}
+
+/// An operation mode byte that isn't a known mode
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct UnknownMode(pub u8);
+
 /// An error happened while setting a new mode
-pub struct SetModeError(usize, OperationMode);
+pub enum SetModeError {
+    /// The mode couldn't be written to the outputs of the device
+    Ethercat(usize, OperationMode, EthercrabError),
+
+    /// The device didn't display the mode in time, faulted or has been emergency stopped
+    Wait(OperationMode, WaitTimeout),
+}
 
 impl Debug for SetModeError {
     fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
-        write!(f, "Failed to set device {} to mode {:?}", self.0, self.1)
+        match self {
+            Self::Ethercat(device, mode, error) => {
+                write!(
+                    f,
+                    "Failed to set device {device} to mode {mode:?}: {error:?}"
+                )
+            }
+            Self::Wait(mode, timeout) => write!(
+                f,
+                "Failed to set device {} to mode {mode:?}:\n{timeout:?}",
+                timeout.device()
+            ),
+        }
     }
 }
 
 /// The error status of the device
-#[derive(Debug, PartialEq, Eq)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum DeviceError {
     /// Fault bit is set on the device
     Fault,
@@ -147,8 +260,24 @@ pub enum DeviceError {
     Ok,
 }
 
+impl DeviceError {
+    /// Decodes the fault and warning bits of a status word
+    pub const fn from_status(status: StatusWord) -> Self {
+        match (
+            status.is_set(StatusWordBit::Fault),
+            status.is_set(StatusWordBit::Warning),
+        ) {
+            (false, false) => Self::Ok,
+            (false, true) => Self::Warning,
+            (true, false) => Self::Fault,
+            (true, true) => Self::FaultAndWarning,
+        }
+    }
+}
+
 /// Bits specifying the current control value
-enum ControlBit {
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ControlBit {
     /// Switch the device on
     SwitchOn,
 
@@ -181,8 +310,8 @@ enum ControlBit {
 }
 
 /// Bits specifying the current status in the status word
-#[expect(dead_code)]
-enum StatusWordBit {
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum StatusWordBit {
     /// Whether the device is ready to switch on
     ReadyToSwitchOn,
 
@@ -219,41 +348,501 @@ enum StatusWordBit {
     /// Ack start or Ref reached
     AckStartRefReached = 12,
 
+    /// Mode specific error: homing error in homing mode, following error in position modes
+    ModeSpecificError = 13,
+
     /// Drive moved to home position
     DriveHomed = 15,
 }
 
-#[expect(dead_code)]
-/// Mapped PDO bytes
-enum MappedPdo {
-    /// Control or status word
-    ControlStatusWord,
+/// A copy of the control word of a device.
+/// The desired control word is computed first and then written with a single access.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct ControlWord(u16);
+
+impl ControlWord {
+    /// Creates a control word from the raw value
+    pub const fn new(raw: u16) -> Self {
+        Self(raw)
+    }
+
+    /// Returns the raw value of the control word
+    pub const fn raw(self) -> u16 {
+        self.0
+    }
+
+    /// Returns whether the requested bit is set in the control word
+    pub const fn is_set(self, bit: ControlBit) -> bool {
+        self.0 & (1 << bit as u16) != 0
+    }
+
+    /// Returns the control word with the requested bit set
+    #[must_use]
+    pub const fn with(self, bit: ControlBit) -> Self {
+        Self(self.0 | (1 << bit as u16))
+    }
+
+    /// Returns the control word with the requested bit cleared
+    #[must_use]
+    pub const fn without(self, bit: ControlBit) -> Self {
+        Self(self.0 & !(1 << bit as u16))
+    }
+
+    /// Returns the control word with the mode specific control bits (4, 5, 6, and 9) cleared
+    #[must_use]
+    pub const fn without_control(self) -> Self {
+        self.without(ControlBit::Control4)
+            .without(ControlBit::Control5)
+            .without(ControlBit::Control6)
+            .without(ControlBit::Control9)
+    }
+}
+
+/// A decoded copy of the status word of a device
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct StatusWord(u16);
+
+impl StatusWord {
+    /// Creates a status word from the raw value read from the device
+    pub const fn new(raw: u16) -> Self {
+        Self(raw)
+    }
+
+    /// Returns the raw value of the status word
+    pub const fn raw(self) -> u16 {
+        self.0
+    }
+
+    /// Returns whether the requested bit is set in the status word
+    pub const fn is_set(self, bit: StatusWordBit) -> bool {
+        self.0 & (1 << bit as u16) != 0
+    }
+
+    /// Decodes the state of the `CiA402` state machine from the status word
+    pub const fn state(self) -> Cia402State {
+        // The state is encoded in bits 0 to 3, 5 and 6
+        match (self.0 & 0x4F, self.0 & 0x6F) {
+            (0x00, _) => Cia402State::NotReadyToSwitchOn,
+            (0x40, _) => Cia402State::SwitchOnDisabled,
+            (0x0F, _) => Cia402State::FaultReactionActive,
+            (0x08, _) => Cia402State::Fault,
+            (_, 0x21) => Cia402State::ReadyToSwitchOn,
+            (_, 0x23) => Cia402State::SwitchedOn,
+            (_, 0x27) => Cia402State::OperationEnabled,
+            (_, 0x07) => Cia402State::QuickStopActive,
+            _ => Cia402State::Unknown,
+        }
+    }
+}
+
+/// The states of the `CiA402` state machine of a drive
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Cia402State {
+    /// The drive is initializing
+    NotReadyToSwitchOn,
+
+    /// The drive is initialized, but the voltage may not be switched on
+    SwitchOnDisabled,
+
+    /// The voltage may be switched on
+    ReadyToSwitchOn,
+
+    /// The voltage is switched on, but operation is disabled
+    SwitchedOn,
+
+    /// The drive follows the commands
+    OperationEnabled,
+
+    /// The drive is stopping or stopped by a quick stop
+    QuickStopActive,
+
+    /// The drive is reacting to a fault
+    FaultReactionActive,
+
+    /// The drive is in fault
+    Fault,
+
+    /// The status word doesn't encode a valid state
+    Unknown,
+}
+
+/// The conditions besides the requested state that end a wait on the status word
+#[derive(Debug, Clone, Copy, Default)]
+pub(crate) struct WaitConditions {
+    /// Whether the device has been emergency stopped
+    pub emergency_stopped: bool,
+
+    /// Whether a fault ends the wait, see `Device::set_abort_on_fault`
+    pub abort_on_fault: bool,
+
+    /// Whether the deadline of the wait passed
+    pub expired: bool,
+}
+
+impl WaitConditions {
+    /// Decides whether a wait on the status word ends, see `Device::check_wait`.
+    ///
+    /// # Parameters
+    /// `id`: The device number
+    /// `status`: The status word of the current cycle
+    /// `reached`: Whether the status word satisfies the predicate of the wait
+    ///
+    /// # Returns
+    /// `None` if the wait continues, otherwise the result of the wait
+    pub(crate) const fn wait_result(
+        self,
+        id: usize,
+        status: StatusWord,
+        reached: bool,
+    ) -> Option<Result<StatusWord, WaitTimeout>> {
+        if reached {
+            Some(Ok(status))
+        } else if self.emergency_stopped {
+            Some(Err(WaitTimeout::EmergencyStopped(id, status)))
+        } else if self.abort_on_fault && status.is_set(StatusWordBit::Fault) {
+            Some(Err(WaitTimeout::Fault(id, status)))
+        } else if self.expired {
+            Some(Err(WaitTimeout::Expired(id, status)))
+        } else {
+            None
+        }
+    }
+
+    /// Decides whether a wait on the status word during a motion ends, see
+    /// `Device::check_motion`. A fault or leaving the operation enabled state always ends the
+    /// wait and takes precedence over the expired deadline. Warnings don't end the wait.
+    ///
+    /// # Returns
+    /// `None` if the wait continues, otherwise the result of the wait
+    pub(crate) fn motion_result(
+        self,
+        id: usize,
+        status: StatusWord,
+        reached: bool,
+    ) -> Option<Result<StatusWord, WaitTimeout>> {
+        match self.wait_result(id, status, reached) {
+            result @ Some(Ok(_) | Err(WaitTimeout::EmergencyStopped(..))) => result,
+            _ if status.is_set(StatusWordBit::Fault)
+                || status.state() != Cia402State::OperationEnabled =>
+            {
+                Some(Err(WaitTimeout::Fault(id, status)))
+            }
+            result => result,
+        }
+    }
+}
+
+/// The status word didn't reach the requested state in time
+pub enum WaitTimeout {
+    /// The deadline passed before the requested state was reached
+    Expired(usize, StatusWord),
+
+    /// The device reported a fault while waiting
+    Fault(usize, StatusWord),
+
+    /// The device has been emergency stopped while waiting
+    EmergencyStopped(usize, StatusWord),
+}
+
+impl WaitTimeout {
+    /// # Returns
+    /// The number of the device that was waited on
+    pub const fn device(&self) -> usize {
+        match self {
+            Self::Expired(device, _)
+            | Self::Fault(device, _)
+            | Self::EmergencyStopped(device, _) => *device,
+        }
+    }
+
+    /// # Returns
+    /// The last status word read from the device
+    pub const fn status(&self) -> StatusWord {
+        match self {
+            Self::Expired(_, status)
+            | Self::Fault(_, status)
+            | Self::EmergencyStopped(_, status) => *status,
+        }
+    }
+}
+
+impl Debug for WaitTimeout {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Expired(device, status) => write!(
+                f,
+                "Timeout while waiting on device {device}, status word {:#06x}",
+                status.raw()
+            ),
+            Self::Fault(device, status) => write!(
+                f,
+                "Device {device} faulted while waiting, status word {:#06x}",
+                status.raw()
+            ),
+            Self::EmergencyStopped(device, status) => write!(
+                f,
+                "Device {device} emergency stopped while waiting, status word {:#06x}",
+                status.raw()
+            ),
+        }
+    }
+}
+
+/// An error returned while disabling a device
+pub enum DisableError {
+    /// The device didn't leave the operation enabled state in time
+    Wait {
+        /// Why the wait ended, containing the last status word reported by the device
+        timeout: WaitTimeout,
+
+        /// The time waited for the device to get disabled
+        waited: Duration,
+    },
+
+    /// The control word couldn't be written or the status word couldn't be read
+    Ethercat(usize, EthercrabError),
+}
+
+impl DisableError {
+    /// # Returns
+    /// The number of the device that didn't get disabled
+    pub const fn device(&self) -> usize {
+        match self {
+            Self::Wait { timeout, .. } => timeout.device(),
+            Self::Ethercat(device, _) => *device,
+        }
+    }
+}
+
+impl Debug for DisableError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Wait { timeout, waited } => write!(
+                f,
+                "Device {} didn't get disabled within {waited:?}, state {:?}:\n{timeout:?}",
+                timeout.device(),
+                timeout.status().state()
+            ),
+            Self::Ethercat(device, error) => {
+                write!(f, "Disabling device {device} failed:\n{error:?}")
+            }
+        }
+    }
+}
+
+/// The result of disabling a device
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct DisableReport {
+    /// The state the device ended up in
+    pub state: Cia402State,
+
+    /// The status word reported by the device once it was disabled
+    pub status: StatusWord,
+
+    /// The time it took to disable the device
+    pub elapsed: Duration,
+}
+
+/// Identifying information of a device on the network
+#[derive(Debug, Clone)]
+pub struct DeviceInfo {
+    /// The device number (index on the network)
+    pub number: usize,
+
+    /// The (partial) name of the device
+    pub name: String,
+
+    /// The vendor, product, revision, and serial numbers of the device
+    pub identity: SubDeviceIdentity,
+
+    /// The alias address of the device
+    pub alias_address: u16,
+
+    /// The configured address of the device
+    pub configured_address: u16,
+
+    /// The full name of the device, if it could be read
+    pub description: Option<String>,
+}
+
+impl DeviceInfo {
+    /// Returns the kind of drive, telling whether to use a `Servo` or a `Stepper`
+    pub fn drive_type(&self) -> DriveType {
+        DriveType::detect(&self.name, self.identity.product_id)
+    }
+}
+
+/// The kind of drive, detected from the name and product code of the device
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum DriveType {
+    /// A CMMT-AS servo drive, controlled with `Servo`
+    Servo,
+
+    /// A CMMT-ST stepper drive, controlled with `Stepper`
+    Stepper,
+
+    /// A device that isn't a known drive
+    Unknown,
+}
+
+impl DriveType {
+    /// Detects the kind of drive from the (partial) name or the product code of the device
+    pub fn detect(name: &str, product_id: u32) -> Self {
+        match (name, product_id) {
+            ("CMMT-AS", _) | (_, festo::CMMT_AS_PRODUCT_ID) => Self::Servo,
+            ("CMMT-ST", _) | (_, festo::CMMT_ST_PRODUCT_ID) => Self::Stepper,
+            _ => Self::Unknown,
+        }
+    }
+}
+
+/// Applies the quick stop pattern (quick stop bit cleared, halt bit set) to a control word
+pub(crate) const fn emergency_control_word(control_word: u16) -> u16 {
+    ControlWord::new(control_word)
+        .without(ControlBit::QuickStop)
+        .with(ControlBit::Halt)
+        .raw()
+}
+
+/// The output process image of a device, borrowed once for every access
+pub(crate) trait OutputImage {
+    /// Borrows the output process image once and passes it to the closure.
+    ///
+    /// # Errors
+    /// Returns an error if the output process image couldn't be borrowed
+    fn apply_outputs<R>(
+        &mut self,
+        update: impl FnOnce(&mut [u8]) -> R,
+    ) -> Result<R, EthercrabError>;
+
+    /// Updates the control word with a single access to the output process image, so no
+    /// intermediate control word can be sent.
+    ///
+    /// # Errors
+    /// Returns an error if the output process image couldn't be borrowed
+    fn update_control_word(
+        &mut self,
+        update: impl FnOnce(ControlWord) -> ControlWord,
+    ) -> Result<ControlWord, EthercrabError> {
+        self.apply_outputs(|outputs| {
+            let outputs = &mut outputs[pdo::output::CONTROL_WORD..];
+            let control = update(ControlWord::new(u16::read(outputs)));
+            control.raw().write(outputs);
+            control
+        })
+    }
 
-    /// Mode of operation or mode of operation display
-    OperationMode = 2,
+    /// Sets the halt bit and clears the mode specific control bits, which start setpoints and
+    /// select the jog direction, so the drive stops with the next cycle.
+    ///
+    /// # Errors
+    /// Returns an error if the output process image couldn't be borrowed
+    ///
+    /// # Returns
+    /// Whether the control word changed, because motion was commanded
+    fn halt_motion(&mut self) -> Result<bool, EthercrabError> {
+        let mut previous = 0;
+        let halted = self.update_control_word(|control| {
+            previous = control.raw();
+            control.without_control().with(ControlBit::Halt)
+        })?;
+        Ok(halted.raw() != previous)
+    }
+}
 
-    /// Target position of actual position
-    Position = 3,
+/// The cyclic access to a drive the motion sequences of the servo are written against, so the
+/// tests can run them against a simulated drive
+pub(crate) trait Drive: OutputImage + Send {
+    /// Returns the device number
+    fn id(&self) -> usize;
 
-    /// Requested velocity
-    ProfileVelocity = 7,
+    /// Returns the current time, deadlines are measured against it
+    fn now(&self) -> Instant;
 
-    /// Target velocity or actual velocity
-    Velocity = 11,
+    /// Returns the number of cycles performed, see `Controller::cycle_count`
+    fn cycle_count(&self) -> u64;
 
-    /// Target torque or actual torque
-    Torque = 15,
+    /// Reads the status word of the device, see `Device::status_word`
+    ///
+    /// # Errors
+    /// Returns an error if another reference to the device exists
+    fn status_word(&mut self) -> Result<StatusWord, EthercrabError>;
 
-    /// Velocity difference
-    VelocityOffset = 17,
+    /// Borrows the input and output process image once, see `Device::with_process_image`
+    ///
+    /// # Errors
+    /// Returns an error if another reference to the device exists
+    fn with_process_image<R>(
+        &mut self,
+        read: impl FnOnce(&[u8], &[u8]) -> R,
+    ) -> Result<R, EthercrabError>;
+
+    /// Checks the status word once for waits during a motion, see `Device::check_motion`
+    fn check_motion(
+        &mut self,
+        predicate: impl Fn(StatusWord) -> bool,
+        start: Instant,
+        timeout: Duration,
+    ) -> Option<Result<StatusWord, WaitTimeout>>;
+
+    /// Waits until the next cycle exchanged the process images
+    fn next_cycle(&mut self) -> impl Future<Output = ()> + Send;
+
+    /// Sets the device to the operation mode, see `Device::set_mode`
+    fn set_mode(
+        &mut self,
+        mode: OperationMode,
+    ) -> impl Future<Output = Result<(), SetModeError>> + Send;
+
+    /// Sends a motion event to the motion event streams of the device
+    fn emit(&mut self, kind: MotionEventKind);
+
+    /// Reads a mapped input, see `Device::read_input`
+    ///
+    /// # Errors
+    /// Returns an error if another reference to the device exists
+    fn read_input<T: PdoValue>(&mut self, offset: usize) -> Result<T, EthercrabError> {
+        self.with_process_image(|inputs, _| T::read(&inputs[offset..]))
+    }
 
-    /// Torque difference
-    TorqueOffset = 21,
+    /// Waits until the status word satisfies the predicate during a motion, see
+    /// `Device::wait_for_motion`
+    ///
+    /// # Errors
+    /// See `Device::check_motion`
+    fn wait_for_motion(
+        &mut self,
+        predicate: impl Fn(StatusWord) -> bool + Send,
+        start: Instant,
+        timeout: Duration,
+    ) -> impl Future<Output = Result<StatusWord, WaitTimeout>> + Send {
+        async move {
+            loop {
+                if let Some(result) = self.check_motion(&predicate, start, timeout) {
+                    return result;
+                }
+                self.next_cycle().await;
+            }
+        }
+    }
 }
 
-/// A timeout happened
-#[derive(Debug)]
-pub struct Timeout;
+/// The maximum time enabling a device may take
+const ENABLE_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// The number of cycles resetting the fault and warning bits of a device may take
+const RESET_TIMEOUT_CYCLES: u32 = 1_000;
+
+/// The number of cycles a device may take to display a new operation mode
+const SET_MODE_TIMEOUT_CYCLES: u32 = 100;
+
+/// The number of cycles disabling a device may take by default
+const DISABLE_TIMEOUT_CYCLES: u32 = 2_000;
+
+/// The minimum time disabling a device may take by default, for short cycle times
+const MIN_DISABLE_TIMEOUT: Duration = Duration::from_secs(2);
 
 /// A generic device type.
 /// All kinds of subdevices should contain this struct.
@@ -264,6 +853,24 @@ pub struct Device<'device, 'controller: 'device, const MAX_DEVICES: usize, const
 
     /// The controller used
     controller: &'device Controller<'controller, MAX_DEVICES, PDI_LENGTH>,
+
+    /// Whether waiting on the status word should stop when the device faults
+    abort_on_fault: bool,
+
+    /// The description of the device, read on first use
+    description: Option<String>,
+
+    /// Whether the description has already been read
+    description_read: bool,
+
+    /// The status word and the cycle it was read in, reused until the next cycle
+    status_cache: Option<(u64, StatusWord)>,
+
+    /// Whether commanded motion is halted when the device is dropped
+    halt_on_drop: bool,
+
+    /// The status word found when last checking it during a motion, to log raised warnings once
+    motion_status: StatusWord,
 }
 
 impl<'device, 'controller: 'device, const MAX_DEVICES: usize, const PDI_LENGTH: usize>
@@ -286,211 +893,630 @@ impl<'device, 'controller: 'device, const MAX_DEVICES: usize, const PDI_LENGTH:
     pub async fn new(
         controller: &'controller Controller<'_, MAX_DEVICES, PDI_LENGTH>,
         device_number: usize,
+    ) -> Result<Self, EnableError> {
+        Self::new_with_timeout(controller, device_number, ENABLE_TIMEOUT).await
+    }
+
+    /// Creates a new generic device, waiting at most `enable_timeout` for each step of enabling
+    /// it. See `Device::new`.
+    ///
+    /// # Errors
+    /// Returns an error if:
+    /// - The device failed to reset
+    /// - The configuration timed out
+    /// - Failed to configure device
+    pub async fn new_with_timeout(
+        controller: &'controller Controller<'_, MAX_DEVICES, PDI_LENGTH>,
+        device_number: usize,
+        enable_timeout: Duration,
     ) -> Result<Self, EnableError> {
         if controller.verbose() {
             log::info!("Start enabling drive {device_number}");
         }
 
-        // Create a device instance
+        // Create a device instance, which releases the claim when dropped on an error
+        if !controller.claim(device_number) {
+            return Err(EnableError::AlreadyClaimed(device_number));
+        }
         let mut result = Self {
             id: device_number,
             controller,
+            abort_on_fault: true,
+            description: None,
+            description_read: false,
+            status_cache: None,
+            halt_on_drop: false,
+            motion_status: StatusWord::default(),
         };
 
-        // Reset the device
+        // Reset and enable the device
         result.reset().await.map_err(EnableError::ResetFailed)?;
+        result.enable_with_timeout(enable_timeout).await?;
+        Ok(result)
+    }
 
-        // Enable the voltage while making sure the device is in quickstop mode
-        let mut timeout = 1_000_000;
-        while !(result.get_bit(
-            StatusWordBit::VoltageEnabled as u8,
-            MappedPdo::ControlStatusWord,
-        ) && result.get_bit(StatusWordBit::QuickStop as u8, MappedPdo::ControlStatusWord))
-            && timeout > 0
-        {
-            result.set_bit(ControlBit::QuickStop as u8, MappedPdo::ControlStatusWord);
-            result.set_bit(
-                ControlBit::EnableVoltage as u8,
-                MappedPdo::ControlStatusWord,
-            );
-            controller.cycle().await;
-            timeout -= 1;
-        }
+    /// Creates a handle to a device without resetting or enabling it.
+    /// The outputs of the device aren't changed, so a device can be observed or taken over
+    /// while it's operating. Enabling the device is left to the caller.
+    ///
+    /// # Parameters
+    /// `controller`: A reference to the controller struct to communicate with the device.
+    /// `device_number`: The id of the device to communicate with
+    ///
+    /// # Errors
+    /// Returns an error if:
+    /// - The device doesn't exist
+    /// - Another handle to the device exists
+    pub fn attach(
+        controller: &'controller Controller<'_, MAX_DEVICES, PDI_LENGTH>,
+        device_number: usize,
+    ) -> Result<Self, AttachError> {
+        // Check whether the device exists and read its state
+        let status = controller
+            .group()
+            .subdevice(controller.main_device(), device_number)
+            .map(|sub_device| {
+                StatusWord::new(u16::read(
+                    &sub_device.inputs_raw()[pdo::input::STATUS_WORD..],
+                ))
+            })
+            .map_err(|error| AttachError::NotFound(device_number, error))?;
 
-        // Turn the device on and enable operation
-        while !(result.get_bit(
-            StatusWordBit::OperationEnabled as u8,
-            MappedPdo::ControlStatusWord,
-        ) && result.get_bit(
-            StatusWordBit::SwitchedOn as u8,
-            MappedPdo::ControlStatusWord,
-        )) && timeout > 0
-        {
-            timeout -= 1;
-            result.set_bit(
-                ControlBit::EnableOperation as u8,
-                MappedPdo::ControlStatusWord,
+        if !controller.claim(device_number) {
+            return Err(AttachError::AlreadyClaimed(device_number));
+        }
+        if controller.verbose() {
+            log::info!(
+                "Attached to drive {device_number} in state {:?}",
+                status.state()
             );
-            result.set_bit(ControlBit::SwitchOn as u8, MappedPdo::ControlStatusWord);
-            controller.cycle().await;
         }
 
-        // Return the device if the voltage is still enabled, stopped, and operational.
-        // Return an error otherwise.
-        if result.get_bit(
-            StatusWordBit::VoltageEnabled as u8,
-            MappedPdo::ControlStatusWord,
-        ) && result.get_bit(StatusWordBit::QuickStop as u8, MappedPdo::ControlStatusWord)
-            && result.get_bit(
-                StatusWordBit::OperationEnabled as u8,
-                MappedPdo::ControlStatusWord,
-            )
-        {
-            if controller.verbose() {
-                log::info!("Enable drive {device_number} successful");
-            }
-            Ok(result)
-        } else if timeout == 0 {
-            Err(EnableError::Timeout(device_number))
-        } else {
-            Err(EnableError::Failed(device_number))
-        }
+        Ok(Self {
+            id: device_number,
+            controller,
+            abort_on_fault: true,
+            description: None,
+            description_read: false,
+            status_cache: None,
+            halt_on_drop: false,
+            motion_status: StatusWord::default(),
+        })
     }
 
-    /// Resets the device to it's original state.
+    /// Reads the current state of the `CiA402` state machine of the device.
     ///
     /// # Errors
-    /// Returns an error if:
-    /// - The subdevice couldn't be retrieved (already in use or doesn't exist)
-    /// - Fault or warning bit was still set after multiple tries to reset the device
+    /// Returns an error if another reference to the device exists
+    pub fn cia402_state(&mut self) -> Result<Cia402State, EthercrabError> {
+        self.status_word().map(StatusWord::state)
+    }
+
+    /// Enables the voltage and operation of the device.
     ///
-    /// # Returns
-    /// `()` or error
-    pub async fn reset(&mut self) -> Result<(), ResetError> {
+    /// # Errors
+    /// Returns an error if:
+    /// - The configuration timed out
+    /// - Failed to configure device
+    /// - The device is emergency stopped
+    pub async fn enable(&mut self) -> Result<(), EnableError> {
+        self.enable_with_timeout(ENABLE_TIMEOUT).await
+    }
+
+    /// Enables the voltage and operation of the device, waiting at most `timeout` for each step.
+    ///
+    /// # Errors
+    /// Returns an error if:
+    /// - The configuration timed out
+    /// - Failed to configure device
+    /// - The device is emergency stopped
+    pub async fn enable_with_timeout(&mut self, timeout: Duration) -> Result<(), EnableError> {
+        if self.emergency_stopped() {
+            return Err(EnableError::EmergencyStopped(self.id));
+        }
+
+        // Enable the voltage while making sure the device is in quickstop mode.
+        // A failed write is detected by the state check at the end.
+        let _ = self.update_control_word(|control| {
+            control
+                .with(ControlBit::QuickStop)
+                .with(ControlBit::EnableVoltage)
+        });
+        let mut wait_result = self
+            .wait_for(
+                |status| {
+                    status.is_set(StatusWordBit::VoltageEnabled)
+                        && status.is_set(StatusWordBit::QuickStop)
+                },
+                timeout,
+            )
+            .await;
+
+        // Turn the device on and enable operation
+        if wait_result.is_ok() {
+            let _ = self.update_control_word(|control| {
+                control
+                    .with(ControlBit::EnableOperation)
+                    .with(ControlBit::SwitchOn)
+            });
+            wait_result = self
+                .wait_for(
+                    |status| {
+                        status.is_set(StatusWordBit::OperationEnabled)
+                            && status.is_set(StatusWordBit::SwitchedOn)
+                    },
+                    timeout,
+                )
+                .await;
+        }
+
+        // Return if the voltage is still enabled, stopped, and operational.
+        // Return an error otherwise.
+        let status = self.status_word().unwrap_or_default();
+        if status.is_set(StatusWordBit::VoltageEnabled)
+            && status.is_set(StatusWordBit::QuickStop)
+            && status.is_set(StatusWordBit::OperationEnabled)
+        {
+            if self.controller.verbose() {
+                log::info!("Enable drive {} successful", self.id);
+            }
+            Ok(())
+        } else if let Err(WaitTimeout::Expired(..)) = wait_result {
+            Err(EnableError::Timeout(self.id))
+        } else if let Err(WaitTimeout::EmergencyStopped(..)) = wait_result {
+            Err(EnableError::EmergencyStopped(self.id))
+        } else {
+            Err(EnableError::Failed(self.id))
+        }
+    }
+
+    /// Stops the device as fast as the drive allows.
+    /// The quick stop command is written directly into the output image and every operation
+    /// waiting on this device is aborted. Motion is only possible again after calling
+    /// `clear_emergency` and `recover`.
+    pub fn emergency_stop(&mut self) {
+        self.controller.emergency_stop(self.id);
+    }
+
+    /// Returns whether the device is emergency stopped
+    pub fn emergency_stopped(&self) -> bool {
+        self.controller.emergency_stopped(self.id)
+    }
+
+    /// Clears the emergency stop of the device.
+    /// The device stays in quick stop until `recover` is called.
+    pub fn clear_emergency(&mut self) {
+        self.controller.clear_emergency(self.id);
+    }
+
+    /// Resets and enables the device after an emergency stop or fault.
+    ///
+    /// # Errors
+    /// Returns an error if:
+    /// - The emergency stop hasn't been cleared
+    /// - The device failed to reset
+    /// - The device couldn't be enabled
+    pub async fn recover(&mut self) -> Result<(), EnableError> {
+        if self.emergency_stopped() {
+            return Err(EnableError::EmergencyStopped(self.id));
+        }
         if self.controller.verbose() {
-            log::info!("Resetting device number: {}", self.id);
+            log::info!("Recovering device {}", self.id);
         }
+        self.reset().await.map_err(EnableError::ResetFailed)?;
+        self.enable().await
+    }
 
-        // Set the outputs of the device to 0.
+    /// Returns the device number (index on the network)
+    pub const fn number(&self) -> usize {
+        self.id
+    }
+
+    /// Reads the (partial) name of the device.
+    ///
+    /// # Errors
+    /// Returns an error if another reference to the device exists
+    pub fn name(&mut self) -> Result<String, EthercrabError> {
+        Ok(self
+            .controller
+            .group()
+            .subdevice(self.controller.main_device(), self.id)?
+            .name()
+            .to_owned())
+    }
+
+    /// Reads the vendor, product, revision, and serial numbers of the device.
+    ///
+    /// # Errors
+    /// Returns an error if another reference to the device exists
+    pub fn identity(&mut self) -> Result<SubDeviceIdentity, EthercrabError> {
+        Ok(self
+            .controller
+            .group()
+            .subdevice(self.controller.main_device(), self.id)?
+            .identity())
+    }
+
+    /// Detects the kind of drive from the name and product code of the device.
+    ///
+    /// # Errors
+    /// Returns an error if another reference to the device exists
+    pub fn drive_type(&mut self) -> Result<DriveType, EthercrabError> {
+        let sub_device = self
+            .controller
+            .group()
+            .subdevice(self.controller.main_device(), self.id)?;
+        Ok(DriveType::detect(
+            sub_device.name(),
+            sub_device.identity().product_id,
+        ))
+    }
+
+    /// Reads the alias address of the device.
+    ///
+    /// # Errors
+    /// Returns an error if another reference to the device exists
+    pub fn alias_address(&mut self) -> Result<u16, EthercrabError> {
+        Ok(self
+            .controller
+            .group()
+            .subdevice(self.controller.main_device(), self.id)?
+            .alias_address())
+    }
+
+    /// Reads the configured address of the device.
+    ///
+    /// # Errors
+    /// Returns an error if another reference to the device exists
+    pub fn configured_address(&mut self) -> Result<u16, EthercrabError> {
+        Ok(self
+            .controller
+            .group()
+            .subdevice(self.controller.main_device(), self.id)?
+            .configured_address())
+    }
+
+    /// Reads the full name of the device.
+    /// The description is only read from the device once, later calls return the stored value.
+    ///
+    /// # Errors
+    /// Returns an error if:
+    /// - Another reference to the device exists
+    /// - The description couldn't be read
+    pub async fn description(&mut self) -> Result<Option<String>, EthercrabError> {
+        if self.description_read {
+            return Ok(self.description.clone());
+        }
+
+        // Read the description from the device
+        let description = self
+            .controller
+            .group()
+            .subdevice(self.controller.main_device(), self.id)?
+            .description()
+            .await?
+            .map(|description| description.as_str().to_owned());
+        self.description.clone_from(&description);
+        self.description_read = true;
+        Ok(description)
+    }
+
+    /// Reads all identifying information of the device.
+    ///
+    /// # Errors
+    /// Returns an error if another reference to the device exists
+    pub async fn info(&mut self) -> Result<DeviceInfo, EthercrabError> {
+        let description = self.description().await.ok().flatten();
+        Ok(DeviceInfo {
+            number: self.id,
+            name: self.name()?,
+            identity: self.identity()?,
+            alias_address: self.alias_address()?,
+            configured_address: self.configured_address()?,
+            description,
+        })
+    }
+
+    /// Reads an object from the object dictionary of the device over SDO.
+    ///
+    /// # Errors
+    /// Returns an error if:
+    /// - Another reference to the device exists
+    /// - The object couldn't be read
+    pub async fn read_object<T: EtherCrabWireReadSized>(
+        &mut self,
+        object: Object,
+    ) -> Result<T, EthercrabError> {
         self.controller
             .group()
-            .subdevice(self.controller.main_device(), self.id)
-            .map_err(|error| ResetError::DeviceInUse(self.id, error))?
-            .outputs_raw_mut()
-            .fill(0);
+            .subdevice(self.controller.main_device(), self.id)?
+            .sdo_read(object.index, object.sub_index)
+            .await
+    }
 
-        // Wait a cycle so inputs and outputs can be send
-        if self.controller.verbose() {
-            log::info!("Wait for empty frame device number: {}", self.id);
+    /// Reads an object from the object dictionary of the device over SDO.
+    /// Objects the device doesn't support are returned as `None` instead of an error.
+    ///
+    /// # Errors
+    /// Returns an error if:
+    /// - Another reference to the device exists
+    /// - Communication with the device failed
+    pub async fn read_optional_object<T: EtherCrabWireReadSized>(
+        &mut self,
+        object: Object,
+    ) -> Result<Option<T>, EthercrabError> {
+        match self.read_object(object).await {
+            Ok(value) => Ok(Some(value)),
+            Err(EthercrabError::Mailbox(MailboxError::Aborted { .. })) => Ok(None),
+            Err(error) => Err(error),
         }
-        self.controller.cycle().await;
+    }
 
-        // Reset the device while there is an error
-        let mut retries = 1000;
-        while self.get_error() != DeviceError::Ok && retries > 0 {
-            retries -= 1;
-            self.set_bit(ControlBit::FaultReset as u8, MappedPdo::ControlStatusWord);
-            if self.controller.verbose() {
-                log::info!("Waiting on fault device number: {}", self.id);
+    /// Writes an object to the object dictionary of the device over SDO.
+    ///
+    /// # Errors
+    /// Returns an error if:
+    /// - Another reference to the device exists
+    /// - The object couldn't be written
+    pub async fn write_object<T: EtherCrabWireWrite>(
+        &mut self,
+        object: Object,
+        value: T,
+    ) -> Result<(), EthercrabError> {
+        self.controller
+            .group()
+            .subdevice(self.controller.main_device(), self.id)?
+            .sdo_write(object.index, object.sub_index, value)
+            .await
+    }
+
+    /// Sets whether waiting on the status word should stop as soon as the device reports a fault.
+    /// This is enabled by default.
+    pub fn set_abort_on_fault(&mut self, abort: bool) {
+        self.abort_on_fault = abort;
+    }
+
+    /// Reads the status word of the device.
+    /// The status word only changes when the controller cycles, so it's read from the device
+    /// once per cycle and cached until the next cycle.
+    ///
+    /// # Errors
+    /// Returns an error if:
+    /// - Slave doesn't exist
+    /// - Another reference to the subdevice exists
+    pub fn status_word(&mut self) -> Result<StatusWord, EthercrabError> {
+        // Return the cached status word, if it was read during the current cycle
+        let cycle = self.controller.cycle_count();
+        if let Some((cached_cycle, status)) = self.status_cache {
+            if cached_cycle == cycle {
+                return Ok(status);
             }
-            self.controller.cycle().await;
-            self.unset_bit(ControlBit::FaultReset as u8, MappedPdo::ControlStatusWord);
         }
 
-        // Check whether the fault or warning bit is set, return an error if so
-        match (
-            self.get_bit(StatusWordBit::Fault as u8, MappedPdo::ControlStatusWord),
-            &&self.get_bit(StatusWordBit::Warning as u8, MappedPdo::ControlStatusWord),
-        ) {
-            (false, false) => {
-                if self.controller.verbose() {
-                    log::info!("Ressetting device number: {} done", self.id);
-                }
-                Ok(())
+        let status = StatusWord(self.read_input(pdo::input::STATUS_WORD)?);
+        self.status_cache = Some((cycle, status));
+        Ok(status)
+    }
+
+    /// Cycles until the status word of the device satisfies the predicate.
+    ///
+    /// # Parameters
+    /// `predicate`: Returns whether the requested state has been reached
+    /// `timeout`: The maximum time to wait for the requested state
+    ///
+    /// # Errors
+    /// Returns an error if:
+    /// - The timeout expired before the requested state was reached
+    /// - The device reported a fault, unless disabled with `set_abort_on_fault`
+    /// - The device has been emergency stopped
+    ///
+    /// # Returns
+    /// The status word satisfying the predicate or an error
+    pub async fn wait_for(
+        &mut self,
+        predicate: impl Fn(StatusWord) -> bool,
+        timeout: Duration,
+    ) -> Result<StatusWord, WaitTimeout> {
+        let start = Instant::now();
+        loop {
+            if let Some(result) = self.check_wait(&predicate, start, timeout) {
+                return result;
             }
-            (false, true) => Err(ResetError::ResetFailedWarning(self.id)),
-            (true, false) => Err(ResetError::ResetFailedFault(self.id)),
-            (true, true) => Err(ResetError::ResetFailed(self.id)),
+            self.controller.next_cycle().await;
         }
     }
 
-    /// Sets the specified bit in the device.
+    /// Checks the status word once for `wait_for` and futures polling the device themselves.
     ///
-    /// # Parameters
-    /// `bit`: The bit to be set
-    /// `byte`: The byte containing that bit
+    /// # Returns
+    /// `None` if the caller should keep waiting, the status word satisfying the predicate, or an
+    /// error if the device is emergency stopped, faulted, or the timeout expired
+    pub(crate) fn check_wait(
+        &mut self,
+        predicate: impl Fn(StatusWord) -> bool,
+        start: Instant,
+        timeout: Duration,
+    ) -> Option<Result<StatusWord, WaitTimeout>> {
+        let status = self.status_word().unwrap_or_default();
+        let result =
+            self.wait_conditions(start, timeout)
+                .wait_result(self.id, status, predicate(status));
+        self.keep_emergency_stop(result.as_ref());
+        result
+    }
+
+    /// Collects the conditions ending a wait besides the requested state
+    fn wait_conditions(&self, start: Instant, timeout: Duration) -> WaitConditions {
+        WaitConditions {
+            emergency_stopped: self.emergency_stopped(),
+            abort_on_fault: self.abort_on_fault,
+            expired: start.elapsed() >= timeout,
+        }
+    }
+
+    /// Makes sure the quick stop isn't overwritten by the caller, if the wait ended because the
+    /// device is emergency stopped
+    fn keep_emergency_stop(&self, result: Option<&Result<StatusWord, WaitTimeout>>) {
+        if let Some(Err(WaitTimeout::EmergencyStopped(..))) = result {
+            self.controller.emergency_stop(self.id);
+        }
+    }
+
+    /// Cycles until the status word of the device satisfies the predicate, like `wait_for`.
+    /// Faults and emergency stops don't end the wait, for waits that recover from them, like
+    /// resetting or disabling the device.
+    ///
+    /// # Errors
+    /// Returns an error if the status word couldn't be read
     ///
     /// # Returns
-    /// The new value of the byte or 0
-    #[expect(
-        clippy::needless_pass_by_ref_mut,
-        reason = "Mutable reference is used to decrease the chance of having multiple SubDevice refs at a time"
-    )]
-    fn set_bit(&mut self, mut bit: u8, byte: MappedPdo) -> u8 {
-        // Select the device
-        let Ok(mut sub_device) = self
-            .controller
-            .group()
-            .subdevice(self.controller.main_device(), self.id)
-        else {
-            return 0;
-        };
+    /// The status word satisfying the predicate, or `WaitTimeout::Expired` with the last status
+    /// word if the timeout expired first
+    async fn wait_without_aborts(
+        &mut self,
+        predicate: impl Fn(StatusWord) -> bool,
+        timeout: Duration,
+    ) -> Result<Result<StatusWord, WaitTimeout>, EthercrabError> {
+        let start = Instant::now();
+        loop {
+            let status = self.status_word()?;
+            let conditions = WaitConditions {
+                expired: start.elapsed() >= timeout,
+                ..WaitConditions::default()
+            };
+            if let Some(result) = conditions.wait_result(self.id, status, predicate(status)) {
+                return Ok(result);
+            }
+            self.controller.next_cycle().await;
+        }
+    }
 
-        // Calculate the correct byte and bit
-        let byte = (byte as usize + usize::from(bit / 8)) % sub_device.outputs_raw().len();
-        bit %= 8;
+    /// Waits until the status word satisfies the predicate during a motion.
+    /// Unlike `wait_for`, the wait always stops when the drive reports a fault or leaves the
+    /// operation enabled state (quick stop, switch on disabled, safe torque off). Warnings don't
+    /// stop the wait, they are logged once when raised and reported to the `on_fault` handlers.
+    /// The timeout is counted from `start`, so multiple waits can share one deadline.
+    ///
+    /// # Errors
+    /// See `check_motion`
+    pub(crate) async fn wait_for_motion(
+        &mut self,
+        predicate: impl Fn(StatusWord) -> bool + Send,
+        start: Instant,
+        timeout: Duration,
+    ) -> Result<StatusWord, WaitTimeout> {
+        Drive::wait_for_motion(self, predicate, start, timeout).await
+    }
 
-        // Set the requested bit and return the new value of that byte
-        let outputs = sub_device.outputs_raw_mut();
-        outputs[byte] |= 1 << bit;
-        outputs[byte]
+    /// Checks the status word once for waits during a motion.
+    ///
+    /// # Returns
+    /// `None` if the caller should keep waiting, the status word satisfying the predicate, or an
+    /// error if the device is emergency stopped, faulted, left the operation enabled state, or
+    /// the timeout expired
+    pub(crate) fn check_motion(
+        &mut self,
+        predicate: impl Fn(StatusWord) -> bool,
+        start: Instant,
+        timeout: Duration,
+    ) -> Option<Result<StatusWord, WaitTimeout>> {
+        let status = self.status_word().unwrap_or_default();
+
+        // Warnings don't end the motion, log them once when they are raised
+        if status.is_set(StatusWordBit::Warning)
+            && !self.motion_status.is_set(StatusWordBit::Warning)
+        {
+            log::warn!(
+                "Device {} reported a warning during a motion, status word {:#06x}",
+                self.id,
+                status.raw()
+            );
+        }
+        self.motion_status = status;
+
+        let result =
+            self.wait_conditions(start, timeout)
+                .motion_result(self.id, status, predicate(status));
+        self.keep_emergency_stop(result.as_ref());
+        result
     }
 
-    /// Clears the specified bit in the device
+    /// Resets the device to it's original state.
     ///
-    /// # Parameters
-    /// `bit`: The bit to clear
-    /// `byte`: The byte containing that bit
+    /// # Errors
+    /// Returns an error if:
+    /// - The subdevice couldn't be retrieved (already in use or doesn't exist)
+    /// - The fault or warning bit was still set after 1000 cycles of resetting the device
     ///
     /// # Returns
-    /// The new value of that byte or 0
-    #[expect(
-        clippy::needless_pass_by_ref_mut,
-        reason = "Mutable reference is used to decrease the chance of having multiple SubDevice refs at a time"
-    )]
-    fn unset_bit(&mut self, mut bit: u8, byte: MappedPdo) -> u8 {
-        // Select the device
-        let Ok(mut sub_device) = self
-            .controller
+    /// `()` or error
+    pub async fn reset(&mut self) -> Result<(), ResetError> {
+        if self.controller.verbose() {
+            log::info!("Resetting device number: {}", self.id);
+        }
+
+        // Set the outputs of the device to 0.
+        self.controller
             .group()
             .subdevice(self.controller.main_device(), self.id)
-        else {
-            return 0;
-        };
+            .map_err(|error| ResetError::DeviceInUse(self.id, error))?
+            .outputs_raw_mut()
+            .fill(0);
 
-        // Calculate the correct byte and bit
-        let byte = (byte as usize + usize::from(bit / 8)) % sub_device.outputs_raw().len();
-        bit %= 8;
+        // Wait a cycle so inputs and outputs can be send
+        if self.controller.verbose() {
+            log::info!("Wait for empty frame device number: {}", self.id);
+        }
+        self.controller.next_cycle().await;
+
+        // Hold the fault reset bit until the fault and warning bits are cleared
+        let timeout = self.controller.cycle_time() * RESET_TIMEOUT_CYCLES;
+        self.update_control_word(|control| control.with(ControlBit::FaultReset))
+            .map_err(|error| ResetError::DeviceInUse(self.id, error))?;
+        if self.controller.verbose() {
+            log::info!("Waiting on fault device number: {}", self.id);
+        }
+        let result = self
+            .wait_without_aborts(
+                |status| DeviceError::from_status(status) == DeviceError::Ok,
+                timeout,
+            )
+            .await;
+        self.update_control_word(|control| control.without(ControlBit::FaultReset))
+            .map_err(|error| ResetError::DeviceInUse(self.id, error))?;
 
-        // Clear the requested bit and return the new value of that byte
-        let outputs = sub_device.outputs_raw_mut();
-        outputs[byte] &= !(1 << bit);
-        outputs[byte]
+        // Return an error if the fault or warning bit is still set
+        match result.map_err(|error| ResetError::DeviceInUse(self.id, error))? {
+            Ok(_) => {
+                if self.controller.verbose() {
+                    log::info!("Ressetting device number: {} done", self.id);
+                }
+                Ok(())
+            }
+            Err(timeout) => Err(match DeviceError::from_status(timeout.status()) {
+                DeviceError::Warning => ResetError::ResetFailedWarning(timeout),
+                DeviceError::Fault => ResetError::ResetFailedFault(timeout),
+                DeviceError::FaultAndWarning | DeviceError::Ok => ResetError::ResetFailed(timeout),
+            }),
+        }
     }
 
-    #[expect(
-        clippy::needless_pass_by_ref_mut,
-        reason = "Mutable reference is used to decrease the chance of having multiple SubDevice refs at a time"
-    )]
     /// Reads the specified bit from the device
     ///
     /// # Parameters
     /// `bit`: The bit to read
-    /// `byte`: The byte containing that bit
+    /// `offset`: The offset of the input containing that bit
     ///
     /// # Returns
     /// The value of the requested bit
-    fn get_bit(&mut self, mut bit: u8, byte: MappedPdo) -> bool {
+    fn get_bit(&mut self, mut bit: u8, offset: usize) -> bool {
+        // Read bits of the status word from the status cache
+        if offset == pdo::input::STATUS_WORD && bit < 16 {
+            return self
+                .status_word()
+                .is_ok_and(|status| status.raw() & (1 << bit) != 0);
+        }
+
         // Select the device
         let Ok(sub_device) = self
             .controller
@@ -501,7 +1527,7 @@ impl<'device, 'controller: 'device, const MAX_DEVICES: usize, const PDI_LENGTH:
         };
 
         // Calculate the correct byte and bit
-        let byte = (byte as usize + usize::from(bit / 8)) % sub_device.inputs_raw().len();
+        let byte = (offset + usize::from(bit / 8)) % sub_device.inputs_raw().len();
         bit %= 8;
 
         // Read and return the requested bit of the requested byte
@@ -516,7 +1542,7 @@ impl<'device, 'controller: 'device, const MAX_DEVICES: usize, const PDI_LENGTH:
         // Check and return whether operation is enabled
         self.get_bit(
             StatusWordBit::OperationEnabled as u8,
-            MappedPdo::ControlStatusWord,
+            pdo::input::STATUS_WORD,
         )
     }
 
@@ -525,14 +1551,81 @@ impl<'device, 'controller: 'device, const MAX_DEVICES: usize, const PDI_LENGTH:
     /// # Returns
     /// Returns the new value of the control bytes
     fn unset_control(&mut self) -> u16 {
-        // Clear the control bits and store the bytes
-        self.unset_bit(ControlBit::Control4 as u8, MappedPdo::ControlStatusWord);
-        self.unset_bit(ControlBit::Control5 as u8, MappedPdo::ControlStatusWord);
-        let byte0 = self.unset_bit(ControlBit::Control6 as u8, MappedPdo::ControlStatusWord);
-        let byte1 = self.unset_bit(ControlBit::Control9 as u8, MappedPdo::ControlStatusWord);
+        self.update_control_word(ControlWord::without_control)
+            .map_or(0, ControlWord::raw)
+    }
+
+    /// Updates the control word of the device with a single access to the process image,
+    /// so no intermediate control word can be sent.
+    ///
+    /// # Parameters
+    /// `update`: Computes the new control word from the current control word
+    ///
+    /// # Errors
+    /// Returns an error if another reference to the device exists
+    ///
+    /// # Returns
+    /// The new control word
+    pub fn update_control_word(
+        &mut self,
+        update: impl FnOnce(ControlWord) -> ControlWord,
+    ) -> Result<ControlWord, EthercrabError> {
+        OutputImage::update_control_word(self, update)
+    }
+
+    /// Borrows the output process image of the device once and passes it to the closure.
+    /// Use this to update multiple outputs at once, so every cycle sends a consistent image.
+    /// The offsets of the mapped objects can be found in `pdo::output`.
+    ///
+    /// # Errors
+    /// Returns an error if another reference to the device exists
+    ///
+    /// # Returns
+    /// The value returned by the closure
+    pub fn apply_outputs<R>(
+        &mut self,
+        update: impl FnOnce(&mut [u8]) -> R,
+    ) -> Result<R, EthercrabError> {
+        let mut sub_device = self
+            .controller
+            .group()
+            .subdevice(self.controller.main_device(), self.id)?;
+        Ok(update(sub_device.outputs_raw_mut()))
+    }
+
+    /// Borrows the input process image of the device once and passes it to the closure.
+    /// The offsets of the mapped objects can be found in `pdo::input`.
+    ///
+    /// # Errors
+    /// Returns an error if another reference to the device exists
+    ///
+    /// # Returns
+    /// The value returned by the closure
+    pub fn with_inputs<R>(&mut self, read: impl FnOnce(&[u8]) -> R) -> Result<R, EthercrabError> {
+        let sub_device = self
+            .controller
+            .group()
+            .subdevice(self.controller.main_device(), self.id)?;
+        Ok(read(sub_device.inputs_raw()))
+    }
 
-        // Return the bytes as a single unsigned 16-bit integer
-        (u16::from(byte1) << 8) | u16::from(byte0)
+    /// Borrows the input and output process image of the device once and passes them to the
+    /// closure. The offsets of the mapped objects can be found in `pdo::input` and `pdo::output`.
+    ///
+    /// # Errors
+    /// Returns an error if another reference to the device exists
+    ///
+    /// # Returns
+    /// The value returned by the closure
+    pub fn with_process_image<R>(
+        &mut self,
+        read: impl FnOnce(&[u8], &[u8]) -> R,
+    ) -> Result<R, EthercrabError> {
+        let sub_device = self
+            .controller
+            .group()
+            .subdevice(self.controller.main_device(), self.id)?;
+        Ok(read(sub_device.inputs_raw(), sub_device.outputs_raw()))
     }
 
     /// Reads the device error flags
@@ -542,8 +1635,8 @@ impl<'device, 'controller: 'device, const MAX_DEVICES: usize, const PDI_LENGTH:
     pub fn get_error(&mut self) -> DeviceError {
         // Check whether the fault or warning bit is set and return the correct error
         match (
-            self.get_bit(StatusWordBit::Fault as u8, MappedPdo::ControlStatusWord),
-            self.get_bit(StatusWordBit::Warning as u8, MappedPdo::ControlStatusWord),
+            self.get_bit(StatusWordBit::Fault as u8, pdo::input::STATUS_WORD),
+            self.get_bit(StatusWordBit::Warning as u8, pdo::input::STATUS_WORD),
         ) {
             (false, false) => DeviceError::Ok,
             (false, true) => DeviceError::Warning,
@@ -575,105 +1668,457 @@ impl<'device, 'controller: 'device, const MAX_DEVICES: usize, const PDI_LENGTH:
         Ok(u16::from(inputs[byte]) | (u16::from(inputs[byte + 1]) << 8))
     }
 
-    /// Sets a the device to the specified operation mode.
+    /// Reads a value from the input process image of the device.
     ///
     /// # Errors
-    /// Returns an error if the mode couldn't be set
-    ///
-    /// # Returns
-    /// `()` or error
-    async fn set_mode(&mut self, mode: OperationMode) -> Result<(), SetModeError> {
-        let mut timeout = 100;
-
-        // Wait for mode to get active
-        while timeout > 0
-            && self
-                .controller
-                .group()
-                .subdevice(self.controller.main_device(), self.id)
-                .is_ok_and(|sub_device| {
-                    sub_device.inputs_raw()[MappedPdo::OperationMode as usize] != mode as u8
-                })
-        {
-            timeout -= 1;
+    /// Returns an error if another reference to the device exists
+    pub fn read_input<T: PdoValue>(&mut self, offset: usize) -> Result<T, EthercrabError> {
+        let sub_device = self
+            .controller
+            .group()
+            .subdevice(self.controller.main_device(), self.id)?;
+        Ok(T::read(&sub_device.inputs_raw()[offset..]))
+    }
 
-            // Clear the control bits
-            self.unset_control();
+    /// Reads a value back from the output process image of the device.
+    ///
+    /// # Errors
+    /// Returns an error if another reference to the device exists
+    pub fn read_output<T: PdoValue>(&mut self, offset: usize) -> Result<T, EthercrabError> {
+        let sub_device = self
+            .controller
+            .group()
+            .subdevice(self.controller.main_device(), self.id)?;
+        Ok(T::read(&sub_device.outputs_raw()[offset..]))
+    }
 
-            // Take the requested device
-            if let Ok(mut sub_device) = self
-                .controller
-                .group()
-                .subdevice(self.controller.main_device(), self.id)
-            {
-                // Set the requested mode
-                sub_device.outputs_raw_mut()[MappedPdo::OperationMode as usize] = mode as u8;
-            }
+    /// Writes a value to the output process image of the device, send during the next cycle.
+    ///
+    /// # Errors
+    /// Returns an error if another reference to the device exists
+    pub fn write_output<T: PdoValue>(
+        &mut self,
+        offset: usize,
+        value: T,
+    ) -> Result<(), EthercrabError> {
+        let mut sub_device = self
+            .controller
+            .group()
+            .subdevice(self.controller.main_device(), self.id)?;
+        value.write(&mut sub_device.outputs_raw_mut()[offset..]);
+        Ok(())
+    }
 
-            // Execute an update cycle
-            self.controller.cycle().await;
+    /// Reads an object from the process image if it's mapped, over SDO otherwise.
+    ///
+    /// # Errors
+    /// Returns an error if:
+    /// - Another reference to the device exists
+    /// - The object isn't mapped and couldn't be read over SDO
+    pub async fn read_mapped_object<T: PdoValue + EtherCrabWireReadSized>(
+        &mut self,
+        object: Object,
+    ) -> Result<T, EthercrabError> {
+        if let Some(offset) = pdo::input_offset(object) {
+            self.read_input(offset)
+        } else if let Some(offset) = pdo::output_offset(object) {
+            self.read_output(offset)
+        } else {
+            self.read_object(object).await
         }
+    }
 
-        // Select the device
-        if self
-            .controller
-            .group()
-            .subdevice(self.controller.main_device(), self.id)
-            .is_ok_and(|sub_device| {
-                sub_device.inputs_raw()[MappedPdo::OperationMode as usize] != mode as u8
-            })
-        {
-            return Err(SetModeError(self.id, mode));
+    /// Writes an object to the process image if it's mapped, over SDO otherwise.
+    /// Objects written over SDO take effect after the SDO transfer, not with the next cycle.
+    ///
+    /// # Errors
+    /// Returns an error if:
+    /// - Another reference to the device exists
+    /// - The object isn't mapped and couldn't be written over SDO
+    pub async fn write_mapped_object<T: PdoValue + EtherCrabWireWrite>(
+        &mut self,
+        object: Object,
+        value: T,
+    ) -> Result<(), EthercrabError> {
+        if let Some(offset) = pdo::output_offset(object) {
+            self.write_output(offset, value)
+        } else {
+            self.write_object(object, value).await
         }
+    }
+
+    /// Sets a the device to the specified operation mode.
+    ///
+    /// # Errors
+    /// Returns an error if:
+    /// - The mode couldn't be written to the outputs
+    /// - The device didn't display the mode within 100 cycles
+    /// - The device faulted or has been emergency stopped while waiting
+    ///
+    /// # Returns
+    /// `()` or error
+    async fn set_mode(&mut self, mode: OperationMode) -> Result<(), SetModeError> {
+        // Clear the control bits and set the requested mode in the same cycle
+        self.apply_outputs(|outputs| {
+            let control = ControlWord::new(u16::read(&outputs[pdo::output::CONTROL_WORD..]));
+            control
+                .without_control()
+                .raw()
+                .write(&mut outputs[pdo::output::CONTROL_WORD..]);
+            outputs[pdo::output::MODES_OF_OPERATION] = mode as u8;
+        })
+        .map_err(|error| SetModeError::Ethercat(self.id, mode, error))?;
+
+        // Wait for the mode to get active
+        let (controller, id) = (self.controller, self.id);
+        let timeout = controller.cycle_time() * SET_MODE_TIMEOUT_CYCLES;
+        self.wait_for(
+            |_| {
+                controller
+                    .group()
+                    .subdevice(controller.main_device(), id)
+                    .is_ok_and(|sub_device| {
+                        sub_device.inputs_raw()[pdo::input::MODES_OF_OPERATION_DISPLAY]
+                            == mode as u8
+                    })
+            },
+            timeout,
+        )
+        .await
+        .map_err(|error| SetModeError::Wait(mode, error))?;
 
-        // Clear the control bits
-        self.unset_control();
         if self.controller.verbose() {
             log::info!("Arrived in mode {mode:?}");
         }
         Ok(())
     }
 
-    /// Disables the device after use.
+    /// Disables the device after use, waiting at most 2000 cycles and at least 2 seconds.
     ///
     /// # Errors
     /// Returns an error if the device didn't get disabled.
-    pub async fn disable(mut self) -> Result<(), Timeout> {
-        // Clear the enable operation bit to disable operation
-        self.unset_bit(
-            ControlBit::EnableOperation as u8,
-            MappedPdo::ControlStatusWord,
-        );
+    pub async fn disable(self) -> Result<DisableReport, DisableError> {
+        let timeout =
+            (self.controller.cycle_time() * DISABLE_TIMEOUT_CYCLES).max(MIN_DISABLE_TIMEOUT);
+        self.disable_with_timeout(timeout).await
+    }
 
-        // Clear the switch on bit to turn the device off
-        self.unset_bit(ControlBit::SwitchOn as u8, MappedPdo::ControlStatusWord);
+    /// Disables the device after use, waiting at most `timeout` until operation is disabled.
+    /// Faults and emergency stops don't end the wait, a drive that is still stopping keeps
+    /// operation enabled until it stands still.
+    ///
+    /// # Errors
+    /// Returns an error if:
+    /// - The control word couldn't be written or the status word couldn't be read
+    /// - The device didn't get disabled in time, containing the last status word
+    pub async fn disable_with_timeout(
+        mut self,
+        timeout: Duration,
+    ) -> Result<DisableReport, DisableError> {
+        // Nothing is commanded anymore, so there's nothing to halt when dropped
+        self.halt_on_drop = false;
+        let start = Instant::now();
+        let id = self.id;
+
+        // Clear the enable operation and switch on bits to disable operation and turn the device
+        // off
+        self.update_control_word(|control| {
+            control
+                .without(ControlBit::EnableOperation)
+                .without(ControlBit::SwitchOn)
+        })
+        .map_err(|error| DisableError::Ethercat(id, error))?;
 
         // Perform an update cycle
-        self.controller.cycle().await;
+        self.controller.next_cycle().await;
 
         // Disable the quick stop and disable the voltage
-        self.unset_bit(ControlBit::QuickStop as u8, MappedPdo::ControlStatusWord);
-        self.unset_bit(
-            ControlBit::EnableVoltage as u8,
-            MappedPdo::ControlStatusWord,
-        );
+        self.update_control_word(|control| {
+            control
+                .without(ControlBit::QuickStop)
+                .without(ControlBit::EnableVoltage)
+        })
+        .map_err(|error| DisableError::Ethercat(id, error))?;
+
+        // Wait until the device is disabled, return an error on timeout
+        match self
+            .wait_without_aborts(
+                |status| !status.is_set(StatusWordBit::OperationEnabled),
+                timeout,
+            )
+            .await
+            .map_err(|error| DisableError::Ethercat(id, error))?
+        {
+            Ok(status) => Ok(DisableReport {
+                state: status.state(),
+                status,
+                elapsed: start.elapsed(),
+            }),
+            Err(timeout) => Err(DisableError::Wait {
+                timeout,
+                waited: start.elapsed(),
+            }),
+        }
+    }
+}
 
-        // Wait until the device is disabled
-        let mut timeout = 1_000;
-        while self.get_bit(
-            StatusWordBit::OperationEnabled as u8,
-            MappedPdo::ControlStatusWord,
-        ) && timeout > 0
+impl<const MAX_DEVICES: usize, const PDI_LENGTH: usize> OutputImage
+    for Device<'_, '_, MAX_DEVICES, PDI_LENGTH>
+{
+    fn apply_outputs<R>(
+        &mut self,
+        update: impl FnOnce(&mut [u8]) -> R,
+    ) -> Result<R, EthercrabError> {
+        Self::apply_outputs(self, update)
+    }
+}
+
+impl<const MAX_DEVICES: usize, const PDI_LENGTH: usize> Drive
+    for Device<'_, '_, MAX_DEVICES, PDI_LENGTH>
+{
+    fn id(&self) -> usize {
+        self.id
+    }
+
+    fn now(&self) -> Instant {
+        Instant::now()
+    }
+
+    fn cycle_count(&self) -> u64 {
+        self.controller.cycle_count()
+    }
+
+    fn status_word(&mut self) -> Result<StatusWord, EthercrabError> {
+        Self::status_word(self)
+    }
+
+    fn with_process_image<R>(
+        &mut self,
+        read: impl FnOnce(&[u8], &[u8]) -> R,
+    ) -> Result<R, EthercrabError> {
+        Self::with_process_image(self, read)
+    }
+
+    fn check_motion(
+        &mut self,
+        predicate: impl Fn(StatusWord) -> bool,
+        start: Instant,
+        timeout: Duration,
+    ) -> Option<Result<StatusWord, WaitTimeout>> {
+        Self::check_motion(self, predicate, start, timeout)
+    }
+
+    fn next_cycle(&mut self) -> impl Future<Output = ()> + Send {
+        self.controller.next_cycle()
+    }
+
+    fn set_mode(
+        &mut self,
+        mode: OperationMode,
+    ) -> impl Future<Output = Result<(), SetModeError>> + Send {
+        Self::set_mode(self, mode)
+    }
+
+    fn emit(&mut self, kind: MotionEventKind) {
+        self.controller.emit_motion_event(self.id, kind);
+    }
+}
+
+impl<const MAX_DEVICES: usize, const PDI_LENGTH: usize> Drop
+    for Device<'_, '_, MAX_DEVICES, PDI_LENGTH>
+{
+    fn drop(&mut self) {
+        // Halt commanded motion, the stop is sent with the next cycle as drop can't await it
+        if self.halt_on_drop
+            && self.halt_motion().is_ok_and(|changed| changed)
+            && !self.controller.is_cycling()
         {
-            timeout -= 1;
-            self.controller.cycle().await;
+            log::warn!(
+                "Device {} was dropped while commanding motion, the halt is only sent once the controller cycles",
+                self.id
+            );
         }
 
-        // Return an error on timeout
-        if timeout == 0 {
-            Err(Timeout)
-        } else {
-            Ok(())
+        // Allow a new handle to the device to be created
+        self.controller.release(self.id);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    //! Tests of the accesses to the output process image
+
+    use super::*;
+    use simulation::SimulatedDrive;
+
+    /// An output process image counting how often it has been borrowed
+    struct CountingImage {
+        /// The output process image
+        outputs: Vec<u8>,
+
+        /// The number of times the output process image has been borrowed
+        borrows: usize,
+    }
+
+    impl CountingImage {
+        /// Creates an output process image of the mapping holding a target position
+        fn new(target: i32) -> Self {
+            let size = pdo::OUTPUTS
+                .iter()
+                .map(|entry| (entry & 0xFF) as usize / 8)
+                .sum();
+            let mut outputs = vec![0; size];
+            target.write(&mut outputs[pdo::output::TARGET_POSITION..]);
+            Self {
+                outputs,
+                borrows: 0,
+            }
         }
+
+        /// Returns the control word in the output process image
+        fn control_word(&self) -> u16 {
+            u16::read(&self.outputs[pdo::output::CONTROL_WORD..])
+        }
+    }
+
+    impl OutputImage for CountingImage {
+        fn apply_outputs<R>(
+            &mut self,
+            update: impl FnOnce(&mut [u8]) -> R,
+        ) -> Result<R, EthercrabError> {
+            self.borrows += 1;
+            Ok(update(&mut self.outputs))
+        }
+    }
+
+    /// Every control word update of the enable sequence borrows the outputs once and only
+    /// changes the control word
+    #[test]
+    fn control_word_update_borrows_once() {
+        let mut image = CountingImage::new(-123_456);
+        let untouched = image.outputs.clone();
+
+        // Enable the voltage in quick stop, then switch on and enable operation
+        let control = image
+            .update_control_word(|control| {
+                control
+                    .with(ControlBit::QuickStop)
+                    .with(ControlBit::EnableVoltage)
+            })
+            .unwrap();
+        assert_eq!(control.raw(), 0x0006);
+        assert_eq!(image.borrows, 1);
+        image
+            .update_control_word(|control| {
+                control
+                    .with(ControlBit::EnableOperation)
+                    .with(ControlBit::SwitchOn)
+            })
+            .unwrap();
+        assert_eq!(image.control_word(), 0x000F);
+        assert_eq!(image.borrows, 2);
+
+        // Start a setpoint and clear the control bits again
+        image
+            .update_control_word(|control| {
+                control
+                    .with(ControlBit::Control4)
+                    .with(ControlBit::Control5)
+            })
+            .unwrap();
+        assert_eq!(image.control_word(), 0x003F);
+        image
+            .update_control_word(ControlWord::without_control)
+            .unwrap();
+        assert_eq!(image.control_word(), 0x000F);
+        assert_eq!(image.borrows, 4);
+
+        // Only the control word changed
+        let control = pdo::output::CONTROL_WORD..pdo::output::CONTROL_WORD + 2;
+        for (index, (byte, before)) in image.outputs.iter().zip(&untouched).enumerate() {
+            if !control.contains(&index) {
+                assert_eq!(byte, before, "Byte {index} changed");
+            }
+        }
+    }
+
+    /// A composite update of the setpoint and the control word borrows the outputs once
+    #[test]
+    fn composite_update_borrows_once() {
+        let mut image = CountingImage::new(0);
+        image
+            .apply_outputs(|outputs| {
+                1_000_i32.write(&mut outputs[pdo::output::TARGET_POSITION..]);
+                500_u32.write(&mut outputs[pdo::output::PROFILE_VELOCITY..]);
+                ControlWord::new(u16::read(&outputs[pdo::output::CONTROL_WORD..]))
+                    .with(ControlBit::Control4)
+                    .raw()
+                    .write(&mut outputs[pdo::output::CONTROL_WORD..]);
+            })
+            .unwrap();
+        assert_eq!(image.borrows, 1);
+        assert_eq!(
+            i32::read(&image.outputs[pdo::output::TARGET_POSITION..]),
+            1_000
+        );
+        assert_eq!(
+            u32::read(&image.outputs[pdo::output::PROFILE_VELOCITY..]),
+            500
+        );
+        assert_eq!(image.control_word(), 0x0010);
+    }
+
+    /// Halting a drive executing a setpoint, like dropping the device does, only sets the halt
+    /// bit and clears the start bits in the outputs, after which the drive stops
+    #[test]
+    fn halt_stops_commanded_motion() {
+        let mut drive = SimulatedDrive::new(100);
+        drive.set_mode(OperationMode::ProfilePosition);
+        drive
+            .apply_outputs(|outputs| {
+                10_000_i32.write(&mut outputs[pdo::output::TARGET_POSITION..]);
+            })
+            .unwrap();
+        drive
+            .update_control_word(|control| {
+                control
+                    .with(ControlBit::Control4)
+                    .with(ControlBit::Control5)
+            })
+            .unwrap();
+        for _ in 0..5 {
+            drive.cycle();
+        }
+        assert_eq!(drive.position(), 500);
+
+        let before = drive.outputs().to_vec();
+        assert!(drive.halt_motion().unwrap());
+        let outputs = drive.outputs();
+        assert_eq!(u16::read(&outputs[pdo::output::CONTROL_WORD..]), 0x010F);
+        let control_end = pdo::output::CONTROL_WORD + 2;
+        assert_eq!(outputs[control_end..], before[control_end..]);
+
+        // The next cycle carries the halt, the drive stops and stays halted
+        drive.cycle();
+        drive.cycle();
+        assert_eq!(drive.position(), 500);
+        assert!(drive.status().is_set(StatusWordBit::MotionComplete));
+        assert!(!drive.halt_motion().unwrap());
+    }
+
+    /// Halting a jog clears the jog direction bit
+    #[test]
+    fn halt_clears_jog_direction() {
+        let mut drive = SimulatedDrive::new(100);
+        drive.set_mode(OperationMode::Jog);
+        drive
+            .update_control_word(|control| control.with(ControlBit::Control5))
+            .unwrap();
+
+        assert!(drive.halt_motion().unwrap());
+        assert_eq!(
+            u16::read(&drive.outputs()[pdo::output::CONTROL_WORD..]),
+            0x010F
+        );
     }
 }
@@ -0,0 +1,417 @@
+//! This module contains the `AnalogIo` type, for analog input and output terminals that aren't
+//! drives, like 4-20 mA pressure sensors and 0-10 V setpoints.
+//!
+//! The channel map is declared by the application, as the layout of the process image differs
+//! between terminals. Every channel converts between the raw integer in the process image and
+//! engineering units with `value = raw * scale + bias`. Like `DigitalIo`, the terminal isn't
+//! reset or enabled and is only accessed through the process image.
+
+use super::digital_io::process_image_layout;
+use crate::{controller::Controller, pdo::PdoValue};
+use core::fmt::{self, Debug, Formatter};
+use ethercrab::error::Error as EthercrabError;
+
+/// The process image a channel is located in
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum ChannelDirection {
+    /// The channel is read from the inputs
+    Input,
+
+    /// The channel is written to the outputs
+    Output,
+}
+
+/// A channel of an analog terminal
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct AnalogChannel {
+    /// Whether the channel is an input or an output
+    pub direction: ChannelDirection,
+
+    /// The byte offset of the raw value in the process image of the direction
+    pub offset: usize,
+
+    /// The width of the raw value in bits, 8, 16 or 32
+    pub width: u8,
+
+    /// Whether the raw value is signed
+    pub signed: bool,
+
+    /// The engineering units per raw increment
+    pub scale: f64,
+
+    /// The value in engineering units at a raw value of zero
+    pub bias: f64,
+
+    /// The engineering unit, only used for display
+    pub unit: &'static str,
+}
+
+impl AnalogChannel {
+    /// Creates a signed 16-bit input channel without bias
+    pub const fn input(offset: usize, scale: f64, unit: &'static str) -> Self {
+        Self {
+            direction: ChannelDirection::Input,
+            offset,
+            width: 16,
+            signed: true,
+            scale,
+            bias: 0.0,
+            unit,
+        }
+    }
+
+    /// Creates a signed 16-bit output channel without bias
+    pub const fn output(offset: usize, scale: f64, unit: &'static str) -> Self {
+        Self {
+            direction: ChannelDirection::Output,
+            offset,
+            width: 16,
+            signed: true,
+            scale,
+            bias: 0.0,
+            unit,
+        }
+    }
+
+    /// Sets the width and signedness of the raw value
+    #[must_use]
+    pub const fn with_format(mut self, width: u8, signed: bool) -> Self {
+        self.width = width;
+        self.signed = signed;
+        self
+    }
+
+    /// Sets the value in engineering units at a raw value of zero
+    #[must_use]
+    pub const fn with_bias(mut self, bias: f64) -> Self {
+        self.bias = bias;
+        self
+    }
+
+    /// Returns the smallest and largest raw value the channel can hold.
+    /// Unsupported widths are handled as 32 bits.
+    pub const fn raw_range(&self) -> (i64, i64) {
+        let bits = match self.width {
+            8 | 16 => self.width,
+            _ => 32,
+        };
+        if self.signed {
+            (-(1 << (bits - 1)), (1 << (bits - 1)) - 1)
+        } else {
+            (0, (1 << bits) - 1)
+        }
+    }
+
+    /// Converts a raw value to engineering units
+    #[expect(
+        clippy::cast_precision_loss,
+        reason = "Raw values are at most 32 bits wide, which an f64 represents exactly"
+    )]
+    pub fn to_value(self, raw: i64) -> f64 {
+        (raw as f64).mul_add(self.scale, self.bias)
+    }
+
+    /// Converts a value in engineering units to the nearest raw value, clamped to the range
+    /// of the channel
+    #[expect(
+        clippy::cast_possible_truncation,
+        clippy::cast_precision_loss,
+        reason = "The raw range is at most 32 bits wide and the value is rounded and clamped to \
+                  it before the cast, NaN is converted to zero"
+    )]
+    pub fn to_raw(self, value: f64) -> i64 {
+        let (min, max) = self.raw_range();
+        ((value - self.bias) / self.scale)
+            .round()
+            .clamp(min as f64, max as f64) as i64
+    }
+
+    /// Returns the number of bytes of the raw value
+    fn bytes(&self) -> usize {
+        usize::from(self.width / 8)
+    }
+
+    /// Reads the raw value from the start of the bytes
+    fn read_raw(&self, bytes: &[u8]) -> i64 {
+        match (self.width, self.signed) {
+            (8, true) => i8::read(bytes).into(),
+            (8, false) => u8::read(bytes).into(),
+            (16, true) => i16::read(bytes).into(),
+            (16, false) => u16::read(bytes).into(),
+            (_, true) => i32::read(bytes).into(),
+            (_, false) => u32::read(bytes).into(),
+        }
+    }
+
+    /// Writes the raw value to the start of the bytes, the value has to be within the raw range
+    #[expect(
+        clippy::cast_possible_truncation,
+        clippy::cast_sign_loss,
+        reason = "The raw value is clamped to the raw range of the channel"
+    )]
+    fn write_raw(&self, raw: i64, bytes: &mut [u8]) {
+        match (self.width, self.signed) {
+            (8, true) => (raw as i8).write(bytes),
+            (8, false) => (raw as u8).write(bytes),
+            (16, true) => (raw as i16).write(bytes),
+            (16, false) => (raw as u16).write(bytes),
+            (_, true) => (raw as i32).write(bytes),
+            (_, false) => (raw as u32).write(bytes),
+        }
+    }
+}
+
+/// An error returned while accessing an analog I/O terminal
+pub enum AnalogIoError {
+    /// The device doesn't exist or couldn't be accessed
+    NotFound(usize, EthercrabError),
+
+    /// Another handle to the device already exists
+    AlreadyClaimed(usize),
+
+    /// The width of a channel isn't 8, 16 or 32 bits
+    InvalidWidth {
+        /// The device number
+        device: usize,
+
+        /// The index of the channel
+        channel: usize,
+
+        /// The requested width in bits
+        width: u8,
+    },
+
+    /// The scale of a channel is zero or not finite
+    InvalidScale(usize, usize),
+
+    /// A channel extends beyond the process image of the device
+    ChannelOutOfRange {
+        /// The device number
+        device: usize,
+
+        /// The index of the channel
+        channel: usize,
+
+        /// The number of bytes the channel requires
+        required: usize,
+
+        /// The size of the process image in bytes
+        available: usize,
+    },
+
+    /// The requested channel doesn't exist
+    NoChannel(usize, usize),
+
+    /// The requested channel is an input and can't be written
+    NotAnOutput(usize, usize),
+
+    /// Another reference to the device exists
+    Ethercat(usize, EthercrabError),
+}
+
+impl Debug for AnalogIoError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::NotFound(device, error) => {
+                write!(f, "Failed to access I/O device {device}: {error}")
+            }
+            Self::AlreadyClaimed(device) => {
+                write!(f, "I/O device {device} is already used by another handle")
+            }
+            Self::InvalidWidth {
+                device,
+                channel,
+                width,
+            } => write!(
+                f,
+                "Channel {channel} of I/O device {device} is {width} bits wide, only 8, 16 and \
+                 32 bits are supported"
+            ),
+            Self::InvalidScale(device, channel) => write!(
+                f,
+                "The scale of channel {channel} of I/O device {device} has to be finite and not \
+                 zero"
+            ),
+            Self::ChannelOutOfRange {
+                device,
+                channel,
+                required,
+                available,
+            } => write!(
+                f,
+                "Channel {channel} of I/O device {device} requires {required} bytes of the \
+                 process image, but only {available} are available"
+            ),
+            Self::NoChannel(device, channel) => {
+                write!(f, "I/O device {device} has no channel {channel}")
+            }
+            Self::NotAnOutput(device, channel) => {
+                write!(f, "Channel {channel} of I/O device {device} is an input")
+            }
+            Self::Ethercat(device, error) => {
+                write!(f, "Accessing I/O device {device} failed: {error}")
+            }
+        }
+    }
+}
+
+/// An analog input and output terminal, accessed through the process image
+pub struct AnalogIo<
+    'device,
+    'controller: 'device,
+    const MAX_DEVICES: usize,
+    const PDI_LENGTH: usize,
+> {
+    /// The device number
+    id: usize,
+
+    /// The controller used to communicate with the terminal
+    controller: &'device Controller<'controller, MAX_DEVICES, PDI_LENGTH>,
+
+    /// The channels of the terminal, in the order declared by the application
+    channels: Vec<AnalogChannel>,
+}
+
+impl<'device, 'controller: 'device, const MAX_DEVICES: usize, const PDI_LENGTH: usize>
+    AnalogIo<'device, 'controller, MAX_DEVICES, PDI_LENGTH>
+{
+    /// Creates a handle to an analog I/O terminal with the channel map declared by the
+    /// application. The channels are numbered in the order of the map. The outputs aren't
+    /// changed.
+    ///
+    /// # Errors
+    /// Returns an error if:
+    /// - The device doesn't exist
+    /// - A channel has an unsupported width or an invalid scale
+    /// - A channel extends beyond the process image of the device
+    /// - Another handle to the device exists
+    pub fn new(
+        controller: &'device Controller<'controller, MAX_DEVICES, PDI_LENGTH>,
+        device_number: usize,
+        channels: Vec<AnalogChannel>,
+    ) -> Result<Self, AnalogIoError> {
+        let layout = process_image_layout(controller, device_number)
+            .map_err(|error| AnalogIoError::NotFound(device_number, error))?;
+
+        // Check every channel against the actual size of the process image
+        for (index, channel) in channels.iter().enumerate() {
+            if !matches!(channel.width, 8 | 16 | 32) {
+                return Err(AnalogIoError::InvalidWidth {
+                    device: device_number,
+                    channel: index,
+                    width: channel.width,
+                });
+            }
+            if !channel.scale.is_normal() {
+                return Err(AnalogIoError::InvalidScale(device_number, index));
+            }
+            let available = match channel.direction {
+                ChannelDirection::Input => layout.input_bytes,
+                ChannelDirection::Output => layout.output_bytes,
+            };
+            let required = channel.offset.saturating_add(channel.bytes());
+            if required > available {
+                return Err(AnalogIoError::ChannelOutOfRange {
+                    device: device_number,
+                    channel: index,
+                    required,
+                    available,
+                });
+            }
+        }
+
+        if !controller.claim(device_number) {
+            return Err(AnalogIoError::AlreadyClaimed(device_number));
+        }
+        if controller.verbose() {
+            log::info!(
+                "Attached to I/O device {device_number} with {} analog channels",
+                channels.len()
+            );
+        }
+        Ok(Self {
+            id: device_number,
+            controller,
+            channels,
+        })
+    }
+
+    /// Returns the device number of the terminal
+    pub const fn id(&self) -> usize {
+        self.id
+    }
+
+    /// Returns the channels of the terminal
+    pub fn channels(&self) -> &[AnalogChannel] {
+        &self.channels
+    }
+
+    /// Reads the raw value of a channel. Output channels are read back from the outputs.
+    ///
+    /// # Errors
+    /// Returns an error if the channel doesn't exist or another reference to the device exists
+    pub fn read_raw(&mut self, channel: usize) -> Result<i64, AnalogIoError> {
+        let config = *self
+            .channels
+            .get(channel)
+            .ok_or(AnalogIoError::NoChannel(self.id, channel))?;
+        let sub_device = self
+            .controller
+            .group()
+            .subdevice(self.controller.main_device(), self.id)
+            .map_err(|error| AnalogIoError::Ethercat(self.id, error))?;
+        let image = match config.direction {
+            ChannelDirection::Input => sub_device.inputs_raw(),
+            ChannelDirection::Output => sub_device.outputs_raw(),
+        };
+        Ok(config.read_raw(&image[config.offset..]))
+    }
+
+    /// Reads the value of a channel in engineering units.
+    /// Output channels are read back from the outputs.
+    ///
+    /// # Errors
+    /// Returns an error if the channel doesn't exist or another reference to the device exists
+    pub fn read_channel(&mut self, channel: usize) -> Result<f64, AnalogIoError> {
+        let raw = self.read_raw(channel)?;
+        Ok(self.channels[channel].to_value(raw))
+    }
+
+    /// Writes the value of an output channel in engineering units, sent during the next cycle.
+    /// Values outside of the range of the channel are clamped.
+    ///
+    /// # Errors
+    /// Returns an error if:
+    /// - The channel doesn't exist or is an input
+    /// - Another reference to the device exists
+    ///
+    /// # Returns
+    /// The value actually written in engineering units, after rounding and clamping
+    pub fn write_channel(&mut self, channel: usize, value: f64) -> Result<f64, AnalogIoError> {
+        let config = *self
+            .channels
+            .get(channel)
+            .ok_or(AnalogIoError::NoChannel(self.id, channel))?;
+        if config.direction != ChannelDirection::Output {
+            return Err(AnalogIoError::NotAnOutput(self.id, channel));
+        }
+        let raw = config.to_raw(value);
+        let mut sub_device = self
+            .controller
+            .group()
+            .subdevice(self.controller.main_device(), self.id)
+            .map_err(|error| AnalogIoError::Ethercat(self.id, error))?;
+        config.write_raw(raw, &mut sub_device.outputs_raw_mut()[config.offset..]);
+        Ok(config.to_value(raw))
+    }
+}
+
+impl<const MAX_DEVICES: usize, const PDI_LENGTH: usize> Drop
+    for AnalogIo<'_, '_, MAX_DEVICES, PDI_LENGTH>
+{
+    fn drop(&mut self) {
+        // Allow a new handle to the device to be created
+        self.controller.release(self.id);
+    }
+}
@@ -0,0 +1,329 @@
+//! This module contains the `DigitalIo` type, for digital input and output terminals that aren't
+//! drives, like valve terminals and I/O modules next to the drives on the bus.
+//!
+//! The terminal is accessed through the process image only. It isn't reset or enabled, as it
+//! has no `CiA402` state machine, and the controller doesn't configure its PDO mapping. The bits
+//! are numbered from the least significant bit of the first byte of the process image.
+
+use crate::controller::Controller;
+use core::fmt::{self, Debug, Formatter};
+use ethercrab::error::Error as EthercrabError;
+
+/// The number of bytes of the process image used by a terminal
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct IoLayout {
+    /// The number of input bytes, starting at the first byte of the input process image
+    pub input_bytes: usize,
+
+    /// The number of output bytes, starting at the first byte of the output process image
+    pub output_bytes: usize,
+}
+
+impl IoLayout {
+    /// Creates the layout of a terminal
+    pub const fn new(input_bytes: usize, output_bytes: usize) -> Self {
+        Self {
+            input_bytes,
+            output_bytes,
+        }
+    }
+
+    /// Returns the number of input bits
+    pub const fn input_bits(&self) -> usize {
+        self.input_bytes * 8
+    }
+
+    /// Returns the number of output bits
+    pub const fn output_bits(&self) -> usize {
+        self.output_bytes * 8
+    }
+}
+
+/// An error returned while accessing a digital I/O terminal
+pub enum DigitalIoError {
+    /// The device doesn't exist or couldn't be accessed
+    NotFound(usize, EthercrabError),
+
+    /// Another handle to the device already exists
+    AlreadyClaimed(usize),
+
+    /// The layout is larger than the process image of the device
+    LayoutTooLarge {
+        /// The device number
+        device: usize,
+
+        /// The requested layout
+        layout: IoLayout,
+
+        /// The actual size of the process image
+        actual: IoLayout,
+    },
+
+    /// The requested input bit doesn't exist in the layout
+    InvalidInput(usize, u16),
+
+    /// The requested output bit doesn't exist in the layout
+    InvalidOutput(usize, u16),
+
+    /// The mask contains bits beyond the outputs of the layout
+    InvalidMask(usize, u64),
+
+    /// Another reference to the device exists
+    Ethercat(usize, EthercrabError),
+}
+
+impl Debug for DigitalIoError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::NotFound(device, error) => {
+                write!(f, "Failed to access I/O device {device}: {error}")
+            }
+            Self::AlreadyClaimed(device) => {
+                write!(f, "I/O device {device} is already used by another handle")
+            }
+            Self::LayoutTooLarge {
+                device,
+                layout,
+                actual,
+            } => write!(
+                f,
+                "I/O device {device} has {} input and {} output bytes, but the layout requires \
+                 {} input and {} output bytes",
+                actual.input_bytes, actual.output_bytes, layout.input_bytes, layout.output_bytes
+            ),
+            Self::InvalidInput(device, bit) => {
+                write!(f, "I/O device {device} has no input bit {bit}")
+            }
+            Self::InvalidOutput(device, bit) => {
+                write!(f, "I/O device {device} has no output bit {bit}")
+            }
+            Self::InvalidMask(device, mask) => write!(
+                f,
+                "The output mask {mask:#X} exceeds the outputs of I/O device {device}"
+            ),
+            Self::Ethercat(device, error) => {
+                write!(f, "Accessing I/O device {device} failed: {error}")
+            }
+        }
+    }
+}
+
+/// A digital input and output terminal, accessed through the process image
+pub struct DigitalIo<
+    'device,
+    'controller: 'device,
+    const MAX_DEVICES: usize,
+    const PDI_LENGTH: usize,
+> {
+    /// The device number
+    id: usize,
+
+    /// The controller used to communicate with the terminal
+    controller: &'device Controller<'controller, MAX_DEVICES, PDI_LENGTH>,
+
+    /// The part of the process image used
+    layout: IoLayout,
+}
+
+impl<'device, 'controller: 'device, const MAX_DEVICES: usize, const PDI_LENGTH: usize>
+    DigitalIo<'device, 'controller, MAX_DEVICES, PDI_LENGTH>
+{
+    /// Creates a handle to a digital I/O terminal using the requested part of its process image.
+    /// The outputs aren't changed.
+    ///
+    /// # Errors
+    /// Returns an error if:
+    /// - The device doesn't exist
+    /// - The layout is larger than the process image of the device
+    /// - Another handle to the device exists
+    pub fn new(
+        controller: &'device Controller<'controller, MAX_DEVICES, PDI_LENGTH>,
+        device_number: usize,
+        layout: IoLayout,
+    ) -> Result<Self, DigitalIoError> {
+        let actual = process_image_layout(controller, device_number)
+            .map_err(|error| DigitalIoError::NotFound(device_number, error))?;
+        if layout.input_bytes > actual.input_bytes || layout.output_bytes > actual.output_bytes {
+            return Err(DigitalIoError::LayoutTooLarge {
+                device: device_number,
+                layout,
+                actual,
+            });
+        }
+        if !controller.claim(device_number) {
+            return Err(DigitalIoError::AlreadyClaimed(device_number));
+        }
+        if controller.verbose() {
+            log::info!(
+                "Attached to I/O device {device_number} with {} inputs and {} outputs",
+                layout.input_bits(),
+                layout.output_bits()
+            );
+        }
+        Ok(Self {
+            id: device_number,
+            controller,
+            layout,
+        })
+    }
+
+    /// Creates a handle to a digital I/O terminal using its whole process image.
+    /// The outputs aren't changed.
+    ///
+    /// # Errors
+    /// Returns an error if the device doesn't exist or another handle to the device exists
+    pub fn detect(
+        controller: &'device Controller<'controller, MAX_DEVICES, PDI_LENGTH>,
+        device_number: usize,
+    ) -> Result<Self, DigitalIoError> {
+        let layout = process_image_layout(controller, device_number)
+            .map_err(|error| DigitalIoError::NotFound(device_number, error))?;
+        Self::new(controller, device_number, layout)
+    }
+
+    /// Returns the device number of the terminal
+    pub const fn id(&self) -> usize {
+        self.id
+    }
+
+    /// Returns the part of the process image used
+    pub const fn layout(&self) -> IoLayout {
+        self.layout
+    }
+
+    /// Reads an input bit.
+    ///
+    /// # Errors
+    /// Returns an error if the bit doesn't exist or another reference to the device exists
+    pub fn read_input(&mut self, bit: u16) -> Result<bool, DigitalIoError> {
+        if usize::from(bit) >= self.layout.input_bits() {
+            return Err(DigitalIoError::InvalidInput(self.id, bit));
+        }
+        let sub_device = self
+            .controller
+            .group()
+            .subdevice(self.controller.main_device(), self.id)
+            .map_err(|error| DigitalIoError::Ethercat(self.id, error))?;
+        let byte = sub_device.inputs_raw()[usize::from(bit / 8)];
+        Ok(byte & (1 << (bit % 8)) != 0)
+    }
+
+    /// Reads the first 64 input bits at once, all from the same cycle.
+    /// Bits beyond the inputs of the layout are cleared.
+    ///
+    /// # Errors
+    /// Returns an error if another reference to the device exists
+    pub fn read_inputs(&mut self) -> Result<u64, DigitalIoError> {
+        let sub_device = self
+            .controller
+            .group()
+            .subdevice(self.controller.main_device(), self.id)
+            .map_err(|error| DigitalIoError::Ethercat(self.id, error))?;
+        Ok(read_bits(
+            &sub_device.inputs_raw()[..self.layout.input_bytes],
+        ))
+    }
+
+    /// Reads the first 64 output bits back from the output process image.
+    /// Bits beyond the outputs of the layout are cleared.
+    ///
+    /// # Errors
+    /// Returns an error if another reference to the device exists
+    pub fn read_outputs(&mut self) -> Result<u64, DigitalIoError> {
+        let sub_device = self
+            .controller
+            .group()
+            .subdevice(self.controller.main_device(), self.id)
+            .map_err(|error| DigitalIoError::Ethercat(self.id, error))?;
+        Ok(read_bits(
+            &sub_device.outputs_raw()[..self.layout.output_bytes],
+        ))
+    }
+
+    /// Sets or clears an output bit, sent during the next cycle.
+    ///
+    /// # Errors
+    /// Returns an error if the bit doesn't exist or another reference to the device exists
+    pub fn set_output(&mut self, bit: u16, state: bool) -> Result<(), DigitalIoError> {
+        if usize::from(bit) >= self.layout.output_bits() {
+            return Err(DigitalIoError::InvalidOutput(self.id, bit));
+        }
+        let mut sub_device = self
+            .controller
+            .group()
+            .subdevice(self.controller.main_device(), self.id)
+            .map_err(|error| DigitalIoError::Ethercat(self.id, error))?;
+        let byte = &mut sub_device.outputs_raw_mut()[usize::from(bit / 8)];
+        if state {
+            *byte |= 1 << (bit % 8);
+        } else {
+            *byte &= !(1 << (bit % 8));
+        }
+        Ok(())
+    }
+
+    /// Sets the output bits selected by the mask to the bits of the value, the other outputs
+    /// are kept. All bits are sent during the same cycle.
+    ///
+    /// # Errors
+    /// Returns an error if the mask selects bits beyond the outputs of the layout, or another
+    /// reference to the device exists
+    pub fn write_outputs(&mut self, mask: u64, value: u64) -> Result<(), DigitalIoError> {
+        let bytes = self.layout.output_bytes.min(8);
+        if bytes < 8 && mask >> (bytes * 8) != 0 {
+            return Err(DigitalIoError::InvalidMask(self.id, mask));
+        }
+        let mut sub_device = self
+            .controller
+            .group()
+            .subdevice(self.controller.main_device(), self.id)
+            .map_err(|error| DigitalIoError::Ethercat(self.id, error))?;
+        let outputs = &mut sub_device.outputs_raw_mut()[..bytes];
+        for (output, (mask, value)) in outputs
+            .iter_mut()
+            .zip(mask.to_le_bytes().into_iter().zip(value.to_le_bytes()))
+        {
+            *output = (*output & !mask) | (value & mask);
+        }
+        Ok(())
+    }
+}
+
+impl<const MAX_DEVICES: usize, const PDI_LENGTH: usize> Drop
+    for DigitalIo<'_, '_, MAX_DEVICES, PDI_LENGTH>
+{
+    fn drop(&mut self) {
+        // Allow a new handle to the device to be created
+        self.controller.release(self.id);
+    }
+}
+
+/// Reads the size of the process image of a device.
+///
+/// # Errors
+/// Returns an error if the device doesn't exist or couldn't be accessed
+pub(crate) fn process_image_layout<const MAX_DEVICES: usize, const PDI_LENGTH: usize>(
+    controller: &Controller<'_, MAX_DEVICES, PDI_LENGTH>,
+    device_number: usize,
+) -> Result<IoLayout, EthercrabError> {
+    controller
+        .group()
+        .subdevice(controller.main_device(), device_number)
+        .map(|sub_device| {
+            IoLayout::new(
+                sub_device.inputs_raw().len(),
+                sub_device.outputs_raw().len(),
+            )
+        })
+}
+
+/// Combines up to 8 bytes of the process image into bits, the first byte being the least
+/// significant
+fn read_bits(bytes: &[u8]) -> u64 {
+    let mut bits = [0; 8];
+    for (bit, byte) in bits.iter_mut().zip(bytes) {
+        *bit = *byte;
+    }
+    u64::from_le_bytes(bits)
+}
@@ -0,0 +1,130 @@
+//! This module contains everything related to the digital inputs and outputs of a drive.
+//! The inputs and outputs are read from the process image when mapped, over SDO otherwise.
+
+use super::{objects, Device};
+use crate::pdo;
+use core::fmt::{self, Debug, Formatter};
+use ethercrab::error::Error as EthercrabError;
+
+/// The decoded digital inputs of a drive (0x60FD)
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct DigitalInputs {
+    /// Whether the negative limit switch is active
+    pub negative_limit_switch: bool,
+
+    /// Whether the positive limit switch is active
+    pub positive_limit_switch: bool,
+
+    /// Whether the home (reference) switch is active
+    pub home_switch: bool,
+
+    /// The manufacturer specific inputs (bits 16 to 31)
+    pub manufacturer: u16,
+
+    /// The raw value of the digital inputs
+    pub raw: u32,
+}
+
+impl DigitalInputs {
+    /// Decodes the raw value of the digital inputs object
+    pub const fn from_raw(raw: u32) -> Self {
+        Self {
+            negative_limit_switch: raw & 1 != 0,
+            positive_limit_switch: raw & (1 << 1) != 0,
+            home_switch: raw & (1 << 2) != 0,
+            manufacturer: (raw >> 16) as u16,
+            raw,
+        }
+    }
+}
+
+/// An error returned while setting a digital output
+pub enum DigitalOutputError {
+    /// The requested bit doesn't exist in the digital outputs object
+    InvalidBit(usize, u8),
+
+    /// Communication failed or there was an existing reference to the device
+    Ethercat(usize, EthercrabError),
+}
+
+impl Debug for DigitalOutputError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::InvalidBit(device, bit) => {
+                write!(f, "Device {device} has no digital output bit {bit}")
+            }
+            Self::Ethercat(device, error) => {
+                write!(
+                    f,
+                    "Setting digital output of device {device} failed: {error}"
+                )
+            }
+        }
+    }
+}
+
+impl<const MAX_DEVICES: usize, const PDI_LENGTH: usize> Device<'_, '_, MAX_DEVICES, PDI_LENGTH> {
+    /// Reads the digital inputs of the drive.
+    ///
+    /// # Errors
+    /// Returns an error if:
+    /// - Another reference to the device exists
+    /// - The inputs aren't mapped and couldn't be read over SDO
+    pub async fn digital_inputs(&mut self) -> Result<DigitalInputs, EthercrabError> {
+        self.read_mapped_object(objects::DIGITAL_INPUTS)
+            .await
+            .map(DigitalInputs::from_raw)
+    }
+
+    /// Sets or clears a bit of the physical digital outputs of the drive.
+    ///
+    /// # Errors
+    /// Returns an error if:
+    /// - The bit doesn't exist
+    /// - Another reference to the device exists
+    /// - The outputs aren't mapped and couldn't be written over SDO
+    pub async fn set_digital_output(
+        &mut self,
+        bit: u8,
+        state: bool,
+    ) -> Result<(), DigitalOutputError> {
+        if bit >= 32 {
+            return Err(DigitalOutputError::InvalidBit(self.id, bit));
+        }
+        let mask = 1u32 << bit;
+        let update = |outputs: u32| {
+            if state {
+                outputs | mask
+            } else {
+                outputs & !mask
+            }
+        };
+
+        // Write the process image if the outputs are mapped
+        let id = self.id;
+        if let Some(offset) = pdo::output_offset(objects::DIGITAL_OUTPUTS) {
+            let outputs = self
+                .read_output(offset)
+                .map_err(|error| DigitalOutputError::Ethercat(id, error))?;
+            return self
+                .write_output(offset, update(outputs))
+                .map_err(|error| DigitalOutputError::Ethercat(id, error));
+        }
+
+        // Otherwise update the outputs over SDO, making sure the output is enabled in the mask
+        let outputs: u32 = self
+            .read_object(objects::DIGITAL_OUTPUTS)
+            .await
+            .map_err(|error| DigitalOutputError::Ethercat(id, error))?;
+        let enabled: u32 = self
+            .read_object(objects::DIGITAL_OUTPUTS_MASK)
+            .await
+            .map_err(|error| DigitalOutputError::Ethercat(id, error))?;
+        self.write_object(objects::DIGITAL_OUTPUTS_MASK, enabled | mask)
+            .await
+            .map_err(|error| DigitalOutputError::Ethercat(id, error))?;
+        self.write_object(objects::DIGITAL_OUTPUTS, update(outputs))
+            .await
+            .map_err(|error| DigitalOutputError::Ethercat(id, error))
+    }
+}
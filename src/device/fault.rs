@@ -0,0 +1,194 @@
+//! This module contains the notification of device faults.
+//!
+//! The controller scans the fault and warning bits of every watched device each cycle and
+//! notifies the registered handlers once per change to a fault or warning.
+
+use super::{Device, DeviceError, StatusWord};
+use crate::channel::{self, Receiver};
+use std::sync::{
+    atomic::{AtomicU8, Ordering},
+    Mutex, PoisonError,
+};
+
+/// A handler called by the controller when a device faults
+pub(crate) type FaultHandler = Box<dyn FnMut(FaultEvent) + Send>;
+
+/// Calls every handler with the event. The handlers are taken out of the list while they run,
+/// so a handler can register other handlers without deadlocking. Handlers registered while
+/// running are kept after the existing handlers, and are called from the next event on.
+pub(crate) fn notify_fault_handlers(handlers: &Mutex<Vec<FaultHandler>>, event: FaultEvent) {
+    let mut running =
+        core::mem::take(&mut *handlers.lock().unwrap_or_else(PoisonError::into_inner));
+    for handler in &mut running {
+        handler(event);
+    }
+    let mut handlers = handlers.lock().unwrap_or_else(PoisonError::into_inner);
+    running.append(&mut handlers);
+    *handlers = running;
+}
+
+/// A fault or warning reported by a device
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct FaultEvent {
+    /// The number of the device reporting the fault
+    pub device: usize,
+
+    /// Whether the fault and/or warning bit is set
+    pub error: DeviceError,
+
+    /// The status word at the time of the fault
+    pub status: StatusWord,
+
+    /// The diagnosis message of the drive, identifying the cause of the fault
+    pub diagnosis: Option<u32>,
+}
+
+impl FaultEvent {
+    /// Checks a scanned status word for a new fault or warning. Only changes of the error status
+    /// to a fault or warning are reported, the error status of the previous scan is replaced.
+    ///
+    /// # Parameters
+    /// `last_error`: The error status found during the previous scan
+    ///
+    /// `diagnosis`: Reads the diagnosis message, only called when an event is reported
+    pub(crate) fn detect(
+        last_error: &AtomicU8,
+        device: usize,
+        status: StatusWord,
+        diagnosis: impl FnOnce() -> Option<u32>,
+    ) -> Option<Self> {
+        let error = DeviceError::from_status(status);
+        let previous = last_error.swap(error as u8, Ordering::AcqRel);
+        if previous == error as u8 || error == DeviceError::Ok {
+            return None;
+        }
+        Some(Self {
+            device,
+            error,
+            status,
+            diagnosis: diagnosis(),
+        })
+    }
+}
+
+/// A stream of fault events of a device, for awaiting faults instead of using a callback.
+///
+/// Holds the most recent events, older events are dropped if they aren't received in time.
+pub struct FaultEvents {
+    /// The receiving end of the channel filled by the controller
+    receiver: Receiver<FaultEvent>,
+}
+
+impl FaultEvents {
+    /// Waits for the next fault event.
+    ///
+    /// # Returns
+    /// The next fault event or `None` if the controller has been dropped
+    pub async fn recv(&self) -> Option<FaultEvent> {
+        self.receiver.recv().await
+    }
+
+    /// Returns the next fault event if one occured, without waiting
+    pub fn try_recv(&self) -> Option<FaultEvent> {
+        self.receiver.try_recv()
+    }
+
+    /// Returns the number of fault events dropped because they weren't received in time
+    pub fn dropped(&self) -> u64 {
+        self.receiver.dropped()
+    }
+}
+
+impl<const MAX_DEVICES: usize, const PDI_LENGTH: usize> Device<'_, '_, MAX_DEVICES, PDI_LENGTH> {
+    /// Registers a callback, called once every time the device reports a new fault or warning.
+    ///
+    /// The callback runs on the task calling `Controller::cycle`. It must return quickly and
+    /// must not block or await, use `fault_events` to await faults instead. It may register
+    /// other fault handlers, which are called from the next fault on.
+    pub fn on_fault(&mut self, callback: impl FnMut(FaultEvent) + Send + 'static) {
+        self.controller
+            .register_fault_handler(self.id, Box::new(callback));
+    }
+
+    /// Creates a stream of fault events of the device, holding at most `capacity` events.
+    pub fn fault_events(&mut self, capacity: usize) -> FaultEvents {
+        let (sender, receiver) = channel::bounded(capacity);
+        self.on_fault(move |event| sender.send(event));
+        FaultEvents { receiver }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    //! Tests of notifying the fault handlers
+
+    use super::*;
+    use std::sync::{atomic::AtomicUsize, Arc};
+
+    /// A fault of device 1
+    const EVENT: FaultEvent = FaultEvent {
+        device: 1,
+        error: DeviceError::Fault,
+        status: StatusWord::new(0x0008),
+        diagnosis: Some(0x1234),
+    };
+
+    /// A handler registering another handler doesn't deadlock, and the new handler is called
+    /// from the next event on
+    #[test]
+    fn handlers_can_register_handlers() {
+        let handlers = Arc::new(Mutex::new(Vec::<FaultHandler>::new()));
+        let calls = Arc::new(AtomicUsize::new(0));
+
+        let list = Arc::clone(&handlers);
+        let counter = Arc::clone(&calls);
+        let mut registered = false;
+        handlers.lock().unwrap().push(Box::new(move |event| {
+            assert_eq!(event, EVENT);
+            counter.fetch_add(1, Ordering::SeqCst);
+            if !registered {
+                registered = true;
+                let counter = Arc::clone(&counter);
+                list.lock().unwrap().push(Box::new(move |_| {
+                    counter.fetch_add(10, Ordering::SeqCst);
+                }));
+            }
+        }));
+
+        notify_fault_handlers(&handlers, EVENT);
+        assert_eq!(calls.load(Ordering::SeqCst), 1);
+        assert_eq!(handlers.lock().unwrap().len(), 2);
+        notify_fault_handlers(&handlers, EVENT);
+        assert_eq!(calls.load(Ordering::SeqCst), 12);
+    }
+
+    /// Scanning a sequence of status words reports every change to a fault or warning once
+    #[test]
+    fn scans_report_changes() {
+        let last_error = AtomicU8::new(DeviceError::Ok as u8);
+        let handlers = Mutex::new(Vec::<FaultHandler>::new());
+        let events = Arc::new(Mutex::new(Vec::new()));
+        let received = Arc::clone(&events);
+        handlers.lock().unwrap().push(Box::new(move |event| {
+            received.lock().unwrap().push(event.error);
+        }));
+
+        // Ok, fault, the same fault, fault and warning, cleared, warning
+        for raw in [0x0000, 0x0008, 0x0008, 0x0088, 0x0000, 0x0080] {
+            if let Some(event) =
+                FaultEvent::detect(&last_error, 1, StatusWord::new(raw), || Some(7))
+            {
+                assert_eq!(event.diagnosis, Some(7));
+                notify_fault_handlers(&handlers, event);
+            }
+        }
+        assert_eq!(
+            *events.lock().unwrap(),
+            [
+                DeviceError::Fault,
+                DeviceError::FaultAndWarning,
+                DeviceError::Warning
+            ]
+        );
+    }
+}
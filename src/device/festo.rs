@@ -0,0 +1,76 @@
+//! This module contains the addresses of Festo specific objects.
+//! Other drive families can provide their own `VendorObjects` to use their addresses instead.
+
+use super::objects::Object;
+
+/// The addresses of vendor specific objects.
+/// Objects set to `None` aren't supported by the drive family.
+#[derive(Debug, Clone, Copy)]
+pub struct VendorObjects {
+    /// The temperature of the power stage in degrees celsius (32-bit float)
+    pub power_stage_temperature: Option<Object>,
+
+    /// The temperature of the motor in degrees celsius (32-bit float)
+    pub motor_temperature: Option<Object>,
+
+    /// Overrides the holding brake control, 1 releases the brake and 0 returns control to the
+    /// drive (8-bit unsigned)
+    pub brake_release: Option<Object>,
+
+    /// The torque applied during the brake test in thousandths of the rated torque
+    /// (16-bit unsigned)
+    pub brake_test_torque: Option<Object>,
+
+    /// Starts the brake test when 1 is written (8-bit unsigned)
+    pub brake_test_start: Option<Object>,
+
+    /// The state of the brake test, see `BrakeTestState` (8-bit unsigned)
+    pub brake_test_state: Option<Object>,
+
+    /// Blinks the front LED of the drive while 1, 0 returns the LED to normal (8-bit unsigned)
+    pub identify: Option<Object>,
+
+    /// The number of the motion record started by the start bit (8-bit unsigned)
+    pub record_select: Option<Object>,
+
+    /// The target positions of the record table, the sub index selects the record
+    /// (32-bit signed)
+    pub record_target: Option<Object>,
+
+    /// The velocities of the record table, the sub index selects the record (32-bit unsigned)
+    pub record_velocity: Option<Object>,
+
+    /// The reduction of the motor current at standstill of a stepper in percent of the rated
+    /// current (8-bit unsigned)
+    pub standstill_current_reduction: Option<Object>,
+
+    /// Whether a stepper runs in open loop (0) or closed loop (1), see `LoopMode`
+    /// (8-bit unsigned)
+    pub loop_mode: Option<Object>,
+}
+
+/// The product code of the CMMT-AS servo drive
+pub const CMMT_AS_PRODUCT_ID: u32 = 0x7B_5A25;
+
+/// The product code of the CMMT-ST stepper drive
+pub const CMMT_ST_PRODUCT_ID: u32 = 0x7B_1A95;
+
+/// The diagnosis message of the drive, identifying the active fault or warning (unsigned 32-bit).
+/// Part of the default PDO mapping.
+pub const DIAGNOSIS_MESSAGE: Object = Object::new(0x2194, 5);
+
+/// The vendor specific objects of the Festo CMMT drive family
+pub const CMMT: VendorObjects = VendorObjects {
+    power_stage_temperature: Some(Object::new(0x2110, 1)),
+    motor_temperature: Some(Object::new(0x2110, 2)),
+    brake_release: Some(Object::new(0x2161, 1)),
+    brake_test_torque: Some(Object::new(0x2162, 1)),
+    brake_test_start: Some(Object::new(0x2162, 2)),
+    brake_test_state: Some(Object::new(0x2162, 3)),
+    identify: Some(Object::new(0x2168, 1)),
+    record_select: Some(Object::new(0x2180, 1)),
+    record_target: Some(Object::new(0x2181, 0)),
+    record_velocity: Some(Object::new(0x2182, 0)),
+    standstill_current_reduction: Some(Object::new(0x2190, 1)),
+    loop_mode: Some(Object::new(0x2190, 2)),
+};
@@ -0,0 +1,153 @@
+//! This module contains the `Monitor`, a read-only view of the process image of a device.
+//!
+//! A monitor only reads the inputs of the device, so it can be used next to the `Device` or
+//! `Servo` controlling the same device, for example from a dashboard task.
+
+use super::{objects, Cia402State, StatusWord};
+use crate::{
+    controller::Controller,
+    device::objects::Object,
+    pdo::{self, PdoValue},
+};
+use core::fmt::{self, Debug, Formatter};
+use ethercrab::error::Error as EthercrabError;
+
+/// An error returned while reading a device through a monitor
+pub enum MonitorError {
+    /// The device is no longer part of the network
+    DeviceGone(usize),
+
+    /// The object isn't part of the input process image
+    NotMapped(usize, Object),
+
+    /// The process image of the device couldn't be accessed
+    Ethercat(usize, EthercrabError),
+}
+
+impl Debug for MonitorError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::DeviceGone(device) => write!(f, "Device {device} is no longer available"),
+            Self::NotMapped(device, object) => write!(
+                f,
+                "Object {:#06x}:{} of device {device} isn't mapped to the inputs",
+                object.index, object.sub_index
+            ),
+            Self::Ethercat(device, error) => {
+                write!(f, "Failed to read inputs of device {device}: {error}")
+            }
+        }
+    }
+}
+
+/// A read-only view of the inputs of a device.
+/// Can exist next to the handle controlling the device and can be sent to other tasks.
+pub struct Monitor<'controller, 'main_device, const MAX_DEVICES: usize, const PDI_LENGTH: usize> {
+    /// The device ID
+    id: usize,
+
+    /// The controller used
+    controller: &'controller Controller<'main_device, MAX_DEVICES, PDI_LENGTH>,
+}
+
+impl<'controller, 'main_device, const MAX_DEVICES: usize, const PDI_LENGTH: usize>
+    Monitor<'controller, 'main_device, MAX_DEVICES, PDI_LENGTH>
+{
+    /// Creates a monitor for the requested device.
+    ///
+    /// # Errors
+    /// Returns an error if the device doesn't exist
+    pub fn new(
+        controller: &'controller Controller<'main_device, MAX_DEVICES, PDI_LENGTH>,
+        device_number: usize,
+    ) -> Result<Self, MonitorError> {
+        if device_number >= controller.device_count() {
+            return Err(MonitorError::DeviceGone(device_number));
+        }
+        Ok(Self {
+            id: device_number,
+            controller,
+        })
+    }
+
+    /// Returns the device number (index on the network)
+    pub const fn number(&self) -> usize {
+        self.id
+    }
+
+    /// Reads a mapped object from the input process image
+    fn read<T: PdoValue>(&self, object: Object) -> Result<T, MonitorError> {
+        let offset = pdo::input_offset(object).ok_or(MonitorError::NotMapped(self.id, object))?;
+        if self.id >= self.controller.device_count() {
+            return Err(MonitorError::DeviceGone(self.id));
+        }
+        let sub_device = self
+            .controller
+            .group()
+            .subdevice(self.controller.main_device(), self.id)
+            .map_err(|error| MonitorError::Ethercat(self.id, error))?;
+        Ok(T::read(&sub_device.inputs_raw()[offset..]))
+    }
+
+    /// Reads the status word of the device.
+    ///
+    /// # Errors
+    /// Returns an error if the inputs of the device couldn't be read
+    pub fn status_word(&self) -> Result<StatusWord, MonitorError> {
+        self.read(objects::STATUS_WORD).map(StatusWord::new)
+    }
+
+    /// Reads the state of the `CiA402` state machine of the device.
+    ///
+    /// # Errors
+    /// Returns an error if the inputs of the device couldn't be read
+    pub fn state(&self) -> Result<Cia402State, MonitorError> {
+        self.status_word().map(StatusWord::state)
+    }
+
+    /// Reads the operation mode the device is currently in.
+    ///
+    /// # Errors
+    /// Returns an error if the inputs of the device couldn't be read
+    pub fn mode_display(&self) -> Result<i8, MonitorError> {
+        self.read(objects::MODES_OF_OPERATION_DISPLAY)
+    }
+
+    /// Reads the actual position in increments.
+    ///
+    /// # Errors
+    /// Returns an error if the inputs of the device couldn't be read
+    pub fn position(&self) -> Result<i32, MonitorError> {
+        self.read(objects::POSITION_ACTUAL_VALUE)
+    }
+
+    /// Reads the actual velocity in increments per second.
+    ///
+    /// # Errors
+    /// Returns an error if the inputs of the device couldn't be read
+    pub fn velocity(&self) -> Result<i32, MonitorError> {
+        self.read(objects::VELOCITY_ACTUAL_VALUE)
+    }
+
+    /// Reads the actual torque in thousandths of the rated torque.
+    ///
+    /// # Errors
+    /// Returns an error if the inputs of the device couldn't be read
+    pub fn torque(&self) -> Result<i16, MonitorError> {
+        self.read(objects::TORQUE_ACTUAL_VALUE)
+    }
+}
+
+impl<const MAX_DEVICES: usize, const PDI_LENGTH: usize> Controller<'_, MAX_DEVICES, PDI_LENGTH> {
+    /// Creates a read-only monitor for the requested device.
+    /// See `Monitor`.
+    ///
+    /// # Errors
+    /// Returns an error if the device doesn't exist
+    pub fn monitor(
+        &self,
+        device_number: usize,
+    ) -> Result<Monitor<'_, '_, MAX_DEVICES, PDI_LENGTH>, MonitorError> {
+        Monitor::new(self, device_number)
+    }
+}
@@ -0,0 +1,238 @@
+//! This module contains the addresses of the standard (`CiA402`) objects in the object dictionary.
+//! Vendor specific objects can be found in the module of the vendor.
+
+/// The address of an object in the object dictionary of a device
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Object {
+    /// The index of the object
+    pub index: u16,
+
+    /// The sub-index of the object
+    pub sub_index: u8,
+}
+
+impl Object {
+    /// Creates the address of an object
+    pub const fn new(index: u16, sub_index: u8) -> Self {
+        Self { index, sub_index }
+    }
+}
+
+/// The synchronization mode of the output sync manager, 2 when synchronized to the distributed
+/// clocks SYNC0 event (unsigned 16-bit)
+pub const SYNC_MODE: Object = Object::new(0x1C32, 1);
+
+/// The control word of the `CiA402` state machine (unsigned 16-bit)
+pub const CONTROL_WORD: Object = Object::new(0x6040, 0);
+
+/// The requested operation mode (signed 8-bit)
+pub const MODES_OF_OPERATION: Object = Object::new(0x6060, 0);
+
+/// The target position in increments (signed 32-bit)
+pub const TARGET_POSITION: Object = Object::new(0x607A, 0);
+
+/// The velocity of profile movements in increments per second (unsigned 32-bit)
+pub const PROFILE_VELOCITY: Object = Object::new(0x6081, 0);
+
+/// The maximum velocity of profile movements in increments per second (unsigned 32-bit)
+pub const MAX_PROFILE_VELOCITY: Object = Object::new(0x607F, 0);
+
+/// The maximum speed of the motor in revolutions per minute, faster velocities are clamped by
+/// the drive (unsigned 32-bit)
+pub const MAX_MOTOR_SPEED: Object = Object::new(0x6080, 0);
+
+/// The acceleration of profile movements in increments per second squared (unsigned 32-bit)
+pub const PROFILE_ACCELERATION: Object = Object::new(0x6083, 0);
+
+/// The deceleration of profile movements in increments per second squared (unsigned 32-bit)
+pub const PROFILE_DECELERATION: Object = Object::new(0x6084, 0);
+
+/// The polarity of positions and velocities (unsigned 8-bit)
+pub const POLARITY: Object = Object::new(0x607E, 0);
+
+/// The minimum software position limit in increments (signed 32-bit)
+pub const SOFTWARE_POSITION_LIMIT_MIN: Object = Object::new(0x607D, 1);
+
+/// The maximum software position limit in increments (signed 32-bit)
+pub const SOFTWARE_POSITION_LIMIT_MAX: Object = Object::new(0x607D, 2);
+
+/// The motor revolutions of the gear ratio (unsigned 32-bit)
+pub const GEAR_RATIO_MOTOR_REVOLUTIONS: Object = Object::new(0x6091, 1);
+
+/// The shaft revolutions of the gear ratio (unsigned 32-bit)
+pub const GEAR_RATIO_SHAFT_REVOLUTIONS: Object = Object::new(0x6091, 2);
+
+/// The feed in position units of the feed constant (unsigned 32-bit)
+pub const FEED_CONSTANT_FEED: Object = Object::new(0x6092, 1);
+
+/// The shaft revolutions of the feed constant (unsigned 32-bit)
+pub const FEED_CONSTANT_SHAFT_REVOLUTIONS: Object = Object::new(0x6092, 2);
+
+/// The homing method (signed 8-bit)
+pub const HOMING_METHOD: Object = Object::new(0x6098, 0);
+
+/// The homing speed while searching for the switch in increments per second (unsigned 32-bit)
+pub const HOMING_SPEED_SEARCH_SWITCH: Object = Object::new(0x6099, 1);
+
+/// The homing speed while searching for the zero position in increments per second
+/// (unsigned 32-bit)
+pub const HOMING_SPEED_SEARCH_ZERO: Object = Object::new(0x6099, 2);
+
+/// The homing acceleration in increments per second squared (unsigned 32-bit)
+pub const HOMING_ACCELERATION: Object = Object::new(0x609A, 0);
+
+/// The position the home position is set to in increments (signed 32-bit)
+pub const HOME_OFFSET: Object = Object::new(0x607C, 0);
+
+/// The target velocity in increments per second (signed 32-bit)
+pub const TARGET_VELOCITY: Object = Object::new(0x60FF, 0);
+
+/// The target torque in thousandths of the rated torque (signed 16-bit)
+pub const TARGET_TORQUE: Object = Object::new(0x6071, 0);
+
+/// The deceleration of a quick stop in increments per second squared (unsigned 32-bit)
+pub const QUICK_STOP_DECELERATION: Object = Object::new(0x6085, 0);
+
+/// The shape of the ramps of profile movements (signed 16-bit)
+pub const MOTION_PROFILE_TYPE: Object = Object::new(0x6086, 0);
+
+/// The maximum torque in thousandths of the rated torque (unsigned 16-bit)
+pub const MAX_TORQUE: Object = Object::new(0x6072, 0);
+
+/// The maximum torque in positive direction in thousandths of the rated torque (unsigned 16-bit)
+pub const POSITIVE_TORQUE_LIMIT: Object = Object::new(0x60E0, 0);
+
+/// The maximum torque in negative direction in thousandths of the rated torque (unsigned 16-bit)
+pub const NEGATIVE_TORQUE_LIMIT: Object = Object::new(0x60E1, 0);
+
+/// The rate of change of the torque in thousandths of the rated torque per second
+/// (unsigned 32-bit)
+pub const TORQUE_SLOPE: Object = Object::new(0x6087, 0);
+
+/// The value of the interpolation time period (unsigned 8-bit)
+pub const INTERPOLATION_TIME_PERIOD_VALUE: Object = Object::new(0x60C2, 1);
+
+/// The power of ten of the interpolation time period in seconds (signed 8-bit)
+pub const INTERPOLATION_TIME_INDEX: Object = Object::new(0x60C2, 2);
+
+/// The next setpoint of the interpolated position mode in increments (signed 32-bit)
+pub const INTERPOLATION_DATA_RECORD: Object = Object::new(0x60C1, 1);
+
+/// The number of setpoints the interpolation buffer can hold (unsigned 32-bit)
+pub const INTERPOLATION_BUFFER_SIZE: Object = Object::new(0x60C4, 2);
+
+/// The number of setpoints in the interpolation buffer (unsigned 16-bit)
+pub const INTERPOLATION_BUFFER_POSITION: Object = Object::new(0x60C4, 4);
+
+/// Clears the interpolation buffer when 0 is written and enables it when 1 is written
+/// (unsigned 8-bit)
+pub const INTERPOLATION_BUFFER_CLEAR: Object = Object::new(0x60C4, 6);
+
+/// The velocity added to the velocity setpoint in increments per second (signed 32-bit)
+pub const VELOCITY_OFFSET: Object = Object::new(0x60B1, 0);
+
+/// The torque added to the torque setpoint in thousandths of the rated torque (signed 16-bit)
+pub const TORQUE_OFFSET: Object = Object::new(0x60B2, 0);
+
+/// How positioning moves are executed, bits 0 and 1 select what relative targets are relative
+/// to (unsigned 16-bit)
+pub const POSITION_OPTION_CODE: Object = Object::new(0x60F2, 0);
+
+/// The code of the last error reported by the drive (unsigned 16-bit)
+pub const ERROR_CODE: Object = Object::new(0x603F, 0);
+
+/// The status word of the `CiA402` state machine (unsigned 16-bit)
+pub const STATUS_WORD: Object = Object::new(0x6041, 0);
+
+/// The operation mode the drive is currently in (signed 8-bit)
+pub const MODES_OF_OPERATION_DISPLAY: Object = Object::new(0x6061, 0);
+
+/// The actual position in increments (signed 32-bit)
+pub const POSITION_ACTUAL_VALUE: Object = Object::new(0x6064, 0);
+
+/// The actual velocity in increments per second (signed 32-bit)
+pub const VELOCITY_ACTUAL_VALUE: Object = Object::new(0x606C, 0);
+
+/// The actual torque in thousandths of the rated torque (signed 16-bit)
+pub const TORQUE_ACTUAL_VALUE: Object = Object::new(0x6077, 0);
+
+/// The position demanded by the trajectory generator of the drive in increments (signed 32-bit)
+pub const POSITION_DEMAND_VALUE: Object = Object::new(0x6062, 0);
+
+/// The velocity demanded by the trajectory generator of the drive in increments per second
+/// (signed 32-bit)
+pub const VELOCITY_DEMAND_VALUE: Object = Object::new(0x606B, 0);
+
+/// The difference between the demanded and actual position in increments (signed 32-bit)
+pub const FOLLOWING_ERROR_ACTUAL_VALUE: Object = Object::new(0x60F4, 0);
+
+/// The following error at which the drive reports a following error in increments
+/// (unsigned 32-bit)
+pub const FOLLOWING_ERROR_WINDOW: Object = Object::new(0x6065, 0);
+
+/// The time the following error has to exceed the window before it's reported in milliseconds
+/// (unsigned 16-bit)
+pub const FOLLOWING_ERROR_TIME_OUT: Object = Object::new(0x6066, 0);
+
+/// The distance around the target in which the drive considers the target reached in increments
+/// (unsigned 32-bit)
+pub const POSITION_WINDOW: Object = Object::new(0x6067, 0);
+
+/// The time the position has to stay within the position window before the target is reached in
+/// milliseconds (unsigned 16-bit)
+pub const POSITION_WINDOW_TIME: Object = Object::new(0x6068, 0);
+
+/// The difference from the target velocity within which the target velocity is reached in
+/// increments per second (unsigned 16-bit)
+pub const VELOCITY_WINDOW: Object = Object::new(0x606D, 0);
+
+/// The time the velocity has to stay within the velocity window before the target velocity is
+/// reached in milliseconds (unsigned 16-bit)
+pub const VELOCITY_WINDOW_TIME: Object = Object::new(0x606E, 0);
+
+/// The velocity below which the servo is considered standing still in increments per second
+/// (unsigned 16-bit)
+pub const VELOCITY_THRESHOLD: Object = Object::new(0x606F, 0);
+
+/// The time the velocity has to stay below the velocity threshold before the servo is
+/// considered standing still in milliseconds (unsigned 16-bit)
+pub const VELOCITY_THRESHOLD_TIME: Object = Object::new(0x6070, 0);
+
+/// The voltage of the DC link in millivolts (unsigned 32-bit)
+pub const DC_LINK_VOLTAGE: Object = Object::new(0x6079, 0);
+
+/// The rated current of the motor in milliampere (unsigned 32-bit)
+pub const MOTOR_RATED_CURRENT: Object = Object::new(0x6075, 0);
+
+/// The rated torque of the motor in millinewton meter (unsigned 32-bit)
+pub const MOTOR_RATED_TORQUE: Object = Object::new(0x6076, 0);
+
+/// The actual current in thousandths of the rated current (signed 16-bit)
+pub const CURRENT_ACTUAL_VALUE: Object = Object::new(0x6078, 0);
+
+/// The digital inputs of the drive (unsigned 32-bit)
+pub const DIGITAL_INPUTS: Object = Object::new(0x60FD, 0);
+
+/// The physical digital outputs of the drive (unsigned 32-bit)
+pub const DIGITAL_OUTPUTS: Object = Object::new(0x60FE, 1);
+
+/// The mask selecting which digital outputs are controlled (unsigned 32-bit)
+pub const DIGITAL_OUTPUTS_MASK: Object = Object::new(0x60FE, 2);
+
+/// The touch probe function (unsigned 16-bit)
+pub const TOUCH_PROBE_FUNCTION: Object = Object::new(0x60B8, 0);
+
+/// The touch probe status (unsigned 16-bit)
+pub const TOUCH_PROBE_STATUS: Object = Object::new(0x60B9, 0);
+
+/// The position latched by touch probe 1 on the rising edge (signed 32-bit)
+pub const TOUCH_PROBE_1_POSITION_RISING: Object = Object::new(0x60BA, 0);
+
+/// The position latched by touch probe 1 on the falling edge (signed 32-bit)
+pub const TOUCH_PROBE_1_POSITION_FALLING: Object = Object::new(0x60BB, 0);
+
+/// The position latched by touch probe 2 on the rising edge (signed 32-bit)
+pub const TOUCH_PROBE_2_POSITION_RISING: Object = Object::new(0x60BC, 0);
+
+/// The position latched by touch probe 2 on the falling edge (signed 32-bit)
+pub const TOUCH_PROBE_2_POSITION_FALLING: Object = Object::new(0x60BD, 0);
@@ -1,13 +1,92 @@
 //! This module contains everything related to the `Servo` drive struct.
 //! The `Servo` drive struct can control Servo's controlled by most Festo Servomotor drives.
 
-use super::{Device, EnableError, OperationMode, SetModeError, StatusWordBit, Timeout};
+use super::{
+    AttachError, Device, DeviceInfo, DisableError, DisableReport, DriveType, EnableError,
+    OperationMode, SetModeError, StatusWord, StatusWordBit, WaitTimeout,
+};
 use crate::{
     controller::Controller,
-    device::{ControlBit, MappedPdo},
+    device::{
+        festo::{self, VendorObjects},
+        objects, ControlBit, ControlWord,
+    },
+    pdo::{self, PdoValue},
+};
+use config::ServoConfig;
+use core::{
+    fmt::{self, Debug, Formatter},
+    time::Duration,
 };
-use core::fmt::{self, Debug, Formatter};
-use ethercrab::error::Error as EthercrabError;
+use diagnostics::MotorRatings;
+use ethercrab::{error::Error as EthercrabError, SubDeviceIdentity};
+use events::MotionEventKind;
+use fault_details::FaultDetails;
+use following_error::FollowingErrorSupervision;
+use homing::{HomingConfigError, HomingPolicy};
+use limits::{JogSupervision, SoftwareLimits};
+use motion::MoveOptions;
+use polarity::PositionPolarity;
+use std::time::Instant;
+use switches::LimitSwitches;
+use torque_limit::TorqueLimits;
+use units::{RawVelocity, UnitScaling};
+
+pub mod brake;
+pub mod config;
+pub mod contact;
+pub mod cyclic;
+pub mod diagnostics;
+pub mod events;
+pub mod fault_callback;
+pub mod fault_details;
+pub mod following_error;
+pub mod gantry;
+pub mod group;
+mod handshake;
+pub mod homing;
+pub mod identify;
+pub mod interpolated;
+pub mod jog;
+pub mod limits;
+pub mod lookup;
+pub mod mechanics;
+pub mod modulo;
+pub mod motion;
+pub mod path;
+pub mod playback;
+pub mod polarity;
+pub mod position_window;
+pub mod profile;
+pub mod queue;
+pub mod record;
+pub mod registration;
+pub mod retry;
+pub mod standstill;
+pub mod status;
+pub mod switches;
+pub mod teach;
+pub mod telemetry;
+pub mod torque;
+pub mod torque_limit;
+pub mod touch_probe;
+pub mod units;
+pub mod velocity;
+
+/// The maximum time homing may take
+const HOMING_TIMEOUT: Duration = Duration::from_secs(120);
+
+/// The maximum time a single movement may take
+const MOTION_TIMEOUT: Duration = Duration::from_secs(60);
+
+/// The maximum time the drive may take to acknowledge a setpoint sent during a movement
+const SETPOINT_TIMEOUT: Duration = Duration::from_secs(1);
+
+/// The velocity in increments per second below which the servo is considered standing still
+const MOVING_DEADBAND: u32 = 100;
+
+/// The time the velocity has to stay within the deadband before the servo stands still
+const STANDSTILL_TIME: Duration = Duration::from_millis(20);
 
 /// An error returned while moving the servo to it's default (home) position
 pub enum HomingError {
@@ -16,6 +95,83 @@ pub enum HomingError {
 
     /// The mode couldn't be set to the requested value
     SetMode(SetModeError),
+
+    /// Waiting for the drive failed while homing, because the device has been emergency stopped
+    /// or a wait other than the homing timeout expired
+    Wait(WaitTimeout),
+
+    /// The drive faulted or left the operation enabled state while homing
+    Fault {
+        /// The device number
+        device: usize,
+
+        /// The status word reported by the drive
+        status: StatusWord,
+
+        /// The details of the fault, if they could be collected
+        details: Option<FaultDetails>,
+    },
+
+    /// The drive reported a homing error, like a reference switch that wasn't found
+    HomingFault {
+        /// The device number
+        device: usize,
+
+        /// The status word reported by the drive
+        status: StatusWord,
+
+        /// The error code of the drive (0x603F), if it could be read
+        code: Option<u16>,
+
+        /// The limit and reference switches, if they could be read
+        switches: Option<LimitSwitches>,
+    },
+
+    /// The home position wasn't reached in time, homing has been halted
+    Timeout {
+        /// The device number
+        device: usize,
+
+        /// The last status word reported by the drive
+        status: StatusWord,
+
+        /// The time waited before homing was halted
+        waited: Duration,
+    },
+
+    /// The homing parameters couldn't be configured
+    Configuration(HomingConfigError),
+
+    /// The device isn't homed, while the homing policy requires it to be
+    NotHomed(usize),
+
+    /// The torque limit was reached while homing to a block, but the drive didn't report the
+    /// home position as reached within the settle time. Homing has been halted.
+    BlockReachedNotHomed {
+        /// The device number
+        device: usize,
+
+        /// The last status word reported by the drive
+        status: StatusWord,
+
+        /// The actual torque in thousandths of the rated torque
+        torque: i16,
+    },
+}
+
+impl From<WaitTimeout> for HomingError {
+    fn from(error: WaitTimeout) -> Self {
+        match error {
+            WaitTimeout::Fault(device, status) => Self::Fault {
+                device,
+                status,
+                details: None,
+            },
+            error @ (WaitTimeout::Expired(..) | WaitTimeout::EmergencyStopped(..)) => {
+                Self::Wait(error)
+            }
+        }
+    }
 }
 
 impl Debug for HomingError {
@@ -25,6 +181,60 @@ impl Debug for HomingError {
                 write!(f, "Homing not possible, device {device} is disabled")
             }
             Self::SetMode(error) => write!(f, "Error while setting homing mode: {error:?}"),
+            Self::Wait(error) => write!(f, "Homing failed: {error:?}"),
+            Self::Fault {
+                device,
+                status,
+                details,
+            } => match details {
+                Some(details) => write!(f, "Device {device} faulted while homing: {details}"),
+                None => write!(
+                    f,
+                    "Device {device} faulted while homing, status word {:#06x}",
+                    status.raw()
+                ),
+            },
+            Self::HomingFault {
+                device,
+                status,
+                code,
+                switches,
+            } => {
+                write!(
+                    f,
+                    "Device {device} reported a homing error, status word {:#06x}",
+                    status.raw()
+                )?;
+                if let Some(code) = code {
+                    write!(f, ", error code {code:#06x}")?;
+                }
+                match switches {
+                    Some(switches) if switches.on_limit() => {
+                        write!(f, ", {}", switches.description())
+                    }
+                    _ => Ok(()),
+                }
+            }
+            Self::Timeout {
+                device,
+                status,
+                waited,
+            } => write!(
+                f,
+                "Device {device} didn't reach the home position within {waited:?}, status word {:#06x}",
+                status.raw()
+            ),
+            Self::Configuration(error) => write!(f, "Configuring homing failed: {error:?}"),
+            Self::NotHomed(device) => write!(f, "Device {device} isn't homed"),
+            Self::BlockReachedNotHomed {
+                device,
+                status,
+                torque,
+            } => write!(
+                f,
+                "Device {device} reached the block with torque {torque}, but isn't homed, status word {:#06x}",
+                status.raw()
+            ),
         }
     }
 }
@@ -36,6 +246,76 @@ pub enum JoggingError {
 
     /// The device couldn't be set to the requested mode
     SetMode(SetModeError),
+
+    /// Waiting for the drive was interrupted by an emergency stop while jogging, an expired wait
+    /// is reported as `Timeout`
+    Wait(WaitTimeout),
+
+    /// The drive faulted or left the operation enabled state while jogging
+    Fault {
+        /// The device number
+        device: usize,
+
+        /// The status word reported by the drive
+        status: StatusWord,
+
+        /// The details of the fault, if they could be collected
+        details: Option<FaultDetails>,
+    },
+
+    /// The motion didn't complete in time, the last status word is included
+    Timeout(usize, StatusWord),
+
+    /// The requested jog velocity is zero, so the servo wouldn't move
+    ZeroVelocity(usize),
+
+    /// The requested jog velocity exceeds the maximum motor speed and the policy rejects it
+    VelocityAboveMotorLimit {
+        /// The device number
+        device: usize,
+
+        /// The requested velocity in increments per second
+        requested: u32,
+
+        /// The maximum motor speed in increments per second
+        max: u32,
+    },
+
+    /// Communication with the drive failed
+    Ethercat(usize, EthercrabError),
+
+    /// The jog was stopped, because it came within the stopping distance of a software position
+    /// limit
+    LimitReached {
+        /// The position at which the jog was stopped
+        position: i32,
+
+        /// The software position limit
+        limit: i32,
+    },
+
+    /// The jog was stopped, because it ran into the limit switch in its direction
+    LimitSwitch {
+        /// The device number
+        device: usize,
+
+        /// The state of the switches when the jog was stopped
+        switches: LimitSwitches,
+    },
+}
+
+impl From<WaitTimeout> for JoggingError {
+    fn from(error: WaitTimeout) -> Self {
+        match error {
+            WaitTimeout::Fault(device, status) => Self::Fault {
+                device,
+                status,
+                details: None,
+            },
+            WaitTimeout::Expired(device, status) => Self::Timeout(device, status),
+            error @ WaitTimeout::EmergencyStopped(..) => Self::Wait(error),
+        }
+    }
 }
 
 impl Debug for JoggingError {
@@ -45,6 +325,48 @@ impl Debug for JoggingError {
                 write!(f, "Jogging not possible device {device} is disabled")
             }
             Self::SetMode(error) => write!(f, "Error while setting jogging mode: {error:?}"),
+            Self::Wait(error) => write!(f, "Jogging failed: {error:?}"),
+            Self::Fault {
+                device,
+                status,
+                details,
+            } => match details {
+                Some(details) => write!(f, "Device {device} faulted while jogging: {details}"),
+                None => write!(
+                    f,
+                    "Device {device} faulted while jogging, status word {:#06x}",
+                    status.raw()
+                ),
+            },
+            Self::Timeout(device, status) => write!(
+                f,
+                "Jog movement of device {device} didn't stop in time, status word {:#06x}",
+                status.raw()
+            ),
+            Self::ZeroVelocity(device) => {
+                write!(f, "Device {device} can't jog with a velocity of zero")
+            }
+            Self::VelocityAboveMotorLimit {
+                device,
+                requested,
+                max,
+            } => write!(
+                f,
+                "Jog velocity {requested} exceeds the maximum motor speed {max} of device {device}"
+            ),
+            Self::Ethercat(device, error) => write!(
+                f,
+                "Failed to communicate with device {device} while jogging: {error:?}"
+            ),
+            Self::LimitReached { position, limit } => write!(
+                f,
+                "Jog stopped at position {position} before software position limit {limit}"
+            ),
+            Self::LimitSwitch { device, switches } => write!(
+                f,
+                "Jog of device {device} stopped, {}",
+                switches.description()
+            ),
         }
     }
 }
@@ -60,6 +382,123 @@ pub enum MovementError {
 
     /// Failed to set the device to the requested mode
     SetMode(SetModeError),
+
+    /// The device faulted or the movement didn't complete in time
+    Wait(WaitTimeout),
+
+    /// The device has been emergency stopped
+    EmergencyStopped(usize),
+
+    /// The movement was aborted, the servo stopped at the contained position
+    Aborted(usize, i32),
+
+    /// No positioning move is running or paused
+    NoActiveMove(usize),
+
+    /// The drive didn't acknowledge the new setpoint
+    SetpointRejected(usize),
+
+    /// The drive can't accept more queued setpoints
+    QueueFull(usize),
+
+    /// Failed to set the acceleration to the requested value
+    WritingAcceleration(usize, EthercrabError),
+
+    /// Failed to set the deceleration to the requested value
+    WritingDeceleration(usize, EthercrabError),
+
+    /// The drive faulted or left the operation enabled state during the movement
+    Fault {
+        /// The device number
+        device: usize,
+
+        /// The status word reported by the drive
+        status: StatusWord,
+
+        /// The details of the fault, if they could be collected
+        details: Option<FaultDetails>,
+    },
+
+    /// The movement didn't complete before the deadline, the last status word is included
+    Timeout(usize, StatusWord),
+
+    /// The absolute target is outside of the software position limits of the drive
+    TargetOutOfLimits {
+        /// The absolute target position
+        target: i32,
+
+        /// The minimum software position limit
+        min: i32,
+
+        /// The maximum software position limit
+        max: i32,
+    },
+
+    /// The requested velocity exceeds the maximum profile velocity of the drive
+    VelocityTooHigh {
+        /// The requested velocity in increments per second
+        requested: u32,
+
+        /// The maximum profile velocity in increments per second
+        max: u32,
+    },
+
+    /// The requested velocity exceeds the maximum motor speed and the policy rejects it
+    VelocityAboveMotorLimit {
+        /// The device number
+        device: usize,
+
+        /// The requested velocity in increments per second
+        requested: u32,
+
+        /// The maximum motor speed in increments per second
+        max: u32,
+    },
+
+    /// An absolute move was requested, but the device isn't homed
+    NotHomed(usize),
+
+    /// Another motion of the device is still active
+    MotionActive(usize),
+
+    /// The following error exceeded the supervision threshold, the move has been halted
+    FollowingErrorExceeded {
+        /// The following error in increments
+        value: i32,
+
+        /// The supervision threshold in increments
+        threshold: u32,
+    },
+
+    /// The requested velocity is zero, so the servo wouldn't move
+    ZeroVelocity(usize),
+
+    /// The move was retried after a fault, but every attempt failed or the drive couldn't be
+    /// recovered
+    RetryFailed {
+        /// The device number
+        device: usize,
+
+        /// The errors of all attempts, the last one ended the retries
+        attempts: Vec<Self>,
+
+        /// The error recovering from the last fault, if recovering failed
+        recovery: Option<EnableError>,
+    },
+}
+
+impl From<WaitTimeout> for MovementError {
+    fn from(error: WaitTimeout) -> Self {
+        match error {
+            WaitTimeout::EmergencyStopped(device, _) => Self::EmergencyStopped(device),
+            WaitTimeout::Fault(device, status) => Self::Fault {
+                device,
+                status,
+                details: None,
+            },
+            WaitTimeout::Expired(device, status) => Self::Timeout(device, status),
+        }
+    }
 }
 
 impl Debug for MovementError {
@@ -70,6 +509,99 @@ impl Debug for MovementError {
             }
             Self::Ethercat(error) => write!(f, "{error}"),
             Self::SetMode(error) => write!(f, "Error while setting movement mode: {error:?}"),
+            Self::Wait(error) => write!(f, "Movement failed: {error:?}"),
+            Self::EmergencyStopped(device) => {
+                write!(f, "Movement of device {device} aborted by emergency stop")
+            }
+            Self::Aborted(device, position) => {
+                write!(
+                    f,
+                    "Movement of device {device} aborted at position {position}"
+                )
+            }
+            Self::NoActiveMove(device) => write!(f, "No movement of device {device} is active"),
+            Self::SetpointRejected(device) => {
+                write!(f, "Device {device} didn't acknowledge the new setpoint")
+            }
+            Self::QueueFull(device) => {
+                write!(f, "Device {device} can't accept more queued setpoints")
+            }
+            Self::WritingAcceleration(device, error) => {
+                write!(
+                    f,
+                    "Writing acceleration failed on device {device}: {error:?}"
+                )
+            }
+            Self::WritingDeceleration(device, error) => {
+                write!(
+                    f,
+                    "Writing deceleration failed on device {device}: {error:?}"
+                )
+            }
+            Self::Fault {
+                device,
+                status,
+                details,
+            } => match details {
+                Some(details) => write!(f, "Device {device} faulted during movement: {details}"),
+                None => write!(
+                    f,
+                    "Device {device} faulted during movement, status word {:#06x}",
+                    status.raw()
+                ),
+            },
+            Self::Timeout(device, status) => write!(
+                f,
+                "Movement of device {device} didn't complete in time, status word {:#06x}",
+                status.raw()
+            ),
+            Self::TargetOutOfLimits { target, min, max } => write!(
+                f,
+                "Target {target} is outside of the software position limits {min}..={max}"
+            ),
+            Self::VelocityTooHigh { requested, max } => write!(
+                f,
+                "Velocity {requested} exceeds the maximum profile velocity {max}"
+            ),
+            Self::VelocityAboveMotorLimit {
+                device,
+                requested,
+                max,
+            } => write!(
+                f,
+                "Velocity {requested} exceeds the maximum motor speed {max} of device {device}"
+            ),
+            Self::NotHomed(device) => write!(
+                f,
+                "Device {device} isn't homed, absolute moves require a home position"
+            ),
+            Self::MotionActive(device) => {
+                write!(f, "Device {device} is still executing another motion")
+            }
+            Self::FollowingErrorExceeded { value, threshold } => write!(
+                f,
+                "Following error {value} exceeded the threshold {threshold}, the move was halted"
+            ),
+            Self::ZeroVelocity(device) => {
+                write!(f, "Device {device} can't move with a velocity of zero")
+            }
+            Self::RetryFailed {
+                device,
+                attempts,
+                recovery,
+            } => {
+                write!(
+                    f,
+                    "Move of device {device} failed after {} attempts",
+                    attempts.len()
+                )?;
+                if let Some(error) = recovery {
+                    write!(f, ", recovering failed: {error:?}")?;
+                }
+                attempts
+                    .last()
+                    .map_or(Ok(()), |error| write!(f, ", last error: {error:?}"))
+            }
         }
     }
 }
@@ -112,18 +644,109 @@ impl Debug for FullControlMovementError {
     }
 }
 
-/// How to calculate the new position for the servo
-#[derive(Debug, PartialEq, Eq)]
+/// An error returned when a device doesn't look like a `CiA402` drive.
+/// The device is returned, so it can be used for something else.
+pub struct FromDeviceError<
+    'device,
+    'controller: 'device,
+    const MAX_DEVICES: usize,
+    const PDI_LENGTH: usize,
+> {
+    /// The device that couldn't be wrapped
+    pub device: Device<'device, 'controller, MAX_DEVICES, PDI_LENGTH>,
+
+    /// The error returned while reading the operation mode display
+    pub error: EthercrabError,
+}
+
+impl<const MAX_DEVICES: usize, const PDI_LENGTH: usize> Debug
+    for FromDeviceError<'_, '_, MAX_DEVICES, PDI_LENGTH>
+{
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "Device {} doesn't look like a CiA402 drive, reading the operation mode failed: {:?}",
+            self.device.id, self.error
+        )
+    }
+}
+
+/// How to calculate the new position for the servo.
+///
+/// The drive interprets relative targets (control word bit 6) according to the relative option
+/// of the position option code (0x60F2, bits 0 and 1): relative to the previous target,
+/// relative to the position demand value, or relative to the actual position.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum MovementMode {
-    /// Set the new position based on the current position
+    /// Set the new position based on the current position, as interpreted by the position
+    /// option code the drive is configured with
+    #[deprecated(
+        note = "The meaning depends on the drive configuration, use `MovementMode::RelativeToTarget` or `MovementMode::RelativeToActual`"
+    )]
     Relative,
 
     /// Set the new position to the requested value
     Absolute,
+
+    /// Add the requested value to the previous target. Chained moves don't accumulate the
+    /// following error. The position option code is set to relative to the previous target on
+    /// the first move, if the drive supports it.
+    RelativeToTarget,
+
+    /// Add the requested value to the actual position when the move starts. The target is sent
+    /// to the drive as an absolute target, so the position option code doesn't matter.
+    RelativeToActual,
+}
+
+impl MovementMode {
+    /// Returns whether the target is relative to a position instead of absolute
+    pub const fn is_relative(self) -> bool {
+        !matches!(self, Self::Absolute)
+    }
+
+    /// Returns whether the target is sent to the drive as a relative target (control word bit 6)
+    #[expect(
+        deprecated,
+        reason = "The drive interprets plain relative targets itself"
+    )]
+    pub(crate) const fn drive_relative(self) -> bool {
+        matches!(self, Self::Relative | Self::RelativeToTarget)
+    }
+
+    /// Calculates the absolute position a move to the target ends at, starting from the
+    /// position. Moves relative to the previous target add the target to `previous_target`, see
+    /// `Servo::previous_target`. Plain relative moves are calculated from the position.
+    #[expect(
+        deprecated,
+        reason = "Plain relative moves are calculated from the position"
+    )]
+    pub const fn end_position(self, target: i32, position: i32, previous_target: i32) -> i32 {
+        match self {
+            Self::Absolute => target,
+            Self::RelativeToTarget => previous_target.saturating_add(target),
+            Self::Relative | Self::RelativeToActual => position.saturating_add(target),
+        }
+    }
+}
+
+/// Checks whether the drive reached the home position or reported a homing error
+const fn homing_ended(status: StatusWord) -> bool {
+    status.is_set(StatusWordBit::AckStartRefReached)
+        || status.is_set(StatusWordBit::ModeSpecificError)
+}
+
+/// Clears the control bits and the halt bit and sets the homing operation start bit
+const fn start_homing_control(control: ControlWord) -> ControlWord {
+    control
+        .without_control()
+        .without(ControlBit::Halt)
+        .with(ControlBit::Control4)
 }
 
 /// The direction the device should jog in
-enum JoggingDirection {
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum JoggingDirection {
     /// Positive jogging direction
     Positive,
 
@@ -131,10 +754,108 @@ enum JoggingDirection {
     Negative,
 }
 
+/// Clears the jog direction bits when dropped, so a cancelled jog doesn't keep moving
+struct JogGuard<'servo, 'device, 'controller, const MAX_DEVICES: usize, const PDI_LENGTH: usize> {
+    /// The jogging servo
+    servo: &'servo mut Servo<'device, 'controller, MAX_DEVICES, PDI_LENGTH>,
+}
+
+impl<const MAX_DEVICES: usize, const PDI_LENGTH: usize> Drop
+    for JogGuard<'_, '_, '_, MAX_DEVICES, PDI_LENGTH>
+{
+    fn drop(&mut self) {
+        self.servo.device.unset_control();
+    }
+}
+
 /// The struct responsible for controlling the servo motor.
-pub struct Servo<'device, 'controller: 'device, const MAX_DEVICES: usize, const PDI_LENGTH: usize>(
-    Device<'device, 'controller, MAX_DEVICES, PDI_LENGTH>,
-);
+///
+/// Dropping the servo sets the halt bit and clears the start and direction bits in the outputs,
+/// so a drive executing a jog or setpoint stops once the controller cycles again.
+#[expect(
+    clippy::struct_excessive_bools,
+    reason = "The flags track independent state of the drive, not a mode"
+)]
+pub struct Servo<'device, 'controller: 'device, const MAX_DEVICES: usize, const PDI_LENGTH: usize> {
+    /// The device used to communicate with the drive
+    device: Device<'device, 'controller, MAX_DEVICES, PDI_LENGTH>,
+
+    /// The addresses of the vendor specific objects of the drive
+    vendor_objects: &'static VendorObjects,
+
+    /// The rated current and torque of the motor, read on first use
+    motor_ratings: Option<MotorRatings>,
+
+    /// The maximum torque in thousandths of the rated torque, read on first use
+    max_torque: Option<u16>,
+
+    /// Whether setpoints have been queued since entering interpolated position mode
+    interpolation_fed: bool,
+
+    /// Whether the running positioning move has been paused with the halt bit
+    paused: bool,
+
+    /// The number of queued setpoints the drive may still be executing
+    queued_moves: u8,
+
+    /// The per-axis defaults used by the motion calls
+    config: ServoConfig,
+
+    /// The distance before a software position limit at which jogs are stopped, if configured
+    jog_stop_margin: Option<u32>,
+
+    /// The position at which the running jog is stopped, if it's moving towards a limit
+    jog_supervision: Option<JogSupervision>,
+
+    /// The direction of the running jog, if jogging
+    jog_direction: Option<JoggingDirection>,
+
+    /// The torque limits to restore after a move until contact, if it didn't restore them
+    pending_torque_limits: Option<TorqueLimits>,
+
+    /// The supervision of the following error during positioning moves, if enabled
+    following_error_supervision: Option<FollowingErrorSupervision>,
+
+    /// The software position limits of the drive, read on first use.
+    /// Contains `None` if the drive doesn't support software position limits.
+    #[expect(
+        clippy::option_option,
+        reason = "The outer option tells whether the limits have been read"
+    )]
+    software_limits: Option<Option<SoftwareLimits>>,
+
+    /// Whether targets are checked against the software position limits before moving
+    check_limits: bool,
+
+    /// The maximum profile velocity in increments per second, read on first use
+    max_profile_velocity: Option<u32>,
+
+    /// The maximum motor speed of the drive, read on first use
+    max_motor_speed: Option<u32>,
+
+    /// The last profile acceleration written to or read from the drive
+    profile_acceleration: Option<u32>,
+
+    /// The last profile deceleration written to or read from the drive
+    profile_deceleration: Option<u32>,
+
+    /// The polarity of the drive, read on first use
+    polarity: Option<PositionPolarity>,
+
+    /// The conversion between increments and engineering units, if configured
+    unit_scaling: Option<UnitScaling>,
+
+    /// The range at which the position of a modulo axis wraps, if configured
+    modulo_range: Option<i32>,
+
+    /// Whether the position option code has been set to interpret relative targets relative
+    /// to the previous target, or the drive turned out not to support it
+    relative_option_configured: bool,
+
+    /// The absolute target of the last setpoint relative to the previous target, while the
+    /// commanded target holds its distance. `None` while the commanded target is absolute.
+    relative_target: Option<i32>,
+}
 
 impl<'device, 'controller: 'device, const MAX_DEVICES: usize, const PDI_LENGTH: usize>
     Servo<'device, 'controller, MAX_DEVICES, PDI_LENGTH>
@@ -153,176 +874,509 @@ impl<'device, 'controller: 'device, const MAX_DEVICES: usize, const PDI_LENGTH:
         controller: &'controller Controller<'_, MAX_DEVICES, PDI_LENGTH>,
         device_number: usize,
     ) -> Result<Self, EnableError> {
-        Ok(Self(Device::new(controller, device_number).await?))
+        let mut device = Device::new(controller, device_number).await?;
+        if device.drive_type() == Ok(DriveType::Stepper) {
+            log::info!(
+                "Device {device_number} is a stepper drive, `Stepper` offers its specific settings"
+            );
+        }
+        Ok(Self::with_device(device, ServoConfig::new()))
+    }
+
+    /// Creates a handle to a servo drive without resetting or enabling it.
+    /// See `Device::attach`.
+    ///
+    /// # Errors
+    /// Returns an error if:
+    /// - The device doesn't exist
+    /// - Another handle to the device exists
+    pub fn attach(
+        controller: &'controller Controller<'_, MAX_DEVICES, PDI_LENGTH>,
+        device_number: usize,
+    ) -> Result<Self, AttachError> {
+        Ok(Self::with_device(
+            Device::attach(controller, device_number)?,
+            ServoConfig::new(),
+        ))
+    }
+
+    /// Wraps a device that is already in use as a servo, without resetting or enabling it.
+    /// The device is only wrapped if it looks like a `CiA402` drive, which is checked by reading
+    /// the operation mode display (0x6061) over SDO.
+    ///
+    /// # Errors
+    /// Returns the device if the operation mode display couldn't be read
+    pub async fn from_device(
+        mut device: Device<'device, 'controller, MAX_DEVICES, PDI_LENGTH>,
+    ) -> Result<Self, FromDeviceError<'device, 'controller, MAX_DEVICES, PDI_LENGTH>> {
+        match device
+            .read_object::<u8>(objects::MODES_OF_OPERATION_DISPLAY)
+            .await
+        {
+            Ok(_) => Ok(Self::with_device(device, ServoConfig::new())),
+            Err(error) => Err(FromDeviceError { device, error }),
+        }
+    }
+
+    /// Unwraps the inner device, so it can be used by other code.
+    /// The device stays claimed and isn't changed on the bus, also not when the device is
+    /// dropped later on.
+    pub fn release(mut self) -> Device<'device, 'controller, MAX_DEVICES, PDI_LENGTH> {
+        self.device.halt_on_drop = false;
+        self.device
+    }
+
+    /// Creates a servo around a device with the configuration, without writing anything to the
+    /// drive. The device halts commanded motion when the servo is dropped.
+    const fn with_device(
+        mut device: Device<'device, 'controller, MAX_DEVICES, PDI_LENGTH>,
+        config: ServoConfig,
+    ) -> Self {
+        device.halt_on_drop = true;
+        Self {
+            device,
+            vendor_objects: &festo::CMMT,
+            motor_ratings: None,
+            max_torque: None,
+            interpolation_fed: false,
+            paused: false,
+            queued_moves: 0,
+            config,
+            jog_stop_margin: None,
+            jog_supervision: None,
+            jog_direction: None,
+            pending_torque_limits: None,
+            following_error_supervision: None,
+            software_limits: None,
+            check_limits: true,
+            max_profile_velocity: None,
+            max_motor_speed: None,
+            profile_acceleration: None,
+            profile_deceleration: None,
+            polarity: None,
+            unit_scaling: None,
+            modulo_range: None,
+            relative_option_configured: false,
+            relative_target: None,
+        }
     }
 
     /// Returns a reference to the inner device for more specific control
     pub const fn device(&self) -> &Device<'device, 'controller, MAX_DEVICES, PDI_LENGTH> {
-        &self.0
+        &self.device
     }
 
     /// Returns a mutable reference to the inner device for more specific control
     pub fn device_mut(&mut self) -> &mut Device<'device, 'controller, MAX_DEVICES, PDI_LENGTH> {
-        &mut self.0
+        &mut self.device
+    }
+
+    /// Returns the addresses of the vendor specific objects used for this drive
+    pub const fn vendor_objects(&self) -> &'static VendorObjects {
+        self.vendor_objects
+    }
+
+    /// Sets the addresses of the vendor specific objects, for drives not in the Festo CMMT family
+    pub fn set_vendor_objects(&mut self, vendor_objects: &'static VendorObjects) {
+        self.vendor_objects = vendor_objects;
+    }
+
+    /// Reads the (partial) name of the drive.
+    ///
+    /// # Errors
+    /// Returns an error if another reference to the device exists
+    pub fn name(&mut self) -> Result<String, EthercrabError> {
+        self.device.name()
+    }
+
+    /// Reads the vendor, product, revision, and serial numbers of the drive.
+    ///
+    /// # Errors
+    /// Returns an error if another reference to the device exists
+    pub fn identity(&mut self) -> Result<SubDeviceIdentity, EthercrabError> {
+        self.device.identity()
+    }
+
+    /// Reads the alias address of the drive.
+    ///
+    /// # Errors
+    /// Returns an error if another reference to the device exists
+    pub fn alias_address(&mut self) -> Result<u16, EthercrabError> {
+        self.device.alias_address()
+    }
+
+    /// Reads the configured address of the drive.
+    ///
+    /// # Errors
+    /// Returns an error if another reference to the device exists
+    pub fn configured_address(&mut self) -> Result<u16, EthercrabError> {
+        self.device.configured_address()
+    }
+
+    /// Reads the full name of the drive, only the first call communicates with the drive.
+    ///
+    /// # Errors
+    /// Returns an error if:
+    /// - Another reference to the device exists
+    /// - The description couldn't be read
+    pub async fn description(&mut self) -> Result<Option<String>, EthercrabError> {
+        self.device.description().await
+    }
+
+    /// Reads all identifying information of the drive.
+    ///
+    /// # Errors
+    /// Returns an error if another reference to the device exists
+    pub async fn info(&mut self) -> Result<DeviceInfo, EthercrabError> {
+        self.device.info().await
+    }
+
+    /// Retrieves the current position of the servo in increments.
+    ///
+    ///  # Errors
+    /// Returns an error if another reference to the device exists
+    pub fn get_position(&mut self) -> Result<i32, EthercrabError> {
+        self.device.read_input(pdo::input::POSITION_ACTUAL_VALUE)
     }
 
-    /// Retrieves the current position of the servo.
+    /// Retrieves the current position of the servo as the unsigned value of the raw bytes.
+    /// Positions left of zero wrap around, use `get_position` unless the raw value is needed.
     ///
     ///  # Errors
     /// Returns an error if another reference to the device exists
-    pub fn get_position(&mut self) -> Result<u32, EthercrabError> {
-        /// The address of the actual current position
-        const POSITION_ACTUAL_VALUE_ADDRESS: usize = 3;
-
-        // Select the device
-        let sub_device = self
-            .0
-            .controller
-            .group()
-            .subdevice(self.0.controller.main_device(), self.0.id)?;
-
-        // Read the position as an u32
-        Ok(sub_device
-            .inputs_raw()
-            .iter()
-            .copied()
-            .skip(POSITION_ACTUAL_VALUE_ADDRESS)
-            .take(4)
-            .enumerate()
-            .map(|(i, value)| u32::from(value) << (8 * i))
-            .sum())
-    }
-
-    /// Moves the servo to home (default position).
+    pub fn get_position_raw(&mut self) -> Result<u32, EthercrabError> {
+        self.device.read_input(pdo::input::POSITION_ACTUAL_VALUE)
+    }
+
+    /// Retrieves the current velocity of the servo in increments per second.
+    ///
+    /// # Errors
+    /// Returns an error if another reference to the device exists
+    pub fn get_velocity(&mut self) -> Result<i32, EthercrabError> {
+        self.device.read_input(pdo::input::VELOCITY_ACTUAL_VALUE)
+    }
+
+    /// Retrieves the current torque of the servo in thousandths of the rated torque.
+    ///
+    /// # Errors
+    /// Returns an error if another reference to the device exists
+    pub fn get_torque(&mut self) -> Result<i16, EthercrabError> {
+        self.device.read_input(pdo::input::TORQUE_ACTUAL_VALUE)
+    }
+
+    /// Retrieves the current torque of the servo in percent of the rated torque.
+    ///
+    /// # Errors
+    /// Returns an error if another reference to the device exists
+    pub fn get_torque_percent(&mut self) -> Result<f32, EthercrabError> {
+        self.get_torque().map(|torque| f32::from(torque) / 10.0)
+    }
+
+    /// Sets the maximum time homing may take, 120 seconds by default
+    pub fn set_homing_timeout(&mut self, timeout: Duration) {
+        self.config.homing_timeout = timeout;
+    }
+
+    /// Sets the maximum time a jog movement may take to stop, 60 seconds by default
+    pub fn set_jog_timeout(&mut self, timeout: Duration) {
+        self.config.jog_timeout = timeout;
+    }
+
+    /// Sets the velocity of `jog_positive` and `jog_negative` in increments per second.
+    /// Without it, they move at the profile velocity left in the outputs.
+    pub fn set_jog_velocity(&mut self, velocity: u32) {
+        self.config.default_jog_velocity = Some(velocity);
+    }
+
+    /// Moves the servo to home (default position) according to the policy, waiting at most the
+    /// homing timeout. See `home_with_timeout`.
+    ///
+    /// This replaces `home(always: bool)`: `true` is `HomingPolicy::Always` and `false` is
+    /// `HomingPolicy::IfNotHomed`. The deprecated `home_bool` keeps the old signature.
     ///
     /// # Errors
     /// Returns an error if:
     /// - The device is disabled
     /// - The servo can't be set to homing mode
-    pub async fn home(&mut self, always: bool) -> Result<(), HomingError> {
-        if !self.0.ready_state() {
-            return Err(HomingError::DeviceDisabled(self.0.id));
-        }
-
-        // Set the device to the homing mode
-        self.0
-            .set_mode(OperationMode::Homing)
+    /// - The device faulted or reported a homing error
+    /// - The device didn't reach the home position in time
+    /// - The policy requires the device to be homed, but it isn't
+    ///
+    /// # Returns
+    /// Whether homing was performed
+    pub async fn home(&mut self, policy: HomingPolicy) -> Result<bool, HomingError> {
+        self.home_with_timeout(policy, self.config.homing_timeout)
             .await
-            .map_err(HomingError::SetMode)?;
+    }
 
-        // Display a warning, if the device is already homed and the device shouldn't always home.
-        if self.0.get_bit(
-            StatusWordBit::DriveHomed as u8,
-            MappedPdo::ControlStatusWord,
-        ) && !always
-        {
-            log::info!("device already homed");
+    /// Moves the servo to home (default position), always if `always` is set, otherwise only
+    /// if it isn't homed yet.
+    ///
+    /// # Errors
+    /// See `home`
+    #[deprecated(note = "Use `home` with a `HomingPolicy` instead")]
+    pub async fn home_bool(&mut self, always: bool) -> Result<(), HomingError> {
+        let policy = if always {
+            HomingPolicy::Always
         } else {
-            log::info!("device {} starting homing", self.0.id);
-            // Clear the control bits, but set control bit 4
-            self.0.unset_control();
-            self.0
-                .set_bit(ControlBit::Control4 as u8, MappedPdo::ControlStatusWord);
-
-            // Wait until the device is homed
-            while !self.0.get_bit(
-                StatusWordBit::AckStartRefReached as u8,
-                MappedPdo::ControlStatusWord,
-            ) {
-                self.0.controller.cycle().await;
-            }
+            HomingPolicy::IfNotHomed
+        };
+        self.home(policy).await.map(|_| ())
+    }
 
-            // Clear the bit
-            self.0
-                .unset_bit(ControlBit::Control4 as u8, MappedPdo::ControlStatusWord);
+    /// Moves the servo to home (default position) according to the policy.
+    /// The timeout covers both switching to the homing mode and reaching the home position.
+    /// Homing is halted when the timeout expires.
+    ///
+    /// # Errors
+    /// Returns an error if:
+    /// - The device is disabled
+    /// - The servo can't be set to homing mode
+    /// - The device faulted or reported a homing error
+    /// - The device didn't reach the home position in time
+    /// - The policy requires the device to be homed, but it isn't
+    ///
+    /// # Returns
+    /// Whether homing was performed
+    pub async fn home_with_timeout(
+        &mut self,
+        policy: HomingPolicy,
+        timeout: Duration,
+    ) -> Result<bool, HomingError> {
+        let start = Instant::now();
+        match homing::run_homing(&mut self.device, policy, start, timeout).await {
+            Ok(homed) => Ok(homed),
+            Err(failure) => Err(self.homing_failure(failure).await),
         }
-        Ok(())
     }
 
-    /// Move the servo in the requested direction.
+    /// Move the servo in the requested direction, at the velocity if one is passed.
     ///
     /// # Errors
     /// Returns an error if:
+    /// - The velocity is zero
     /// - The device is disabled
     /// - The servo couldn't be set to jogging mode
-    async fn jog(&mut self, direction: JoggingDirection) -> Result<(), JoggingError> {
-        if !self.0.ready_state() {
-            return Err(JoggingError::DeviceDisabled(self.0.id));
+    /// - The previous motion didn't complete in time
+    /// - The velocity couldn't be written
+    async fn jog(
+        &mut self,
+        direction: JoggingDirection,
+        velocity: Option<u32>,
+    ) -> Result<(), JoggingError> {
+        if velocity == Some(0) {
+            return Err(JoggingError::ZeroVelocity(self.device.id));
         }
+        if !self.device.ready_state() {
+            return Err(JoggingError::DeviceDisabled(self.device.id));
+        }
+        let velocity = match velocity {
+            Some(velocity) => Some(self.check_jog_velocity(velocity).await?),
+            None => None,
+        };
 
         // Set the jogging mode
-        self.0
+        self.device
             .set_mode(OperationMode::Jog)
             .await
             .map_err(JoggingError::SetMode)?;
 
         // Clear the control bits
-        self.0.unset_control();
+        self.device.unset_control();
 
         // Wait until the previous motion has completed
-        while !self.0.get_bit(
-            StatusWordBit::MotionComplete as u8,
-            MappedPdo::ControlStatusWord,
-        ) {
-            self.0.controller.cycle().await;
+        let result = self
+            .device
+            .wait_for_motion(
+                |status| status.is_set(StatusWordBit::MotionComplete),
+                Instant::now(),
+                self.config.jog_timeout,
+            )
+            .await;
+        if let Err(error) = result {
+            return Err(self.jog_error(error).await);
+        }
+
+        // Supervise the software position limit in the jogging direction
+        self.start_jog_supervision(direction, velocity).await?;
+
+        // Set the jog velocity before the direction, so the servo never starts at another one
+        if let Some(velocity) = velocity {
+            self.device
+                .apply_outputs(|outputs| {
+                    velocity.write(&mut outputs[pdo::output::PROFILE_VELOCITY..]);
+                })
+                .map_err(|error| JoggingError::Ethercat(self.device.id, error))?;
         }
 
         // Set the jogging direction
-        self.0.set_bit(
-            match direction {
+        self.jog_direction = Some(direction);
+        let _ = self.device.update_control_word(|control| {
+            control.with(match direction {
                 JoggingDirection::Positive => ControlBit::Control4,
                 JoggingDirection::Negative => ControlBit::Control5,
-            } as u8,
-            MappedPdo::ControlStatusWord,
-        );
+            })
+        });
         Ok(())
     }
 
-    /// Moves the servo in positive direction.
+    /// Moves the servo in positive direction, at the jog velocity if one was set.
     ///
     /// # Errors
     /// Returns an error if:
+    /// - The jog velocity is zero
     /// - The device is disabled
     /// - The servo couldn't be set to jogging mode
     pub async fn jog_positive(&mut self) -> Result<(), JoggingError> {
-        if self.0.controller.verbose() {
+        if self.device.controller.verbose() {
             log::info!("Begin jog in positive direction");
         }
-        self.jog(JoggingDirection::Positive).await
+        self.jog(JoggingDirection::Positive, self.config.default_jog_velocity)
+            .await
     }
 
-    /// Moves the servo in negative direction.
+    /// Moves the servo in negative direction, at the jog velocity if one was set.
     ///
     /// # Errors
     /// Returns an error if:
+    /// - The jog velocity is zero
     /// - The device is disabled
     /// - The servo couldn't be set to jogging mode
     pub async fn jog_negative(&mut self) -> Result<(), JoggingError> {
-        if self.0.controller.verbose() {
+        if self.device.controller.verbose() {
             log::info!("Begin jog in negative direction");
         }
-        self.jog(JoggingDirection::Negative).await
+        self.jog(JoggingDirection::Negative, self.config.default_jog_velocity)
+            .await
+    }
+
+    /// Moves the servo in positive direction at the velocity in increments per second.
+    ///
+    /// # Errors
+    /// Returns an error if:
+    /// - The velocity is zero
+    /// - The device is disabled
+    /// - The servo couldn't be set to jogging mode
+    /// - The velocity couldn't be written
+    pub async fn jog_positive_at(&mut self, velocity: u32) -> Result<(), JoggingError> {
+        if self.device.controller.verbose() {
+            log::info!("Begin jog in positive direction at {velocity}");
+        }
+        self.jog(JoggingDirection::Positive, Some(velocity)).await
+    }
+
+    /// Moves the servo in negative direction at the velocity in increments per second.
+    ///
+    /// # Errors
+    /// Returns an error if:
+    /// - The velocity is zero
+    /// - The device is disabled
+    /// - The servo couldn't be set to jogging mode
+    /// - The velocity couldn't be written
+    pub async fn jog_negative_at(&mut self, velocity: u32) -> Result<(), JoggingError> {
+        if self.device.controller.verbose() {
+            log::info!("Begin jog in negative direction at {velocity}");
+        }
+        self.jog(JoggingDirection::Negative, Some(velocity)).await
+    }
+
+    /// Jogs the servo in the direction at the velocity in increments per second for the
+    /// duration, then stops it.
+    /// The jog is stopped even if the returned future is dropped before it completed.
+    ///
+    /// # Errors
+    /// Returns an error if:
+    /// - The velocity is zero
+    /// - The device is disabled
+    /// - The servo couldn't be set to jogging mode
+    /// - The position couldn't be read
+    /// - The device faulted while jogging or didn't stop in time
+    ///
+    /// # Returns
+    /// The distance travelled in increments
+    pub async fn jog_for(
+        &mut self,
+        direction: JoggingDirection,
+        velocity: u32,
+        duration: Duration,
+    ) -> Result<i32, JoggingError> {
+        let id = self.device.id;
+        let start_position = self
+            .get_position()
+            .map_err(|error| JoggingError::Ethercat(id, error))?;
+        self.jog(direction, Some(velocity)).await?;
+
+        // Keep jogging until the duration expired or the software position limit is reached,
+        // the guard stops the jog when cancelled
+        let started = Instant::now();
+        let mut limit_reached = None;
+        {
+            let guard = JogGuard { servo: self };
+            loop {
+                match guard
+                    .servo
+                    .device
+                    .check_motion(|_| false, started, duration)
+                {
+                    Some(Err(WaitTimeout::Expired(..))) => break,
+                    Some(Err(error)) => return Err(guard.servo.jog_error(error).await),
+                    _ => {}
+                }
+                let reached = guard
+                    .servo
+                    .jog_limit_reached()
+                    .map_err(|error| JoggingError::Ethercat(id, error))?;
+                if reached.is_some() {
+                    limit_reached = reached;
+                    break;
+                }
+                guard.servo.device.controller.next_cycle().await;
+            }
+        }
+
+        self.jog_stop().await?;
+        if let Some(error) = limit_reached {
+            return Err(error);
+        }
+        let end_position = self
+            .get_position()
+            .map_err(|error| JoggingError::Ethercat(id, error))?;
+        Ok(end_position.saturating_sub(start_position))
     }
 
     /// Stop moving the servo (required position has been reached)
-    pub async fn jog_stop(&mut self) {
-        if self.0.controller.verbose() {
+    ///
+    /// # Errors
+    /// Returns an error if the device faulted or didn't stop in time
+    pub async fn jog_stop(&mut self) -> Result<(), JoggingError> {
+        if self.device.controller.verbose() {
             log::info!("Stopping jog movement");
         }
         // Return if the device isn't operational
-        if !self.0.ready_state() {
-            return;
+        self.jog_supervision = None;
+        self.jog_direction = None;
+        if !self.device.ready_state() {
+            return Ok(());
         }
 
         // Clear the control bits
-        self.0.unset_control();
+        self.device.unset_control();
 
         // Wait until the motion is complete
-        while !self.0.get_bit(
-            StatusWordBit::MotionComplete as u8,
-            MappedPdo::ControlStatusWord,
-        ) {
-            self.0.controller.cycle().await;
+        let result = self
+            .device
+            .wait_for_motion(
+                |status| status.is_set(StatusWordBit::MotionComplete),
+                Instant::now(),
+                self.config.jog_timeout,
+            )
+            .await;
+        match result {
+            Ok(_) => {
+                self.emit(MotionEventKind::Halted);
+                Ok(())
+            }
+            Err(error) => Err(self.jog_error(error).await),
         }
     }
 
@@ -347,79 +1401,34 @@ impl<'device, 'controller: 'device, const MAX_DEVICES: usize, const PDI_LENGTH:
     /// - The drive is not enabled
     /// - The positition profile couldn't be set to set mode
     /// - The position couldn't be set
+    /// - The device faulted or the movement didn't complete in time
+    /// - The device has been emergency stopped
     pub async fn move_position(
         &mut self,
         target: i32,
         movement: MovementMode,
     ) -> Result<(), MovementError> {
-        if self.0.controller.verbose() {
-            log::info!(
-                "Starting {movement:?} movement to position {target} of device {}",
-                self.0.id
-            );
-        }
-        if !self.0.ready_state() {
-            return Err(MovementError::DriveDisabled(self.0.id));
-        }
-        // Set the direction to move in
-        self.0
-            .set_mode(OperationMode::ProfilePosition)
+        self.move_with(target, &MoveOptions::new().with_mode(movement))
             .await
-            .map_err(MovementError::SetMode)?;
-
-        // Clear the control bits
-        self.0.unset_control();
-
-        // Set control bit 6 if the motion has to be relative to the current positon
-        if movement == MovementMode::Relative {
-            self.0
-                .set_bit(ControlBit::Control6 as u8, MappedPdo::ControlStatusWord);
-        }
-
-        // Set the position to the requested value
-        self.set_position(target, 3)
-            .map_err(MovementError::Ethercat)?;
-
-        // Perform an update cycle
-        self.0.controller.cycle().await;
-
-        // Clear the halt bit
-        self.0
-            .unset_bit(ControlBit::Halt as u8, MappedPdo::ControlStatusWord);
-
-        // Set control bit 4
-        self.0
-            .set_bit(ControlBit::Control4 as u8, MappedPdo::ControlStatusWord);
-
-        // Wait until the requested position has been reached
-        while !self.0.get_bit(
-            StatusWordBit::AckStartRefReached as u8,
-            MappedPdo::ControlStatusWord,
-        ) {
-            self.0.controller.cycle().await;
-        }
-
-        // Wait until the motion is complete
-        while !self.0.get_bit(
-            StatusWordBit::MotionComplete as u8,
-            MappedPdo::ControlStatusWord,
-        ) {
-            if self.0.controller.verbose() {
-                let id = self.0.id;
-                log::info!(
-                    "Move device {id} {movement:?} : {target} {}",
-                    self.get_position().map_err(MovementError::Ethercat)?
-                );
-            }
+    }
 
-            //  Clear the control bits
-            self.0.unset_control();
-            self.0.controller.cycle().await;
-        }
-        if self.0.controller.verbose() {
-            log::info!("Movement completed");
-        }
-        Ok(())
+    /// Moves the servo to the requested position with the requested options and waits until
+    /// the motion is complete.
+    ///
+    /// # Errors
+    /// Returns an error if:
+    /// - The drive is not enabled or emergency stopped
+    /// - The target is outside of the software position limits
+    /// - The velocity exceeds the maximum profile velocity
+    /// - The device couldn't be set to profile position mode
+    /// - The acceleration, deceleration, position or velocity couldn't be written
+    /// - The device faulted or the movement didn't complete in time
+    pub async fn move_with(
+        &mut self,
+        target: i32,
+        options: &MoveOptions,
+    ) -> Result<(), MovementError> {
+        self.move_with_retry(target, options).await
     }
 
     /// Move the servo to the requested position with the requested velocity.
@@ -428,20 +1437,20 @@ impl<'device, 'controller: 'device, const MAX_DEVICES: usize, const PDI_LENGTH:
     /// Returns an error if:
     /// - Another reference to the device already exists
     /// - The device isn't enabled
+    /// - The velocity exceeds the maximum profile velocity of the drive
     /// - The position profile couldn't be set to set mode
     /// - The position couldn't be set
     pub async fn move_position_velocity(
         &mut self,
         target: i32,
-        velocity: u32,
+        velocity: impl Into<RawVelocity>,
         movement: MovementMode,
     ) -> Result<(), MovementError> {
-        // Set the profile velocity
-        self.set_profile_velocity(velocity, MappedPdo::ProfileVelocity)
-            .map_err(MovementError::Ethercat)?;
-
-        // Move to the requested position
-        self.move_position(target, movement).await
+        // Move to the requested position with the requested profile velocity
+        let options = MoveOptions::new()
+            .with_mode(movement)
+            .with_velocity(velocity);
+        self.move_with(target, &options).await
     }
 
     /// Move the servo at the requested velocity, acceleration, and deceleration to the
@@ -464,90 +1473,43 @@ impl<'device, 'controller: 'device, const MAX_DEVICES: usize, const PDI_LENGTH:
         deceleration: u32,
         movement: MovementMode,
     ) -> Result<(), FullControlMovementError> {
-        {
-            // Select the requested device
-            let sub_device = self
-                .0
-                .controller
-                .group()
-                .subdevice(self.0.controller.main_device(), self.0.id)
-                .map_err(FullControlMovementError::DeviceInUse)?;
-
-            // Set the requested acceleration
-            sub_device
-                .sdo_write(0x6083, 0, acceleration)
-                .await
-                .map_err(|error| {
-                    FullControlMovementError::WritingAccelerationFailed(self.0.id, error)
-                })?;
-
-            // Set the requested deceleration
-            sub_device
-                .sdo_write(0x6084, 0, deceleration)
-                .await
-                .map_err(|error| {
-                    FullControlMovementError::WritingDecelerationFailed(self.0.id, error)
-                })?;
-        }
-
-        // Move to the requested position with the requested velocity
-        self.move_position_velocity(target, velocity, movement)
+        // Move to the requested position with the requested velocity, acceleration and
+        // deceleration
+        let options = MoveOptions::new()
+            .with_mode(movement)
+            .with_velocity(velocity)
+            .with_acceleration(acceleration)
+            .with_deceleration(deceleration);
+        self.move_with(target, &options)
             .await
-            .map_err(FullControlMovementError::MovementFailed)
-    }
-
-    #[expect(
-        clippy::needless_pass_by_ref_mut,
-        reason = "Mutable reference is used to decrease the chance of multiple references existing for the same device"
-    )]
-    /// Set the servo position to the requested value.
-    fn set_position(&mut self, target: i32, byte: u8) -> Result<(), EthercrabError> {
-        // Select the device
-        let mut sub_device = self
-            .0
-            .controller
-            .group()
-            .subdevice(self.0.controller.main_device(), self.0.id)?;
-
-        // Set the positon bit to the requested value
-        let byte = usize::from(byte);
-        sub_device.outputs_raw_mut()[byte..byte + size_of::<i32>()]
-            .copy_from_slice(&target.to_le_bytes());
-        Ok(())
+            .map_err(|error| match error {
+                MovementError::WritingAcceleration(device, error) => {
+                    FullControlMovementError::WritingAccelerationFailed(device, error)
+                }
+                MovementError::WritingDeceleration(device, error) => {
+                    FullControlMovementError::WritingDecelerationFailed(device, error)
+                }
+                error => FullControlMovementError::MovementFailed(error),
+            })
     }
 
-    /// Set the velocity to the requested value.
+    /// Disables the device after use, see `Device::disable`.
     ///
     /// # Errors
-    /// Returns an error if another reference to the device exists
-    #[expect(
-        clippy::needless_pass_by_ref_mut,
-        reason = "Mutable reference is used to decrease the chance of multiple references existing for the same device"
-    )]
-    fn set_profile_velocity(
-        &mut self,
-        velocity: u32,
-        byte: MappedPdo,
-    ) -> Result<(), EthercrabError> {
-        // Select the device
-        let mut sub_device = self
-            .0
-            .controller
-            .group()
-            .subdevice(self.0.controller.main_device(), self.0.id)?;
-
-        // Set the requested profile velocity
-        let byte = byte as usize;
-        sub_device.outputs_raw_mut()[byte..byte + size_of::<u32>()]
-            .copy_from_slice(&velocity.to_le_bytes());
-        Ok(())
+    /// Returns an error if the device didn't get disabled.
+    pub async fn disable(self) -> Result<DisableReport, DisableError> {
+        self.device.disable().await
     }
 
-    /// Disables the device after use.
+    /// Disables the device after use, waiting at most `timeout` until operation is disabled.
     ///
     /// # Errors
-    /// Returns an error if the device didn't get disabled.
-    pub async fn disable(self) -> Result<(), Timeout> {
-        self.0.disable().await
+    /// Returns an error if the device couldn't be accessed or didn't get disabled in time, see
+    /// `Device::disable_with_timeout`
+    pub async fn disable_with_timeout(
+        self,
+        timeout: Duration,
+    ) -> Result<DisableReport, DisableError> {
+        self.device.disable_with_timeout(timeout).await
     }
 }
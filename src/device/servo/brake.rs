@@ -0,0 +1,234 @@
+//! This module contains the control of the holding brake of the motor.
+//!
+//! # Safety
+//! A released holding brake no longer holds the load when the drive isn't producing torque.
+//! On vertical or otherwise loaded axes this lets the load fall, possibly injuring people or
+//! damaging the machine. Only release the brake while the load is secured, or while the drive
+//! is enabled and holding the position. Brake tests apply a torque against the closed brake,
+//! so the axis has to be able to withstand the test torque.
+
+use super::Servo;
+use crate::device::objects::Object;
+use core::{
+    fmt::{self, Debug, Formatter},
+    time::Duration,
+};
+use ethercrab::error::{Error as EthercrabError, MailboxError};
+use std::time::Instant;
+
+/// The maximum time a brake test may take
+const BRAKE_TEST_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// An error returned while controlling or testing the holding brake
+pub enum BrakeError {
+    /// The drive doesn't support this brake function
+    Unsupported(usize),
+
+    /// The drive isn't enabled for operation and the brake release wasn't forced
+    DeviceDisabled(usize),
+
+    /// The device is emergency stopped
+    EmergencyStopped(usize),
+
+    /// The brake test didn't finish in time
+    Timeout(usize),
+
+    /// The drive aborted the brake test
+    TestAborted(usize),
+
+    /// Communication with the drive failed
+    Ethercat(usize, EthercrabError),
+}
+
+impl Debug for BrakeError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Unsupported(device) => {
+                write!(f, "Device {device} doesn't support this brake function")
+            }
+            Self::DeviceDisabled(device) => write!(
+                f,
+                "Releasing the brake of device {device} refused, the device is disabled"
+            ),
+            Self::EmergencyStopped(device) => {
+                write!(f, "Device {device} is emergency stopped")
+            }
+            Self::Timeout(device) => write!(f, "Brake test of device {device} timed out"),
+            Self::TestAborted(device) => write!(f, "Device {device} aborted the brake test"),
+            Self::Ethercat(device, error) => {
+                write!(f, "Failed to communicate with device {device}: {error}")
+            }
+        }
+    }
+}
+
+/// The result of a brake holding test
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BrakeTestResult {
+    /// The brake held the load at the test torque
+    Passed,
+
+    /// The brake slipped at the test torque
+    Failed,
+}
+
+/// The state reported by the drive while running the brake test
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum BrakeTestState {
+    /// No test has been started
+    Idle,
+
+    /// The test is running
+    Running,
+
+    /// The brake held the load
+    Passed,
+
+    /// The brake slipped
+    Failed,
+
+    /// The drive aborted the test
+    Aborted,
+}
+
+impl From<u8> for BrakeTestState {
+    fn from(value: u8) -> Self {
+        match value {
+            0 => Self::Idle,
+            1 => Self::Running,
+            2 => Self::Passed,
+            3 => Self::Failed,
+            _ => Self::Aborted,
+        }
+    }
+}
+
+impl<const MAX_DEVICES: usize, const PDI_LENGTH: usize> Servo<'_, '_, MAX_DEVICES, PDI_LENGTH> {
+    /// Maps an error of a brake object to a brake error.
+    /// Aborted transfers mean the drive doesn't support the object.
+    const fn brake_error(&self, error: EthercrabError) -> BrakeError {
+        match error {
+            EthercrabError::Mailbox(MailboxError::Aborted { .. }) => {
+                BrakeError::Unsupported(self.device.id)
+            }
+            error => BrakeError::Ethercat(self.device.id, error),
+        }
+    }
+
+    /// Returns the requested brake object or an error if the drive doesn't support it
+    const fn brake_object(&self, object: Option<Object>) -> Result<Object, BrakeError> {
+        match object {
+            Some(object) => Ok(object),
+            None => Err(BrakeError::Unsupported(self.device.id)),
+        }
+    }
+
+    /// Manually releases the holding brake of the motor.
+    /// See the module documentation for the safety implications.
+    ///
+    /// The release is refused while the drive isn't enabled for operation, as the load isn't
+    /// held by the motor then. Passing `force` skips this check, for maintenance on a secured
+    /// axis only.
+    ///
+    /// # Errors
+    /// Returns an error if:
+    /// - The drive doesn't support releasing the brake
+    /// - The drive isn't enabled and `force` is false
+    /// - The device is emergency stopped
+    /// - Communication with the drive failed
+    pub async fn release_brake(&mut self, force: bool) -> Result<(), BrakeError> {
+        let object = self.brake_object(self.vendor_objects.brake_release)?;
+        if self.device.emergency_stopped() {
+            return Err(BrakeError::EmergencyStopped(self.device.id));
+        }
+        if !force && !self.device.ready_state() {
+            return Err(BrakeError::DeviceDisabled(self.device.id));
+        }
+
+        log::warn!("Releasing the holding brake of device {}", self.device.id);
+        self.device
+            .write_object(object, 1u8)
+            .await
+            .map_err(|error| self.brake_error(error))
+    }
+
+    /// Returns the control of the holding brake to the drive.
+    ///
+    /// # Errors
+    /// Returns an error if:
+    /// - The drive doesn't support releasing the brake
+    /// - Communication with the drive failed
+    pub async fn engage_brake(&mut self) -> Result<(), BrakeError> {
+        let object = self.brake_object(self.vendor_objects.brake_release)?;
+        if self.device.controller.verbose() {
+            log::info!("Engaging the holding brake of device {}", self.device.id);
+        }
+        self.device
+            .write_object(object, 0u8)
+            .await
+            .map_err(|error| self.brake_error(error))
+    }
+
+    /// Tests whether the holding brake holds the load.
+    /// The drive applies the requested torque against the closed brake and checks whether the
+    /// motor moves. The drive has to be enabled for operation.
+    ///
+    /// # Arguments
+    /// `load_torque_threshold`: the test torque in thousandths of the rated torque
+    ///
+    /// # Errors
+    /// Returns an error if:
+    /// - The drive doesn't support the brake test
+    /// - The drive is disabled or emergency stopped
+    /// - The test didn't finish in time or was aborted by the drive
+    /// - Communication with the drive failed
+    pub async fn brake_test(
+        &mut self,
+        load_torque_threshold: u16,
+    ) -> Result<BrakeTestResult, BrakeError> {
+        let torque = self.brake_object(self.vendor_objects.brake_test_torque)?;
+        let start = self.brake_object(self.vendor_objects.brake_test_start)?;
+        let state = self.brake_object(self.vendor_objects.brake_test_state)?;
+        if self.device.emergency_stopped() {
+            return Err(BrakeError::EmergencyStopped(self.device.id));
+        }
+        if !self.device.ready_state() {
+            return Err(BrakeError::DeviceDisabled(self.device.id));
+        }
+
+        // Set the test torque and start the test
+        self.device
+            .write_object(torque, load_torque_threshold)
+            .await
+            .map_err(|error| self.brake_error(error))?;
+        self.device
+            .write_object(start, 1u8)
+            .await
+            .map_err(|error| self.brake_error(error))?;
+
+        // Wait for the drive to finish the test
+        let deadline = Instant::now() + BRAKE_TEST_TIMEOUT;
+        loop {
+            let raw: u8 = self
+                .device
+                .read_object(state)
+                .await
+                .map_err(|error| self.brake_error(error))?;
+            match BrakeTestState::from(raw) {
+                BrakeTestState::Passed => return Ok(BrakeTestResult::Passed),
+                BrakeTestState::Failed => return Ok(BrakeTestResult::Failed),
+                BrakeTestState::Aborted => return Err(BrakeError::TestAborted(self.device.id)),
+                BrakeTestState::Idle | BrakeTestState::Running => {}
+            }
+
+            // Stop waiting if the device is emergency stopped or the deadline passed
+            if self.device.emergency_stopped() {
+                return Err(BrakeError::EmergencyStopped(self.device.id));
+            }
+            if Instant::now() >= deadline {
+                return Err(BrakeError::Timeout(self.device.id));
+            }
+            self.device.controller.next_cycle().await;
+        }
+    }
+}
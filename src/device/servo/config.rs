@@ -0,0 +1,179 @@
+//! This module contains the `ServoConfig`, the per-axis defaults used by the motion calls.
+//!
+//! Most applications move an axis with the same velocity, acceleration and deceleration every
+//! time. These are configured once instead of being passed to every move. The acceleration and
+//! deceleration are written to the drive when the configuration is applied, so moves only write
+//! them over SDO when they override them.
+
+use super::{Servo, HOMING_TIMEOUT, MOTION_TIMEOUT, MOVING_DEADBAND, STANDSTILL_TIME};
+use crate::{
+    controller::Controller,
+    device::{Device, EnableError, ENABLE_TIMEOUT},
+};
+use core::{
+    fmt::{self, Debug, Formatter},
+    time::Duration,
+};
+use ethercrab::error::Error as EthercrabError;
+
+/// What to do when a move or jog requests a velocity above the maximum motor speed (0x6080)
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum MotorSpeedPolicy {
+    /// Command the maximum motor speed instead and log a warning
+    #[default]
+    Clamp,
+
+    /// Refuse to start the move or jog
+    Reject,
+}
+
+/// The per-axis defaults of a servo.
+/// Defaults that are `None` keep the value currently configured in the drive.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct ServoConfig {
+    /// The profile velocity of moves in increments per second
+    pub default_velocity: Option<u32>,
+
+    /// The profile acceleration of moves in increments per second squared
+    pub default_acceleration: Option<u32>,
+
+    /// The profile deceleration of moves in increments per second squared
+    pub default_deceleration: Option<u32>,
+
+    /// The velocity of jogs without an explicit velocity in increments per second
+    pub default_jog_velocity: Option<u32>,
+
+    /// The maximum time each step of enabling the drive may take, 10 seconds by default
+    pub enable_timeout: Duration,
+
+    /// The maximum time homing may take, 120 seconds by default
+    pub homing_timeout: Duration,
+
+    /// The maximum time a jog movement may take to stop, 60 seconds by default
+    pub jog_timeout: Duration,
+
+    /// The velocity in increments per second below which the servo is considered standing
+    /// still, 100 by default
+    pub moving_deadband: u32,
+
+    /// The time the velocity has to stay within the moving deadband before the servo is
+    /// considered standing still, 20 milliseconds by default
+    pub standstill_time: Duration,
+
+    /// The bit of the digital inputs (0x60FD) reporting safe torque off, if the drive reports it
+    pub sto_input: Option<u8>,
+
+    /// What to do when a velocity above the maximum motor speed is requested, clamped by default
+    pub motor_speed_policy: MotorSpeedPolicy,
+}
+
+impl ServoConfig {
+    /// Creates a configuration that keeps the settings of the drive
+    pub const fn new() -> Self {
+        Self {
+            default_velocity: None,
+            default_acceleration: None,
+            default_deceleration: None,
+            default_jog_velocity: None,
+            enable_timeout: ENABLE_TIMEOUT,
+            homing_timeout: HOMING_TIMEOUT,
+            jog_timeout: MOTION_TIMEOUT,
+            moving_deadband: MOVING_DEADBAND,
+            standstill_time: STANDSTILL_TIME,
+            sto_input: None,
+            motor_speed_policy: MotorSpeedPolicy::Clamp,
+        }
+    }
+}
+
+impl Default for ServoConfig {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// An error returned while applying a `ServoConfig`
+pub enum ConfigError {
+    /// The device couldn't be enabled
+    Enable(EnableError),
+
+    /// The default acceleration couldn't be written
+    WritingAcceleration(usize, EthercrabError),
+
+    /// The default deceleration couldn't be written
+    WritingDeceleration(usize, EthercrabError),
+}
+
+impl Debug for ConfigError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Enable(error) => write!(f, "{error:?}"),
+            Self::WritingAcceleration(device, error) => write!(
+                f,
+                "Writing the default acceleration of device {device} failed: {error:?}"
+            ),
+            Self::WritingDeceleration(device, error) => write!(
+                f,
+                "Writing the default deceleration of device {device} failed: {error:?}"
+            ),
+        }
+    }
+}
+
+impl From<EnableError> for ConfigError {
+    fn from(value: EnableError) -> Self {
+        Self::Enable(value)
+    }
+}
+
+impl<'device, 'controller: 'device, const MAX_DEVICES: usize, const PDI_LENGTH: usize>
+    Servo<'device, 'controller, MAX_DEVICES, PDI_LENGTH>
+{
+    /// Creates a new device that can be used to control a servomotor with per-axis defaults.
+    /// The default acceleration and deceleration are written to the drive once.
+    ///
+    /// # Errors
+    /// Returns an error if:
+    /// - The device failed to reset or enable
+    /// - The default acceleration or deceleration couldn't be written
+    pub async fn new_with_config(
+        controller: &'controller Controller<'_, MAX_DEVICES, PDI_LENGTH>,
+        device_number: usize,
+        config: ServoConfig,
+    ) -> Result<Self, ConfigError> {
+        let device =
+            Device::new_with_timeout(controller, device_number, config.enable_timeout).await?;
+        let mut servo = Self::with_device(device, config);
+        servo.set_config(config).await?;
+        Ok(servo)
+    }
+
+    /// Returns the per-axis defaults
+    #[must_use]
+    pub const fn config(&self) -> ServoConfig {
+        self.config
+    }
+
+    /// Changes the per-axis defaults and writes the default acceleration and deceleration to the
+    /// drive. The enable timeout is only used while creating the servo.
+    ///
+    /// # Errors
+    /// Returns an error if the default acceleration or deceleration couldn't be written
+    pub async fn set_config(&mut self, config: ServoConfig) -> Result<(), ConfigError> {
+        let id = self.device.id;
+        if let Some(acceleration) = config.default_acceleration {
+            self.set_profile_acceleration(acceleration)
+                .await
+                .map_err(|error| ConfigError::WritingAcceleration(id, error))?;
+        }
+        if let Some(deceleration) = config.default_deceleration {
+            self.set_profile_deceleration(deceleration)
+                .await
+                .map_err(|error| ConfigError::WritingDeceleration(id, error))?;
+        }
+        self.config = config;
+        Ok(())
+    }
+}
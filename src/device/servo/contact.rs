@@ -0,0 +1,139 @@
+//! This module contains moves that stop as soon as the servo makes contact, like gripping or
+//! probing.
+//!
+//! The torque limits are lowered to the contact threshold during the move with
+//! `Servo::with_torque_limit`, so the servo can't push harder than requested.
+
+use super::{motion::MoveOptions, torque_limit::TorqueLimitError, MovementError, Servo};
+use core::fmt::{self, Debug, Formatter};
+
+/// The result of a move until contact
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum ContactResult {
+    /// The servo made contact and was halted
+    Contact {
+        /// The position at which the servo stopped
+        position: i32,
+
+        /// The torque at the moment of contact in thousandths of the rated torque
+        torque: i16,
+    },
+
+    /// The servo reached the target without making contact
+    NoContact {
+        /// The position at which the servo stopped
+        position: i32,
+    },
+}
+
+/// An error returned by a move until contact
+pub enum ContactError {
+    /// The torque threshold is zero
+    ZeroThreshold(usize),
+
+    /// Lowering or restoring the torque limits failed
+    TorqueLimit(TorqueLimitError),
+
+    /// The move failed
+    Movement(MovementError),
+}
+
+impl Debug for ContactError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::ZeroThreshold(device) => {
+                write!(f, "The contact threshold of device {device} can't be zero")
+            }
+            Self::TorqueLimit(error) => write!(f, "{error:?}"),
+            Self::Movement(error) => write!(f, "{error:?}"),
+        }
+    }
+}
+
+impl From<MovementError> for ContactError {
+    fn from(value: MovementError) -> Self {
+        Self::Movement(value)
+    }
+}
+
+impl From<TorqueLimitError> for ContactError {
+    fn from(value: TorqueLimitError) -> Self {
+        Self::TorqueLimit(value)
+    }
+}
+
+/// The fraction of the requested velocity below which the servo is considered stopped
+const STALL_VELOCITY_DIVISOR: u32 = 10;
+
+impl<const MAX_DEVICES: usize, const PDI_LENGTH: usize> Servo<'_, '_, MAX_DEVICES, PDI_LENGTH> {
+    /// Moves to the target, but halts as soon as the servo makes contact.
+    /// Contact is detected when the torque exceeds the threshold while the velocity dropped to a
+    /// tenth of the requested velocity. The torque limits are lowered to the threshold during the
+    /// move and restored afterwards.
+    ///
+    /// # Parameters
+    /// `torque_threshold_per_mille`: The torque in thousandths of the rated torque
+    ///
+    /// # Errors
+    /// Returns an error if:
+    /// - The threshold is zero
+    /// - The torque limits couldn't be read or written
+    /// - The move couldn't be started or failed
+    pub async fn move_until_contact(
+        &mut self,
+        target: i32,
+        velocity: u32,
+        torque_threshold_per_mille: u16,
+        options: &MoveOptions,
+    ) -> Result<ContactResult, ContactError> {
+        if torque_threshold_per_mille == 0 {
+            return Err(ContactError::ZeroThreshold(self.device.id));
+        }
+
+        // Run the move with the torque limits lowered to the threshold
+        let options = options.clone();
+        self.with_torque_limit(torque_threshold_per_mille, |servo| {
+            Box::pin(servo.run_until_contact(target, velocity, torque_threshold_per_mille, options))
+        })
+        .await?
+        .map_err(ContactError::from)
+    }
+
+    /// Runs the move until contact with the lowered torque limits.
+    ///
+    /// # Errors
+    /// Returns an error if the move couldn't be started or failed
+    async fn run_until_contact(
+        &mut self,
+        target: i32,
+        velocity: u32,
+        torque_threshold_per_mille: u16,
+        options: MoveOptions,
+    ) -> Result<ContactResult, MovementError> {
+        let stall_velocity = velocity / STALL_VELOCITY_DIVISOR;
+        let mut handle = self
+            .start_move(target, options.with_velocity(velocity))
+            .await?;
+
+        // Check the torque and velocity every cycle until contact or the end of the move
+        loop {
+            if handle.is_done() {
+                handle.await?;
+                let position = self.get_position().map_err(MovementError::Ethercat)?;
+                return Ok(ContactResult::NoContact { position });
+            }
+            let status = handle.snapshot().map_err(MovementError::Ethercat)?;
+            if status.torque.unsigned_abs() >= torque_threshold_per_mille
+                && status.velocity.unsigned_abs() <= stall_velocity
+            {
+                let position = handle.abort().await?;
+                return Ok(ContactResult::Contact {
+                    position,
+                    torque: status.torque,
+                });
+            }
+            handle.wait_cycle().await;
+        }
+    }
+}
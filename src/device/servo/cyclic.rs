@@ -0,0 +1,381 @@
+//! This module contains the cyclic synchronous position (CSP) and torque (CST) modes of the servo.
+//!
+//! In these modes the application calculates the trajectory and writes a new target position or
+//! torque every cycle, which the drive follows without planning a profile itself. This requires:
+//! - Background cycling (`Controller::start_cycling`), so a setpoint is sent every cycle
+//! - The drive to be synchronized to the distributed clocks SYNC0 event
+//! - The interpolation time period of the drive to match the cycle time
+//!
+//! A target written with `csp_target` or `cst_target` is sent with the next cycle, so the drive
+//! follows the setpoints with a delay of one to two cycles.
+
+use super::Servo;
+use crate::{
+    device::{objects, OperationMode, SetModeError},
+    pdo::{self, PdoValue},
+    trajectory::Profile,
+};
+use core::{
+    fmt::{self, Debug, Formatter},
+    time::Duration,
+};
+use ethercrab::error::Error as EthercrabError;
+use std::time::Instant;
+
+/// The sync mode of a drive synchronized to the distributed clocks SYNC0 event
+const SYNC_MODE_DC_SYNC0: u16 = 2;
+
+/// The time the torque is ramped down to zero when leaving cyclic synchronous torque mode
+const CST_RAMP_OUT_TIME: Duration = Duration::from_millis(100);
+
+/// An error returned while using the cyclic synchronous modes
+pub enum CyclicModeError {
+    /// The controller isn't cycling in the background, start it with `Controller::start_cycling`
+    NotCycling(usize),
+
+    /// The drive isn't synchronized to the distributed clocks, contains the sync mode
+    DcSyncDisabled(usize, u16),
+
+    /// The interpolation time period of the drive doesn't match the cycle time
+    InterpolationPeriodMismatch(usize, Duration, Duration),
+
+    /// The drive is disabled
+    DriveDisabled(usize),
+
+    /// The device has been emergency stopped
+    EmergencyStopped(usize),
+
+    /// The device couldn't be set to the requested mode
+    SetMode(SetModeError),
+
+    /// Communication failed or there was an existing reference to the device
+    Ethercat(usize, EthercrabError),
+}
+
+impl Debug for CyclicModeError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::NotCycling(device) => write!(
+                f,
+                "Cyclic mode of device {device} requires background cycling, call \
+                 Controller::start_cycling first"
+            ),
+            Self::DcSyncDisabled(device, mode) => write!(
+                f,
+                "Cyclic mode of device {device} requires distributed clocks synchronization, \
+                 sync mode is {mode}"
+            ),
+            Self::InterpolationPeriodMismatch(device, period, cycle_time) => write!(
+                f,
+                "Interpolation period {period:?} of device {device} doesn't match cycle time \
+                 {cycle_time:?}"
+            ),
+            Self::DriveDisabled(device) => {
+                write!(f, "Drive {device} is disabled, cyclic mode not possible")
+            }
+            Self::EmergencyStopped(device) => write!(f, "Device {device} is emergency stopped"),
+            Self::SetMode(error) => write!(f, "Error while setting cyclic mode: {error:?}"),
+            Self::Ethercat(device, error) => {
+                write!(f, "Failed to communicate with device {device}: {error}")
+            }
+        }
+    }
+}
+
+/// Converts the interpolation time period of the drive to a duration
+fn interpolation_period(value: u8, index: i8) -> Duration {
+    Duration::from_secs_f64(f64::from(value) * 10_f64.powi(i32::from(index)))
+}
+
+impl<const MAX_DEVICES: usize, const PDI_LENGTH: usize> Servo<'_, '_, MAX_DEVICES, PDI_LENGTH> {
+    /// Checks whether the drive can be used in a cyclic synchronous mode and switches to it.
+    /// The current position is written as target first, so the servo doesn't jump.
+    ///
+    /// # Errors
+    /// Returns an error if:
+    /// - The controller isn't cycling in the background
+    /// - The drive is disabled or emergency stopped
+    /// - The drive isn't synchronized to the distributed clocks
+    /// - The interpolation time period doesn't match the cycle time
+    /// - The device couldn't be set to the requested mode
+    pub(crate) async fn enter_cyclic_mode(
+        &mut self,
+        mode: OperationMode,
+    ) -> Result<(), CyclicModeError> {
+        let id = self.device.id;
+        if !self.device.controller.is_cycling() {
+            return Err(CyclicModeError::NotCycling(id));
+        }
+        if self.device.emergency_stopped() {
+            return Err(CyclicModeError::EmergencyStopped(id));
+        }
+        if !self.device.ready_state() {
+            return Err(CyclicModeError::DriveDisabled(id));
+        }
+
+        // Check whether the drive is synchronized to the distributed clocks
+        let sync_mode: u16 = self
+            .device
+            .read_object(objects::SYNC_MODE)
+            .await
+            .map_err(|error| CyclicModeError::Ethercat(id, error))?;
+        if sync_mode != SYNC_MODE_DC_SYNC0 {
+            return Err(CyclicModeError::DcSyncDisabled(id, sync_mode));
+        }
+
+        // Check whether the drive interpolates over a single cycle
+        let value = self
+            .device
+            .read_object(objects::INTERPOLATION_TIME_PERIOD_VALUE)
+            .await
+            .map_err(|error| CyclicModeError::Ethercat(id, error))?;
+        let index = self
+            .device
+            .read_object(objects::INTERPOLATION_TIME_INDEX)
+            .await
+            .map_err(|error| CyclicModeError::Ethercat(id, error))?;
+        let period = interpolation_period(value, index);
+        let cycle_time = self.device.controller.cycle_time();
+        if period.abs_diff(cycle_time) > Duration::from_micros(1) {
+            return Err(CyclicModeError::InterpolationPeriodMismatch(
+                id, period, cycle_time,
+            ));
+        }
+
+        // Hold the current position and switch to the requested mode
+        self.hold_position()
+            .map_err(|error| CyclicModeError::Ethercat(id, error))?;
+        self.device
+            .set_mode(mode)
+            .await
+            .map_err(CyclicModeError::SetMode)
+    }
+
+    /// Writes the current position as target position
+    ///
+    /// # Errors
+    /// Returns an error if another reference to the device exists
+    fn hold_position(&mut self) -> Result<(), EthercrabError> {
+        let position = self.get_position()?;
+        self.relative_target = None;
+        self.device
+            .write_output(pdo::output::TARGET_POSITION, position)
+    }
+
+    /// Switches the servo to cyclic synchronous position mode.
+    /// See the module documentation for the requirements.
+    ///
+    /// # Errors
+    /// Returns an error if:
+    /// - The controller isn't cycling in the background
+    /// - The drive is disabled or emergency stopped
+    /// - The drive isn't synchronized to the distributed clocks
+    /// - The interpolation time period doesn't match the cycle time
+    /// - The device couldn't be set to cyclic synchronous position mode
+    pub async fn enter_csp(&mut self) -> Result<(), CyclicModeError> {
+        self.enter_cyclic_mode(OperationMode::CyclingSyncPosition)
+            .await
+    }
+
+    /// Writes the target position for the next cycle in cyclic synchronous position mode.
+    ///
+    /// # Errors
+    /// Returns an error if another reference to the device exists
+    pub fn csp_target(&mut self, position: i32) -> Result<(), CyclicModeError> {
+        self.relative_target = None;
+        self.device
+            .write_output(pdo::output::TARGET_POSITION, position)
+            .map_err(|error| CyclicModeError::Ethercat(self.device.id, error))
+    }
+
+    /// Leaves cyclic synchronous position mode, holding the current position in profile position
+    /// mode.
+    ///
+    /// # Errors
+    /// Returns an error if the current position couldn't be held or the mode couldn't be set
+    pub async fn leave_csp(&mut self) -> Result<(), CyclicModeError> {
+        self.hold_position()
+            .map_err(|error| CyclicModeError::Ethercat(self.device.id, error))?;
+        self.device
+            .set_mode(OperationMode::ProfilePosition)
+            .await
+            .map_err(CyclicModeError::SetMode)
+    }
+
+    /// Switches the servo to cyclic synchronous torque mode.
+    /// The target torque is set to zero first and the maximum torque is read for clamping the
+    /// setpoints. See the module documentation for the requirements.
+    ///
+    /// # Errors
+    /// Returns an error if:
+    /// - The controller isn't cycling in the background
+    /// - The drive is disabled or emergency stopped
+    /// - The maximum torque couldn't be read
+    /// - The drive isn't synchronized to the distributed clocks
+    /// - The interpolation time period doesn't match the cycle time
+    /// - The device couldn't be set to cyclic synchronous torque mode
+    pub async fn enter_cst(&mut self) -> Result<(), CyclicModeError> {
+        let id = self.device.id;
+        self.max_torque()
+            .await
+            .map_err(|error| CyclicModeError::Ethercat(id, error))?;
+        self.device
+            .write_output(pdo::output::TARGET_TORQUE, 0_i16)
+            .map_err(|error| CyclicModeError::Ethercat(id, error))?;
+        self.enter_cyclic_mode(OperationMode::CyclingSyncTorque)
+            .await
+    }
+
+    /// Writes the target torque in thousandths of the rated torque for the next cycle in cyclic
+    /// synchronous torque mode. The torque is clamped to the maximum torque of the drive.
+    ///
+    /// # Errors
+    /// Returns an error if another reference to the device exists
+    ///
+    /// # Returns
+    /// The clamped torque that was written
+    pub fn cst_target(&mut self, torque: i16) -> Result<i16, CyclicModeError> {
+        let torque = clamp_torque(torque, self.max_torque.unwrap_or(0));
+        self.device
+            .write_output(pdo::output::TARGET_TORQUE, torque)
+            .map_err(|error| CyclicModeError::Ethercat(self.device.id, error))?;
+        Ok(torque)
+    }
+
+    /// Leaves cyclic synchronous torque mode.
+    /// The torque is ramped down to zero, after which the current position is held in profile
+    /// position mode.
+    ///
+    /// # Errors
+    /// Returns an error if the torque couldn't be written or the mode couldn't be set
+    pub async fn leave_cst(&mut self) -> Result<(), CyclicModeError> {
+        let id = self.device.id;
+        let start: i16 = self
+            .device
+            .read_output(pdo::output::TARGET_TORQUE)
+            .map_err(|error| CyclicModeError::Ethercat(id, error))?;
+
+        // Ramp the torque down to zero over multiple cycles
+        let cycle_time = self.device.controller.cycle_time();
+        let steps = u32::try_from(CST_RAMP_OUT_TIME.as_nanos() / cycle_time.as_nanos().max(1))
+            .unwrap_or(u32::MAX)
+            .max(1);
+        for step in (0..steps).rev() {
+            self.device
+                .write_output(pdo::output::TARGET_TORQUE, ramp_torque(start, step, steps))
+                .map_err(|error| CyclicModeError::Ethercat(id, error))?;
+            self.device.controller.next_cycle().await;
+        }
+
+        self.leave_csp().await
+    }
+}
+
+impl<const MAX_DEVICES: usize, const PDI_LENGTH: usize> Servo<'_, '_, MAX_DEVICES, PDI_LENGTH> {
+    /// Sets the velocity feed-forward in increments per second.
+    /// The drive adds this velocity to the output of its position controller, so it's mainly
+    /// useful in cyclic synchronous position mode, where it's sent with every setpoint.
+    ///
+    /// # Errors
+    /// Returns an error if another reference to the device exists
+    pub fn set_velocity_offset(&mut self, offset: i32) -> Result<(), EthercrabError> {
+        self.device
+            .apply_outputs(|outputs| offset.write(&mut outputs[pdo::output::VELOCITY_OFFSET..]))
+    }
+
+    /// Returns the velocity feed-forward currently written to the outputs.
+    ///
+    /// # Errors
+    /// Returns an error if another reference to the device exists
+    pub fn velocity_offset(&mut self) -> Result<i32, EthercrabError> {
+        self.device.read_output(pdo::output::VELOCITY_OFFSET)
+    }
+
+    /// Sets the torque feed-forward in thousandths of the rated torque.
+    /// The drive adds this torque to the output of its velocity controller, so it's mainly
+    /// useful in the cyclic synchronous position and velocity modes, for example to compensate
+    /// gravity.
+    ///
+    /// # Errors
+    /// Returns an error if another reference to the device exists
+    pub fn set_torque_offset(&mut self, offset: i16) -> Result<(), EthercrabError> {
+        self.device
+            .apply_outputs(|outputs| offset.write(&mut outputs[pdo::output::TORQUE_OFFSET..]))
+    }
+
+    /// Returns the torque feed-forward currently written to the outputs.
+    ///
+    /// # Errors
+    /// Returns an error if another reference to the device exists
+    pub fn torque_offset(&mut self) -> Result<i16, EthercrabError> {
+        self.device.read_output(pdo::output::TORQUE_OFFSET)
+    }
+}
+
+impl<const MAX_DEVICES: usize, const PDI_LENGTH: usize> Servo<'_, '_, MAX_DEVICES, PDI_LENGTH> {
+    /// Moves the servo along the trajectory in cyclic synchronous position mode.
+    /// Every cycle the next sample of the trajectory is written as target position. The end
+    /// position is held for `settle_time` before returning, the servo stays in cyclic
+    /// synchronous position mode afterwards.
+    ///
+    /// # Errors
+    /// Returns an error if:
+    /// - The servo couldn't be switched to cyclic synchronous position mode
+    /// - A target position couldn't be written
+    /// - The device has been emergency stopped
+    pub async fn run_profile(
+        &mut self,
+        profile: &(impl Profile + Sync),
+        settle_time: Duration,
+    ) -> Result<(), CyclicModeError> {
+        self.enter_csp().await?;
+
+        // Write a sample of the trajectory every cycle
+        let cycle_time = self.device.controller.cycle_time();
+        let mut end = 0;
+        for sample in profile.samples(cycle_time) {
+            if self.device.emergency_stopped() {
+                return Err(CyclicModeError::EmergencyStopped(self.device.id));
+            }
+            end = round_position(sample.position);
+            self.csp_target(end)?;
+            self.device.controller.next_cycle().await;
+        }
+
+        // Hold the end position until the servo settled
+        let deadline = Instant::now() + settle_time;
+        while Instant::now() < deadline {
+            if self.device.emergency_stopped() {
+                return Err(CyclicModeError::EmergencyStopped(self.device.id));
+            }
+            self.csp_target(end)?;
+            self.device.controller.next_cycle().await;
+        }
+        Ok(())
+    }
+}
+
+/// Rounds a trajectory position to the nearest increment
+#[expect(
+    clippy::cast_possible_truncation,
+    reason = "Trajectories run between 32-bit positions, the cast saturates otherwise"
+)]
+pub(super) fn round_position(position: f64) -> i32 {
+    position.round() as i32
+}
+
+/// Clamps the torque to the maximum torque in both directions
+fn clamp_torque(torque: i16, max_torque: u16) -> i16 {
+    let max_torque = i16::try_from(max_torque).unwrap_or(i16::MAX);
+    torque.clamp(-max_torque, max_torque)
+}
+
+/// Returns the torque of a linear ramp from `start` to zero, with `remaining` of `steps` steps to
+/// go
+#[expect(
+    clippy::cast_possible_truncation,
+    reason = "The ramped torque is between zero and the start torque"
+)]
+fn ramp_torque(start: i16, remaining: u32, steps: u32) -> i16 {
+    (i64::from(start) * i64::from(remaining) / i64::from(steps)) as i16
+}
@@ -0,0 +1,244 @@
+//! This module contains readouts of the condition of the drive, like temperatures.
+//! These values are read over SDO, so they shouldn't be read in time critical loops.
+
+use super::{status::ServoStatus, Servo};
+use crate::device::objects;
+use ethercrab::error::{Error as EthercrabError, MailboxError};
+
+/// The temperatures measured by the drive
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Temperatures {
+    /// The temperature of the power stage in degrees celsius
+    pub power_stage_c: f32,
+
+    /// The temperature of the motor in degrees celsius, if the drive measures it
+    pub motor_c: Option<f32>,
+}
+
+/// Selects which optional values are read for a health report.
+/// Every selected value costs an extra SDO read.
+#[derive(Debug, Default, Clone, Copy)]
+pub struct HealthReportOptions {
+    /// Read the voltage of the DC link
+    pub dc_bus_voltage: bool,
+
+    /// Read the actual motor current
+    pub current: bool,
+}
+
+/// The condition of the drive at the time of reading
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct HealthReport {
+    /// The inputs of the drive at the time of reading
+    pub status: ServoStatus,
+
+    /// The temperatures of the drive, if reported
+    pub temperatures: Option<Temperatures>,
+
+    /// The DC link voltage in volts, if requested and supported by the drive
+    pub dc_bus_voltage: Option<f32>,
+
+    /// The actual motor current, if requested and supported by the drive
+    pub current: Option<MotorCurrent>,
+}
+
+/// The current flowing through the motor
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct MotorCurrent {
+    /// The current in thousandths of the rated current of the motor
+    pub per_mille: i16,
+
+    /// The current in ampere
+    pub amps: f32,
+}
+
+/// The rated values of the motor, the reference of values in thousandths of the rated value
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct MotorRatings {
+    /// The rated current in milliampere (0x6075)
+    pub rated_current_ma: u32,
+
+    /// The rated torque in millinewton meter (0x6076), `None` if the drive doesn't report it
+    pub rated_torque_mnm: Option<u32>,
+}
+
+/// Converts a voltage in millivolts to volts
+#[expect(
+    clippy::cast_precision_loss,
+    reason = "Voltages are far below the range where f32 loses precision"
+)]
+fn millivolts_to_volts(millivolts: u32) -> f32 {
+    millivolts as f32 / 1_000.0
+}
+
+/// Converts a value in thousandths of the rated value to the unit of the rated value.
+/// The rated value is in thousandths of that unit.
+#[expect(
+    clippy::cast_possible_truncation,
+    reason = "Drive currents and torques are far below the range of f32"
+)]
+fn per_mille_of_rated(per_mille: i16, rated: u32) -> f32 {
+    (f64::from(per_mille) * f64::from(rated) / 1_000_000.0) as f32
+}
+
+impl<const MAX_DEVICES: usize, const PDI_LENGTH: usize> Servo<'_, '_, MAX_DEVICES, PDI_LENGTH> {
+    /// Reads the power stage and motor temperature of the drive.
+    ///
+    /// # Errors
+    /// Returns an error if communication with the drive failed
+    ///
+    /// # Returns
+    /// The temperatures or `None` if the drive doesn't report the power stage temperature
+    pub async fn temperatures(&mut self) -> Result<Option<Temperatures>, EthercrabError> {
+        // Read the power stage temperature, which every supported drive should report
+        let Some(object) = self.vendor_objects.power_stage_temperature else {
+            return Ok(None);
+        };
+        let Some(power_stage_c) = self.device.read_optional_object(object).await? else {
+            return Ok(None);
+        };
+
+        // Read the motor temperature, if the drive measures it
+        let motor_c = match self.vendor_objects.motor_temperature {
+            Some(object) => self.device.read_optional_object(object).await?,
+            None => None,
+        };
+
+        Ok(Some(Temperatures {
+            power_stage_c,
+            motor_c,
+        }))
+    }
+
+    /// Reads the voltage of the DC link (0x6079) in volts.
+    ///
+    /// # Errors
+    /// Returns an error if:
+    /// - Communication with the drive failed
+    /// - The drive doesn't support reading the DC link voltage
+    pub async fn dc_bus_voltage(&mut self) -> Result<f32, EthercrabError> {
+        self.device
+            .read_object(objects::DC_LINK_VOLTAGE)
+            .await
+            .map(millivolts_to_volts)
+    }
+
+    /// Reads the rated current (0x6075) and rated torque (0x6076) of the motor.
+    /// Only the first call communicates with the drive. A rated torque the drive doesn't support
+    /// or reports as zero is returned as `None`.
+    ///
+    /// # Errors
+    /// Returns an error if the rated current couldn't be read
+    pub async fn motor_ratings(&mut self) -> Result<MotorRatings, EthercrabError> {
+        if let Some(ratings) = self.motor_ratings {
+            return Ok(ratings);
+        }
+        let rated_current_ma = self
+            .device
+            .read_object(objects::MOTOR_RATED_CURRENT)
+            .await?;
+        let rated_torque_mnm = self
+            .device
+            .read_optional_object(objects::MOTOR_RATED_TORQUE)
+            .await?
+            .filter(|torque| *torque != 0);
+        let ratings = MotorRatings {
+            rated_current_ma,
+            rated_torque_mnm,
+        };
+        self.motor_ratings = Some(ratings);
+        Ok(ratings)
+    }
+
+    /// Reads the rated current of the motor (0x6075) in milliampere.
+    /// Only the first call communicates with the drive.
+    ///
+    /// # Errors
+    /// Returns an error if the rated current couldn't be read
+    pub async fn rated_current(&mut self) -> Result<u32, EthercrabError> {
+        self.motor_ratings()
+            .await
+            .map(|ratings| ratings.rated_current_ma)
+    }
+
+    /// Reads the actual current of the motor (0x6078).
+    ///
+    /// # Errors
+    /// Returns an error if the actual or rated current couldn't be read
+    pub async fn actual_current(&mut self) -> Result<MotorCurrent, EthercrabError> {
+        let rated_current = self.rated_current().await?;
+        let per_mille = self
+            .device
+            .read_object(objects::CURRENT_ACTUAL_VALUE)
+            .await?;
+        Ok(MotorCurrent {
+            per_mille,
+            amps: per_mille_of_rated(per_mille, rated_current),
+        })
+    }
+
+    /// Reads the actual current of the motor (0x6078) in ampere.
+    ///
+    /// # Errors
+    /// Returns an error if the actual or rated current couldn't be read
+    pub async fn actual_current_amps(&mut self) -> Result<f32, EthercrabError> {
+        self.actual_current().await.map(|current| current.amps)
+    }
+
+    /// Reads the actual torque in newton meter, converted with the rated torque of the motor.
+    ///
+    /// # Errors
+    /// Returns an error if the actual torque or the motor ratings couldn't be read
+    ///
+    /// # Returns
+    /// The torque or `None` if the drive doesn't report the rated torque
+    pub async fn actual_torque_nm(&mut self) -> Result<Option<f32>, EthercrabError> {
+        let Some(rated_torque) = self.motor_ratings().await?.rated_torque_mnm else {
+            return Ok(None);
+        };
+        let per_mille = self.get_torque()?;
+        Ok(Some(per_mille_of_rated(per_mille, rated_torque)))
+    }
+
+    /// Reads a report about the condition of the drive.
+    /// Values the drive doesn't support are left empty.
+    ///
+    /// # Errors
+    /// Returns an error if communication with the drive failed
+    pub async fn health_report(
+        &mut self,
+        options: HealthReportOptions,
+    ) -> Result<HealthReport, EthercrabError> {
+        let status = self.snapshot()?;
+        let temperatures = self.temperatures().await?;
+
+        // Only read the DC link voltage when requested
+        let dc_bus_voltage = if options.dc_bus_voltage {
+            self.device
+                .read_optional_object(objects::DC_LINK_VOLTAGE)
+                .await?
+                .map(millivolts_to_volts)
+        } else {
+            None
+        };
+
+        // Only read the motor current when requested, unsupported drives report none
+        let current = if options.current {
+            match self.actual_current().await {
+                Ok(current) => Some(current),
+                Err(EthercrabError::Mailbox(MailboxError::Aborted { .. })) => None,
+                Err(error) => return Err(error),
+            }
+        } else {
+            None
+        };
+
+        Ok(HealthReport {
+            status,
+            temperatures,
+            dc_bus_voltage,
+            current,
+        })
+    }
+}
@@ -0,0 +1,260 @@
+//! This module contains streams of motion events of a servo, for following its motion without
+//! polling.
+//!
+//! The motion calls emit the events they cause, like a started move or a halt. The controller
+//! scans the status word of the servo every cycle and emits the target reached and fault
+//! events. Every event is tagged with the cycle it was emitted in. A receiver that can't keep
+//! up loses the oldest events instead of stalling the cycle.
+
+use super::{fault_details::FaultDetails, status::ServoStatus, Servo};
+use crate::{
+    channel::{self, Receiver},
+    device::{StatusWord, StatusWordBit},
+};
+
+/// What happened to the motion of a servo
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum MotionEventKind {
+    /// A positioning move has been requested from the drive
+    MoveStarted {
+        /// The target position in increments, as requested
+        target: i32,
+    },
+
+    /// The drive acknowledged the setpoint of the move
+    SetpointAcknowledged,
+
+    /// The drive reports the target of the current mode has been reached (status bit 10)
+    TargetReached,
+
+    /// A positioning move completed at its target
+    MotionComplete,
+
+    /// The servo has been stopped by the halt bit or a quick stop
+    Halted,
+
+    /// A positioning move has been aborted
+    Aborted,
+
+    /// The drive faulted
+    Faulted {
+        /// The state of the drive when it faulted
+        details: FaultDetails,
+    },
+
+    /// Homing completed
+    Homed,
+}
+
+/// A motion event of a servo
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct MotionEvent {
+    /// The cycle the event was emitted in, see `Controller::cycle_count`
+    pub cycle: u64,
+
+    /// What happened
+    pub kind: MotionEventKind,
+}
+
+/// A stream of motion events of a servo.
+/// Events stop being emitted when the stream is dropped.
+///
+/// Holds the most recent events, older events are dropped if they aren't received in time.
+pub struct MotionEvents {
+    /// The receiving end of the channel filled by the servo and the controller
+    receiver: Receiver<MotionEvent>,
+}
+
+impl MotionEvents {
+    /// Waits for the next event.
+    ///
+    /// # Returns
+    /// The next event or `None` if the controller has been dropped
+    pub async fn recv(&self) -> Option<MotionEvent> {
+        self.receiver.recv().await
+    }
+
+    /// Returns the next event if one was emitted, without waiting
+    pub fn try_recv(&self) -> Option<MotionEvent> {
+        self.receiver.try_recv()
+    }
+
+    /// Returns the number of events dropped because they weren't received in time
+    pub fn dropped(&self) -> u64 {
+        self.receiver.dropped()
+    }
+}
+
+/// Collects the events of the status bits raised since the previous status scan, see
+/// `Controller::scan_motion_events`
+///
+/// # Parameters
+/// `device`: The device number
+/// `previous`: The status word found during the previous scan
+/// `status`: The decoded process image of the current cycle
+pub(crate) fn status_events(
+    device: usize,
+    previous: StatusWord,
+    status: &ServoStatus,
+) -> impl Iterator<Item = MotionEventKind> {
+    let raised = |bit| status.status.is_set(bit) && !previous.is_set(bit);
+    let faulted = raised(StatusWordBit::Fault).then(|| MotionEventKind::Faulted {
+        details: FaultDetails::from_status(device, status),
+    });
+    let reached = raised(StatusWordBit::MotionComplete).then_some(MotionEventKind::TargetReached);
+    faulted.into_iter().chain(reached)
+}
+
+impl<const MAX_DEVICES: usize, const PDI_LENGTH: usize> Servo<'_, '_, MAX_DEVICES, PDI_LENGTH> {
+    /// Creates a stream of the motion events of the servo, holding at most `capacity` events.
+    /// The target reached and fault events are only emitted while the controller cycles.
+    pub fn events(&mut self, capacity: usize) -> MotionEvents {
+        let (sender, receiver) = channel::bounded(capacity);
+        self.device
+            .controller
+            .register_event_sender(self.device.id, sender);
+        MotionEvents { receiver }
+    }
+
+    /// Sends a motion event to the motion event streams of the servo
+    pub(super) fn emit(&self, kind: MotionEventKind) {
+        self.device
+            .controller
+            .emit_motion_event(self.device.id, kind);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    //! Tests of the motion events of a move against a simulated drive
+
+    use super::*;
+    use crate::device::{
+        servo::{handshake, motion::Setpoint, MovementError, MovementMode},
+        simulation::{block_on, SimulatedDrive, ID, TIMEOUT},
+        Cia402State, ControlBit, Drive, OperationMode, OutputImage,
+    };
+    use std::time::Instant;
+
+    /// Creates a drive standing still in the profile position mode
+    fn profile_position_drive() -> SimulatedDrive {
+        let mut drive = SimulatedDrive::new(100);
+        drive.set_mode(OperationMode::ProfilePosition);
+        drive.cycle();
+        drive
+    }
+
+    /// Starts an absolute move with `handshake::start_move`
+    ///
+    /// # Returns
+    /// When the move was started
+    fn start_move(drive: &mut SimulatedDrive, target: i32) -> Instant {
+        let setpoint = Setpoint {
+            position: target,
+            velocity: Some(1_000),
+            mode: MovementMode::Absolute,
+            change_immediately: false,
+        };
+        let start = drive.now();
+        block_on(handshake::start_move(drive, setpoint, start, TIMEOUT)).unwrap();
+        start
+    }
+
+    /// Cycles the drive until the move ended, like awaiting the `MotionHandle`
+    fn await_move(
+        drive: &mut SimulatedDrive,
+        aborting: bool,
+        start: Instant,
+    ) -> Result<(), MovementError> {
+        loop {
+            if let Some(result) = handshake::check_move_end(drive, true, aborting, start, TIMEOUT) {
+                return result;
+            }
+            drive.cycle();
+        }
+    }
+
+    /// Returns what happened in the emitted events
+    fn kinds(drive: &SimulatedDrive) -> Vec<MotionEventKind> {
+        drive.events.iter().map(|event| event.kind).collect()
+    }
+
+    /// A simple move emits exactly the started, acknowledged, target reached and complete events,
+    /// in that order
+    #[test]
+    fn simple_move_events() {
+        let mut drive = profile_position_drive();
+        let start = start_move(&mut drive, 1_000);
+        await_move(&mut drive, false, start).unwrap();
+
+        assert_eq!(
+            kinds(&drive),
+            [
+                MotionEventKind::MoveStarted { target: 1_000 },
+                MotionEventKind::SetpointAcknowledged,
+                MotionEventKind::TargetReached,
+                MotionEventKind::MotionComplete,
+            ]
+        );
+        assert!(drive
+            .events
+            .windows(2)
+            .all(|pair| pair[0].cycle <= pair[1].cycle));
+        assert_eq!(drive.events[2].cycle, drive.events[3].cycle);
+    }
+
+    /// A fault in the middle of a move emits a single fault event with the faulted status, and
+    /// no completion event
+    #[test]
+    fn fault_event() {
+        let mut drive = profile_position_drive();
+        let start = start_move(&mut drive, 10_000);
+        drive.fault();
+        let result = await_move(&mut drive, false, start);
+        assert!(
+            matches!(result, Err(MovementError::Fault { device: ID, .. })),
+            "{result:?}"
+        );
+
+        let Some(MotionEvent {
+            kind: MotionEventKind::Faulted { details },
+            ..
+        }) = drive.events.last()
+        else {
+            panic!("No fault event: {:?}", drive.events);
+        };
+        assert_eq!(drive.events.len(), 3);
+        assert_eq!(details.device, ID);
+        assert_eq!(details.status.state(), Cia402State::Fault);
+    }
+
+    /// A move halted on its way ends as aborted at the position it stopped at, and emits the
+    /// aborted event instead of the completion event
+    #[test]
+    fn aborted_move_events() {
+        let mut drive = profile_position_drive();
+        let start = start_move(&mut drive, 10_000);
+        drive
+            .update_control_word(|control| control.with(ControlBit::Halt))
+            .unwrap();
+        let result = await_move(&mut drive, true, start);
+
+        let position = drive.position();
+        assert!(position < 10_000);
+        assert!(
+            matches!(result, Err(MovementError::Aborted(ID, stopped)) if stopped == position),
+            "{result:?}"
+        );
+        assert_eq!(
+            kinds(&drive),
+            [
+                MotionEventKind::MoveStarted { target: 10_000 },
+                MotionEventKind::SetpointAcknowledged,
+                MotionEventKind::TargetReached,
+                MotionEventKind::Aborted,
+            ]
+        );
+    }
+}
@@ -0,0 +1,187 @@
+//! This module contains the fault callbacks of a servo, for alarm handling per axis.
+//!
+//! Unlike `Device::on_fault`, which reports every change of the fault and warning bits, a fault
+//! callback is only called when the fault bit is raised and receives the collected
+//! `FaultDetails`. The controller scans the status word every cycle, so fault callbacks require
+//! background cycling. The details are taken from the process image, the error code (0x603F) is
+//! read over SDO on a separate task before the callbacks are called, so it doesn't stall the
+//! cycle.
+
+use super::{fault_details::FaultDetails, Servo};
+use crate::controller::Controller;
+use core::fmt::{self, Debug, Formatter};
+use std::sync::{Arc, Mutex, PoisonError};
+
+/// A callback called by the controller when the fault bit of a device is raised
+pub(crate) type FaultCallback = Arc<dyn Fn(FaultDetails) + Send + Sync>;
+
+/// Calls every callback with the details of a fault. The callbacks are cloned out of the list
+/// first, so a callback can register or remove fault callbacks without deadlocking.
+pub(crate) fn call_fault_callbacks(
+    callbacks: &Mutex<Vec<(u64, FaultCallback)>>,
+    details: FaultDetails,
+) {
+    let callbacks: Vec<_> = callbacks
+        .lock()
+        .unwrap_or_else(PoisonError::into_inner)
+        .iter()
+        .map(|(_, callback)| Arc::clone(callback))
+        .collect();
+    for callback in callbacks {
+        callback(details);
+    }
+}
+
+/// An error returned while registering a fault callback
+pub enum FaultCallbackError {
+    /// The controller isn't cycling in the background, start it with `Controller::start_cycling`
+    NotCycling(usize),
+
+    /// The device doesn't exist
+    NoDevice(usize),
+}
+
+impl Debug for FaultCallbackError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::NotCycling(device) => write!(
+                f,
+                "Fault callbacks of device {device} require background cycling, call \
+                 Controller::start_cycling first"
+            ),
+            Self::NoDevice(device) => write!(f, "Device {device} doesn't exist"),
+        }
+    }
+}
+
+/// Removes the fault callback when dropped.
+/// Use `FaultCallbackGuard::keep` to keep the callback for the lifetime of the controller.
+#[must_use = "The fault callback is removed when the guard is dropped"]
+pub struct FaultCallbackGuard<
+    'device,
+    'controller,
+    const MAX_DEVICES: usize,
+    const PDI_LENGTH: usize,
+> {
+    /// The controller calling the callback
+    controller: &'device Controller<'controller, MAX_DEVICES, PDI_LENGTH>,
+
+    /// The device the callback is registered for
+    device: usize,
+
+    /// The identifier of the callback
+    id: u64,
+}
+
+impl<const MAX_DEVICES: usize, const PDI_LENGTH: usize>
+    FaultCallbackGuard<'_, '_, MAX_DEVICES, PDI_LENGTH>
+{
+    /// Keeps the callback registered for the lifetime of the controller
+    pub const fn keep(self) {
+        core::mem::forget(self);
+    }
+}
+
+impl<const MAX_DEVICES: usize, const PDI_LENGTH: usize> Drop
+    for FaultCallbackGuard<'_, '_, MAX_DEVICES, PDI_LENGTH>
+{
+    fn drop(&mut self) {
+        self.controller.remove_fault_callback(self.device, self.id);
+    }
+}
+
+impl<'device, 'controller: 'device, const MAX_DEVICES: usize, const PDI_LENGTH: usize>
+    Servo<'device, 'controller, MAX_DEVICES, PDI_LENGTH>
+{
+    /// Registers a callback, called once every time the fault bit of the drive is raised, with
+    /// the details of the fault. A fault that is active while registering is reported with
+    /// the next cycle.
+    ///
+    /// The callback runs on the fault reporting task of the background cycling, after the
+    /// error code (0x603F) has been read. It must return quickly and must not block, as it
+    /// delays the reports of later faults. It may register or remove fault callbacks.
+    ///
+    /// # Errors
+    /// Returns an error if the controller isn't cycling in the background
+    ///
+    /// # Returns
+    /// A guard removing the callback when dropped
+    pub fn on_fault(
+        &mut self,
+        callback: impl Fn(FaultDetails) + Send + Sync + 'static,
+    ) -> Result<FaultCallbackGuard<'device, 'controller, MAX_DEVICES, PDI_LENGTH>, FaultCallbackError>
+    {
+        let controller = self.device.controller;
+        let device = self.device.id;
+        if !controller.is_cycling() {
+            return Err(FaultCallbackError::NotCycling(device));
+        }
+        let id = controller
+            .register_fault_callback(device, Arc::new(callback))
+            .ok_or(FaultCallbackError::NoDevice(device))?;
+        Ok(FaultCallbackGuard {
+            controller,
+            device,
+            id,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    //! Tests of calling the fault callbacks
+
+    use super::*;
+    use crate::device::StatusWord;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    /// The details of a fault of device 3
+    const DETAILS: FaultDetails = FaultDetails {
+        device: 3,
+        status: StatusWord::new(0x0008),
+        error_code: Some(0x7380),
+        diagnosis: 0,
+        position: 0,
+    };
+
+    /// Every callback receives the details, also when a callback changes the callbacks
+    #[test]
+    fn callbacks_can_change_callbacks() {
+        let callbacks = Arc::new(Mutex::new(Vec::<(u64, FaultCallback)>::new()));
+        let calls = Arc::new(AtomicUsize::new(0));
+
+        // The first callback removes itself and registers another callback
+        let list = Arc::clone(&callbacks);
+        let counter = Arc::clone(&calls);
+        callbacks.lock().unwrap().push((
+            0,
+            Arc::new(move |details| {
+                assert_eq!(details, DETAILS);
+                counter.fetch_add(1, Ordering::SeqCst);
+                let mut list = list.lock().unwrap();
+                list.retain(|(id, _)| *id != 0);
+                let counter = Arc::clone(&counter);
+                list.push((
+                    2,
+                    Arc::new(move |_| {
+                        counter.fetch_add(100, Ordering::SeqCst);
+                    }),
+                ));
+            }),
+        ));
+        let counter = Arc::clone(&calls);
+        callbacks.lock().unwrap().push((
+            1,
+            Arc::new(move |details| {
+                assert_eq!(details.error_code, Some(0x7380));
+                counter.fetch_add(10, Ordering::SeqCst);
+            }),
+        ));
+
+        // The callbacks registered while calling are only called for the next fault
+        call_fault_callbacks(&callbacks, DETAILS);
+        assert_eq!(calls.load(Ordering::SeqCst), 11);
+        call_fault_callbacks(&callbacks, DETAILS);
+        assert_eq!(calls.load(Ordering::SeqCst), 121);
+    }
+}
@@ -0,0 +1,104 @@
+//! This module contains the details of a fault, collected right after an operation failed.
+//!
+//! The status word, diagnosis message and position are part of the process image, so they're
+//! always collected. The error code (0x603F) is read over SDO on a best-effort basis.
+
+use super::{status::ServoStatus, HomingError, Servo};
+use crate::device::{objects, Drive, StatusWord, WaitTimeout};
+use core::fmt::{self, Display, Formatter};
+use ethercrab::error::Error as EthercrabError;
+
+/// The state of a drive when it faulted
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct FaultDetails {
+    /// The device number
+    pub device: usize,
+
+    /// The status word reported by the drive
+    pub status: StatusWord,
+
+    /// The error code of the drive (0x603F), if it could be read
+    pub error_code: Option<u16>,
+
+    /// The Festo diagnosis message of the drive
+    pub diagnosis: u32,
+
+    /// The actual position in increments
+    pub position: i32,
+}
+
+impl FaultDetails {
+    /// Collects the details available in the process image, without the error code
+    #[must_use]
+    pub const fn from_status(device: usize, status: &ServoStatus) -> Self {
+        Self {
+            device,
+            status: status.status,
+            error_code: None,
+            diagnosis: status.diagnosis,
+            position: status.position,
+        }
+    }
+}
+
+impl Display for FaultDetails {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "device {} in state {:?}, status word {:#06x}",
+            self.device,
+            self.status.state(),
+            self.status.raw()
+        )?;
+        if let Some(error_code) = self.error_code {
+            write!(f, ", error code {error_code:#06x}")?;
+        }
+        write!(
+            f,
+            ", diagnosis {:#010x}, position {}",
+            self.diagnosis, self.position
+        )
+    }
+}
+
+/// Collects the details of the current fault of a drive available in the process image
+pub(super) fn pdo_fault_details(drive: &mut impl Drive) -> Option<FaultDetails> {
+    let cycle = drive.cycle_count();
+    let status = drive
+        .with_process_image(|inputs, outputs| {
+            ServoStatus::from_process_image(inputs, outputs, cycle)
+        })
+        .ok()?;
+    Some(FaultDetails::from_status(drive.id(), &status))
+}
+
+impl<const MAX_DEVICES: usize, const PDI_LENGTH: usize> Servo<'_, '_, MAX_DEVICES, PDI_LENGTH> {
+    /// Collects the details of the current fault of the drive.
+    /// The error code is left out if it couldn't be read over SDO.
+    ///
+    /// # Errors
+    /// Returns an error if the inputs couldn't be read
+    pub async fn fault_details(&mut self) -> Result<FaultDetails, EthercrabError> {
+        let mut details = FaultDetails::from_status(self.device.id, &self.snapshot()?);
+        details.error_code = self
+            .device
+            .read_optional_object(objects::ERROR_CODE)
+            .await
+            .ok()
+            .flatten();
+        Ok(details)
+    }
+
+    /// Converts an error while homing, attaching the fault details if the drive faulted
+    pub(super) async fn homing_error(&mut self, error: WaitTimeout) -> HomingError {
+        match error {
+            WaitTimeout::Fault(device, status) => HomingError::Fault {
+                device,
+                status,
+                details: self.fault_details().await.ok(),
+            },
+            error => error.into(),
+        }
+    }
+}
@@ -0,0 +1,97 @@
+//! This module contains the following error of the servo, the difference between the position
+//! demanded by the drive and the actual position.
+//!
+//! The drive reports a following error once it exceeds the window (0x6065) for longer than the
+//! time out (0x6066). A jam shows up in the following error long before that if the window is
+//! configured loosely, so moves can also be supervised against a tighter threshold.
+
+use super::Servo;
+use crate::{device::objects, pdo};
+use core::time::Duration;
+use ethercrab::error::Error as EthercrabError;
+
+/// The supervision of the following error during positioning moves
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct FollowingErrorSupervision {
+    /// The largest following error allowed in increments
+    pub threshold: u32,
+
+    /// The number of cycles between checks, 0 is handled as 1
+    pub every_cycles: u32,
+}
+
+impl<const MAX_DEVICES: usize, const PDI_LENGTH: usize> Servo<'_, '_, MAX_DEVICES, PDI_LENGTH> {
+    /// Reads the actual following error in increments.
+    ///
+    /// # Errors
+    /// Returns an error if the inputs couldn't be read
+    pub fn following_error(&mut self) -> Result<i32, EthercrabError> {
+        self.device
+            .read_input(pdo::input::FOLLOWING_ERROR_ACTUAL_VALUE)
+    }
+
+    /// Aborts positioning moves once the following error exceeds the threshold.
+    /// Passing `None` disables the supervision.
+    pub fn set_following_error_supervision(
+        &mut self,
+        supervision: Option<FollowingErrorSupervision>,
+    ) {
+        self.following_error_supervision = supervision;
+    }
+
+    /// Returns the supervision of the following error, if enabled
+    #[must_use]
+    pub const fn following_error_supervision(&self) -> Option<FollowingErrorSupervision> {
+        self.following_error_supervision
+    }
+
+    /// Writes the window (0x6065) and time out (0x6066) at which the drive reports a following
+    /// error. The time out is rounded down to whole milliseconds and saturated at 65535.
+    ///
+    /// # Errors
+    /// Returns an error if a value couldn't be written
+    pub async fn set_following_error_window(
+        &mut self,
+        window: u32,
+        time_out: Duration,
+    ) -> Result<(), EthercrabError> {
+        let time_out = u16::try_from(time_out.as_millis()).unwrap_or(u16::MAX);
+        self.device
+            .write_object(objects::FOLLOWING_ERROR_WINDOW, window)
+            .await?;
+        self.device
+            .write_object(objects::FOLLOWING_ERROR_TIME_OUT, time_out)
+            .await
+    }
+
+    /// Reads the window (0x6065) and time out (0x6066) at which the drive reports a following
+    /// error.
+    ///
+    /// # Errors
+    /// Returns an error if a value couldn't be read
+    pub async fn following_error_window(&mut self) -> Result<(u32, Duration), EthercrabError> {
+        let window = self
+            .device
+            .read_object(objects::FOLLOWING_ERROR_WINDOW)
+            .await?;
+        let time_out: u16 = self
+            .device
+            .read_object(objects::FOLLOWING_ERROR_TIME_OUT)
+            .await?;
+        Ok((window, Duration::from_millis(u64::from(time_out))))
+    }
+
+    /// Checks the following error against the supervision threshold.
+    ///
+    /// # Returns
+    /// The following error if it exceeds the threshold
+    pub(super) fn following_error_exceeded(&mut self, cycle: u32) -> Option<(i32, u32)> {
+        let supervision = self.following_error_supervision?;
+        if cycle % supervision.every_cycles.max(1) != 0 {
+            return None;
+        }
+        let value = self.following_error().ok()?;
+        (value.unsigned_abs() > supervision.threshold).then_some((value, supervision.threshold))
+    }
+}
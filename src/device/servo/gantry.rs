@@ -0,0 +1,497 @@
+//! This module contains gantry axes, two parallel servos driven as a single axis.
+//!
+//! Both servos receive the same setpoints and the difference between their positions, the
+//! skew, is checked every cycle. Both servos are emergency stopped as soon as the skew exceeds
+//! the limit, after which `Device::clear_emergency` and `Device::recover` have to be called on
+//! both before the gantry can move again.
+//!
+//! Moves use cyclic synchronous position mode when the controller is cycling in the background
+//! and the velocity and acceleration are known, so both drives follow the same trajectory cycle
+//! by cycle. Otherwise both servos get a profile position move, started in the same cycle.
+
+use super::{
+    cyclic::{round_position, CyclicModeError},
+    homing::HomingPolicy,
+    motion::MoveOptions,
+    HomingError, JoggingDirection, JoggingError, MovementError, MovementMode, Servo,
+    MOTION_TIMEOUT,
+};
+use crate::{
+    device::{objects, ControlBit},
+    trajectory::{Profile, TrapezoidalProfile},
+};
+use core::{
+    fmt::{self, Debug, Formatter},
+    future::{poll_fn, Future},
+    pin::pin,
+    task::Poll,
+    time::Duration,
+};
+use ethercrab::error::Error as EthercrabError;
+use std::time::Instant;
+
+/// An error returned while moving a gantry
+pub enum GantryError {
+    /// The difference between the positions of the servos exceeded the limit, both servos have
+    /// been emergency stopped
+    SkewExceeded {
+        /// The position of the master minus the position of the slave in increments
+        skew: i32,
+    },
+
+    /// A positioning move failed
+    Movement(MovementError),
+
+    /// Homing failed
+    Homing(HomingError),
+
+    /// A jog failed
+    Jogging(JoggingError),
+
+    /// The cyclic synchronous position mode failed
+    Cyclic(CyclicModeError),
+
+    /// Communication with a drive failed
+    Ethercat(usize, EthercrabError),
+}
+
+impl Debug for GantryError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::SkewExceeded { skew } => write!(
+                f,
+                "The skew of the gantry exceeded the limit at {skew} increments"
+            ),
+            Self::Movement(error) => write!(f, "{error:?}"),
+            Self::Homing(error) => write!(f, "{error:?}"),
+            Self::Jogging(error) => write!(f, "{error:?}"),
+            Self::Cyclic(error) => write!(f, "{error:?}"),
+            Self::Ethercat(device, error) => write!(
+                f,
+                "Failed to communicate with gantry device {device}: {error:?}"
+            ),
+        }
+    }
+}
+
+impl From<MovementError> for GantryError {
+    fn from(value: MovementError) -> Self {
+        Self::Movement(value)
+    }
+}
+
+impl From<HomingError> for GantryError {
+    fn from(value: HomingError) -> Self {
+        Self::Homing(value)
+    }
+}
+
+impl From<JoggingError> for GantryError {
+    fn from(value: JoggingError) -> Self {
+        Self::Jogging(value)
+    }
+}
+
+impl From<CyclicModeError> for GantryError {
+    fn from(value: CyclicModeError) -> Self {
+        Self::Cyclic(value)
+    }
+}
+
+/// Two parallel servos driven as a single axis
+pub struct Gantry<'device, 'controller: 'device, const MAX_DEVICES: usize, const PDI_LENGTH: usize>
+{
+    /// The servo whose position is the position of the gantry
+    master: Servo<'device, 'controller, MAX_DEVICES, PDI_LENGTH>,
+
+    /// The servo following the master
+    slave: Servo<'device, 'controller, MAX_DEVICES, PDI_LENGTH>,
+
+    /// The largest allowed difference between the positions of the servos in increments
+    max_skew: i32,
+}
+
+impl<'device, 'controller: 'device, const MAX_DEVICES: usize, const PDI_LENGTH: usize>
+    Gantry<'device, 'controller, MAX_DEVICES, PDI_LENGTH>
+{
+    /// Creates a gantry from two servos, which are homed and moved together.
+    ///
+    /// # Parameters
+    /// `max_skew`: The largest allowed difference between the positions in increments
+    pub const fn new(
+        master: Servo<'device, 'controller, MAX_DEVICES, PDI_LENGTH>,
+        slave: Servo<'device, 'controller, MAX_DEVICES, PDI_LENGTH>,
+        max_skew: i32,
+    ) -> Self {
+        Self {
+            master,
+            slave,
+            max_skew,
+        }
+    }
+
+    /// Splits the gantry into the master and slave servo
+    pub fn into_servos(
+        self,
+    ) -> (
+        Servo<'device, 'controller, MAX_DEVICES, PDI_LENGTH>,
+        Servo<'device, 'controller, MAX_DEVICES, PDI_LENGTH>,
+    ) {
+        (self.master, self.slave)
+    }
+
+    /// Returns the largest allowed difference between the positions in increments
+    pub const fn max_skew(&self) -> i32 {
+        self.max_skew
+    }
+
+    /// Reads the position of the gantry, the position of the master, in increments.
+    ///
+    /// # Errors
+    /// Returns an error if the position couldn't be read
+    pub fn get_position(&mut self) -> Result<i32, GantryError> {
+        self.master
+            .get_position()
+            .map_err(|error| GantryError::Ethercat(self.master.device.id, error))
+    }
+
+    /// Reads the position of the master minus the position of the slave in increments.
+    ///
+    /// # Errors
+    /// Returns an error if a position couldn't be read
+    pub fn skew(&mut self) -> Result<i32, GantryError> {
+        let master = self.get_position()?;
+        let slave = self
+            .slave
+            .get_position()
+            .map_err(|error| GantryError::Ethercat(self.slave.device.id, error))?;
+        Ok(master.saturating_sub(slave))
+    }
+
+    /// Emergency stops both servos if the skew exceeds the limit.
+    ///
+    /// # Errors
+    /// Returns an error if a position couldn't be read or the skew exceeded the limit
+    fn check_skew(&mut self) -> Result<(), GantryError> {
+        let skew = self.skew()?;
+        self.stop_on_skew(skew)
+    }
+
+    /// Emergency stops both servos if the skew exceeds the limit.
+    ///
+    /// # Errors
+    /// Returns an error if the skew exceeded the limit
+    fn stop_on_skew(&mut self, skew: i32) -> Result<(), GantryError> {
+        if skew.unsigned_abs() <= self.max_skew.unsigned_abs() {
+            return Ok(());
+        }
+        log::error!(
+            "Gantry of devices {} and {} exceeded the skew limit at {skew}, stopping both",
+            self.master.device.id,
+            self.slave.device.id
+        );
+        self.master.device.emergency_stop();
+        self.slave.device.emergency_stop();
+        Err(GantryError::SkewExceeded { skew })
+    }
+
+    /// Moves the gantry to the target and waits until both servos completed the move.
+    /// See the module documentation for how the move is executed.
+    ///
+    /// # Errors
+    /// Returns an error if:
+    /// - The move of either servo couldn't be started or failed
+    /// - The skew exceeded the limit
+    pub async fn move_position(
+        &mut self,
+        target: i32,
+        options: &MoveOptions,
+    ) -> Result<(), GantryError> {
+        // Both servos move to the same absolute position
+        let target = match options.mode {
+            MovementMode::Absolute => target,
+            mode => {
+                let previous_target = self
+                    .master
+                    .previous_target()
+                    .map_err(|error| GantryError::Ethercat(self.master.device.id, error))?;
+                mode.end_position(target, self.get_position()?, previous_target)
+            }
+        };
+        let options = options.clone().with_mode(MovementMode::Absolute);
+
+        // Follow a trajectory in cyclic synchronous position mode, if possible
+        let velocity = options.velocity.or(self.master.config.default_velocity);
+        let acceleration = options
+            .acceleration
+            .or(self.master.config.default_acceleration);
+        if let (true, Some(velocity), Some(acceleration)) = (
+            self.master.device.controller.is_cycling(),
+            velocity,
+            acceleration,
+        ) {
+            if self.enter_csp().await {
+                let result = self.run_csp(target, velocity, acceleration).await;
+                if self.master.device.emergency_stopped() {
+                    return result;
+                }
+
+                // Leave the mode on both servos, also when the move or the other servo failed
+                let master_left = self.master.leave_csp().await;
+                let slave_left = self.slave.leave_csp().await;
+                result?;
+                master_left?;
+                slave_left?;
+                return Ok(());
+            }
+        }
+        self.run_profile_position(target, options).await
+    }
+
+    /// Switches both servos to cyclic synchronous position mode.
+    ///
+    /// # Returns
+    /// Whether both servos are in cyclic synchronous position mode, if not neither is
+    async fn enter_csp(&mut self) -> bool {
+        if let Err(error) = self.master.enter_csp().await {
+            log::info!("Gantry falls back to profile position moves: {error:?}");
+            return false;
+        }
+        if let Err(error) = self.slave.enter_csp().await {
+            log::info!("Gantry falls back to profile position moves: {error:?}");
+            let _ = self.master.leave_csp().await;
+            return false;
+        }
+        true
+    }
+
+    /// Writes the same trajectory to both servos in cyclic synchronous position mode, checking
+    /// the skew every cycle. Waits until both servos are within the skew limit of the target
+    /// afterwards.
+    ///
+    /// # Errors
+    /// Returns an error if a target couldn't be written, a device has been emergency stopped,
+    /// the skew exceeded the limit, or the servos didn't reach the target in time
+    async fn run_csp(
+        &mut self,
+        target: i32,
+        velocity: u32,
+        acceleration: u32,
+    ) -> Result<(), GantryError> {
+        let start = self.get_position()?;
+        let Some(profile) =
+            TrapezoidalProfile::new(start, target, f64::from(velocity), f64::from(acceleration))
+        else {
+            return Err(MovementError::ZeroVelocity(self.master.device.id).into());
+        };
+
+        // Write a sample of the trajectory to both servos every cycle
+        let cycle_time = self.master.device.controller.cycle_time();
+        for sample in profile.samples(cycle_time) {
+            self.csp_targets(round_position(sample.position))?;
+            self.master.device.controller.next_cycle().await;
+            self.check_skew()?;
+        }
+
+        // Hold the target until both servos reached it
+        let settle_start = Instant::now();
+        loop {
+            self.csp_targets(target)?;
+            self.master.device.controller.next_cycle().await;
+            self.check_skew()?;
+            let position = self.get_position()?;
+            if position.abs_diff(target) <= self.max_skew.unsigned_abs() {
+                return Ok(());
+            }
+            if settle_start.elapsed() >= MOTION_TIMEOUT {
+                let status = self.master.device.status_word().unwrap_or_default();
+                return Err(MovementError::Timeout(self.master.device.id, status).into());
+            }
+        }
+    }
+
+    /// Writes the target position for the next cycle to both servos.
+    ///
+    /// # Errors
+    /// Returns an error if a device has been emergency stopped or a target couldn't be written
+    fn csp_targets(&mut self, position: i32) -> Result<(), GantryError> {
+        for servo in [&mut self.master, &mut self.slave] {
+            if servo.device.emergency_stopped() {
+                return Err(CyclicModeError::EmergencyStopped(servo.device.id).into());
+            }
+            servo.csp_target(position)?;
+        }
+        Ok(())
+    }
+
+    /// Starts a profile position move on both servos in the same cycle and checks the skew every
+    /// cycle until both moves completed. Both servos are halted as soon as either move fails.
+    ///
+    /// # Errors
+    /// Returns an error if a move couldn't be started or failed, or the skew exceeded the limit
+    async fn run_profile_position(
+        &mut self,
+        target: i32,
+        options: MoveOptions,
+    ) -> Result<(), GantryError> {
+        let controller = self.master.device.controller;
+        let max_skew = self.max_skew.unsigned_abs();
+
+        // Write both setpoints first, then set the new setpoint bits together, so the outputs of
+        // the same cycle start both moves
+        let master_move = self.master.load_move(target, options.clone()).await?;
+        let slave_move = self.slave.load_move(target, options).await?;
+        self.master
+            .device
+            .update_control_word(|control| control.with(ControlBit::Control4))
+            .map_err(MovementError::Ethercat)?;
+        if let Err(error) = self
+            .slave
+            .device
+            .update_control_word(|control| control.with(ControlBit::Control4))
+        {
+            // The master didn't see its new setpoint bit yet, halting it clears the bit again
+            let _ = self.master.halt();
+            return Err(MovementError::Ethercat(error).into());
+        }
+        let mut master = match self.master.start_loaded(master_move).await {
+            Ok(master) => master,
+            Err(error) => {
+                let _ = self.slave.abort_move().await;
+                return Err(error.into());
+            }
+        };
+        let mut slave = match self.slave.start_loaded(slave_move).await {
+            Ok(slave) => slave,
+            Err(error) => {
+                let _ = master.abort().await;
+                return Err(error.into());
+            }
+        };
+
+        // Check the skew every cycle until both moves completed or either failed
+        let skew = loop {
+            let master_position = master.snapshot().map_err(MovementError::Ethercat)?.position;
+            let slave_position = slave.snapshot().map_err(MovementError::Ethercat)?.position;
+            let skew = master_position.saturating_sub(slave_position);
+            if skew.unsigned_abs() > max_skew {
+                break Some(skew);
+            }
+            let done = master.is_done() && slave.is_done();
+            if done || master.failed() || slave.failed() {
+                break None;
+            }
+            controller.next_cycle().await;
+        };
+        if let Some(skew) = skew {
+            drop((master, slave));
+            return self.stop_on_skew(skew);
+        }
+
+        // Halt the other servo if a move failed, so one side doesn't continue on its own
+        let (master_result, slave_result) = if master.failed() {
+            let stopped = slave.abort().await.map(|_| ());
+            (master.await, stopped)
+        } else if slave.failed() {
+            let stopped = master.abort().await.map(|_| ());
+            (stopped, slave.await)
+        } else {
+            (master.await, slave.await)
+        };
+        master_result?;
+        slave_result?;
+        Ok(())
+    }
+
+    /// Homes both servos at the same time, then aligns them by moving both to the home
+    /// position of the master and checking the skew.
+    ///
+    /// # Errors
+    /// Returns an error if homing either servo failed, the alignment move failed, or the skew
+    /// exceeded the limit
+    pub async fn home(&mut self, policy: HomingPolicy) -> Result<(), GantryError> {
+        // Home both servos at the same time, so neither side moves on its own for long
+        let (master_result, slave_result) = {
+            let mut master = pin!(self.master.home(policy));
+            let mut slave = pin!(self.slave.home(policy));
+            let (mut master_result, mut slave_result) = (None, None);
+            poll_fn(|cx| {
+                if master_result.is_none() {
+                    if let Poll::Ready(result) = master.as_mut().poll(cx) {
+                        master_result = Some(result);
+                    }
+                }
+                if slave_result.is_none() {
+                    if let Poll::Ready(result) = slave.as_mut().poll(cx) {
+                        slave_result = Some(result);
+                    }
+                }
+                if master_result.is_some() && slave_result.is_some() {
+                    Poll::Ready(())
+                } else {
+                    Poll::Pending
+                }
+            })
+            .await;
+            (master_result, slave_result)
+        };
+        if let Some(Err(error)) = master_result {
+            return Err(error.into());
+        }
+        if let Some(Err(error)) = slave_result {
+            return Err(error.into());
+        }
+
+        // Align the servos at the position the master was homed to, its home offset (0x607C)
+        let home_position = self
+            .master
+            .device
+            .read_object(objects::HOME_OFFSET)
+            .await
+            .map_err(|error| GantryError::Ethercat(self.master.device.id, error))?;
+        self.move_position(home_position, &MoveOptions::new())
+            .await?;
+        self.check_skew()
+    }
+
+    /// Jogs the gantry in the direction at the velocity for the duration, then stops it.
+    /// The skew is checked every cycle.
+    ///
+    /// # Errors
+    /// Returns an error if:
+    /// - Either servo couldn't start or stop jogging
+    /// - Either servo faulted while jogging
+    /// - The skew exceeded the limit
+    pub async fn jog_for(
+        &mut self,
+        direction: JoggingDirection,
+        velocity: u32,
+        duration: Duration,
+    ) -> Result<(), GantryError> {
+        self.master.jog(direction, Some(velocity)).await?;
+        if let Err(error) = self.slave.jog(direction, Some(velocity)).await {
+            let _ = self.master.jog_stop().await;
+            return Err(error.into());
+        }
+
+        // Keep jogging until the duration expired, checking the skew every cycle
+        let start = Instant::now();
+        let result = loop {
+            if start.elapsed() >= duration {
+                break Ok(());
+            }
+            if let Err(error) = self.check_skew() {
+                break Err(error);
+            }
+            self.master.device.controller.next_cycle().await;
+        };
+
+        // Stop both servos, also when the skew was exceeded
+        let master_stopped = self.master.jog_stop().await;
+        let slave_stopped = self.slave.jog_stop().await;
+        result?;
+        master_stopped?;
+        slave_stopped?;
+        Ok(())
+    }
+}
@@ -0,0 +1,1097 @@
+//! This module contains groups of servos, which are enabled, homed and moved concurrently.
+//!
+//! Every operation runs the per-axis operations at the same time and waits until all of them
+//! completed. A failing axis doesn't stop the others, so the results are returned per axis.
+//! The futures of the axes wait for the same cycles, so the controller should be cycling in
+//! the background, see `Controller::start_cycling`.
+//!
+//! `ServoGroup::move_coordinated` goes further: every axis gets a profile taking the same time,
+//! and all setpoints are started in the same cycle, so the axes start and arrive together.
+//! `ServoGroup::move_linear` streams a straight line between two poses in cyclic synchronous
+//! position mode, so the axes stay on the line during the whole move.
+
+use super::{
+    cyclic::{round_position, CyclicModeError},
+    homing::HomingPolicy,
+    motion::MoveOptions,
+    HomingError, MovementError, Servo, MOTION_TIMEOUT,
+};
+use crate::{
+    device::{
+        ControlBit, ControlWord, DisableError, DisableReport, EnableError, OperationMode,
+        StatusWordBit,
+    },
+    pdo::{self, PdoValue},
+    trajectory::{minimum_move_duration, synchronized_move, LinearPath, Profile, SynchronizedMove},
+};
+use core::{
+    fmt::{self, Debug, Formatter},
+    future::{poll_fn, Future},
+    task::Poll,
+    time::Duration,
+};
+use std::time::Instant;
+
+/// An error returned for a single move of `ServoGroup::move_all`
+pub enum GroupError {
+    /// The group has no servo at the index
+    NoServo(usize),
+
+    /// Another move of the same call was already assigned to the servo at the index
+    DuplicateServo(usize),
+
+    /// The axis can't complete its part of a coordinated move within the duration
+    Infeasible {
+        /// The index of the servo
+        index: usize,
+
+        /// The duration of the coordinated move
+        requested: Duration,
+
+        /// The shortest duration the axis can complete the move in, `Duration::MAX` if its
+        /// velocity or acceleration limit is zero
+        minimum: Duration,
+    },
+
+    /// The lead axis of a coordinated move isn't one of the moved servos
+    LeadAxisNotMoved(usize),
+
+    /// The move wasn't started, because another move of the same coordinated move failed
+    NotStarted(usize),
+
+    /// The number of targets of a linear move doesn't match the number of servos
+    TargetCount {
+        /// The number of servos in the group
+        expected: usize,
+
+        /// The number of targets
+        actual: usize,
+    },
+
+    /// An axis deviated further from the line of a linear move than the skew limit, all axes
+    /// have been stopped
+    SkewExceeded {
+        /// The index of the servo furthest away from the line
+        index: usize,
+
+        /// The distance of the servo from the line in increments
+        skew: u32,
+    },
+
+    /// The cyclic synchronous position mode failed
+    Cyclic(CyclicModeError),
+
+    /// The move failed
+    Movement(MovementError),
+}
+
+impl Debug for GroupError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::NoServo(index) => write!(f, "The group has no servo at index {index}"),
+            Self::DuplicateServo(index) => {
+                write!(f, "The servo at index {index} was given multiple moves")
+            }
+            Self::Infeasible {
+                index,
+                requested,
+                minimum,
+            } => write!(
+                f,
+                "The servo at index {index} can't complete its move in {requested:?}, it needs at least {minimum:?}"
+            ),
+            Self::LeadAxisNotMoved(index) => write!(
+                f,
+                "The lead axis at index {index} isn't part of the coordinated move"
+            ),
+            Self::NotStarted(index) => write!(
+                f,
+                "The move of the servo at index {index} wasn't started, because another axis failed"
+            ),
+            Self::TargetCount { expected, actual } => write!(
+                f,
+                "A linear move of {expected} servos got {actual} targets"
+            ),
+            Self::SkewExceeded { index, skew } => write!(
+                f,
+                "The servo at index {index} deviated {skew} increments from the line, exceeding the skew limit"
+            ),
+            Self::Cyclic(error) => write!(f, "{error:?}"),
+            Self::Movement(error) => write!(f, "{error:?}"),
+        }
+    }
+}
+
+/// How the duration of a coordinated move is chosen
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum CoordinatedTiming {
+    /// Every axis takes the duration
+    Duration(Duration),
+
+    /// Every axis takes as long as the servo at the index needs at its limits
+    LeadAxis(usize),
+
+    /// Every axis takes as long as the slowest axis needs at its limits
+    Slowest,
+}
+
+/// An axis taking part in a coordinated move
+struct CoordinatedAxis {
+    /// The position of the move in the requested moves
+    position: usize,
+
+    /// The absolute target in increments
+    target: i32,
+
+    /// The position of the servo before the move in increments
+    start_position: i32,
+
+    /// The highest velocity the axis may move at in increments per second
+    max_velocity: u32,
+
+    /// The highest acceleration and deceleration of the axis in increments per second squared
+    max_acceleration: u32,
+
+    /// The profile acceleration and deceleration before the move, restored after the move
+    ramps: (u32, u32),
+}
+
+impl CoordinatedAxis {
+    /// Calculates the shortest time the axis can complete its move in
+    fn minimum_duration(&self) -> Duration {
+        minimum_move_duration(
+            self.target.abs_diff(self.start_position),
+            self.max_velocity,
+            self.max_acceleration,
+        )
+        .unwrap_or(Duration::MAX)
+    }
+
+    /// Calculates the profile completing the move of the axis in the duration
+    fn profile(&self, duration: Duration) -> Option<SynchronizedMove> {
+        synchronized_move(
+            self.target.abs_diff(self.start_position),
+            duration,
+            self.max_velocity,
+            self.max_acceleration,
+        )
+    }
+}
+
+/// Marks the moves that haven't failed as not started, if any move failed.
+///
+/// # Returns
+/// Whether any move failed
+fn any_failed(results: &mut [Result<(), GroupError>], moves: &[(usize, i32)]) -> bool {
+    if results.iter().all(Result::is_ok) {
+        return false;
+    }
+    for (result, (index, _)) in results.iter_mut().zip(moves) {
+        if result.is_ok() {
+            *result = Err(GroupError::NotStarted(*index));
+        }
+    }
+    true
+}
+
+/// Chooses the duration of a coordinated move and calculates the profile of every axis,
+/// recording the axes that can't complete their move in time.
+///
+/// # Returns
+/// The duration and the profile of every axis, in the order of the group
+fn coordinated_profiles(
+    plans: &[Option<CoordinatedAxis>],
+    timing: CoordinatedTiming,
+    results: &mut [Result<(), GroupError>],
+) -> (Duration, Vec<Option<SynchronizedMove>>) {
+    let duration = match timing {
+        CoordinatedTiming::Duration(duration) => duration,
+        CoordinatedTiming::LeadAxis(lead) => {
+            let Some(Some(plan)) = plans.get(lead) else {
+                for result in results.iter_mut() {
+                    *result = Err(GroupError::LeadAxisNotMoved(lead));
+                }
+                return (Duration::ZERO, Vec::new());
+            };
+            plan.minimum_duration()
+        }
+        CoordinatedTiming::Slowest => plans
+            .iter()
+            .flatten()
+            .map(CoordinatedAxis::minimum_duration)
+            .max()
+            .unwrap_or_default(),
+    };
+    let profiles = plans
+        .iter()
+        .enumerate()
+        .map(|(index, plan)| {
+            let plan = plan.as_ref()?;
+            let profile = plan.profile(duration);
+            if profile.is_none() {
+                results[plan.position] = Err(GroupError::Infeasible {
+                    index,
+                    requested: duration,
+                    minimum: plan.minimum_duration(),
+                });
+            }
+            profile
+        })
+        .collect();
+    (duration, profiles)
+}
+
+impl From<MovementError> for GroupError {
+    fn from(value: MovementError) -> Self {
+        Self::Movement(value)
+    }
+}
+
+impl From<CyclicModeError> for GroupError {
+    fn from(value: CyclicModeError) -> Self {
+        Self::Cyclic(value)
+    }
+}
+
+/// Polls the futures concurrently until all of them completed.
+///
+/// # Returns
+/// The outputs of the futures, in the order of the futures
+async fn join_all<F: Future>(futures: impl IntoIterator<Item = F>) -> Vec<F::Output> {
+    let mut futures: Vec<_> = futures
+        .into_iter()
+        .map(|future| (Box::pin(future), None))
+        .collect();
+    poll_fn(|cx| {
+        let mut pending = false;
+        for (future, output) in &mut futures {
+            if output.is_none() {
+                match future.as_mut().poll(cx) {
+                    Poll::Ready(result) => *output = Some(result),
+                    Poll::Pending => pending = true,
+                }
+            }
+        }
+        if pending {
+            Poll::Pending
+        } else {
+            Poll::Ready(())
+        }
+    })
+    .await;
+    futures
+        .into_iter()
+        .filter_map(|(_, output)| output)
+        .collect()
+}
+
+/// Several servos operated at the same time
+pub struct ServoGroup<
+    'device,
+    'controller: 'device,
+    const MAX_DEVICES: usize,
+    const PDI_LENGTH: usize,
+> {
+    /// The servos of the group, indexed by their position
+    servos: Vec<Servo<'device, 'controller, MAX_DEVICES, PDI_LENGTH>>,
+
+    /// The largest allowed distance of an axis from the line of a linear move in increments
+    max_skew: Option<u32>,
+}
+
+impl<'device, 'controller: 'device, const MAX_DEVICES: usize, const PDI_LENGTH: usize>
+    ServoGroup<'device, 'controller, MAX_DEVICES, PDI_LENGTH>
+{
+    /// Creates a group from the servos, which are indexed by their position in the list
+    #[must_use]
+    pub const fn new(servos: Vec<Servo<'device, 'controller, MAX_DEVICES, PDI_LENGTH>>) -> Self {
+        Self {
+            servos,
+            max_skew: None,
+        }
+    }
+
+    /// Sets the largest allowed distance of an axis from the line of a linear move in
+    /// increments. Linear moves aren't checked for skew without a limit.
+    #[must_use]
+    pub const fn with_max_skew(mut self, max_skew: u32) -> Self {
+        self.max_skew = Some(max_skew);
+        self
+    }
+
+    /// Returns the largest allowed distance of an axis from the line of a linear move
+    pub const fn max_skew(&self) -> Option<u32> {
+        self.max_skew
+    }
+
+    /// Splits the group into its servos
+    #[must_use]
+    pub fn into_servos(self) -> Vec<Servo<'device, 'controller, MAX_DEVICES, PDI_LENGTH>> {
+        self.servos
+    }
+
+    /// Returns the number of servos in the group
+    #[must_use]
+    pub fn len(&self) -> usize {
+        self.servos.len()
+    }
+
+    /// Returns whether the group has no servos
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.servos.is_empty()
+    }
+
+    /// Returns the servo at the index, to operate it on its own
+    pub fn servo_mut(
+        &mut self,
+        index: usize,
+    ) -> Option<&mut Servo<'device, 'controller, MAX_DEVICES, PDI_LENGTH>> {
+        self.servos.get_mut(index)
+    }
+
+    /// Resets and enables all servos concurrently.
+    ///
+    /// # Returns
+    /// The result of every servo, in the order of the group
+    pub async fn enable_all(&mut self) -> Vec<Result<(), EnableError>> {
+        join_all(self.servos.iter_mut().map(|servo| async move {
+            servo
+                .device
+                .reset()
+                .await
+                .map_err(EnableError::ResetFailed)?;
+            servo.device.enable().await
+        }))
+        .await
+    }
+
+    /// Homes all servos concurrently, see `Servo::home`.
+    ///
+    /// # Returns
+    /// The result of every servo, in the order of the group.
+    /// A servo returns `true` if it performed a homing run.
+    pub async fn home_all(&mut self, policy: HomingPolicy) -> Vec<Result<bool, HomingError>> {
+        join_all(self.servos.iter_mut().map(|servo| servo.home(policy))).await
+    }
+
+    /// Moves the servos concurrently and waits until every move completed,
+    /// see `Servo::move_with`. Servos without a move keep their position.
+    ///
+    /// # Parameters
+    /// `moves`: The index of the servo, the target and the options of each move
+    ///
+    /// # Returns
+    /// The result of every move, in the order of the moves.
+    /// A move fails without moving if its index is unknown or already used by an earlier move.
+    pub async fn move_all(
+        &mut self,
+        moves: &[(usize, i32, MoveOptions)],
+    ) -> Vec<Result<(), GroupError>> {
+        let (mut results, assigned) = self.assign(moves.iter().map(|(index, ..)| *index));
+
+        // Run the assigned moves at the same time
+        let futures = self
+            .servos
+            .iter_mut()
+            .zip(assigned)
+            .filter_map(|(servo, position)| {
+                let position = position?;
+                let (_, target, options) = moves.get(position)?;
+                Some(async move { (position, servo.move_with(*target, options).await) })
+            });
+        for (position, result) in join_all(futures).await {
+            if let Some(slot) = results.get_mut(position) {
+                *slot = result.map_err(GroupError::Movement);
+            }
+        }
+        results
+    }
+
+    /// Assigns each move to its servo, a servo can only perform a single move at a time.
+    ///
+    /// # Returns
+    /// - The result of every move, an error if the index is unknown or already used
+    /// - The position of the move assigned to every servo, in the order of the group
+    fn assign(
+        &self,
+        indices: impl Iterator<Item = usize>,
+    ) -> (Vec<Result<(), GroupError>>, Vec<Option<usize>>) {
+        let mut results = Vec::new();
+        let mut assigned = vec![None; self.servos.len()];
+        for (position, index) in indices.enumerate() {
+            match assigned.get_mut(index) {
+                None => results.push(Err(GroupError::NoServo(index))),
+                Some(Some(_)) => results.push(Err(GroupError::DuplicateServo(index))),
+                Some(slot) => {
+                    *slot = Some(position);
+                    results.push(Ok(()));
+                }
+            }
+        }
+        (results, assigned)
+    }
+
+    /// Moves the servos to absolute targets, so all of them start in the same cycle and arrive
+    /// at the same time. Every axis gets a profile position move accelerating at its profile
+    /// acceleration, with the velocity calculated to take the duration of the coordinated move.
+    /// The profile acceleration and deceleration are set to the lower of both during the move,
+    /// and restored afterwards.
+    ///
+    /// No axis is started if any axis fails before the start, for instance because it can't
+    /// complete its move within the duration. The other axes then return `GroupError::NotStarted`.
+    ///
+    /// # Parameters
+    /// `moves`: The index of the servo and the absolute target of each move
+    ///
+    /// `timing`: How the duration of the move is chosen
+    ///
+    /// # Returns
+    /// The result of every move, in the order of the moves
+    pub async fn move_coordinated(
+        &mut self,
+        moves: &[(usize, i32)],
+        timing: CoordinatedTiming,
+    ) -> Vec<Result<(), GroupError>> {
+        let (mut results, assigned) = self.assign(moves.iter().map(|(index, _)| *index));
+
+        // Check every axis and calculate the profiles, no axis is started if any of them failed
+        let plans = self.plan_coordinated(moves, &assigned, &mut results).await;
+        if any_failed(&mut results, moves) {
+            return results;
+        }
+        let (duration, profiles) = coordinated_profiles(&plans, timing, &mut results);
+        if any_failed(&mut results, moves) {
+            return results;
+        }
+
+        // Write the profiles and setpoints, then wait until every drive accepts a new setpoint
+        let started = Instant::now();
+        let loaded = join_all(
+            self.servos
+                .iter_mut()
+                .zip(plans.iter().zip(&profiles))
+                .filter_map(|(servo, (plan, profile))| {
+                    Some(servo.load_coordinated(plan.as_ref()?, (*profile)?, started))
+                }),
+        )
+        .await;
+        for (position, error) in loaded.into_iter().filter_map(Result::err) {
+            results[position] = Err(error.into());
+        }
+        if any_failed(&mut results, moves) {
+            self.restore_coordinated(&plans, &mut results).await;
+            return results;
+        }
+
+        // Start all setpoints in the same cycle, the outputs are only sent with the next cycle
+        for (servo, plan) in self.servos.iter_mut().zip(&plans) {
+            let Some(plan) = plan else {
+                continue;
+            };
+            if let Err(error) = servo
+                .device
+                .update_control_word(|control| control.with(ControlBit::Control4))
+            {
+                results[plan.position] = Err(MovementError::Ethercat(error).into());
+            }
+        }
+
+        // Wait until every axis acknowledged its setpoint and completed its move
+        let started = Instant::now();
+        let timeout = MOTION_TIMEOUT.saturating_add(duration);
+        let completed = join_all(
+            self.servos
+                .iter_mut()
+                .zip(&plans)
+                .filter_map(|(servo, plan)| {
+                    let plan = plan
+                        .as_ref()
+                        .filter(|plan| results[plan.position].is_ok())?;
+                    Some(servo.finish_coordinated(plan, started, timeout))
+                }),
+        )
+        .await;
+        for (position, result) in completed {
+            results[position] = result.map_err(GroupError::Movement);
+        }
+        self.restore_coordinated(&plans, &mut results).await;
+        results
+    }
+
+    /// Writes the profile acceleration and deceleration every axis of a coordinated move had
+    /// before the move back, recording the errors of the moves that didn't fail otherwise
+    async fn restore_coordinated(
+        &mut self,
+        plans: &[Option<CoordinatedAxis>],
+        results: &mut [Result<(), GroupError>],
+    ) {
+        let restored = join_all(
+            self.servos
+                .iter_mut()
+                .zip(plans)
+                .filter_map(|(servo, plan)| {
+                    let plan = plan.as_ref()?;
+                    Some(async move { (plan.position, servo.restore_ramps(plan.ramps).await) })
+                }),
+        )
+        .await;
+        for (position, result) in restored {
+            if let (Err(error), Some(slot @ Ok(()))) = (result, results.get_mut(position)) {
+                *slot = Err(error.into());
+            }
+        }
+    }
+
+    /// Checks every servo of a coordinated move and reads its limits, recording the errors.
+    ///
+    /// # Returns
+    /// The axis of every servo taking part in the move, in the order of the group
+    async fn plan_coordinated(
+        &mut self,
+        moves: &[(usize, i32)],
+        assigned: &[Option<usize>],
+        results: &mut [Result<(), GroupError>],
+    ) -> Vec<Option<CoordinatedAxis>> {
+        let prepared = join_all(self.servos.iter_mut().zip(assigned).map(
+            |(servo, position)| async move {
+                let position = (*position)?;
+                let (_, target) = moves.get(position)?;
+                Some(servo.prepare_coordinated(position, *target).await)
+            },
+        ))
+        .await;
+        prepared
+            .into_iter()
+            .map(|result| match result? {
+                Ok(plan) => Some(plan),
+                Err((position, error)) => {
+                    results[position] = Err(error.into());
+                    None
+                }
+            })
+            .collect()
+    }
+
+    /// Moves all servos along a straight line from the current pose to the targets, streaming
+    /// the setpoints in cyclic synchronous position mode. The axes accelerate, travel and
+    /// decelerate along the line together, so every axis covers the same part of its distance
+    /// at the same time. Requires background cycling, see `Controller::start_cycling`.
+    ///
+    /// With a skew limit, the distance of every axis from the line is checked each cycle.
+    /// If an axis exceeds the limit or a move of any axis is aborted with
+    /// `Controller::abort_move`, all axes decelerate to standstill along the line.
+    ///
+    /// # Parameters
+    /// `targets`: The absolute target of every servo, in the order of the group
+    ///
+    /// `path_velocity`: The velocity along the line in increments per second
+    ///
+    /// `acceleration`: The acceleration along the line in increments per second squared
+    ///
+    /// # Errors
+    /// Returns an error if:
+    /// - The number of targets doesn't match the number of servos
+    /// - A target is outside of the software position limits
+    /// - The velocity or acceleration is zero
+    /// - A servo couldn't be switched to cyclic synchronous position mode or back
+    /// - A device faulted or has been emergency stopped
+    /// - An axis exceeded the skew limit or the move was aborted
+    /// - The axes didn't reach the targets in time
+    pub async fn move_linear(
+        &mut self,
+        targets: &[i32],
+        path_velocity: u32,
+        acceleration: u32,
+    ) -> Result<(), GroupError> {
+        if targets.len() != self.servos.len() {
+            return Err(GroupError::TargetCount {
+                expected: self.servos.len(),
+                actual: targets.len(),
+            });
+        }
+        let Some(id) = self.servos.first().map(|servo| servo.device.id) else {
+            return Ok(());
+        };
+
+        // Check the targets and lay the line from the current pose
+        let mut start = Vec::with_capacity(targets.len());
+        for (servo, target) in self.servos.iter_mut().zip(targets) {
+            servo.check_limits(*target).await?;
+            start.push(servo.get_position().map_err(MovementError::Ethercat)?);
+        }
+        let Some(path) = LinearPath::new(
+            &start,
+            targets,
+            f64::from(path_velocity),
+            f64::from(acceleration),
+        ) else {
+            return Err(MovementError::ZeroVelocity(id).into());
+        };
+
+        // Switch every servo to cyclic synchronous position mode, or none of them
+        for index in 0..self.servos.len() {
+            if let Err(error) = self.servos[index].enter_csp().await {
+                for servo in &mut self.servos[..index] {
+                    let _ = servo.leave_csp().await;
+                }
+                return Err(error.into());
+            }
+        }
+        let result = self.run_linear(&path, targets).await;
+
+        // Leave the mode on every servo, also when the move or another servo failed
+        let mut left = Ok(());
+        for servo in &mut self.servos {
+            if !servo.device.emergency_stopped() {
+                let result = servo.leave_csp().await;
+                if left.is_ok() {
+                    left = result;
+                }
+            }
+        }
+        result?;
+        left?;
+        Ok(())
+    }
+
+    /// Writes the setpoints of the line to every servo each cycle, stopping all servos along
+    /// the line if an axis exceeds the skew limit or a move is aborted. Waits until the servos
+    /// reached the targets afterwards.
+    ///
+    /// # Errors
+    /// Returns an error if a setpoint couldn't be written, a device faulted or has been
+    /// emergency stopped, the move was stopped, or the targets weren't reached in time
+    async fn run_linear(&mut self, path: &LinearPath, targets: &[i32]) -> Result<(), GroupError> {
+        let Some(controller) = self.servos.first().map(|servo| servo.device.controller) else {
+            return Ok(());
+        };
+        let cycle_time = controller.cycle_time();
+        let started = Instant::now();
+        let timeout = MOTION_TIMEOUT.saturating_add(path.duration());
+        for sample in path.samples(cycle_time) {
+            self.csp_setpoints(&path.point(sample.position))?;
+            controller.next_cycle().await;
+            if let Some(error) = self.linear_stop_reason(path, started, timeout)? {
+                // Decelerate along the line, so the axes don't leave it while stopping
+                for distance in
+                    path.stopping_distances(sample.position, sample.velocity, cycle_time)
+                {
+                    self.csp_setpoints(&path.point(distance))?;
+                    controller.next_cycle().await;
+                }
+                return Err(error);
+            }
+        }
+
+        // Hold the targets until every servo reached them, or for the delay of the drives
+        let Some(max_skew) = self.max_skew else {
+            for _ in 0..2 {
+                controller.next_cycle().await;
+            }
+            return Ok(());
+        };
+        loop {
+            if let Some(error) = self.linear_stop_reason(path, started, timeout)? {
+                return Err(error);
+            }
+            let mut reached = true;
+            for (servo, target) in self.servos.iter_mut().zip(targets) {
+                let position = servo.get_position().map_err(MovementError::Ethercat)?;
+                reached &= position.abs_diff(*target) <= max_skew;
+            }
+            if reached {
+                return Ok(());
+            }
+            controller.next_cycle().await;
+        }
+    }
+
+    /// Writes the positions as the targets for the next cycle to every servo.
+    ///
+    /// # Errors
+    /// Returns an error if a device has been emergency stopped or a target couldn't be written
+    fn csp_setpoints(&mut self, positions: &[f64]) -> Result<(), GroupError> {
+        for (servo, position) in self.servos.iter_mut().zip(positions) {
+            if servo.device.emergency_stopped() {
+                return Err(CyclicModeError::EmergencyStopped(servo.device.id).into());
+            }
+            servo.csp_target(round_position(*position))?;
+        }
+        Ok(())
+    }
+
+    /// Checks whether a linear move has to be stopped, because a device faulted, a move was
+    /// aborted or an axis exceeded the skew limit.
+    ///
+    /// # Errors
+    /// Returns an error if a position couldn't be read
+    ///
+    /// # Returns
+    /// The error to return once the servos stand still, if the move has to be stopped
+    fn linear_stop_reason(
+        &mut self,
+        path: &LinearPath,
+        started: Instant,
+        timeout: Duration,
+    ) -> Result<Option<GroupError>, GroupError> {
+        let mut positions = Vec::with_capacity(self.servos.len());
+        for servo in &mut self.servos {
+            let id = servo.device.id;
+            let position = servo.get_position().map_err(MovementError::Ethercat)?;
+            if servo.device.controller.take_abort(id) {
+                return Ok(Some(MovementError::Aborted(id, position).into()));
+            }
+            if let Some(Err(error)) = servo.device.check_motion(|_| false, started, timeout) {
+                return Ok(Some(servo.motion_error(error).into()));
+            }
+            positions.push(position);
+        }
+
+        // Stop all servos if one of them is too far away from the line
+        let Some(max_skew) = self.max_skew else {
+            return Ok(None);
+        };
+        match path.deviation(&positions) {
+            Some((index, deviation)) if deviation > f64::from(max_skew) => {
+                log::error!(
+                    "Servo {index} of the group deviated {deviation:.0} increments from the line, stopping all servos"
+                );
+                Ok(Some(GroupError::SkewExceeded {
+                    index,
+                    skew: u32::try_from(round_position(deviation)).unwrap_or(u32::MAX),
+                }))
+            }
+            _ => Ok(None),
+        }
+    }
+
+    /// Disables all servos concurrently, see `Servo::disable`.
+    ///
+    /// # Returns
+    /// The report or error of every servo, in the order of the group
+    pub async fn disable_all(self) -> Vec<Result<DisableReport, DisableError>> {
+        join_all(self.servos.into_iter().map(Servo::disable)).await
+    }
+
+    /// Disables all servos concurrently, waiting at most `timeout` for each of them.
+    /// See `Servo::disable_with_timeout`.
+    ///
+    /// # Returns
+    /// The report or error of every servo, in the order of the group
+    pub async fn disable_all_with_timeout(
+        self,
+        timeout: Duration,
+    ) -> Vec<Result<DisableReport, DisableError>> {
+        join_all(
+            self.servos
+                .into_iter()
+                .map(|servo| servo.disable_with_timeout(timeout)),
+        )
+        .await
+    }
+}
+
+impl<const MAX_DEVICES: usize, const PDI_LENGTH: usize> Servo<'_, '_, MAX_DEVICES, PDI_LENGTH> {
+    /// Checks whether the servo can move to the absolute target as part of a coordinated move,
+    /// and reads the limits of the axis.
+    ///
+    /// # Errors
+    /// Returns the position of the move and an error if:
+    /// - The drive is not enabled, emergency stopped or not homed
+    /// - The target is outside of the software position limits
+    /// - The limits couldn't be read
+    async fn prepare_coordinated(
+        &mut self,
+        position: usize,
+        target: i32,
+    ) -> Result<CoordinatedAxis, (usize, MovementError)> {
+        let id = self.device.id;
+        if self.device.emergency_stopped() {
+            return Err((position, MovementError::EmergencyStopped(id)));
+        }
+        if !self.device.ready_state() {
+            return Err((position, MovementError::DriveDisabled(id)));
+        }
+        let result = async {
+            if !self.is_homed().map_err(MovementError::Ethercat)? {
+                return Err(MovementError::NotHomed(id));
+            }
+            self.check_limits(target).await?;
+            let start_position = self.get_position().map_err(MovementError::Ethercat)?;
+
+            // The velocity is limited by both the profile velocity and the motor speed
+            let max_velocity = self
+                .max_profile_velocity()
+                .await
+                .map_err(MovementError::Ethercat)?
+                .min(
+                    self.max_motor_speed()
+                        .await
+                        .map_err(MovementError::Ethercat)?,
+                );
+
+            // Accelerate and decelerate equally with the lower of the axis settings
+            let ramps = self
+                .current_ramps()
+                .await
+                .map_err(MovementError::Ethercat)?;
+            let acceleration = self.config.default_acceleration.unwrap_or(ramps.0);
+            let deceleration = self.config.default_deceleration.unwrap_or(ramps.1);
+            Ok(CoordinatedAxis {
+                position,
+                target,
+                start_position,
+                max_velocity,
+                max_acceleration: acceleration.min(deceleration),
+                ramps,
+            })
+        }
+        .await;
+        result.map_err(|error| (position, error))
+    }
+
+    /// Writes the profile and setpoint of a coordinated move with the new setpoint bit cleared,
+    /// and waits until the drive accepts a new setpoint.
+    ///
+    /// # Errors
+    /// Returns the position of the move and an error if the profile or setpoint couldn't be
+    /// written, or the drive faulted or didn't finish the previous handshake in time
+    async fn load_coordinated(
+        &mut self,
+        axis: &CoordinatedAxis,
+        profile: SynchronizedMove,
+        started: Instant,
+    ) -> Result<(), (usize, MovementError)> {
+        let id = self.device.id;
+        let result = async {
+            self.device
+                .set_mode(OperationMode::ProfilePosition)
+                .await
+                .map_err(MovementError::SetMode)?;
+            self.set_profile_acceleration(profile.acceleration)
+                .await
+                .map_err(|error| MovementError::WritingAcceleration(id, error))?;
+            self.set_profile_deceleration(profile.acceleration)
+                .await
+                .map_err(|error| MovementError::WritingDeceleration(id, error))?;
+
+            // Abort requests and queued setpoints from before this move don't apply to it
+            self.device.controller.take_abort(id);
+            self.paused = false;
+            self.queued_moves = 0;
+            self.relative_target = None;
+
+            // Write the setpoint with the start bit cleared
+            self.device
+                .apply_outputs(|outputs| {
+                    axis.target
+                        .write(&mut outputs[pdo::output::TARGET_POSITION..]);
+                    profile
+                        .velocity
+                        .write(&mut outputs[pdo::output::PROFILE_VELOCITY..]);
+                    let control_word = &mut outputs[pdo::output::CONTROL_WORD..];
+                    ControlWord::new(u16::read(control_word))
+                        .without_control()
+                        .without(ControlBit::Halt)
+                        .raw()
+                        .write(control_word);
+                })
+                .map_err(MovementError::Ethercat)?;
+            self.device.controller.next_cycle().await;
+
+            // Wait until the drive finished the previous handshake, so it sees a rising edge
+            let result = self
+                .device
+                .wait_for_motion(
+                    |status| !status.is_set(StatusWordBit::AckStartRefReached),
+                    started,
+                    MOTION_TIMEOUT,
+                )
+                .await;
+            result.map_err(|error| self.motion_error(error))?;
+            Ok(())
+        }
+        .await;
+        result.map_err(|error| (axis.position, error))
+    }
+
+    /// Waits until the drive acknowledged the started setpoint of a coordinated move, drops the
+    /// new setpoint bit and waits until the move completed.
+    ///
+    /// # Returns
+    /// The position of the move and its result
+    async fn finish_coordinated(
+        &mut self,
+        axis: &CoordinatedAxis,
+        started: Instant,
+        timeout: Duration,
+    ) -> (usize, Result<(), MovementError>) {
+        let result = async {
+            let result = self
+                .device
+                .wait_for_motion(
+                    |status| status.is_set(StatusWordBit::AckStartRefReached),
+                    started,
+                    timeout,
+                )
+                .await;
+            result.map_err(|error| self.motion_error(error))?;
+            self.device
+                .update_control_word(|control| control.without(ControlBit::Control4))
+                .map_err(MovementError::Ethercat)?;
+            self.motion_handle(
+                axis.target,
+                axis.start_position,
+                axis.target,
+                started,
+                timeout,
+                None,
+            )
+            .await
+        }
+        .await;
+        (axis.position, result)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    //! Tests of the planning of coordinated moves
+
+    use super::*;
+
+    /// Creates an axis moving from zero over the distance
+    const fn axis(
+        position: usize,
+        distance: i32,
+        velocity: u32,
+        acceleration: u32,
+    ) -> CoordinatedAxis {
+        CoordinatedAxis {
+            position,
+            target: distance,
+            start_position: 0,
+            max_velocity: velocity,
+            max_acceleration: acceleration,
+            ramps: (acceleration, acceleration),
+        }
+    }
+
+    /// Plans the axes, returning the duration, the profiles and the results
+    fn plan(
+        plans: &[Option<CoordinatedAxis>],
+        timing: CoordinatedTiming,
+    ) -> (
+        Duration,
+        Vec<Option<SynchronizedMove>>,
+        Vec<Result<(), GroupError>>,
+    ) {
+        let moves = plans.iter().flatten().count();
+        let mut results = (0..moves).map(|_| Ok(())).collect::<Vec<_>>();
+        let (duration, profiles) = coordinated_profiles(plans, timing, &mut results);
+        (duration, profiles, results)
+    }
+
+    /// The slowest axis sets the duration, the other axes move slower to arrive together
+    #[test]
+    fn slowest_axis() {
+        let plans = [
+            Some(axis(1, 1000, 100, 50)),
+            None,
+            Some(axis(0, -100, 1000, 100)),
+        ];
+        let (duration, profiles, results) = plan(&plans, CoordinatedTiming::Slowest);
+        assert_eq!(duration, Duration::from_secs(12));
+        assert!(results.iter().all(Result::is_ok));
+        assert_eq!(
+            profiles[0],
+            Some(SynchronizedMove {
+                velocity: 100,
+                acceleration: 50,
+            })
+        );
+        assert_eq!(profiles[1], None);
+        // v * (12 s - v / 100) = 100 has the lower solution 8.39
+        assert_eq!(
+            profiles[2],
+            Some(SynchronizedMove {
+                velocity: 8,
+                acceleration: 100,
+            })
+        );
+    }
+
+    /// Axes that can't keep up with the lead axis are infeasible
+    #[test]
+    fn lead_axis() {
+        let plans = [Some(axis(0, 1000, 100, 50)), Some(axis(1, 100, 1000, 100))];
+        let (duration, profiles, results) = plan(&plans, CoordinatedTiming::LeadAxis(1));
+        assert_eq!(duration, Duration::from_secs(2));
+        assert_eq!(profiles[0], None);
+        assert!(profiles[1].is_some());
+        assert!(matches!(
+            results[0],
+            Err(GroupError::Infeasible {
+                index: 0,
+                requested,
+                minimum,
+            }) if requested == Duration::from_secs(2) && minimum == Duration::from_secs(12)
+        ));
+        assert!(results[1].is_ok());
+    }
+
+    /// The lead axis has to take part in the move
+    #[test]
+    fn lead_axis_not_moved() {
+        let plans = [Some(axis(0, 1000, 100, 50)), None];
+        for lead in [1, 2] {
+            let (_, profiles, results) = plan(&plans, CoordinatedTiming::LeadAxis(lead));
+            assert!(profiles.is_empty());
+            assert!(
+                matches!(results[0], Err(GroupError::LeadAxisNotMoved(index)) if index == lead)
+            );
+        }
+    }
+
+    /// A fixed duration has to be long enough for every axis, axes without velocity or
+    /// acceleration can't move at all
+    #[test]
+    fn fixed_duration() {
+        let plans = [
+            Some(axis(0, 1000, 100, 50)),
+            Some(axis(1, 1000, 0, 50)),
+            Some(axis(2, 1000, 1000, 1000)),
+        ];
+        let (duration, profiles, results) =
+            plan(&plans, CoordinatedTiming::Duration(Duration::from_secs(11)));
+        assert_eq!(duration, Duration::from_secs(11));
+        assert!(matches!(
+            results[0],
+            Err(GroupError::Infeasible { index: 0, minimum, .. }) if minimum == Duration::from_secs(12)
+        ));
+        assert!(matches!(
+            results[1],
+            Err(GroupError::Infeasible { index: 1, minimum, .. }) if minimum == Duration::MAX
+        ));
+        assert!(results[2].is_ok());
+        assert_eq!(profiles[0], None);
+        assert_eq!(profiles[1], None);
+        assert!(profiles[2].is_some());
+    }
+
+    /// A single infeasible axis keeps every other axis from starting
+    #[test]
+    fn failed_axis_stops_all() {
+        let moves = [(0, 1000), (1, 1000), (2, 1000)];
+        let mut results = vec![Ok(()), Err(GroupError::NoServo(1)), Ok(())];
+        assert!(any_failed(&mut results, &moves));
+        assert!(matches!(results[0], Err(GroupError::NotStarted(0))));
+        assert!(matches!(results[1], Err(GroupError::NoServo(1))));
+        assert!(matches!(results[2], Err(GroupError::NotStarted(2))));
+
+        let mut results = vec![Ok(()), Ok(())];
+        assert!(!any_failed(&mut results, &moves[..2]));
+    }
+}
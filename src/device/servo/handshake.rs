@@ -0,0 +1,402 @@
+//! This module contains the set-point handshake of the profile position mode, and the start and
+//! end of moves with their motion events.
+//!
+//! A setpoint is written with the new setpoint bit cleared and started by a rising edge of the
+//! new setpoint bit, which the drive only sees once it dropped the acknowledgement of the
+//! previous setpoint. The handshake only uses the process image and the cycles of the drive, so
+//! it's written against `Drive` and shared by the servo and the tests.
+
+use super::{
+    events::MotionEventKind, fault_details::pdo_fault_details, motion::Setpoint, MovementError,
+    MovementMode,
+};
+use crate::{
+    device::{ControlBit, Drive, StatusWordBit, WaitTimeout},
+    pdo,
+};
+use core::time::Duration;
+use std::time::Instant;
+
+/// Converts an error while waiting on a motion into a movement error.
+/// Attaches the fault details from the process image if the drive faulted.
+pub(super) fn motion_error(drive: &mut impl Drive, error: WaitTimeout) -> MovementError {
+    match error {
+        WaitTimeout::Fault(device, status) => MovementError::Fault {
+            device,
+            status,
+            details: pdo_fault_details(drive),
+        },
+        error => error.into(),
+    }
+}
+
+/// Waits during the handshake until the acknowledgement has the requested state
+///
+/// # Errors
+/// Returns an error if the drive faulted or the state wasn't reached within `timeout` from
+/// `start`
+async fn wait_acknowledgement(
+    drive: &mut impl Drive,
+    acknowledged: bool,
+    start: Instant,
+    timeout: Duration,
+) -> Result<(), MovementError> {
+    let result = drive
+        .wait_for_motion(
+            |status| status.is_set(StatusWordBit::AckStartRefReached) == acknowledged,
+            start,
+            timeout,
+        )
+        .await;
+    result.map_err(|error| motion_error(drive, error))?;
+    Ok(())
+}
+
+/// Writes a setpoint with the new setpoint bit cleared and waits until the drive finished the
+/// previous handshake, so setting the new setpoint bit starts the setpoint.
+/// Targets relative to the actual position are sent as absolute targets. The halt bit is cleared
+/// with the setpoint.
+///
+/// # Errors
+/// Returns an error if the setpoint couldn't be written, the drive faulted, or the previous
+/// handshake didn't finish in time
+pub(super) async fn load_setpoint(
+    drive: &mut impl Drive,
+    mut setpoint: Setpoint,
+    start: Instant,
+    timeout: Duration,
+) -> Result<(), MovementError> {
+    if setpoint.mode == MovementMode::RelativeToActual {
+        let position: i32 = drive
+            .read_input(pdo::input::POSITION_ACTUAL_VALUE)
+            .map_err(MovementError::Ethercat)?;
+        setpoint.position = position.saturating_add(setpoint.position);
+        setpoint.mode = MovementMode::Absolute;
+    }
+
+    // Write the setpoint with the start bit cleared
+    drive
+        .apply_outputs(|outputs| setpoint.write(outputs))
+        .map_err(MovementError::Ethercat)?;
+    drive.next_cycle().await;
+
+    // Wait until the drive finished the previous handshake, so it sees a rising edge
+    wait_acknowledgement(drive, false, start, timeout).await
+}
+
+/// Waits until the drive acknowledged the started setpoint, and drops the new setpoint bit
+/// again so the drive can acknowledge the next setpoint.
+///
+/// # Errors
+/// Returns an error if the new setpoint bit couldn't be dropped, the drive faulted, or the
+/// setpoint wasn't acknowledged in time
+pub(super) async fn acknowledge_setpoint(
+    drive: &mut impl Drive,
+    start: Instant,
+    timeout: Duration,
+) -> Result<(), MovementError> {
+    wait_acknowledgement(drive, true, start, timeout).await?;
+
+    // Drop the new setpoint bit, so the drive can acknowledge the next setpoint
+    drive
+        .update_control_word(|control| control.without(ControlBit::Control4))
+        .map_err(MovementError::Ethercat)?;
+    Ok(())
+}
+
+/// Waits until the drive dropped the acknowledgement of the latched setpoint, which completes
+/// the handshake, so the drive accepts the next setpoint.
+///
+/// # Errors
+/// Returns an error if the drive faulted or didn't drop the acknowledgement in time
+pub(super) async fn finish_handshake(
+    drive: &mut impl Drive,
+    start: Instant,
+    timeout: Duration,
+) -> Result<(), MovementError> {
+    wait_acknowledgement(drive, false, start, timeout).await
+}
+
+/// Sends a setpoint and returns as soon as the drive latched it: the new setpoint bit is raised
+/// once the acknowledgement is low, and dropped again once the drive acknowledged the setpoint.
+///
+/// The drive has to acknowledge the setpoint within `timeout` from `start`.
+///
+/// # Errors
+/// Returns an error if the setpoint couldn't be written, the drive faulted, or the setpoint
+/// wasn't acknowledged in time
+pub(super) async fn latch_setpoint(
+    drive: &mut impl Drive,
+    setpoint: Setpoint,
+    start: Instant,
+    timeout: Duration,
+) -> Result<(), MovementError> {
+    load_setpoint(drive, setpoint, start, timeout).await?;
+    drive
+        .update_control_word(|control| control.with(ControlBit::Control4))
+        .map_err(MovementError::Ethercat)?;
+    acknowledge_setpoint(drive, start, timeout).await
+}
+
+/// Sends a setpoint with the full set-point handshake, see `latch_setpoint`. Returns when the
+/// acknowledgement is low again, so the next setpoint can be sent.
+///
+/// # Errors
+/// Returns an error if the setpoint couldn't be written, the drive faulted, or the setpoint
+/// wasn't acknowledged in time
+pub(super) async fn send_setpoint(
+    drive: &mut impl Drive,
+    setpoint: Setpoint,
+    start: Instant,
+    timeout: Duration,
+) -> Result<(), MovementError> {
+    latch_setpoint(drive, setpoint, start, timeout).await?;
+    finish_handshake(drive, start, timeout).await
+}
+
+/// Loads the setpoint of a move, see `load_setpoint`, and emits that the move started
+///
+/// # Errors
+/// See `load_setpoint`
+pub(super) async fn load_move(
+    drive: &mut impl Drive,
+    setpoint: Setpoint,
+    start: Instant,
+    timeout: Duration,
+) -> Result<(), MovementError> {
+    drive.emit(MotionEventKind::MoveStarted {
+        target: setpoint.position,
+    });
+    load_setpoint(drive, setpoint, start, timeout).await
+}
+
+/// Completes the handshake of a loaded move whose new setpoint bit has been set, and emits that
+/// the setpoint has been acknowledged
+///
+/// # Errors
+/// Returns an error if the new setpoint bit couldn't be dropped, the drive faulted, or the
+/// setpoint wasn't acknowledged in time
+pub(super) async fn start_loaded(
+    drive: &mut impl Drive,
+    start: Instant,
+    timeout: Duration,
+) -> Result<(), MovementError> {
+    acknowledge_setpoint(drive, start, timeout).await?;
+
+    // Wait until the handshake is complete, so the drive accepts the next setpoint
+    finish_handshake(drive, start, timeout).await?;
+    drive.emit(MotionEventKind::SetpointAcknowledged);
+    Ok(())
+}
+
+/// Starts a move with the full set-point handshake, emitting the events of the start
+///
+/// # Errors
+/// Returns an error if the setpoint couldn't be written, the drive faulted, or the setpoint
+/// wasn't acknowledged in time
+pub(super) async fn start_move(
+    drive: &mut impl Drive,
+    setpoint: Setpoint,
+    start: Instant,
+    timeout: Duration,
+) -> Result<(), MovementError> {
+    load_move(drive, setpoint, start, timeout).await?;
+    drive
+        .update_control_word(|control| control.with(ControlBit::Control4))
+        .map_err(MovementError::Ethercat)?;
+    start_loaded(drive, start, timeout).await
+}
+
+/// Checks once whether a started move ended, and emits how it ended.
+/// The move is complete once the drive reports its target reached, if `reached` allows it.
+///
+/// # Returns
+/// `None` while the move runs, otherwise whether it completed. An aborting move that came to a
+/// stop ends with `MovementError::Aborted`.
+pub(super) fn check_move_end(
+    drive: &mut impl Drive,
+    reached: bool,
+    aborting: bool,
+    start: Instant,
+    timeout: Duration,
+) -> Option<Result<(), MovementError>> {
+    let result = drive.check_motion(
+        |status| reached && status.is_set(StatusWordBit::MotionComplete),
+        start,
+        timeout,
+    )?;
+    Some(match result {
+        Ok(_) if aborting => {
+            drive.emit(MotionEventKind::Aborted);
+            let id = drive.id();
+            Err(drive
+                .read_input(pdo::input::POSITION_ACTUAL_VALUE)
+                .map_or_else(MovementError::Ethercat, |position| {
+                    MovementError::Aborted(id, position)
+                }))
+        }
+        Ok(_) => {
+            drive.emit(MotionEventKind::MotionComplete);
+            Ok(())
+        }
+        Err(error) => Err(motion_error(drive, error)),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    //! Tests of the set-point handshake against a simulated drive
+
+    use super::*;
+    use crate::{
+        device::{
+            simulation::{block_on, SimulatedDrive, DEADLINE, ID, TIMEOUT},
+            Cia402State, ControlWord, OperationMode, OutputImage,
+        },
+        pdo::PdoValue,
+    };
+
+    /// Creates the setpoint of an absolute move
+    const fn setpoint(target: i32) -> Setpoint {
+        Setpoint {
+            position: target,
+            velocity: Some(1_000),
+            mode: MovementMode::Absolute,
+            change_immediately: false,
+        }
+    }
+
+    /// Creates a drive standing still in the profile position mode
+    fn profile_position_drive() -> SimulatedDrive {
+        let mut drive = SimulatedDrive::new(100);
+        drive.set_mode(OperationMode::ProfilePosition);
+        drive
+    }
+
+    /// Two setpoints sent right after each other are both latched and executed in order
+    #[test]
+    fn back_to_back_moves_both_execute() {
+        let mut drive = profile_position_drive();
+        let start = drive.now();
+        block_on(send_setpoint(&mut drive, setpoint(1_000), start, TIMEOUT)).unwrap();
+        let start = drive.now();
+        block_on(send_setpoint(&mut drive, setpoint(-500), start, TIMEOUT)).unwrap();
+        let (status, _) = drive.wait_cycles(|status| status.is_set(StatusWordBit::MotionComplete));
+
+        assert!(status.is_ok());
+        assert_eq!(drive.latched, [1_000, -500]);
+        assert_eq!(drive.reached, [1_000, -500]);
+        assert_eq!(drive.position(), -500);
+    }
+
+    /// A setpoint relative to the actual position is sent as the absolute target
+    #[test]
+    fn relative_to_actual_is_sent_absolute() {
+        let mut drive = profile_position_drive();
+        let start = drive.now();
+        block_on(send_setpoint(&mut drive, setpoint(1_000), start, TIMEOUT)).unwrap();
+        drive
+            .wait_cycles(|status| status.is_set(StatusWordBit::MotionComplete))
+            .0
+            .unwrap();
+        let relative = Setpoint {
+            mode: MovementMode::RelativeToActual,
+            ..setpoint(-300)
+        };
+        let start = drive.now();
+        block_on(send_setpoint(&mut drive, relative, start, TIMEOUT)).unwrap();
+
+        assert_eq!(drive.latched, [1_000, 700]);
+        let control = ControlWord::new(u16::read(&drive.outputs()[pdo::output::CONTROL_WORD..]));
+        assert!(!control.is_set(ControlBit::Control6));
+    }
+
+    /// Raising the new setpoint bit again right after the acknowledgement, without letting the
+    /// drive see it dropped, doesn't give the drive a rising edge, so the second move never
+    /// starts
+    #[test]
+    fn skipped_acknowledgement_misses_second_move() {
+        let mut drive = profile_position_drive();
+        let start = drive.now();
+        block_on(latch_setpoint(&mut drive, setpoint(1_000), start, TIMEOUT)).unwrap();
+
+        // Start the next setpoint before the next cycle sent the dropped bit
+        drive
+            .apply_outputs(|outputs| {
+                setpoint(-500).write(outputs);
+                let control = ControlWord::new(u16::read(&outputs[pdo::output::CONTROL_WORD..]));
+                control
+                    .with(ControlBit::Control4)
+                    .raw()
+                    .write(&mut outputs[pdo::output::CONTROL_WORD..]);
+            })
+            .unwrap();
+        drive
+            .wait_cycles(|status| status.is_set(StatusWordBit::MotionComplete))
+            .0
+            .unwrap();
+
+        assert_eq!(drive.latched, [1_000]);
+        assert_eq!(drive.position(), 1_000);
+    }
+
+    /// A fault in the middle of a move ends the wait for the motion in the next cycle, instead
+    /// of after the deadline, and is reported with the fault details
+    #[test]
+    fn fault_mid_move_ends_wait() {
+        let mut drive = profile_position_drive();
+        let start = drive.now();
+        block_on(send_setpoint(&mut drive, setpoint(10_000), start, TIMEOUT)).unwrap();
+        for _ in 0..10 {
+            drive.cycle();
+        }
+        assert!(!drive.status().is_set(StatusWordBit::MotionComplete));
+
+        drive.fault();
+        let (result, cycles) =
+            drive.wait_cycles(|status| status.is_set(StatusWordBit::MotionComplete));
+        assert_eq!(cycles, 1);
+        let Err(error @ WaitTimeout::Fault(ID, status)) = result else {
+            panic!("The wait didn't end with a fault: {result:?}");
+        };
+        assert_eq!(status.state(), Cia402State::Fault);
+        let MovementError::Fault {
+            device: ID,
+            details: Some(details),
+            ..
+        } = motion_error(&mut drive, error)
+        else {
+            panic!("The fault wasn't reported with its details");
+        };
+        assert_eq!(details.status.state(), Cia402State::Fault);
+    }
+
+    /// A drive that faults before acknowledging the setpoint ends the handshake with the fault
+    #[test]
+    fn fault_during_handshake() {
+        let mut drive = profile_position_drive();
+        drive.fault();
+        let start = drive.now();
+        let result = block_on(send_setpoint(&mut drive, setpoint(1_000), start, TIMEOUT));
+
+        assert!(
+            matches!(result, Err(MovementError::Fault { device: ID, .. })),
+            "{result:?}"
+        );
+        assert!(drive.latched.is_empty());
+    }
+
+    /// A setpoint the drive never acknowledges ends with a timeout once the deadline passed
+    #[test]
+    fn unacknowledged_setpoint_times_out() {
+        let mut drive = SimulatedDrive::new(100);
+        let start = drive.now();
+        let result = block_on(send_setpoint(&mut drive, setpoint(1_000), start, TIMEOUT));
+
+        assert!(
+            matches!(result, Err(MovementError::Timeout(ID, _))),
+            "{result:?}"
+        );
+        assert_eq!(drive.sent.len(), DEADLINE);
+    }
+}
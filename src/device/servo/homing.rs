@@ -0,0 +1,782 @@
+//! This module contains the configuration of the homing mode.
+//!
+//! The homing method (0x6098), speeds (0x6099), acceleration (0x609A) and home offset (0x607C)
+//! are written over SDO, so they should be configured before homing instead of in a time
+//! critical loop. The homing sequence itself only uses the process image, so it's written
+//! against `Drive` and shared by the servo and the tests.
+
+use super::{events::MotionEventKind, homing_ended, start_homing_control, HomingError, Servo};
+use crate::device::{
+    objects::{self, Object},
+    ControlBit, Drive, OperationMode, StatusWord, StatusWordBit, WaitTimeout,
+};
+use core::{
+    fmt::{self, Debug, Formatter},
+    time::Duration,
+};
+use ethercrab::{error::Error as EthercrabError, EtherCrabWireReadSized, EtherCrabWireWrite};
+use std::time::Instant;
+
+/// The homing method setting the current position as home position without moving
+const HOMING_ON_CURRENT_POSITION: i8 = 37;
+
+/// The Festo homing method driving into a block in negative direction
+const HOMING_ON_BLOCK_NEGATIVE: i8 = -17;
+
+/// The Festo homing method driving into a block in positive direction
+const HOMING_ON_BLOCK_POSITIVE: i8 = -18;
+
+/// The parameters of the homing mode
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct HomingConfig {
+    /// The homing method, 1 to 35 or 37 for standard methods or negative for vendor specific
+    /// methods
+    pub method: i8,
+
+    /// The speed while searching for the switch in increments per second
+    pub speed_search: u32,
+
+    /// The speed while searching for the zero position in increments per second
+    pub speed_zero: u32,
+
+    /// The acceleration while homing in increments per second squared
+    pub acceleration: u32,
+
+    /// The position the home position is set to in increments
+    pub home_offset: i32,
+}
+
+/// Selects when the servo is homed
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum HomingPolicy {
+    /// Always home the servo, even if it's already homed
+    Always,
+
+    /// Only home the servo if it isn't homed yet
+    IfNotHomed,
+
+    /// Never move the servo, but return an error if it isn't homed
+    FailIfNotHomed,
+}
+
+/// The direction in which the servo drives into the block
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum BlockDirection {
+    /// Drive into the block in positive direction
+    Positive,
+
+    /// Drive into the block in negative direction
+    Negative,
+}
+
+/// The parameters of homing against a mechanical end stop
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct HomeToBlockConfig {
+    /// The direction in which the block is located
+    pub direction: BlockDirection,
+
+    /// The speed while searching for the block in increments per second
+    pub velocity: u32,
+
+    /// The maximum torque while homing in thousandths of the rated torque
+    pub torque_limit_per_mille: u16,
+
+    /// The time the drive may take to report the home position after reaching the torque limit
+    pub settle: Duration,
+}
+
+/// An error returned while configuring the homing mode
+pub enum HomingConfigError {
+    /// The homing method is neither a standard method, nor a vendor specific method
+    InvalidMethod(usize, i8),
+
+    /// The torque limit for homing to a block is zero
+    ZeroTorqueLimit(usize),
+
+    /// The homing method needs the encoder, but the stepper runs in open loop
+    RequiresEncoder(usize, i8),
+
+    /// The drive reports a different value than was written to the object
+    VerificationFailed {
+        /// The device number
+        device: usize,
+
+        /// The object that was written
+        object: Object,
+
+        /// The value that was written
+        written: i64,
+
+        /// The value that was read back
+        read: i64,
+    },
+
+    /// Writing or reading an object failed
+    Ethercat {
+        /// The device number
+        device: usize,
+
+        /// The object that was accessed
+        object: Object,
+
+        /// The error returned by `EtherCrab`
+        error: EthercrabError,
+    },
+}
+
+impl Debug for HomingConfigError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::InvalidMethod(device, method) => write!(
+                f,
+                "Homing method {method} of device {device} isn't a standard or vendor method"
+            ),
+            Self::ZeroTorqueLimit(device) => {
+                write!(
+                    f,
+                    "The homing torque limit of device {device} can't be zero"
+                )
+            }
+            Self::RequiresEncoder(device, method) => write!(
+                f,
+                "Homing method {method} of device {device} needs the encoder, but the stepper \
+                 runs in open loop"
+            ),
+            Self::VerificationFailed {
+                device,
+                object,
+                written,
+                read,
+            } => write!(
+                f,
+                "Device {device} reports {read} instead of {written} for object {:#06x}:{}",
+                object.index, object.sub_index
+            ),
+            Self::Ethercat {
+                device,
+                object,
+                error,
+            } => write!(
+                f,
+                "Accessing object {:#06x}:{} of device {device} failed: {error:?}",
+                object.index, object.sub_index
+            ),
+        }
+    }
+}
+
+impl From<HomingConfigError> for HomingError {
+    fn from(value: HomingConfigError) -> Self {
+        Self::Configuration(value)
+    }
+}
+
+impl HomingConfig {
+    /// Checks whether the method is a standard (1 to 35 or 37) or vendor specific (negative)
+    /// method
+    #[must_use]
+    pub const fn method_is_valid(&self) -> bool {
+        matches!(self.method, i8::MIN..=-1 | 1..=35 | HOMING_ON_CURRENT_POSITION)
+    }
+
+    /// Checks whether the method needs the encoder, because it searches the index pulse or
+    /// detects a block by the following error
+    #[must_use]
+    pub const fn requires_encoder(&self) -> bool {
+        method_requires_encoder(self.method)
+    }
+}
+
+/// Checks whether the homing method searches the index pulse (1 to 14, 33 and 34) or drives
+/// into a block
+pub(crate) const fn method_requires_encoder(method: i8) -> bool {
+    matches!(
+        method,
+        1..=14 | 33 | 34 | HOMING_ON_BLOCK_NEGATIVE | HOMING_ON_BLOCK_POSITIVE
+    )
+}
+
+/// How homing failed, before the details that have to be read over SDO were collected
+#[derive(Debug)]
+pub(super) enum HomingFailure {
+    /// Homing failed with the error
+    Error(HomingError),
+
+    /// Waiting for the end of homing failed before the timeout expired
+    Wait(WaitTimeout),
+
+    /// The drive reported a homing error with the status word
+    Rejected(StatusWord),
+}
+
+/// Moves a drive to home according to the policy, see `Servo::home_with_timeout`
+///
+/// # Errors
+/// Returns an error if the policy requires the drive to be homed but it isn't, or homing failed
+///
+/// # Returns
+/// Whether homing was performed
+pub(super) async fn run_homing(
+    drive: &mut impl Drive,
+    policy: HomingPolicy,
+    start: Instant,
+    timeout: Duration,
+) -> Result<bool, HomingFailure> {
+    // Check whether the policy requires homing
+    let already_homed = drive
+        .status_word()
+        .is_ok_and(|status| status.is_set(StatusWordBit::DriveHomed));
+    match policy {
+        HomingPolicy::FailIfNotHomed if !already_homed => {
+            return Err(HomingFailure::Error(HomingError::NotHomed(drive.id())));
+        }
+        HomingPolicy::FailIfNotHomed | HomingPolicy::IfNotHomed if already_homed => {
+            log::info!("device {} already homed", drive.id());
+            return Ok(false);
+        }
+        _ => {}
+    }
+
+    start_homing(drive, start, timeout)
+        .await
+        .map_err(HomingFailure::Error)?;
+
+    // Wait until the device is homed or reports a homing error
+    let homed = drive.wait_for_motion(homing_ended, start, timeout).await;
+    finish_homing(drive, homed, start)?;
+    Ok(true)
+}
+
+/// Switches a drive to the homing mode and starts homing.
+///
+/// # Errors
+/// Returns an error if:
+/// - The device is disabled
+/// - The servo can't be set to homing mode
+/// - Switching to the homing mode took longer than the timeout
+pub(super) async fn start_homing(
+    drive: &mut impl Drive,
+    start: Instant,
+    timeout: Duration,
+) -> Result<(), HomingError> {
+    let enabled = drive
+        .status_word()
+        .is_ok_and(|status| status.is_set(StatusWordBit::OperationEnabled));
+    if !enabled {
+        return Err(HomingError::DeviceDisabled(drive.id()));
+    }
+
+    // Set the device to the homing mode
+    drive
+        .set_mode(OperationMode::Homing)
+        .await
+        .map_err(HomingError::SetMode)?;
+    let waited = drive.now().saturating_duration_since(start);
+    if waited >= timeout {
+        return Err(HomingError::Timeout {
+            device: drive.id(),
+            status: drive.status_word().unwrap_or_default(),
+            waited,
+        });
+    }
+    log::info!("device {} starting homing", drive.id());
+
+    // Clear the control bits and the halt bit, but set control bit 4
+    let _ = drive.update_control_word(start_homing_control);
+    Ok(())
+}
+
+/// Ends homing and converts the result of waiting for the end of homing.
+/// Homing is halted if the timeout expired, the homed event is emitted if it succeeded.
+///
+/// # Errors
+/// Returns an error if the wait failed or the drive reported a homing error
+pub(super) fn finish_homing(
+    drive: &mut impl Drive,
+    homed: Result<StatusWord, WaitTimeout>,
+    start: Instant,
+) -> Result<(), HomingFailure> {
+    // Clear the bit
+    let _ = drive.update_control_word(|control| control.without(ControlBit::Control4));
+    let status = match homed {
+        Ok(status) => status,
+        Err(WaitTimeout::Expired(device, status)) => {
+            // Stop the search for the reference
+            let _ = drive.update_control_word(|control| {
+                control.with(ControlBit::Halt).without(ControlBit::Control4)
+            });
+            return Err(HomingFailure::Error(HomingError::Timeout {
+                device,
+                status,
+                waited: drive.now().saturating_duration_since(start),
+            }));
+        }
+        Err(error) => return Err(HomingFailure::Wait(error)),
+    };
+    if status.is_set(StatusWordBit::ModeSpecificError) {
+        return Err(HomingFailure::Rejected(status));
+    }
+    drive.emit(MotionEventKind::Homed);
+    Ok(())
+}
+
+impl<const MAX_DEVICES: usize, const PDI_LENGTH: usize> Servo<'_, '_, MAX_DEVICES, PDI_LENGTH> {
+    /// Collects the details of a failed homing that have to be read over SDO
+    pub(super) async fn homing_failure(&mut self, failure: HomingFailure) -> HomingError {
+        match failure {
+            HomingFailure::Error(error) => error,
+            HomingFailure::Wait(error) => self.homing_error(error).await,
+            HomingFailure::Rejected(status) => {
+                // Read the reason the drive gave up homing
+                let code = self
+                    .device
+                    .read_optional_object(objects::ERROR_CODE)
+                    .await
+                    .ok()
+                    .flatten();
+                let switches = self.limit_switches().await.ok();
+                HomingError::HomingFault {
+                    device: self.device.id,
+                    status,
+                    code,
+                    switches,
+                }
+            }
+        }
+    }
+
+    /// Writes a value to an object and reads it back to verify it.
+    ///
+    /// # Errors
+    /// Returns an error if the value couldn't be written or the drive reports a different value
+    async fn write_homing_object<T>(
+        &mut self,
+        object: Object,
+        written: T,
+    ) -> Result<(), HomingConfigError>
+    where
+        T: EtherCrabWireWrite + EtherCrabWireReadSized + Copy + PartialEq + Into<i64>,
+    {
+        let device = self.device.id;
+        let ethercat = |error| HomingConfigError::Ethercat {
+            device,
+            object,
+            error,
+        };
+        self.device
+            .write_object(object, written)
+            .await
+            .map_err(ethercat)?;
+        let read: T = self.device.read_object(object).await.map_err(ethercat)?;
+        if read != written {
+            return Err(HomingConfigError::VerificationFailed {
+                device,
+                object,
+                written: written.into(),
+                read: read.into(),
+            });
+        }
+        Ok(())
+    }
+
+    /// Writes the homing parameters to the drive and reads them back to verify them.
+    ///
+    /// # Errors
+    /// Returns an error if:
+    /// - The homing method is invalid
+    /// - A value couldn't be written or read back
+    /// - The drive reports a different value than was written
+    pub async fn configure_homing(
+        &mut self,
+        config: &HomingConfig,
+    ) -> Result<(), HomingConfigError> {
+        if !config.method_is_valid() {
+            return Err(HomingConfigError::InvalidMethod(
+                self.device.id,
+                config.method,
+            ));
+        }
+
+        // Write every parameter and verify it
+        self.write_homing_object(objects::HOMING_METHOD, config.method)
+            .await?;
+        self.write_homing_object(objects::HOMING_SPEED_SEARCH_SWITCH, config.speed_search)
+            .await?;
+        self.write_homing_object(objects::HOMING_SPEED_SEARCH_ZERO, config.speed_zero)
+            .await?;
+        self.write_homing_object(objects::HOMING_ACCELERATION, config.acceleration)
+            .await?;
+        self.write_homing_object(objects::HOME_OFFSET, config.home_offset)
+            .await?;
+        if self.device.controller.verbose() {
+            log::info!(
+                "Configured homing of device {} to {config:?}",
+                self.device.id
+            );
+        }
+        Ok(())
+    }
+
+    /// Reads the homing parameters from the drive.
+    ///
+    /// # Errors
+    /// Returns an error if a value couldn't be read
+    pub async fn homing_config(&mut self) -> Result<HomingConfig, EthercrabError> {
+        Ok(HomingConfig {
+            method: self.device.read_object(objects::HOMING_METHOD).await?,
+            speed_search: self
+                .device
+                .read_object(objects::HOMING_SPEED_SEARCH_SWITCH)
+                .await?,
+            speed_zero: self
+                .device
+                .read_object(objects::HOMING_SPEED_SEARCH_ZERO)
+                .await?,
+            acceleration: self
+                .device
+                .read_object(objects::HOMING_ACCELERATION)
+                .await?,
+            home_offset: self.device.read_object(objects::HOME_OFFSET).await?,
+        })
+    }
+
+    /// Writes the homing parameters to the drive and moves the servo to home according to the
+    /// policy. See `configure_homing` and `home`.
+    ///
+    /// # Errors
+    /// Returns an error if the parameters couldn't be configured or homing failed
+    ///
+    /// # Returns
+    /// Whether homing was performed
+    pub async fn home_with_config(
+        &mut self,
+        policy: HomingPolicy,
+        config: &HomingConfig,
+    ) -> Result<bool, HomingError> {
+        if !self.device.ready_state() {
+            return Err(HomingError::DeviceDisabled(self.device.id));
+        }
+        self.configure_homing(config).await?;
+        self.home(policy).await
+    }
+
+    /// Declares the current position to be the home offset, without moving the servo.
+    /// Temporarily selects the homing method on the current position (37) and restores the
+    /// previous homing method afterwards.
+    ///
+    /// # Errors
+    /// Returns an error if:
+    /// - The homing method or home offset couldn't be read or written
+    /// - Homing failed
+    pub async fn set_home_here(&mut self, offset: i32) -> Result<(), HomingError> {
+        let device = self.device.id;
+        let previous_method: i8 = self.read_homing_object(objects::HOMING_METHOD).await?;
+
+        // Select homing on the current position with the requested offset
+        self.write_homing_object(objects::HOMING_METHOD, HOMING_ON_CURRENT_POSITION)
+            .await?;
+        let homed = match self.write_homing_object(objects::HOME_OFFSET, offset).await {
+            Ok(()) => self
+                .home_with_timeout(HomingPolicy::Always, self.config.homing_timeout)
+                .await
+                .map(|_| ()),
+            Err(error) => Err(error.into()),
+        };
+
+        // Restore the previous homing method, even if homing failed
+        let restored = self
+            .write_homing_object(objects::HOMING_METHOD, previous_method)
+            .await;
+        homed?;
+        restored?;
+        if self.device.controller.verbose() {
+            log::info!("Set the current position of device {device} to {offset}");
+        }
+        Ok(())
+    }
+
+    /// Reads an object over SDO for the homing configuration.
+    ///
+    /// # Errors
+    /// Returns an error if the object couldn't be read
+    async fn read_homing_object<T: EtherCrabWireReadSized>(
+        &mut self,
+        object: Object,
+    ) -> Result<T, HomingConfigError> {
+        let device = self.device.id;
+        self.device
+            .read_object(object)
+            .await
+            .map_err(|error| HomingConfigError::Ethercat {
+                device,
+                object,
+                error,
+            })
+    }
+
+    /// Homes the servo by driving into a mechanical end stop with a limited torque.
+    /// The previous homing method and maximum torque are restored afterwards.
+    ///
+    /// # Errors
+    /// Returns an error if:
+    /// - The torque limit is zero
+    /// - The homing parameters or maximum torque couldn't be read or written
+    /// - The device is disabled or the servo can't be set to homing mode
+    /// - The torque limit was reached, but the drive didn't report the home position in time
+    /// - The device faulted, reported a homing error or didn't home in time
+    pub async fn home_to_block(&mut self, config: &HomeToBlockConfig) -> Result<(), HomingError> {
+        if config.torque_limit_per_mille == 0 {
+            return Err(HomingConfigError::ZeroTorqueLimit(self.device.id).into());
+        }
+        let previous_method: i8 = self.read_homing_object(objects::HOMING_METHOD).await?;
+        let previous_torque: u16 = self.read_homing_object(objects::MAX_TORQUE).await?;
+
+        // Configure homing to the block with the torque limit
+        let method = match config.direction {
+            BlockDirection::Positive => HOMING_ON_BLOCK_POSITIVE,
+            BlockDirection::Negative => HOMING_ON_BLOCK_NEGATIVE,
+        };
+        self.write_homing_object(objects::HOMING_METHOD, method)
+            .await?;
+        self.max_torque = None;
+        let homed = match self.configure_block_homing(config).await {
+            Ok(()) => self.run_home_to_block(config).await,
+            Err(error) => Err(error.into()),
+        };
+
+        // Restore the previous maximum torque and homing method, even if homing failed
+        let restored_torque = self
+            .write_homing_object(objects::MAX_TORQUE, previous_torque)
+            .await;
+        let restored_method = self
+            .write_homing_object(objects::HOMING_METHOD, previous_method)
+            .await;
+        homed?;
+        restored_torque?;
+        restored_method?;
+        Ok(())
+    }
+
+    /// Writes the search speed and torque limit for homing to a block.
+    ///
+    /// # Errors
+    /// Returns an error if a value couldn't be written or verified
+    async fn configure_block_homing(
+        &mut self,
+        config: &HomeToBlockConfig,
+    ) -> Result<(), HomingConfigError> {
+        self.write_homing_object(objects::HOMING_SPEED_SEARCH_SWITCH, config.velocity)
+            .await?;
+        self.write_homing_object(objects::MAX_TORQUE, config.torque_limit_per_mille)
+            .await
+    }
+
+    /// Runs homing to a block while monitoring the actual torque.
+    ///
+    /// # Errors
+    /// Returns an error if homing failed or the block was reached without homing
+    async fn run_home_to_block(&mut self, config: &HomeToBlockConfig) -> Result<(), HomingError> {
+        let start = Instant::now();
+        let timeout = self.config.homing_timeout;
+        start_homing(&mut self.device, start, timeout).await?;
+
+        // Wait until homing ended, while checking how long the torque limit has been reached
+        let mut block_reached: Option<Instant> = None;
+        let homed = loop {
+            if let Some(result) = self.device.check_motion(homing_ended, start, timeout) {
+                break result;
+            }
+            let torque = self.snapshot().map_or(0, |status| status.torque);
+            if torque.unsigned_abs() >= config.torque_limit_per_mille {
+                let reached = *block_reached.get_or_insert_with(Instant::now);
+                if reached.elapsed() >= config.settle {
+                    let status = self.device.status_word().unwrap_or_default();
+                    let _ = self.halt();
+                    return Err(HomingError::BlockReachedNotHomed {
+                        device: self.device.id,
+                        status,
+                        torque,
+                    });
+                }
+            } else {
+                block_reached = None;
+            }
+            self.device.controller.next_cycle().await;
+        };
+        if let Err(failure) = finish_homing(&mut self.device, homed, start) {
+            return Err(self.homing_failure(failure).await);
+        }
+
+        // Verify the drive considers itself homed
+        if !self
+            .device
+            .status_word()
+            .is_ok_and(|status| status.is_set(StatusWordBit::DriveHomed))
+        {
+            return Err(HomingError::NotHomed(self.device.id));
+        }
+        if self.device.controller.verbose() {
+            log::info!("Homed device {} to the block", self.device.id);
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    //! Tests of homing against a simulated drive
+
+    use super::*;
+    use crate::{
+        device::{
+            simulation::{block_on, SimulatedDrive, CYCLE_TIME, ID, TIMEOUT},
+            ControlWord, OutputImage,
+        },
+        pdo::{self, PdoValue},
+    };
+
+    /// Homes the drive with `run_homing` and the timeout of the simulated drive
+    fn home(drive: &mut SimulatedDrive, policy: HomingPolicy) -> Result<bool, HomingFailure> {
+        let start = drive.now();
+        block_on(run_homing(drive, policy, start, TIMEOUT))
+    }
+
+    /// Checks whether the homing start bit is set in the outputs
+    fn homing_started(drive: &SimulatedDrive) -> bool {
+        ControlWord::new(u16::read(&drive.outputs()[pdo::output::CONTROL_WORD..]))
+            .is_set(ControlBit::Control4)
+    }
+
+    /// A homing error ends homing in the cycle the drive reports it, as rejected by the drive
+    #[test]
+    fn homing_error_ends_homing() {
+        let mut drive = SimulatedDrive::new(100);
+        drive.fail_homing();
+        let result = home(&mut drive, HomingPolicy::Always);
+
+        let Err(HomingFailure::Rejected(status)) = result else {
+            panic!("Homing wasn't rejected: {result:?}");
+        };
+        assert!(status.is_set(StatusWordBit::ModeSpecificError));
+        assert!(!status.is_set(StatusWordBit::DriveHomed));
+        assert_eq!(drive.sent.len(), 2);
+        assert!(!homing_started(&drive));
+        assert!(drive.events.is_empty());
+    }
+
+    /// A fault while homing ends homing in the next cycle and is reported as a fault
+    #[test]
+    fn fault_ends_homing() {
+        let mut drive = SimulatedDrive::new(100);
+        drive.fault();
+        let start = drive.now();
+        block_on(start_homing(&mut drive, start, TIMEOUT)).unwrap();
+        let homed = drive.wait_cycles(homing_ended).0;
+        let result = finish_homing(&mut drive, homed, start);
+
+        let Err(HomingFailure::Wait(error)) = result else {
+            panic!("Homing didn't end with a fault: {result:?}");
+        };
+        assert!(matches!(
+            HomingError::from(error),
+            HomingError::Fault { device: ID, .. }
+        ));
+        assert!(!homing_started(&drive));
+    }
+
+    /// Homing that succeeds leaves the drive homed with the start bit cleared, and emits the
+    /// homed event after the home position has been reached
+    #[test]
+    fn homing_reaches_home() {
+        let mut drive = SimulatedDrive::new(100);
+        assert!(home(&mut drive, HomingPolicy::Always).unwrap());
+
+        assert!(drive.status().is_set(StatusWordBit::DriveHomed));
+        assert!(!homing_started(&drive));
+        let kinds: Vec<_> = drive.events.iter().map(|event| event.kind).collect();
+        assert_eq!(
+            kinds,
+            [MotionEventKind::TargetReached, MotionEventKind::Homed]
+        );
+    }
+
+    /// The policy skips homing a homed drive, and requires an unhomed drive to be homed without
+    /// moving it
+    #[test]
+    fn policy_selects_homing() {
+        let mut drive = SimulatedDrive::new(100);
+        let result = home(&mut drive, HomingPolicy::FailIfNotHomed);
+        assert!(
+            matches!(result, Err(HomingFailure::Error(HomingError::NotHomed(ID)))),
+            "{result:?}"
+        );
+        assert!(drive.sent.is_empty());
+
+        assert!(home(&mut drive, HomingPolicy::IfNotHomed).unwrap());
+        let cycles = drive.sent.len();
+        assert!(!home(&mut drive, HomingPolicy::IfNotHomed).unwrap());
+        assert!(!home(&mut drive, HomingPolicy::FailIfNotHomed).unwrap());
+        assert_eq!(drive.sent.len(), cycles);
+    }
+
+    /// Homing that doesn't end in time is halted and reported as timed out
+    #[test]
+    fn homing_times_out() {
+        let mut drive = SimulatedDrive::new(100);
+        let start = drive.now();
+        let result = block_on(run_homing(
+            &mut drive,
+            HomingPolicy::Always,
+            start,
+            CYCLE_TIME * 2,
+        ));
+
+        assert!(
+            matches!(
+                result,
+                Err(HomingFailure::Error(HomingError::Timeout {
+                    device: ID,
+                    ..
+                }))
+            ),
+            "{result:?}"
+        );
+        let control = ControlWord::new(u16::read(&drive.outputs()[pdo::output::CONTROL_WORD..]));
+        assert!(control.is_set(ControlBit::Halt));
+        assert!(!control.is_set(ControlBit::Control4));
+    }
+
+    /// Setting the home position on the current position runs homing, which only changes the
+    /// control word and the mode in the outputs, so the target position and velocity bytes never
+    /// change
+    #[test]
+    fn set_home_here_keeps_target() {
+        let mut drive = SimulatedDrive::new(100);
+        drive
+            .apply_outputs(|outputs| {
+                1_234_i32.write(&mut outputs[pdo::output::TARGET_POSITION..]);
+                500_u32.write(&mut outputs[pdo::output::PROFILE_VELOCITY..]);
+            })
+            .unwrap();
+        let before = drive.outputs().to_vec();
+
+        // Home like `Servo::set_home_here` does after selecting homing on the current position
+        assert!(home(&mut drive, HomingPolicy::Always).unwrap());
+        assert!(drive.status().is_set(StatusWordBit::DriveHomed));
+        drive.cycle();
+
+        let control = pdo::output::CONTROL_WORD..pdo::output::CONTROL_WORD + 2;
+        for sent in &drive.sent {
+            for (index, (byte, before)) in sent.iter().zip(&before).enumerate() {
+                if !control.contains(&index) && index != pdo::output::MODES_OF_OPERATION {
+                    assert_eq!(byte, before, "Output byte {index} changed");
+                }
+            }
+        }
+        assert!(drive.latched.is_empty());
+        assert_eq!(drive.position(), 0);
+    }
+}
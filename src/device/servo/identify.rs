@@ -0,0 +1,21 @@
+//! This module contains the identification of the drive of the servo.
+//!
+//! Identical drives are hard to tell apart in a cabinet, so the drive can blink its front LED
+//! to show which physical unit belongs to the servo.
+
+use super::Servo;
+use crate::controller::IdentifyError;
+use core::time::Duration;
+
+impl<const MAX_DEVICES: usize, const PDI_LENGTH: usize> Servo<'_, '_, MAX_DEVICES, PDI_LENGTH> {
+    /// Blinks the front LED of the drive for the duration, then returns it to normal.
+    ///
+    /// # Errors
+    /// Returns an error if the drive doesn't support identification or communication failed
+    pub async fn identify(&mut self, duration: Duration) -> Result<(), IdentifyError> {
+        self.device
+            .controller
+            .identify_device_with(self.device.id, self.vendor_objects, duration)
+            .await
+    }
+}
@@ -0,0 +1,249 @@
+//! This module contains the interpolated position mode of the servo.
+//!
+//! In this mode setpoints are queued in a buffer in the drive, which interpolates between them
+//! every interpolation period. This gives smoother multi-segment motion than repeated profile
+//! position moves on drives that don't support cyclic synchronous position mode.
+//! The setpoints are written over SDO, so the buffer level should be used for flow control.
+
+use super::{Servo, MOTION_TIMEOUT};
+use crate::device::{objects, ControlBit, OperationMode, SetModeError, StatusWordBit, WaitTimeout};
+use core::{
+    fmt::{self, Debug, Formatter},
+    time::Duration,
+};
+use ethercrab::error::Error as EthercrabError;
+
+/// An error returned while using the interpolated position mode
+pub enum InterpolationError {
+    /// The drive is disabled
+    DriveDisabled(usize),
+
+    /// The device has been emergency stopped
+    EmergencyStopped(usize),
+
+    /// The cycle time can't be expressed as interpolation time period
+    InvalidPeriod(usize, Duration),
+
+    /// The setpoint buffer of the drive is full, the setpoint wasn't queued
+    BufferFull(usize),
+
+    /// The setpoint buffer ran empty before the setpoint was queued.
+    /// The setpoint was queued, but the servo may have stopped at the previous setpoint.
+    Underrun(usize),
+
+    /// The device couldn't be set to interpolated position mode
+    SetMode(SetModeError),
+
+    /// The device faulted or didn't complete the motion in time
+    Wait(WaitTimeout),
+
+    /// Communication failed or there was an existing reference to the device
+    Ethercat(usize, EthercrabError),
+}
+
+impl From<WaitTimeout> for InterpolationError {
+    fn from(error: WaitTimeout) -> Self {
+        match error {
+            WaitTimeout::EmergencyStopped(device, _) => Self::EmergencyStopped(device),
+            error => Self::Wait(error),
+        }
+    }
+}
+
+impl Debug for InterpolationError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::DriveDisabled(device) => {
+                write!(f, "Drive {device} is disabled, interpolation not possible")
+            }
+            Self::EmergencyStopped(device) => write!(f, "Device {device} is emergency stopped"),
+            Self::InvalidPeriod(device, period) => write!(
+                f,
+                "Cycle time {period:?} can't be used as interpolation period of device {device}"
+            ),
+            Self::BufferFull(device) => {
+                write!(f, "Setpoint buffer of device {device} is full")
+            }
+            Self::Underrun(device) => {
+                write!(f, "Setpoint buffer of device {device} ran empty")
+            }
+            Self::SetMode(error) => {
+                write!(
+                    f,
+                    "Error while setting interpolated position mode: {error:?}"
+                )
+            }
+            Self::Wait(error) => write!(f, "Interpolated motion failed: {error:?}"),
+            Self::Ethercat(device, error) => {
+                write!(f, "Failed to communicate with device {device}: {error}")
+            }
+        }
+    }
+}
+
+/// The fill level of the setpoint buffer of the drive
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct BufferLevel {
+    /// The number of setpoints in the buffer
+    pub queued: u32,
+
+    /// The number of setpoints the buffer can hold
+    pub capacity: u32,
+}
+
+impl BufferLevel {
+    /// Returns the number of setpoints that can still be queued
+    pub const fn free(self) -> u32 {
+        self.capacity.saturating_sub(self.queued)
+    }
+}
+
+/// Converts a duration to an interpolation time period value and power of ten
+fn interpolation_period(period: Duration) -> Option<(u8, i8)> {
+    // Use the largest unit that expresses the period exactly
+    let mut nanos = period.as_nanos();
+    let mut index = -9;
+    while nanos % 10 == 0 && nanos > 0 && index < 0 {
+        nanos /= 10;
+        index += 1;
+    }
+    Some((u8::try_from(nanos).ok().filter(|value| *value > 0)?, index))
+}
+
+impl<const MAX_DEVICES: usize, const PDI_LENGTH: usize> Servo<'_, '_, MAX_DEVICES, PDI_LENGTH> {
+    /// Switches the servo to interpolated position mode.
+    /// The interpolation period is set to the cycle time and the setpoint buffer is cleared.
+    ///
+    /// # Errors
+    /// Returns an error if:
+    /// - The drive is disabled or emergency stopped
+    /// - The cycle time can't be used as interpolation period
+    /// - The interpolation period or buffer couldn't be configured
+    /// - The device couldn't be set to interpolated position mode
+    pub async fn enter_interpolated(&mut self) -> Result<(), InterpolationError> {
+        let id = self.device.id;
+        if self.device.emergency_stopped() {
+            return Err(InterpolationError::EmergencyStopped(id));
+        }
+        if !self.device.ready_state() {
+            return Err(InterpolationError::DriveDisabled(id));
+        }
+
+        // Set the interpolation period to the cycle time
+        let cycle_time = self.device.controller.cycle_time();
+        let (value, index) = interpolation_period(cycle_time)
+            .ok_or(InterpolationError::InvalidPeriod(id, cycle_time))?;
+        self.device
+            .write_object(objects::INTERPOLATION_TIME_PERIOD_VALUE, value)
+            .await
+            .map_err(|error| InterpolationError::Ethercat(id, error))?;
+        self.device
+            .write_object(objects::INTERPOLATION_TIME_INDEX, index)
+            .await
+            .map_err(|error| InterpolationError::Ethercat(id, error))?;
+
+        // Clear and enable the setpoint buffer
+        for value in [0_u8, 1] {
+            self.device
+                .write_object(objects::INTERPOLATION_BUFFER_CLEAR, value)
+                .await
+                .map_err(|error| InterpolationError::Ethercat(id, error))?;
+        }
+        self.interpolation_fed = false;
+
+        // Set the mode and enable the interpolation with control bit 4
+        self.device
+            .set_mode(OperationMode::InterpolatedPosition)
+            .await
+            .map_err(InterpolationError::SetMode)?;
+        self.device
+            .update_control_word(|control| control.with(ControlBit::Control4))
+            .map_err(|error| InterpolationError::Ethercat(id, error))?;
+        Ok(())
+    }
+
+    /// Reads the fill level of the setpoint buffer of the drive.
+    ///
+    /// # Errors
+    /// Returns an error if the buffer level couldn't be read
+    pub async fn interpolation_buffer(&mut self) -> Result<BufferLevel, InterpolationError> {
+        let id = self.device.id;
+        let capacity = self
+            .device
+            .read_object(objects::INTERPOLATION_BUFFER_SIZE)
+            .await
+            .map_err(|error| InterpolationError::Ethercat(id, error))?;
+        let queued: u16 = self
+            .device
+            .read_object(objects::INTERPOLATION_BUFFER_POSITION)
+            .await
+            .map_err(|error| InterpolationError::Ethercat(id, error))?;
+        Ok(BufferLevel {
+            queued: u32::from(queued),
+            capacity,
+        })
+    }
+
+    /// Queues a setpoint in the buffer of the drive.
+    ///
+    /// # Errors
+    /// Returns an error if:
+    /// - The buffer is full, the setpoint isn't queued
+    /// - The buffer ran empty since the previous setpoint, the setpoint is queued
+    /// - The buffer level couldn't be read or the setpoint couldn't be written
+    ///
+    /// # Returns
+    /// The buffer level before queuing the setpoint
+    pub async fn queue_setpoint(
+        &mut self,
+        position: i32,
+    ) -> Result<BufferLevel, InterpolationError> {
+        let id = self.device.id;
+        let level = self.interpolation_buffer().await?;
+        if level.free() == 0 {
+            return Err(InterpolationError::BufferFull(id));
+        }
+
+        self.device
+            .write_object(objects::INTERPOLATION_DATA_RECORD, position)
+            .await
+            .map_err(|error| InterpolationError::Ethercat(id, error))?;
+
+        // Report an underrun if the drive consumed all previous setpoints
+        let underrun = self.interpolation_fed && level.queued == 0;
+        self.interpolation_fed = true;
+        if underrun {
+            return Err(InterpolationError::Underrun(id));
+        }
+        Ok(level)
+    }
+
+    /// Waits until all queued setpoints have been reached and leaves interpolated position mode.
+    ///
+    /// # Errors
+    /// Returns an error if:
+    /// - The buffer level couldn't be read
+    /// - The device faulted or didn't complete the motion in time
+    pub async fn finish_interpolated(&mut self) -> Result<(), InterpolationError> {
+        // Wait until the buffer is empty
+        while self.interpolation_buffer().await?.queued > 0 {
+            if self.device.emergency_stopped() {
+                return Err(InterpolationError::EmergencyStopped(self.device.id));
+            }
+            self.device.controller.next_cycle().await;
+        }
+
+        // Wait until the last setpoint has been reached and disable the interpolation
+        self.device
+            .wait_for(
+                |status| status.is_set(StatusWordBit::MotionComplete),
+                MOTION_TIMEOUT,
+            )
+            .await?;
+        self.interpolation_fed = false;
+        self.device
+            .update_control_word(|control| control.without(ControlBit::Control4))
+            .map_err(|error| InterpolationError::Ethercat(self.device.id, error))?;
+        Ok(())
+    }
+}
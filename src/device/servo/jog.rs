@@ -0,0 +1,407 @@
+//! This module contains jogging with a velocity ramp.
+//!
+//! The jog velocity is written to the outputs every cycle, so the servo accelerates smoothly
+//! instead of jumping to the jog velocity. The ramp runs while the jog future is awaited, so
+//! this works both with and without a background task cycling the controller.
+
+use super::{limits::JogLimits, JogGuard, JoggingDirection, JoggingError, Servo};
+use crate::{
+    device::{Drive, WaitTimeout},
+    pdo::{self, PdoValue},
+};
+use core::{
+    future::{poll_fn, Future},
+    pin::{pin, Pin},
+    task::Poll,
+    time::Duration,
+};
+use std::time::Instant;
+
+/// The shape of the velocity ramp of a jog
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum RampShape {
+    /// The velocity increases at a constant rate
+    #[default]
+    Linear,
+
+    /// The velocity increases slowly at the start and end of the ramp, limiting the jerk
+    SCurve,
+}
+
+/// The settings of a ramped jog
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct JogOptions {
+    /// The velocity the jog starts at in increments per second
+    pub start_velocity: u32,
+
+    /// The velocity reached at the end of the ramp in increments per second
+    pub max_velocity: u32,
+
+    /// The time it takes to ramp from the start velocity to the maximum velocity
+    pub ramp_time: Duration,
+
+    /// The shape of the ramp
+    pub shape: RampShape,
+}
+
+/// Calculates the velocity along a ramp.
+/// The fraction of the ramp that has passed is clamped between 0 and 1.
+#[must_use]
+#[expect(
+    clippy::cast_possible_truncation,
+    clippy::cast_sign_loss,
+    reason = "The velocity is between the start and maximum velocity, which are both u32"
+)]
+pub fn ramp_velocity(shape: RampShape, start: u32, max: u32, fraction: f64) -> u32 {
+    let fraction = fraction.clamp(0.0, 1.0);
+    let progress = match shape {
+        RampShape::Linear => fraction,
+        RampShape::SCurve => fraction * fraction * 2.0f64.mul_add(-fraction, 3.0),
+    };
+    let start_f = f64::from(start);
+    let velocity = (f64::from(max) - start_f)
+        .mul_add(progress, start_f)
+        .round();
+    velocity as u32
+}
+
+/// Calculates the fraction of the ramp that passed after the elapsed time
+fn ramp_fraction(elapsed: Duration, ramp_time: Duration) -> f64 {
+    if ramp_time.is_zero() {
+        return 1.0;
+    }
+    elapsed.as_secs_f64() / ramp_time.as_secs_f64()
+}
+
+/// The velocity ramp of a jog, see `Servo::jog_ramped`
+#[derive(Debug, Clone, Copy)]
+struct JogRamp {
+    /// The shape of the ramp
+    shape: RampShape,
+
+    /// The velocity at the start of the ramp in increments per second
+    start: u32,
+
+    /// The velocity at the end of the ramp in increments per second
+    max: u32,
+
+    /// The time it takes to ramp from the start velocity to the maximum velocity
+    ramp_time: Duration,
+}
+
+impl JogRamp {
+    /// Calculates the velocity after ramping up for the elapsed time
+    fn velocity_at(&self, elapsed: Duration) -> u32 {
+        ramp_velocity(
+            self.shape,
+            self.start,
+            self.max,
+            ramp_fraction(elapsed, self.ramp_time),
+        )
+    }
+
+    /// Calculates the velocity while ramping back down along the same ramp, starting from the
+    /// velocity reached after ramping up for `held`
+    ///
+    /// # Returns
+    /// The velocity after ramping down for the elapsed time, `None` once the ramp down finished
+    fn ramp_down_velocity(&self, held: Duration, elapsed: Duration) -> Option<u32> {
+        held.min(self.ramp_time)
+            .checked_sub(elapsed)
+            .map(|remaining| self.velocity_at(remaining))
+    }
+}
+
+/// How a ramped jog failed, before the details that have to be read over SDO were collected
+#[derive(Debug)]
+enum RampFailure {
+    /// The ramp failed with the error
+    Error(JoggingError),
+
+    /// The drive faulted or has been emergency stopped while ramping
+    Wait(WaitTimeout),
+}
+
+/// Writes the jog velocity to the outputs
+///
+/// # Errors
+/// Returns an error if the outputs couldn't be written
+fn write_jog_velocity(drive: &mut impl Drive, velocity: u32) -> Result<(), RampFailure> {
+    drive
+        .apply_outputs(|outputs| {
+            velocity.write(&mut outputs[pdo::output::PROFILE_VELOCITY..]);
+        })
+        .map_err(|error| RampFailure::Error(JoggingError::Ethercat(drive.id(), error)))
+}
+
+/// Checks a ramping jog once for faults and the limits of the jog
+///
+/// # Errors
+/// Returns an error if the drive faulted or the limits couldn't be checked
+///
+/// # Returns
+/// The error to return once the jog has been stopped, if a limit has been reached
+fn check_ramp(
+    drive: &mut impl Drive,
+    limits: &JogLimits,
+    start: Instant,
+) -> Result<Option<JoggingError>, RampFailure> {
+    if let Some(Err(error)) = drive.check_motion(|_| false, start, Duration::MAX) {
+        return Err(RampFailure::Wait(error));
+    }
+    limits
+        .reached(drive)
+        .map_err(|error| RampFailure::Error(JoggingError::Ethercat(drive.id(), error)))
+}
+
+/// Ramps the velocity of a started jog up until `release` completes, and back down to the start
+/// velocity along the same ramp. Faults and the limits of the jog are checked every cycle, while
+/// ramping up and down.
+///
+/// # Errors
+/// Returns an error if the drive faulted, or the velocity or limits couldn't be written or read
+///
+/// # Returns
+/// The error to return once the jog has been stopped, if a limit has been reached
+async fn ramp_jog<R: Future<Output = ()>>(
+    drive: &mut impl Drive,
+    ramp: &JogRamp,
+    limits: &JogLimits,
+    mut release: Pin<&mut R>,
+) -> Result<Option<JoggingError>, RampFailure> {
+    // Ramp up while the jog is held
+    let ramp_start = drive.now();
+    loop {
+        if poll_fn(|cx| Poll::Ready(release.as_mut().poll(cx).is_ready())).await {
+            break;
+        }
+        if let Some(limit) = check_ramp(drive, limits, ramp_start)? {
+            return Ok(Some(limit));
+        }
+        let elapsed = drive.now().saturating_duration_since(ramp_start);
+        write_jog_velocity(drive, ramp.velocity_at(elapsed))?;
+        drive.next_cycle().await;
+    }
+
+    // Ramp down along the same ramp, unless a limit requires stopping immediately
+    let ramp_down = drive.now();
+    let held = ramp_down.saturating_duration_since(ramp_start);
+    loop {
+        let elapsed = drive.now().saturating_duration_since(ramp_down);
+        let Some(velocity) = ramp.ramp_down_velocity(held, elapsed) else {
+            return Ok(None);
+        };
+        if let Some(limit) = check_ramp(drive, limits, ramp_down)? {
+            return Ok(Some(limit));
+        }
+        write_jog_velocity(drive, velocity)?;
+        drive.next_cycle().await;
+    }
+}
+
+impl<const MAX_DEVICES: usize, const PDI_LENGTH: usize> Servo<'_, '_, MAX_DEVICES, PDI_LENGTH> {
+    /// Jogs the servo in the direction, ramping the velocity up until `release` completes.
+    /// The velocity is then ramped back down to the start velocity before the jog is stopped.
+    /// The jog is stopped immediately if the returned future is dropped before it completed.
+    ///
+    /// # Errors
+    /// Returns an error if:
+    /// - The maximum velocity is zero or exceeds the maximum motor speed, depending on the policy
+    /// - The device is disabled
+    /// - The servo couldn't be set to jogging mode
+    /// - The velocity couldn't be written
+    /// - The device faulted or the jog reached a software position limit
+    /// - The jog didn't stop in time
+    pub async fn jog_ramped(
+        &mut self,
+        direction: JoggingDirection,
+        options: &JogOptions,
+        release: impl Future<Output = ()>,
+    ) -> Result<(), JoggingError> {
+        let id = self.device.id;
+        if options.max_velocity == 0 {
+            return Err(JoggingError::ZeroVelocity(id));
+        }
+        let max_velocity = self.check_jog_velocity(options.max_velocity).await?;
+        let start = options.start_velocity.clamp(1, max_velocity);
+        let ramp = JogRamp {
+            shape: options.shape,
+            start,
+            max: max_velocity,
+            ramp_time: options.ramp_time,
+        };
+        self.jog(direction, Some(start)).await?;
+
+        // Ramp up and down while the jog is held, the guard stops the jog when cancelled
+        let limits = self.jog_limits();
+        let release = pin!(release);
+        let limit_reached = {
+            let guard = JogGuard { servo: self };
+            match ramp_jog(&mut guard.servo.device, &ramp, &limits, release).await {
+                Ok(limit_reached) => limit_reached,
+                Err(RampFailure::Error(error)) => return Err(error),
+                Err(RampFailure::Wait(error)) => return Err(guard.servo.jog_error(error).await),
+            }
+        };
+
+        self.jog_stop().await?;
+        limit_reached.map_or(Ok(()), Err)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    //! Tests of the velocity ramp of a jog against a simulated drive
+
+    use super::*;
+    use crate::device::{
+        servo::limits::JogSupervision,
+        simulation::{block_on, SimulatedDrive, ID},
+        ControlBit, OperationMode, OutputImage,
+    };
+    use core::task::Context;
+
+    /// A jog release that completes once it has been polled a number of times, once per cycle
+    /// of the ramp
+    struct ReleaseAfter(u32);
+
+    impl Future for ReleaseAfter {
+        type Output = ();
+
+        fn poll(mut self: Pin<&mut Self>, _cx: &mut Context<'_>) -> Poll<()> {
+            if self.0 == 0 {
+                return Poll::Ready(());
+            }
+            self.0 -= 1;
+            Poll::Pending
+        }
+    }
+
+    /// A linear ramp from 100 to 10000 increments per second in half a second
+    const LINEAR: JogRamp = JogRamp {
+        shape: RampShape::Linear,
+        start: 100,
+        max: 10_000,
+        ramp_time: Duration::from_millis(500),
+    };
+
+    /// Limits of a jog in positive direction without switches or software position limits
+    const NO_LIMITS: JogLimits = JogLimits {
+        direction: Some(JoggingDirection::Positive),
+        sto_input: None,
+        supervision: None,
+    };
+
+    /// Creates a drive jogging in positive direction
+    fn jogging_drive() -> SimulatedDrive {
+        let mut drive = SimulatedDrive::new(100);
+        drive.set_mode(OperationMode::Jog);
+        drive
+            .update_control_word(|control| control.with(ControlBit::Control4))
+            .unwrap();
+        drive
+    }
+
+    /// Runs `ramp_jog` on the drive, releasing the jog after `held` cycles
+    fn jog(
+        drive: &mut SimulatedDrive,
+        ramp: &JogRamp,
+        limits: &JogLimits,
+        held: u32,
+    ) -> Result<Option<JoggingError>, RampFailure> {
+        block_on(ramp_jog(drive, ramp, limits, pin!(ReleaseAfter(held))))
+    }
+
+    /// Returns the profile velocity sent in every cycle
+    fn sent_velocities(drive: &SimulatedDrive) -> Vec<u32> {
+        drive
+            .sent
+            .iter()
+            .map(|outputs| u32::read(&outputs[pdo::output::PROFILE_VELOCITY..]))
+            .collect()
+    }
+
+    /// Checks that the velocities rise until the jog is released and fall from there on
+    fn assert_monotone(velocities: &[u32], up: usize) {
+        assert!(velocities[..=up].windows(2).all(|pair| pair[0] <= pair[1]));
+        assert!(velocities[up..].windows(2).all(|pair| pair[0] >= pair[1]));
+    }
+
+    /// A linear jog held longer than the ramp time reaches the maximum velocity and ramps back
+    /// down to the start velocity
+    #[test]
+    fn linear_ramp_is_monotone() {
+        let mut drive = jogging_drive();
+        let result = jog(&mut drive, &LINEAR, &NO_LIMITS, 80);
+        assert!(matches!(result, Ok(None)), "{result:?}");
+
+        let velocities = sent_velocities(&drive);
+        let up = 80;
+        assert_monotone(&velocities, up);
+        assert_eq!(velocities[0], 100);
+        assert_eq!(velocities[up - 1], 10_000);
+        assert_eq!(velocities.last(), Some(&100));
+        assert!(velocities.iter().all(|&velocity| velocity <= 10_000));
+        assert!(drive.position() > 0);
+    }
+
+    /// An s-curve jog released before the end of the ramp ramps down from the velocity it
+    /// reached
+    #[test]
+    fn released_s_curve_is_monotone() {
+        let ramp = JogRamp {
+            shape: RampShape::SCurve,
+            ..LINEAR
+        };
+        let mut drive = jogging_drive();
+        let result = jog(&mut drive, &ramp, &NO_LIMITS, 20);
+        assert!(matches!(result, Ok(None)), "{result:?}");
+
+        let velocities = sent_velocities(&drive);
+        let up = 20;
+        assert_monotone(&velocities, up);
+        assert_eq!(velocities[0], 100);
+        assert!(velocities[up - 1] < 10_000);
+        assert_eq!(velocities.last(), Some(&100));
+    }
+
+    /// A fault while ramping down ends the ramp in the cycle the drive reports it
+    #[test]
+    fn fault_ends_ramp_down() {
+        let mut drive = jogging_drive();
+        drive.fault_at(25);
+        let result = jog(&mut drive, &LINEAR, &NO_LIMITS, 20);
+
+        let Err(RampFailure::Wait(WaitTimeout::Fault(ID, _))) = result else {
+            panic!("The ramp didn't end with the fault: {result:?}");
+        };
+        assert_eq!(drive.sent.len(), 25);
+    }
+
+    /// Reaching the software position limit while ramping down ends the ramp right away with
+    /// the limit
+    #[test]
+    fn limit_ends_ramp_down() {
+        let mut drive = jogging_drive();
+        let limits = JogLimits {
+            supervision: Some(JogSupervision {
+                direction: JoggingDirection::Positive,
+                stop_at: 1_500,
+                limit: 2_000,
+            }),
+            ..NO_LIMITS
+        };
+        let result = jog(&mut drive, &LINEAR, &limits, 30);
+
+        let Ok(Some(JoggingError::LimitReached { position, limit })) = result else {
+            panic!("The ramp didn't end at the limit: {result:?}");
+        };
+        assert_eq!(limit, 2_000);
+        assert!(position >= 1_500);
+        assert_eq!(position, drive.position());
+        assert!(drive.sent.len() > 30);
+        assert!(drive.sent.len() < 60);
+    }
+}
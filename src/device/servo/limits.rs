@@ -0,0 +1,636 @@
+//! This module contains the software position limits of the servo.
+//!
+//! The drive faults when it's commanded beyond it's software position limits (0x607D), so
+//! targets are checked against the limits before a move is started. The limits can also be
+//! written, for example from the configuration of an application at startup.
+//!
+//! Requested profile velocities are checked against the maximum profile velocity (0x607F) in
+//! the same way. Velocities above the maximum motor speed (0x6080) are clamped silently by the
+//! drive, so they're clamped or rejected up front depending on the `MotorSpeedPolicy`. The
+//! maximum motor speed is in motor revolutions per minute and converted to increments per second
+//! with the mechanics of the drive.
+//!
+//! Jog movements have no target, so the position is supervised while jogging instead. The jog
+//! is stopped when the servo comes within the stopping distance of the limit it's jogging to,
+//! or when it runs into the limit switch in its direction.
+
+use super::{
+    config::MotorSpeedPolicy,
+    mechanics::Mechanics,
+    switches::mapped_limit_switches,
+    units::{RawVelocity, UnitScaling},
+    JoggingDirection, JoggingError, MovementError, Servo,
+};
+use crate::{
+    device::{objects, Drive},
+    pdo,
+};
+use core::fmt::{self, Debug, Formatter};
+use ethercrab::error::Error as EthercrabError;
+
+/// The range of positions the drive is allowed to move to, in the frame of the drive
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SoftwareLimits {
+    /// The minimum position in increments
+    pub min: i32,
+
+    /// The maximum position in increments
+    pub max: i32,
+}
+
+/// Converts a speed in motor revolutions per minute to increments per second. The increments
+/// per motor revolution are the feed per shaft revolution times the shaft revolutions per motor
+/// revolution.
+///
+/// # Returns
+/// The speed in increments per second, `u32::MAX` if the mechanics contain zero or the speed
+/// doesn't fit in an `u32`, as the drive doesn't limit the speed then
+fn motor_speed_to_velocity(rpm: u32, mechanics: &Mechanics) -> u32 {
+    if mechanics.gear_numerator == 0
+        || mechanics.gear_denominator == 0
+        || mechanics.feed_constant_numerator == 0
+        || mechanics.feed_constant_denominator == 0
+    {
+        return u32::MAX;
+    }
+    let revolution = UnitScaling::new(
+        f64::from(mechanics.feed_constant_numerator) * f64::from(mechanics.gear_denominator)
+            / (f64::from(mechanics.feed_constant_denominator)
+                * f64::from(mechanics.gear_numerator)),
+        "motor revolutions",
+    );
+    RawVelocity::from_units_per_second(f64::from(rpm) / 60.0, &revolution)
+        .map_or(u32::MAX, u32::from)
+}
+
+/// Applies the motor speed policy to a requested velocity in increments per second.
+///
+/// # Returns
+/// The velocity to command, or `None` if the policy rejects the velocity
+const fn limit_motor_speed(policy: MotorSpeedPolicy, requested: u32, max: u32) -> Option<u32> {
+    if requested <= max {
+        return Some(requested);
+    }
+    match policy {
+        MotorSpeedPolicy::Clamp => Some(max),
+        MotorSpeedPolicy::Reject => None,
+    }
+}
+
+/// The position at which a running jog is stopped
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(super) struct JogSupervision {
+    /// The direction of the jog
+    pub(super) direction: JoggingDirection,
+
+    /// The position at which the jog is stopped
+    pub(super) stop_at: i32,
+
+    /// The software position limit the jog is moving to
+    pub(super) limit: i32,
+}
+
+/// The checks that stop a running jog, see `Servo::jog_limit_reached`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(super) struct JogLimits {
+    /// The direction of the running jog, `None` if no jog is running
+    pub(super) direction: Option<JoggingDirection>,
+
+    /// The digital input reporting safe torque off, see `ServoConfig::sto_input`
+    pub(super) sto_input: Option<u8>,
+
+    /// The position at which the jog is stopped, if a software position limit is set
+    pub(super) supervision: Option<JogSupervision>,
+}
+
+impl JogLimits {
+    /// Checks whether a running jog reached the position at which it should be stopped, or the
+    /// limit switch in its direction. Only mapped digital inputs are checked, as this is called
+    /// every cycle.
+    ///
+    /// # Errors
+    /// Returns an error if the position or limit switches couldn't be read
+    ///
+    /// # Returns
+    /// The error to return once the jog has been stopped, if it should be stopped
+    pub(super) fn reached(
+        &self,
+        drive: &mut impl Drive,
+    ) -> Result<Option<JoggingError>, EthercrabError> {
+        if let Some(direction) = self.direction {
+            if let Some(switches) = mapped_limit_switches(drive, self.sto_input)?
+                .filter(|switches| switches.in_direction(direction))
+            {
+                return Ok(Some(JoggingError::LimitSwitch {
+                    device: drive.id(),
+                    switches,
+                }));
+            }
+        }
+        let Some(supervision) = self.supervision else {
+            return Ok(None);
+        };
+        let position = drive.read_input(pdo::input::POSITION_ACTUAL_VALUE)?;
+        let reached = match supervision.direction {
+            JoggingDirection::Positive => position >= supervision.stop_at,
+            JoggingDirection::Negative => position <= supervision.stop_at,
+        };
+        Ok(reached.then_some(JoggingError::LimitReached {
+            position,
+            limit: supervision.limit,
+        }))
+    }
+}
+
+/// Calculates the distance needed to stop from the velocity with the deceleration.
+///
+/// # Returns
+/// The distance in increments, saturated to the range of an `i32`
+fn stopping_distance(velocity: u32, deceleration: u32) -> i32 {
+    if deceleration == 0 {
+        return 0;
+    }
+    let velocity = u64::from(velocity);
+    let distance = velocity * velocity / (2 * u64::from(deceleration));
+    i32::try_from(distance).unwrap_or(i32::MAX)
+}
+
+/// An error returned while writing the software position limits
+pub enum LimitsError {
+    /// The minimum limit isn't below the maximum limit
+    InvalidRange {
+        /// The requested minimum limit
+        min: i32,
+
+        /// The requested maximum limit
+        max: i32,
+    },
+
+    /// Communication with the drive failed
+    Ethercat(usize, EthercrabError),
+}
+
+impl Debug for LimitsError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::InvalidRange { min, max } => {
+                write!(f, "Minimum limit {min} must be below maximum limit {max}")
+            }
+            Self::Ethercat(device, error) => write!(
+                f,
+                "Writing software position limits of device {device} failed: {error:?}"
+            ),
+        }
+    }
+}
+
+impl SoftwareLimits {
+    /// Returns whether the position is within the limits
+    pub const fn contains(&self, position: i32) -> bool {
+        self.min <= position && position <= self.max
+    }
+}
+
+impl<const MAX_DEVICES: usize, const PDI_LENGTH: usize> Servo<'_, '_, MAX_DEVICES, PDI_LENGTH> {
+    /// Sets whether targets are checked against the software position limits before moving.
+    /// Enabled by default, disable it for axes where the limits are intentionally disabled.
+    pub fn set_limit_check(&mut self, check: bool) {
+        self.check_limits = check;
+    }
+
+    /// Reads the software position limits from the drive, also refreshing the limits used to
+    /// check targets.
+    ///
+    /// # Errors
+    /// Returns an error if the limits couldn't be read
+    pub async fn software_limits(&mut self) -> Result<SoftwareLimits, EthercrabError> {
+        let min = self
+            .device
+            .read_object(objects::SOFTWARE_POSITION_LIMIT_MIN)
+            .await?;
+        let max = self
+            .device
+            .read_object(objects::SOFTWARE_POSITION_LIMIT_MAX)
+            .await?;
+        let limits = SoftwareLimits { min, max };
+        self.software_limits = Some(Some(limits));
+        Ok(limits)
+    }
+
+    /// Writes the software position limits to the drive.
+    ///
+    /// # Errors
+    /// Returns an error if:
+    /// - The minimum limit isn't below the maximum limit
+    /// - The limits couldn't be written
+    pub async fn set_software_limits(&mut self, min: i32, max: i32) -> Result<(), LimitsError> {
+        if min >= max {
+            return Err(LimitsError::InvalidRange { min, max });
+        }
+
+        // Forget the previous limits, so they are read again if writing fails halfway
+        self.software_limits = None;
+        let id = self.device.id;
+        self.device
+            .write_object(objects::SOFTWARE_POSITION_LIMIT_MIN, min)
+            .await
+            .map_err(|error| LimitsError::Ethercat(id, error))?;
+        self.device
+            .write_object(objects::SOFTWARE_POSITION_LIMIT_MAX, max)
+            .await
+            .map_err(|error| LimitsError::Ethercat(id, error))?;
+        self.software_limits = Some(Some(SoftwareLimits { min, max }));
+        if self.device.controller.verbose() {
+            log::info!("Set software position limits of device {id} to {min}..={max}");
+        }
+        Ok(())
+    }
+
+    /// Reads the software position limits of the drive.
+    /// Only the first call communicates with the drive.
+    ///
+    /// # Errors
+    /// Returns an error if communication with the drive failed
+    ///
+    /// # Returns
+    /// The limits, or `None` if the drive doesn't support software position limits
+    async fn cached_software_limits(&mut self) -> Result<Option<SoftwareLimits>, EthercrabError> {
+        if let Some(limits) = self.software_limits {
+            return Ok(limits);
+        }
+
+        // Read both limits, drives without limits don't support either
+        let min = self
+            .device
+            .read_optional_object(objects::SOFTWARE_POSITION_LIMIT_MIN)
+            .await?;
+        let max = self
+            .device
+            .read_optional_object(objects::SOFTWARE_POSITION_LIMIT_MAX)
+            .await?;
+        let limits = min.zip(max).map(|(min, max)| SoftwareLimits { min, max });
+        self.software_limits = Some(limits);
+        Ok(limits)
+    }
+
+    /// Reads the maximum profile velocity of the drive (0x607F) in increments per second.
+    /// Only the first call communicates with the drive.
+    ///
+    /// # Errors
+    /// Returns an error if the maximum profile velocity couldn't be read
+    pub async fn max_profile_velocity(&mut self) -> Result<u32, EthercrabError> {
+        if let Some(max_profile_velocity) = self.max_profile_velocity {
+            return Ok(max_profile_velocity);
+        }
+        let max_profile_velocity = self
+            .device
+            .read_object(objects::MAX_PROFILE_VELOCITY)
+            .await?;
+        self.max_profile_velocity = Some(max_profile_velocity);
+        Ok(max_profile_velocity)
+    }
+
+    /// Writes the maximum profile velocity of the drive (0x607F) in increments per second.
+    ///
+    /// # Errors
+    /// Returns an error if the maximum profile velocity couldn't be written
+    pub async fn set_max_profile_velocity(
+        &mut self,
+        max_profile_velocity: u32,
+    ) -> Result<(), EthercrabError> {
+        // Forget the previous value, so it's read again if writing fails
+        self.max_profile_velocity = None;
+        self.device
+            .write_object(objects::MAX_PROFILE_VELOCITY, max_profile_velocity)
+            .await?;
+        self.max_profile_velocity = Some(max_profile_velocity);
+        Ok(())
+    }
+
+    /// Reads the maximum motor speed of the drive (0x6080) in motor revolutions per minute and
+    /// converts it to increments per second with the gear ratio and feed constant of the drive.
+    /// Only the first call communicates with the drive.
+    ///
+    /// # Errors
+    /// Returns an error if the maximum motor speed or the mechanics couldn't be read
+    pub async fn max_motor_speed(&mut self) -> Result<u32, EthercrabError> {
+        if let Some(max_motor_speed) = self.max_motor_speed {
+            return Ok(max_motor_speed);
+        }
+        let rpm = self.device.read_object(objects::MAX_MOTOR_SPEED).await?;
+        let mechanics = self.mechanics().await?;
+        let max_motor_speed = motor_speed_to_velocity(rpm, &mechanics);
+        self.max_motor_speed = Some(max_motor_speed);
+        Ok(max_motor_speed)
+    }
+
+    /// Applies the motor speed policy to a velocity above the maximum motor speed.
+    ///
+    /// # Returns
+    /// The velocity to command, or `None` if the policy rejects the velocity
+    fn apply_motor_speed_policy(&self, requested: u32, max: u32) -> Option<u32> {
+        let velocity = limit_motor_speed(self.config.motor_speed_policy, requested, max)?;
+        if velocity != requested {
+            log::warn!(
+                "Velocity {requested} of device {} exceeds the maximum motor speed, clamped to {max}",
+                self.device.id
+            );
+        }
+        Some(velocity)
+    }
+
+    /// Checks the requested profile velocity against the maximum profile velocity and the
+    /// maximum motor speed.
+    ///
+    /// # Errors
+    /// Returns an error if a maximum couldn't be read or the velocity exceeds it
+    ///
+    /// # Returns
+    /// The velocity to command, clamped to the maximum motor speed if the policy allows it
+    pub(super) async fn check_velocity(&mut self, requested: u32) -> Result<u32, MovementError> {
+        let max = self
+            .max_profile_velocity()
+            .await
+            .map_err(MovementError::Ethercat)?;
+        if requested > max {
+            return Err(MovementError::VelocityTooHigh { requested, max });
+        }
+        let max = self
+            .max_motor_speed()
+            .await
+            .map_err(MovementError::Ethercat)?;
+        self.apply_motor_speed_policy(requested, max).ok_or(
+            MovementError::VelocityAboveMotorLimit {
+                device: self.device.id,
+                requested,
+                max,
+            },
+        )
+    }
+
+    /// Checks the requested jog velocity against the maximum motor speed.
+    ///
+    /// # Errors
+    /// Returns an error if the maximum couldn't be read or the policy rejects the velocity
+    ///
+    /// # Returns
+    /// The velocity to command, clamped to the maximum motor speed if the policy allows it
+    pub(super) async fn check_jog_velocity(&mut self, requested: u32) -> Result<u32, JoggingError> {
+        let id = self.device.id;
+        let max = self
+            .max_motor_speed()
+            .await
+            .map_err(|error| JoggingError::Ethercat(id, error))?;
+        self.apply_motor_speed_policy(requested, max)
+            .ok_or(JoggingError::VelocityAboveMotorLimit {
+                device: id,
+                requested,
+                max,
+            })
+    }
+
+    /// Checks the absolute target against the software position limits, unless disabled.
+    /// The target is converted to the frame of the drive using the polarity first.
+    ///
+    /// # Errors
+    /// Returns an error if the limits couldn't be read or the target is outside of them
+    pub(super) async fn check_limits(&mut self, target: i32) -> Result<(), MovementError> {
+        if !self.check_limits {
+            return Ok(());
+        }
+        let Some(limits) = self
+            .cached_software_limits()
+            .await
+            .map_err(MovementError::Ethercat)?
+        else {
+            return Ok(());
+        };
+
+        // The limits are in the frame of the drive, report them in the frame of the target
+        let polarity = self
+            .cached_polarity()
+            .await
+            .map_err(MovementError::Ethercat)?;
+        if limits.contains(polarity.convert_position(target)) {
+            return Ok(());
+        }
+        let (min, max) = if polarity.invert_position {
+            (limits.max.saturating_neg(), limits.min.saturating_neg())
+        } else {
+            (limits.min, limits.max)
+        };
+        Err(MovementError::TargetOutOfLimits { target, min, max })
+    }
+
+    /// Sets the distance before a software position limit at which a jog is stopped.
+    /// Without it, the distance is calculated from the jog velocity and quick stop deceleration.
+    pub fn set_jog_stop_margin(&mut self, margin: u32) {
+        self.jog_stop_margin = Some(margin);
+    }
+
+    /// Reads the software position limits in the frame of the positions reported by the servo.
+    ///
+    /// # Errors
+    /// Returns an error if the limits or the polarity couldn't be read
+    ///
+    /// # Returns
+    /// The limits, or `None` if limit checks are disabled or the drive doesn't support limits
+    pub(super) async fn user_software_limits(
+        &mut self,
+    ) -> Result<Option<SoftwareLimits>, EthercrabError> {
+        if !self.check_limits {
+            return Ok(None);
+        }
+        let Some(limits) = self.cached_software_limits().await? else {
+            return Ok(None);
+        };
+        let polarity = self.cached_polarity().await?;
+        Ok(Some(if polarity.invert_position {
+            SoftwareLimits {
+                min: limits.max.saturating_neg(),
+                max: limits.min.saturating_neg(),
+            }
+        } else {
+            limits
+        }))
+    }
+
+    /// Prepares the supervision of a jog in the direction, at the velocity if one is passed.
+    /// Without a velocity, the profile velocity in the outputs is used.
+    ///
+    /// # Errors
+    /// Returns an error if the limits, velocity or deceleration couldn't be read
+    pub(super) async fn start_jog_supervision(
+        &mut self,
+        direction: JoggingDirection,
+        velocity: Option<u32>,
+    ) -> Result<(), JoggingError> {
+        let id = self.device.id;
+        self.jog_supervision = None;
+        let Some(limits) = self
+            .user_software_limits()
+            .await
+            .map_err(|error| JoggingError::Ethercat(id, error))?
+        else {
+            return Ok(());
+        };
+
+        // Stop the jog early enough to come to a stop before the limit
+        let margin = if let Some(margin) = self.jog_stop_margin {
+            i32::try_from(margin).unwrap_or(i32::MAX)
+        } else {
+            let velocity = match velocity {
+                Some(velocity) => velocity,
+                None => self
+                    .device
+                    .read_output(pdo::output::PROFILE_VELOCITY)
+                    .map_err(|error| JoggingError::Ethercat(id, error))?,
+            };
+            let deceleration = self
+                .device
+                .read_optional_object(objects::QUICK_STOP_DECELERATION)
+                .await
+                .map_err(|error| JoggingError::Ethercat(id, error))?
+                .unwrap_or(0);
+            stopping_distance(velocity, deceleration)
+        };
+        self.jog_supervision = Some(match direction {
+            JoggingDirection::Positive => JogSupervision {
+                direction,
+                stop_at: limits.max.saturating_sub(margin),
+                limit: limits.max,
+            },
+            JoggingDirection::Negative => JogSupervision {
+                direction,
+                stop_at: limits.min.saturating_add(margin),
+                limit: limits.min,
+            },
+        });
+        Ok(())
+    }
+
+    /// Returns the checks that stop the running jog
+    pub(super) const fn jog_limits(&self) -> JogLimits {
+        JogLimits {
+            direction: self.jog_direction,
+            sto_input: self.config.sto_input,
+            supervision: self.jog_supervision,
+        }
+    }
+
+    /// Checks whether a running jog reached the position at which it should be stopped, or the
+    /// limit switch in its direction, see `JogLimits::reached`
+    ///
+    /// # Errors
+    /// Returns an error if the position or limit switches couldn't be read
+    ///
+    /// # Returns
+    /// The error to return once the jog has been stopped, if it should be stopped
+    pub(super) fn jog_limit_reached(&mut self) -> Result<Option<JoggingError>, EthercrabError> {
+        self.jog_limits().reached(&mut self.device)
+    }
+
+    /// Checks the position of a running jog against the software position limits and stops the
+    /// jog when it reached the stopping distance of the limit or the limit switch in its
+    /// direction.
+    /// Should be called every cycle while jogging with `jog_positive` or `jog_negative`.
+    ///
+    /// # Errors
+    /// Returns an error if:
+    /// - The position couldn't be read
+    /// - The limit or limit switch has been reached and the jog was stopped
+    /// - The jog didn't stop in time
+    pub async fn supervise_jog(&mut self) -> Result<(), JoggingError> {
+        let reached = self
+            .jog_limit_reached()
+            .map_err(|error| JoggingError::Ethercat(self.device.id, error))?;
+        let Some(error) = reached else {
+            return Ok(());
+        };
+        self.jog_stop().await?;
+        Err(error)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    //! Tests of the conversion of the maximum motor speed and the motor speed policies
+
+    use super::*;
+
+    /// A drive without gear with 10000 increments per motor revolution
+    const DIRECT: Mechanics = Mechanics {
+        gear_numerator: 1,
+        gear_denominator: 1,
+        feed_constant_numerator: 10_000,
+        feed_constant_denominator: 1,
+    };
+
+    /// The maximum motor speed is converted from motor revolutions per minute
+    #[test]
+    fn motor_speed_in_increments() {
+        assert_eq!(motor_speed_to_velocity(3_000, &DIRECT), 500_000);
+
+        // A 5:1 gear needs 5 motor revolutions per shaft revolution
+        let geared = Mechanics {
+            gear_numerator: 5,
+            ..DIRECT
+        };
+        assert_eq!(motor_speed_to_velocity(3_000, &geared), 100_000);
+
+        // A feed of 1/3 increment per shaft revolution is rounded
+        let fractional = Mechanics {
+            feed_constant_numerator: 1,
+            feed_constant_denominator: 3,
+            ..DIRECT
+        };
+        assert_eq!(motor_speed_to_velocity(100, &fractional), 1);
+    }
+
+    /// Speeds that can't be limited don't limit the velocity
+    #[test]
+    fn motor_speed_unlimited() {
+        let zero = Mechanics {
+            gear_denominator: 0,
+            ..DIRECT
+        };
+        assert_eq!(motor_speed_to_velocity(3_000, &zero), u32::MAX);
+        let fine = Mechanics {
+            feed_constant_numerator: u32::MAX,
+            ..DIRECT
+        };
+        assert_eq!(motor_speed_to_velocity(u32::MAX, &fine), u32::MAX);
+    }
+
+    /// The clamp policy commands the maximum motor speed instead
+    #[test]
+    fn clamp_policy() {
+        let max = motor_speed_to_velocity(3_000, &DIRECT);
+        assert_eq!(
+            limit_motor_speed(MotorSpeedPolicy::Clamp, 400_000, max),
+            Some(400_000)
+        );
+        assert_eq!(
+            limit_motor_speed(MotorSpeedPolicy::Clamp, 500_000, max),
+            Some(500_000)
+        );
+        assert_eq!(
+            limit_motor_speed(MotorSpeedPolicy::Clamp, 500_001, max),
+            Some(500_000)
+        );
+    }
+
+    /// The reject policy refuses velocities above the maximum motor speed
+    #[test]
+    fn reject_policy() {
+        let max = motor_speed_to_velocity(3_000, &DIRECT);
+        assert_eq!(
+            limit_motor_speed(MotorSpeedPolicy::Reject, 500_000, max),
+            Some(500_000)
+        );
+        assert_eq!(
+            limit_motor_speed(MotorSpeedPolicy::Reject, 500_001, max),
+            None
+        );
+    }
+}
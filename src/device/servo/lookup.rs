@@ -0,0 +1,88 @@
+//! This module contains the creation of servos by address instead of device number.
+//!
+//! Device numbers follow the order in which the devices are wired, so they change when the bus
+//! is re-ordered. Alias addresses are stored in the drive and stay the same.
+
+use super::Servo;
+use crate::{
+    controller::{Controller, DeviceQuery, FindDeviceError},
+    device::EnableError,
+};
+use core::fmt::{self, Debug, Formatter};
+
+/// An error returned while creating a servo by address
+pub enum LookupError {
+    /// No device or multiple devices have the address
+    Find(FindDeviceError),
+
+    /// The device couldn't be enabled
+    Enable(EnableError),
+}
+
+impl Debug for LookupError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Find(error) => write!(f, "{error:?}"),
+            Self::Enable(error) => write!(f, "{error:?}"),
+        }
+    }
+}
+
+impl From<FindDeviceError> for LookupError {
+    fn from(value: FindDeviceError) -> Self {
+        Self::Find(value)
+    }
+}
+
+impl From<EnableError> for LookupError {
+    fn from(value: EnableError) -> Self {
+        Self::Enable(value)
+    }
+}
+
+impl<'device, 'controller: 'device, const MAX_DEVICES: usize, const PDI_LENGTH: usize>
+    Servo<'device, 'controller, MAX_DEVICES, PDI_LENGTH>
+{
+    /// Creates a new servo for the device matching the query, see `Servo::new`.
+    ///
+    /// # Errors
+    /// Returns an error if:
+    /// - No device or multiple devices match the query
+    /// - The device failed to reset or enable
+    pub async fn new_by_query(
+        controller: &'controller Controller<'_, MAX_DEVICES, PDI_LENGTH>,
+        query: DeviceQuery,
+    ) -> Result<Self, LookupError> {
+        let device_number = controller.find_device(query)?;
+        if controller.verbose() {
+            log::info!("Found device {device_number} matching {query:?}");
+        }
+        Ok(Self::new(controller, device_number).await?)
+    }
+
+    /// Creates a new servo for the device with the alias address, see `Servo::new`.
+    ///
+    /// # Errors
+    /// Returns an error if:
+    /// - No device or multiple devices have the alias address
+    /// - The device failed to reset or enable
+    pub async fn new_by_alias(
+        controller: &'controller Controller<'_, MAX_DEVICES, PDI_LENGTH>,
+        alias: u16,
+    ) -> Result<Self, LookupError> {
+        Self::new_by_query(controller, DeviceQuery::Alias(alias)).await
+    }
+
+    /// Creates a new servo for the device with the configured address, see `Servo::new`.
+    ///
+    /// # Errors
+    /// Returns an error if:
+    /// - No device or multiple devices have the configured address
+    /// - The device failed to reset or enable
+    pub async fn new_by_configured_address(
+        controller: &'controller Controller<'_, MAX_DEVICES, PDI_LENGTH>,
+        address: u16,
+    ) -> Result<Self, LookupError> {
+        Self::new_by_query(controller, DeviceQuery::ConfiguredAddress(address)).await
+    }
+}
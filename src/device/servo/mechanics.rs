@@ -0,0 +1,182 @@
+//! This module contains the mechanical scaling of the servo.
+//!
+//! The gear ratio (0x6091) and feed constant (0x6092) convert between motor revolutions and the
+//! position units of the drive. Most drives only accept changes while they're not operation
+//! enabled, so the scaling should be configured before enabling the drive.
+
+use super::Servo;
+use crate::device::{
+    objects::{self, Object},
+    Cia402State,
+};
+use core::fmt::{self, Debug, Formatter};
+use ethercrab::error::Error as EthercrabError;
+
+/// The gear ratio and feed constant of the drive
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Mechanics {
+    /// The number of motor revolutions of the gear ratio
+    pub gear_numerator: u32,
+
+    /// The number of shaft revolutions of the gear ratio
+    pub gear_denominator: u32,
+
+    /// The feed in position units of the feed constant
+    pub feed_constant_numerator: u32,
+
+    /// The number of shaft revolutions of the feed constant
+    pub feed_constant_denominator: u32,
+}
+
+/// An error returned while writing the mechanical scaling
+pub enum MechanicsError {
+    /// The drive is operation enabled, the scaling can only be changed while it's disabled
+    DriveEnabled(usize),
+
+    /// A numerator or denominator is zero
+    Zero(usize),
+
+    /// The drive reports a different value than was written to the object
+    VerificationFailed {
+        /// The device number
+        device: usize,
+
+        /// The object that was written
+        object: Object,
+
+        /// The value that was written
+        written: u32,
+
+        /// The value that was read back
+        read: u32,
+    },
+
+    /// Communication with the drive failed
+    Ethercat(usize, EthercrabError),
+}
+
+impl Debug for MechanicsError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::DriveEnabled(device) => write!(
+                f,
+                "Device {device} is operation enabled, disable it before changing the scaling"
+            ),
+            Self::Zero(device) => {
+                write!(f, "The scaling of device {device} can't contain zero")
+            }
+            Self::VerificationFailed {
+                device,
+                object,
+                written,
+                read,
+            } => write!(
+                f,
+                "Device {device} reports {read} instead of {written} for object {:#06x}:{}",
+                object.index, object.sub_index
+            ),
+            Self::Ethercat(device, error) => {
+                write!(
+                    f,
+                    "Configuring the scaling of device {device} failed: {error:?}"
+                )
+            }
+        }
+    }
+}
+
+impl Mechanics {
+    /// Returns the objects and the values to write to them
+    const fn objects(&self) -> [(Object, u32); 4] {
+        [
+            (objects::GEAR_RATIO_MOTOR_REVOLUTIONS, self.gear_numerator),
+            (objects::GEAR_RATIO_SHAFT_REVOLUTIONS, self.gear_denominator),
+            (objects::FEED_CONSTANT_FEED, self.feed_constant_numerator),
+            (
+                objects::FEED_CONSTANT_SHAFT_REVOLUTIONS,
+                self.feed_constant_denominator,
+            ),
+        ]
+    }
+}
+
+impl<const MAX_DEVICES: usize, const PDI_LENGTH: usize> Servo<'_, '_, MAX_DEVICES, PDI_LENGTH> {
+    /// Writes the gear ratio and feed constant to the drive and reads them back to verify them.
+    /// The values the servo cached in position units are read again on next use.
+    ///
+    /// # Errors
+    /// Returns an error if:
+    /// - The drive is operation enabled
+    /// - A numerator or denominator is zero
+    /// - A value couldn't be written or read back
+    /// - The drive reports a different value than was written
+    pub async fn set_mechanics(&mut self, mechanics: Mechanics) -> Result<(), MechanicsError> {
+        let id = self.device.id;
+        let status = self
+            .device
+            .status_word()
+            .map_err(|error| MechanicsError::Ethercat(id, error))?;
+        if status.state() == Cia402State::OperationEnabled {
+            return Err(MechanicsError::DriveEnabled(id));
+        }
+        let objects = mechanics.objects();
+        if objects.iter().any(|&(_, value)| value == 0) {
+            return Err(MechanicsError::Zero(id));
+        }
+
+        // The cached values are in the old position units
+        self.software_limits = None;
+        self.max_profile_velocity = None;
+        self.max_motor_speed = None;
+        self.profile_acceleration = None;
+        self.profile_deceleration = None;
+
+        // Write every value and read it back
+        for (object, written) in objects {
+            self.device
+                .write_object(object, written)
+                .await
+                .map_err(|error| MechanicsError::Ethercat(id, error))?;
+            let read = self
+                .device
+                .read_object(object)
+                .await
+                .map_err(|error| MechanicsError::Ethercat(id, error))?;
+            if read != written {
+                return Err(MechanicsError::VerificationFailed {
+                    device: id,
+                    object,
+                    written,
+                    read,
+                });
+            }
+        }
+        if self.device.controller.verbose() {
+            log::info!("Set mechanics of device {id} to {mechanics:?}");
+        }
+        Ok(())
+    }
+
+    /// Reads the gear ratio and feed constant from the drive.
+    ///
+    /// # Errors
+    /// Returns an error if a value couldn't be read
+    pub async fn mechanics(&mut self) -> Result<Mechanics, EthercrabError> {
+        Ok(Mechanics {
+            gear_numerator: self
+                .device
+                .read_object(objects::GEAR_RATIO_MOTOR_REVOLUTIONS)
+                .await?,
+            gear_denominator: self
+                .device
+                .read_object(objects::GEAR_RATIO_SHAFT_REVOLUTIONS)
+                .await?,
+            feed_constant_numerator: self.device.read_object(objects::FEED_CONSTANT_FEED).await?,
+            feed_constant_denominator: self
+                .device
+                .read_object(objects::FEED_CONSTANT_SHAFT_REVOLUTIONS)
+                .await?,
+        })
+    }
+}
@@ -0,0 +1,253 @@
+//! This module contains the positioning of endless rotary axes.
+//!
+//! Positions of a modulo axis wrap at the configured range, like a turntable wrapping at 360°.
+//! Moves are executed as relative profile position moves, so the drive itself doesn't need to
+//! support a modulo axis.
+
+use super::{motion::MoveOptions, MovementError, MovementMode, Servo};
+use core::fmt::{self, Debug, Formatter};
+use ethercrab::error::Error as EthercrabError;
+
+/// The direction in which a modulo axis moves to its target
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum ModuloDirection {
+    /// Take the shortest path, moving in positive direction if both paths are equally long
+    #[default]
+    Shortest,
+
+    /// Always move in positive direction
+    Positive,
+
+    /// Always move in negative direction
+    Negative,
+}
+
+/// An error returned while positioning a modulo axis
+pub enum ModuloError {
+    /// No modulo range was configured for the device
+    NotConfigured(usize),
+
+    /// The modulo range isn't positive
+    InvalidRange(usize, i32),
+
+    /// The position couldn't be read
+    Ethercat(usize, EthercrabError),
+
+    /// The movement failed
+    Movement(MovementError),
+}
+
+impl Debug for ModuloError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::NotConfigured(device) => {
+                write!(f, "No modulo range was configured for device {device}")
+            }
+            Self::InvalidRange(device, range) => write!(
+                f,
+                "The modulo range of device {device} must be positive, not {range}"
+            ),
+            Self::Ethercat(device, error) => write!(
+                f,
+                "Reading the position of device {device} failed: {error:?}"
+            ),
+            Self::Movement(error) => write!(f, "{error:?}"),
+        }
+    }
+}
+
+impl From<MovementError> for ModuloError {
+    fn from(value: MovementError) -> Self {
+        Self::Movement(value)
+    }
+}
+
+/// Wraps a position into the range `0..range`.
+/// The range must be positive.
+#[must_use]
+pub const fn wrap_position(position: i32, range: i32) -> i32 {
+    position.rem_euclid(range)
+}
+
+/// Calculates the relative move from the current position to the target on a modulo axis.
+/// Both positions are wrapped first, so the distance is always smaller than the range.
+/// The range must be positive.
+#[must_use]
+pub const fn modulo_distance(
+    current: i32,
+    target: i32,
+    range: i32,
+    direction: ModuloDirection,
+) -> i32 {
+    // Both wrapped positions are in 0..range, so the difference is in -range..range
+    let distance = wrap_position(target, range) - wrap_position(current, range);
+    match direction {
+        ModuloDirection::Positive if distance < 0 => distance + range,
+        ModuloDirection::Negative if distance > 0 => distance - range,
+        ModuloDirection::Shortest if distance.saturating_mul(2) > range => distance - range,
+        ModuloDirection::Shortest if distance.saturating_mul(2) <= -range => distance + range,
+        _ => distance,
+    }
+}
+
+impl<const MAX_DEVICES: usize, const PDI_LENGTH: usize> Servo<'_, '_, MAX_DEVICES, PDI_LENGTH> {
+    /// Configures the servo as a modulo axis, wrapping positions at the range in increments.
+    ///
+    /// # Errors
+    /// Returns an error if the range isn't positive
+    pub fn set_modulo_range(&mut self, range: i32) -> Result<(), ModuloError> {
+        if range <= 0 {
+            return Err(ModuloError::InvalidRange(self.device.id, range));
+        }
+        self.modulo_range = Some(range);
+        Ok(())
+    }
+
+    /// Returns the modulo range in increments, if configured
+    #[must_use]
+    pub const fn modulo_range(&self) -> Option<i32> {
+        self.modulo_range
+    }
+
+    /// Returns the configured modulo range
+    ///
+    /// # Errors
+    /// Returns an error if no modulo range was configured
+    const fn configured_modulo_range(&self) -> Result<i32, ModuloError> {
+        match self.modulo_range {
+            Some(range) => Ok(range),
+            None => Err(ModuloError::NotConfigured(self.device.id)),
+        }
+    }
+
+    /// Reads the actual position wrapped into the modulo range.
+    ///
+    /// # Errors
+    /// Returns an error if no modulo range was configured or the position couldn't be read
+    pub fn position_modulo(&mut self) -> Result<i32, ModuloError> {
+        let range = self.configured_modulo_range()?;
+        let position = self
+            .get_position()
+            .map_err(|error| ModuloError::Ethercat(self.device.id, error))?;
+        Ok(wrap_position(position, range))
+    }
+
+    /// Moves to a position within the modulo range in the requested direction and waits until
+    /// the target is reached.
+    ///
+    /// # Errors
+    /// Returns an error if:
+    /// - No modulo range was configured
+    /// - The position couldn't be read
+    /// - The movement failed
+    pub async fn move_to_modulo(
+        &mut self,
+        target: i32,
+        direction: ModuloDirection,
+    ) -> Result<(), ModuloError> {
+        let range = self.configured_modulo_range()?;
+        let position = self
+            .get_position()
+            .map_err(|error| ModuloError::Ethercat(self.device.id, error))?;
+
+        // Move the calculated distance relative to the current position
+        let distance = modulo_distance(position, target, range, direction);
+        if self.device.controller.verbose() {
+            log::info!(
+                "Moving device {} to modulo position {target} by {distance}",
+                self.device.id
+            );
+        }
+        if distance == 0 {
+            return Ok(());
+        }
+        let options = MoveOptions::new().with_mode(MovementMode::RelativeToActual);
+        self.move_with(distance, &options).await?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    //! Tests of the wrapping and the distances of a modulo axis
+
+    use super::*;
+
+    /// A turntable in tenths of a degree
+    const RANGE: i32 = 3600;
+
+    /// Positions are wrapped into the range, including negative positions
+    #[test]
+    fn wraps_positions() {
+        assert_eq!(wrap_position(0, RANGE), 0);
+        assert_eq!(wrap_position(3599, RANGE), 3599);
+        assert_eq!(wrap_position(3600, RANGE), 0);
+        assert_eq!(wrap_position(7201, RANGE), 1);
+        assert_eq!(wrap_position(-1, RANGE), 3599);
+        assert_eq!(wrap_position(-3600, RANGE), 0);
+        assert_eq!(wrap_position(-3601, RANGE), 3599);
+        assert_eq!(wrap_position(i32::MIN, RANGE), i32::MIN.rem_euclid(RANGE));
+    }
+
+    /// The shortest path crosses the boundary between the end and the start of the range
+    #[test]
+    fn shortest_across_boundary() {
+        let shortest = ModuloDirection::Shortest;
+        assert_eq!(modulo_distance(3590, 10, RANGE, shortest), 20);
+        assert_eq!(modulo_distance(10, 3590, RANGE, shortest), -20);
+        assert_eq!(modulo_distance(100, 200, RANGE, shortest), 100);
+        assert_eq!(modulo_distance(200, 100, RANGE, shortest), -100);
+
+        // Both paths are equally long, so it moves in positive direction
+        assert_eq!(modulo_distance(0, 1800, RANGE, shortest), 1800);
+        assert_eq!(modulo_distance(1800, 0, RANGE, shortest), 1800);
+    }
+
+    /// A fixed direction takes the long way around if needed
+    #[test]
+    fn fixed_direction() {
+        assert_eq!(
+            modulo_distance(10, 3590, RANGE, ModuloDirection::Positive),
+            3580
+        );
+        assert_eq!(
+            modulo_distance(3590, 10, RANGE, ModuloDirection::Positive),
+            20
+        );
+        assert_eq!(
+            modulo_distance(3590, 10, RANGE, ModuloDirection::Negative),
+            -3580
+        );
+        assert_eq!(
+            modulo_distance(10, 3590, RANGE, ModuloDirection::Negative),
+            -20
+        );
+    }
+
+    /// Negative positions and positions of other revolutions are wrapped before the distance
+    /// is calculated
+    #[test]
+    fn negative_inputs() {
+        let shortest = ModuloDirection::Shortest;
+        assert_eq!(modulo_distance(-10, 3610, RANGE, shortest), 20);
+        assert_eq!(modulo_distance(7210, -3610, RANGE, shortest), -20);
+        assert_eq!(
+            modulo_distance(-10, 10, RANGE, ModuloDirection::Negative),
+            -3580
+        );
+        assert_eq!(
+            modulo_distance(-3590, -10, RANGE, ModuloDirection::Positive),
+            3580
+        );
+
+        // The same position in another revolution doesn't move in any direction
+        for direction in [
+            ModuloDirection::Shortest,
+            ModuloDirection::Positive,
+            ModuloDirection::Negative,
+        ] {
+            assert_eq!(modulo_distance(-3600, 7200, RANGE, direction), 0);
+        }
+    }
+}
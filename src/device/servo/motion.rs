@@ -0,0 +1,1146 @@
+//! This module contains non-blocking positioning moves.
+//!
+//! `Servo::start_move` starts a move and returns a `MotionHandle`, which can be awaited while the
+//! caller does other work, polled with `MotionHandle::is_done`, followed with
+//! `MotionHandle::progress`, paused or aborted.
+
+use super::{
+    events::MotionEventKind, handshake, retry::RetryPolicy, status::ServoStatus,
+    units::RawVelocity, MovementError, MovementMode, Servo, MOTION_TIMEOUT, SETPOINT_TIMEOUT,
+};
+use crate::device::WaitTimeout;
+use crate::{
+    device::{ControlBit, ControlWord, OperationMode, StatusWordBit},
+    pdo::{self, PdoValue},
+};
+use core::{
+    future::Future,
+    pin::Pin,
+    task::{ready, Context, Poll},
+    time::Duration,
+};
+use ethercrab::error::Error as EthercrabError;
+use std::time::Instant;
+
+/// The settings of a positioning move.
+/// Settings that are `None` use the defaults of the axis, see `ServoConfig`, or keep the value
+/// currently configured in the drive if the axis has no default.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MoveOptions {
+    /// Whether the target is absolute or relative, see `MovementMode`
+    pub mode: MovementMode,
+
+    /// The profile velocity in increments per second
+    pub velocity: Option<u32>,
+
+    /// The profile acceleration in increments per second squared
+    pub acceleration: Option<u32>,
+
+    /// The profile deceleration in increments per second squared
+    pub deceleration: Option<u32>,
+
+    /// The maximum time the move may take, 60 seconds if `None`
+    pub timeout: Option<Duration>,
+
+    /// Whether to replace a running move immediately instead of after it's target is reached
+    pub change_immediately: bool,
+
+    /// Whether to allow absolute moves while the device isn't homed
+    pub allow_unreferenced: bool,
+
+    /// The distance from the target the actual position has to be within before the move is
+    /// complete, in addition to the motion complete bit. Only the drive decides if `None`.
+    pub in_position_tolerance: Option<u32>,
+
+    /// When to repeat the move after a fault, see `RetryPolicy`. Moves aren't retried if `None`.
+    pub retry: Option<RetryPolicy>,
+}
+
+impl MoveOptions {
+    /// Creates the options of an absolute move with the settings of the drive
+    pub const fn new() -> Self {
+        Self {
+            mode: MovementMode::Absolute,
+            velocity: None,
+            acceleration: None,
+            deceleration: None,
+            timeout: None,
+            change_immediately: false,
+            allow_unreferenced: false,
+            in_position_tolerance: None,
+            retry: None,
+        }
+    }
+
+    /// Sets whether the target is absolute or relative, see `MovementMode`
+    #[must_use]
+    pub const fn with_mode(mut self, mode: MovementMode) -> Self {
+        self.mode = mode;
+        self
+    }
+
+    /// Sets the profile velocity in increments per second, see `RawVelocity` for conversions
+    #[must_use]
+    pub fn with_velocity(mut self, velocity: impl Into<RawVelocity>) -> Self {
+        self.velocity = Some(velocity.into().0);
+        self
+    }
+
+    /// Sets the profile acceleration in increments per second squared
+    #[must_use]
+    pub const fn with_acceleration(mut self, acceleration: u32) -> Self {
+        self.acceleration = Some(acceleration);
+        self
+    }
+
+    /// Sets the profile deceleration in increments per second squared
+    #[must_use]
+    pub const fn with_deceleration(mut self, deceleration: u32) -> Self {
+        self.deceleration = Some(deceleration);
+        self
+    }
+
+    /// Sets the maximum time the move may take
+    #[must_use]
+    pub const fn with_timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = Some(timeout);
+        self
+    }
+
+    /// Sets whether to replace a running move immediately
+    #[must_use]
+    pub const fn with_change_immediately(mut self, change_immediately: bool) -> Self {
+        self.change_immediately = change_immediately;
+        self
+    }
+
+    /// Sets whether to allow absolute moves while the device isn't homed
+    #[must_use]
+    pub const fn with_allow_unreferenced(mut self, allow_unreferenced: bool) -> Self {
+        self.allow_unreferenced = allow_unreferenced;
+        self
+    }
+
+    /// Sets the distance from the target the actual position has to be within before the move is
+    /// complete, see `Servo::in_position`
+    #[must_use]
+    pub const fn with_in_position_tolerance(mut self, tolerance: u32) -> Self {
+        self.in_position_tolerance = Some(tolerance);
+        self
+    }
+
+    /// Sets when to repeat the move after a fault, only used by `Servo::move_with`
+    #[must_use]
+    pub fn with_retry(mut self, retry: RetryPolicy) -> Self {
+        self.retry = Some(retry);
+        self
+    }
+}
+
+impl Default for MoveOptions {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// The maximum time the drive may take to continue a paused move
+const RESUME_TIMEOUT: Duration = Duration::from_secs(1);
+
+/// A setpoint for the set-point handshake of the profile position mode
+pub(super) struct Setpoint {
+    /// The target position in increments
+    pub position: i32,
+
+    /// The profile velocity, the current profile velocity is kept if `None`
+    pub velocity: Option<u32>,
+
+    /// Whether the position is absolute or relative
+    pub mode: MovementMode,
+
+    /// Whether to replace the running setpoint immediately instead of after reaching it
+    pub change_immediately: bool,
+}
+
+impl Setpoint {
+    /// Writes the setpoint to the output process image with the new setpoint bit and the halt
+    /// bit cleared, so setting the new setpoint bit starts it
+    pub(super) fn write(&self, outputs: &mut [u8]) {
+        self.position
+            .write(&mut outputs[pdo::output::TARGET_POSITION..]);
+        if let Some(velocity) = self.velocity {
+            velocity.write(&mut outputs[pdo::output::PROFILE_VELOCITY..]);
+        }
+
+        let control_word = &mut outputs[pdo::output::CONTROL_WORD..];
+        let mut control = ControlWord::new(u16::read(control_word))
+            .without_control()
+            .without(ControlBit::Halt);
+        if self.mode.drive_relative() {
+            control = control.with(ControlBit::Control6);
+        }
+        if self.change_immediately {
+            control = control.with(ControlBit::Control5);
+        }
+        control.raw().write(control_word);
+    }
+}
+
+/// A move whose setpoint has been written with the new setpoint bit cleared, so it can be
+/// started in the same cycle as moves of other servos, see `Servo::load_move`
+pub(super) struct LoadedMove {
+    /// The target position of the move, as requested
+    target: i32,
+
+    /// Whether the target is absolute or relative
+    mode: MovementMode,
+
+    /// The position of the servo when the move was loaded
+    start_position: i32,
+
+    /// The absolute position the servo is moving to
+    end_position: i32,
+
+    /// The moment the move was requested
+    started: Instant,
+
+    /// The maximum time the move may take
+    timeout: Duration,
+
+    /// The distance from the end position the servo has to be within before the move is complete
+    in_position_tolerance: Option<u32>,
+}
+
+/// A cycle of the controller the handle is waiting on
+type CycleFuture<'device> = Pin<Box<dyn Future<Output = ()> + Send + 'device>>;
+
+/// A positioning move that has been acknowledged by the drive, but may not have completed yet.
+///
+/// Awaiting the handle waits until the motion is complete.
+/// The servo is borrowed until the handle is dropped, dropping it doesn't stop the motion.
+pub struct MotionHandle<
+    'servo,
+    'device,
+    'controller: 'device,
+    const MAX_DEVICES: usize,
+    const PDI_LENGTH: usize,
+> {
+    /// The servo performing the move
+    servo: &'servo mut Servo<'device, 'controller, MAX_DEVICES, PDI_LENGTH>,
+
+    /// The target position of the move, as requested
+    target: i32,
+
+    /// The position of the servo when the move was started
+    start_position: i32,
+
+    /// The absolute position the servo is moving to
+    end_position: i32,
+
+    /// The moment the move was started, or the paused move was resumed
+    started: Instant,
+
+    /// The maximum time the move may take
+    timeout: Duration,
+
+    /// The distance from the end position the servo has to be within before the move is complete
+    in_position_tolerance: Option<u32>,
+
+    /// The cycle that is currently being waited on
+    cycle: Option<CycleFuture<'device>>,
+
+    /// Whether the servo is being halted, because an abort was requested
+    aborting: bool,
+
+    /// Whether the motion has ended, either completed or failed
+    ended: bool,
+
+    /// The number of times the status has been checked, used to decimate supervision
+    checks: u32,
+
+    /// The error the motion ended with, until it is returned
+    error: Option<MovementError>,
+}
+
+/// The position of the servo during a move
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ProgressSample {
+    /// The actual position in increments
+    pub position: i32,
+
+    /// The actual velocity in increments per second
+    pub velocity: i32,
+
+    /// The part of the distance that has been covered, between 0 and 1
+    pub fraction_complete: f64,
+
+    /// The cycle the values were read in
+    pub cycle: u64,
+}
+
+/// Reads the position of the servo every few cycles during a move.
+/// Created by `MotionHandle::progress`.
+pub struct Progress<
+    'handle,
+    'servo,
+    'device,
+    'controller: 'device,
+    const MAX_DEVICES: usize,
+    const PDI_LENGTH: usize,
+> {
+    /// The handle of the move being followed
+    handle: &'handle mut MotionHandle<'servo, 'device, 'controller, MAX_DEVICES, PDI_LENGTH>,
+
+    /// The number of cycles between samples
+    decimation: u32,
+}
+
+/// Calculates which part of the distance from start to end has been covered
+fn fraction_complete(start: i32, end: i32, position: i32) -> f64 {
+    if start == end {
+        return 1.0;
+    }
+    let covered = f64::from(position) - f64::from(start);
+    let distance = f64::from(end) - f64::from(start);
+    (covered / distance).clamp(0.0, 1.0)
+}
+
+impl<'device, 'controller: 'device, const MAX_DEVICES: usize, const PDI_LENGTH: usize>
+    Servo<'device, 'controller, MAX_DEVICES, PDI_LENGTH>
+{
+    /// Starts moving the servo to the requested position.
+    /// Returns once the drive acknowledged the setpoint, the returned handle can be awaited to
+    /// wait until the motion is complete.
+    ///
+    /// # Errors
+    /// Returns an error if:
+    /// - The drive is not enabled or emergency stopped
+    /// - The move is absolute, but the device isn't homed and unreferenced moves aren't allowed
+    /// - The target is outside of the software position limits
+    /// - The velocity exceeds the maximum profile velocity
+    /// - The device couldn't be set to profile position mode
+    /// - The position or velocity couldn't be written
+    /// - The device faulted or didn't acknowledge the setpoint in time
+    pub async fn start_move(
+        &mut self,
+        target: i32,
+        options: MoveOptions,
+    ) -> Result<MotionHandle<'_, 'device, 'controller, MAX_DEVICES, PDI_LENGTH>, MovementError>
+    {
+        let (setpoint, loaded) = self.prepare_move(target, options).await?;
+        handshake::start_move(&mut self.device, setpoint, loaded.started, loaded.timeout).await?;
+        self.started_move(&loaded)
+    }
+
+    /// Checks and writes everything a move needs like `Servo::start_move`, but leaves the new
+    /// setpoint bit cleared. Setting the new setpoint bit starts the move, after which
+    /// `Servo::start_loaded` waits for the acknowledgement.
+    ///
+    /// # Errors
+    /// See `Servo::start_move`
+    pub(super) async fn load_move(
+        &mut self,
+        target: i32,
+        options: MoveOptions,
+    ) -> Result<LoadedMove, MovementError> {
+        let (setpoint, loaded) = self.prepare_move(target, options).await?;
+        handshake::load_move(&mut self.device, setpoint, loaded.started, loaded.timeout).await?;
+        Ok(loaded)
+    }
+
+    /// Checks everything a move needs and prepares the drive for it, up to the setpoint
+    ///
+    /// # Errors
+    /// See `Servo::start_move`
+    async fn prepare_move(
+        &mut self,
+        target: i32,
+        options: MoveOptions,
+    ) -> Result<(Setpoint, LoadedMove), MovementError> {
+        let MoveOptions {
+            mode,
+            velocity,
+            acceleration,
+            deceleration,
+            timeout,
+            change_immediately,
+            allow_unreferenced,
+            in_position_tolerance,
+            retry: _,
+        } = options;
+        let timeout = timeout.unwrap_or(MOTION_TIMEOUT);
+        let started = Instant::now();
+
+        // Fall back to the defaults of the axis for the settings the options don't override
+        let velocity = velocity.or(self.config.default_velocity);
+        let acceleration = acceleration.or(self.config.default_acceleration);
+        let deceleration = deceleration.or(self.config.default_deceleration);
+        if self.device.controller.verbose() {
+            log::info!(
+                "Starting {mode:?} movement to position {target} of device {}",
+                self.device.id
+            );
+        }
+        if self.device.emergency_stopped() {
+            return Err(MovementError::EmergencyStopped(self.device.id));
+        }
+        if !self.device.ready_state() {
+            return Err(MovementError::DriveDisabled(self.device.id));
+        }
+        if mode == MovementMode::Absolute
+            && !allow_unreferenced
+            && !self.is_homed().map_err(MovementError::Ethercat)?
+        {
+            return Err(MovementError::NotHomed(self.device.id));
+        }
+        let start_position = self.get_position().map_err(MovementError::Ethercat)?;
+        let previous_target = self.previous_target().map_err(MovementError::Ethercat)?;
+        let end_position = mode.end_position(target, start_position, previous_target);
+        self.check_limits(end_position).await?;
+        let velocity = match velocity {
+            Some(velocity) => Some(self.check_velocity(velocity).await?),
+            None => None,
+        };
+
+        // Moves without a velocity reuse the last commanded one, which may never have been set
+        if velocity.is_none() && self.profile_velocity().map_err(MovementError::Ethercat)? == 0 {
+            log::warn!(
+                "Device {} moves with a profile velocity of zero, request a velocity or set a \
+                 default velocity",
+                self.device.id
+            );
+        }
+
+        // Set the direction to move in
+        self.device
+            .set_mode(OperationMode::ProfilePosition)
+            .await
+            .map_err(MovementError::SetMode)?;
+
+        // Set the requested acceleration and deceleration, unless they were already written
+        if let Some(acceleration) = acceleration {
+            self.set_profile_acceleration(acceleration)
+                .await
+                .map_err(|error| MovementError::WritingAcceleration(self.device.id, error))?;
+        }
+        if let Some(deceleration) = deceleration {
+            self.set_profile_deceleration(deceleration)
+                .await
+                .map_err(|error| MovementError::WritingDeceleration(self.device.id, error))?;
+        }
+
+        // Abort requests from before this move don't apply to it
+        self.device.controller.take_abort(self.device.id);
+        self.paused = false;
+        self.queued_moves = 0;
+
+        // The setpoint is sent with the full set-point handshake, so consecutive moves are all
+        // started by a rising edge of the new setpoint bit
+        let setpoint = Setpoint {
+            position: target,
+            velocity,
+            mode,
+            change_immediately,
+        };
+        self.prepare_setpoint(&setpoint).await?;
+        let loaded = LoadedMove {
+            target,
+            mode,
+            start_position,
+            end_position,
+            started,
+            timeout,
+            in_position_tolerance,
+        };
+        Ok((setpoint, loaded))
+    }
+
+    /// Waits until the drive acknowledged the setpoint of a loaded move whose new setpoint bit
+    /// has been set, and finishes the set-point handshake, see `Servo::load_move`.
+    ///
+    /// # Errors
+    /// Returns an error if the new setpoint bit couldn't be dropped, the drive faulted, or the
+    /// setpoint wasn't acknowledged in time
+    pub(super) async fn start_loaded(
+        &mut self,
+        loaded: LoadedMove,
+    ) -> Result<MotionHandle<'_, 'device, 'controller, MAX_DEVICES, PDI_LENGTH>, MovementError>
+    {
+        handshake::start_loaded(&mut self.device, loaded.started, loaded.timeout).await?;
+        self.started_move(&loaded)
+    }
+
+    /// Creates the handle of a move whose set-point handshake is complete
+    ///
+    /// # Errors
+    /// Returns an error if the position couldn't be read for logging
+    fn started_move(
+        &mut self,
+        loaded: &LoadedMove,
+    ) -> Result<MotionHandle<'_, 'device, 'controller, MAX_DEVICES, PDI_LENGTH>, MovementError>
+    {
+        let &LoadedMove {
+            target,
+            mode,
+            start_position,
+            end_position,
+            started,
+            timeout,
+            in_position_tolerance,
+        } = loaded;
+        if self.device.controller.verbose() {
+            let id = self.device.id;
+            log::info!(
+                "Move device {id} {mode:?} : {target}, at position {}",
+                self.get_position().map_err(MovementError::Ethercat)?
+            );
+        }
+        Ok(self.motion_handle(
+            target,
+            start_position,
+            end_position,
+            started,
+            timeout,
+            in_position_tolerance,
+        ))
+    }
+
+    /// Creates the handle of a move whose setpoint has been acknowledged by the drive
+    pub(super) fn motion_handle(
+        &mut self,
+        target: i32,
+        start_position: i32,
+        end_position: i32,
+        started: Instant,
+        timeout: Duration,
+        in_position_tolerance: Option<u32>,
+    ) -> MotionHandle<'_, 'device, 'controller, MAX_DEVICES, PDI_LENGTH> {
+        MotionHandle {
+            servo: self,
+            target,
+            start_position,
+            end_position,
+            started,
+            timeout,
+            in_position_tolerance,
+            cycle: None,
+            aborting: false,
+            ended: false,
+            checks: 0,
+            error: None,
+        }
+    }
+
+    /// Aborts the running positioning move by setting the halt bit and waits until the servo
+    /// stands still. The new setpoint bit is cleared, so a new move can be started right away.
+    /// The halt bit stays set until the next move is started.
+    /// To abort a move waited on by another task use `Controller::abort_move`.
+    ///
+    /// # Errors
+    /// Returns an error if:
+    /// - The halt bit couldn't be set
+    /// - The servo didn't stop in time, see `Servo::wait_standstill`
+    ///
+    /// # Returns
+    /// The position the servo stopped at
+    pub async fn abort_move(&mut self) -> Result<i32, MovementError> {
+        if self.device.controller.verbose() {
+            log::info!("Aborting movement of device {}", self.device.id);
+        }
+        self.device.controller.take_abort(self.device.id);
+
+        // Set the halt bit and wait until the servo stands still
+        self.halt().map_err(MovementError::Ethercat)?;
+        self.paused = false;
+        self.queued_moves = 0;
+        self.device.controller.next_cycle().await;
+        let position = self.wait_standstill(MOTION_TIMEOUT).await?;
+        self.emit(MotionEventKind::Aborted);
+        Ok(position)
+    }
+
+    /// Pauses the running positioning move by setting the halt bit and waits until the servo
+    /// stands still. The move continues to it's original target when resumed.
+    /// Pausing a paused move does nothing.
+    ///
+    /// # Errors
+    /// Returns an error if:
+    /// - No positioning move is running
+    /// - The halt bit couldn't be set
+    /// - The servo didn't stop in time, see `Servo::wait_standstill`
+    pub async fn pause(&mut self) -> Result<(), MovementError> {
+        if self.paused {
+            return Ok(());
+        }
+
+        // Only a running profile position move can be paused
+        if !self.move_running()? {
+            return Err(MovementError::NoActiveMove(self.device.id));
+        }
+        if self.device.controller.verbose() {
+            log::info!("Pausing movement of device {}", self.device.id);
+        }
+
+        // Set the halt bit and wait until the servo stands still
+        self.device
+            .update_control_word(|control| control.with(ControlBit::Halt))
+            .map_err(MovementError::Ethercat)?;
+        self.paused = true;
+        self.device.controller.next_cycle().await;
+        self.wait_standstill(MOTION_TIMEOUT).await?;
+        self.emit(MotionEventKind::Halted);
+        Ok(())
+    }
+
+    /// Resumes a paused positioning move by clearing the halt bit.
+    /// Returns once the servo moves again, or right away if it was paused at it's target.
+    ///
+    /// # Errors
+    /// Returns an error if:
+    /// - No positioning move is paused
+    /// - The halt bit couldn't be cleared
+    /// - The device faulted
+    /// - The device has been emergency stopped
+    pub async fn resume(&mut self) -> Result<(), MovementError> {
+        if !self.paused {
+            return Err(MovementError::NoActiveMove(self.device.id));
+        }
+        if self.device.controller.verbose() {
+            log::info!("Resuming movement of device {}", self.device.id);
+        }
+
+        // Clear the halt bit and wait until the drive continues the move
+        self.device
+            .update_control_word(|control| control.without(ControlBit::Halt))
+            .map_err(MovementError::Ethercat)?;
+        self.paused = false;
+        match self
+            .device
+            .wait_for(
+                |status| !status.is_set(StatusWordBit::MotionComplete),
+                RESUME_TIMEOUT,
+            )
+            .await
+        {
+            Ok(_) | Err(WaitTimeout::Expired(..)) => Ok(()),
+            Err(error) => Err(error.into()),
+        }
+    }
+
+    /// Changes the target of the running positioning move to the requested absolute position.
+    /// The drive plans a new profile towards the new target without stopping.
+    ///
+    /// # Errors
+    /// Returns an error if:
+    /// - No positioning move is running
+    /// - The target couldn't be written
+    /// - The drive didn't acknowledge the new target
+    /// - The device faulted or has been emergency stopped
+    pub async fn update_target(&mut self, new_target: i32) -> Result<(), MovementError> {
+        if self.paused || !self.move_running()? {
+            return Err(MovementError::NoActiveMove(self.device.id));
+        }
+        if self.device.controller.verbose() {
+            log::info!(
+                "Changing target of device {} to {new_target}",
+                self.device.id
+            );
+        }
+
+        // Send the new target, replacing the current one immediately
+        let setpoint = Setpoint {
+            position: new_target,
+            velocity: None,
+            mode: MovementMode::Absolute,
+            change_immediately: true,
+        };
+        self.send_setpoint(setpoint, Instant::now(), SETPOINT_TIMEOUT)
+            .await
+            .map_err(|error| match error {
+                MovementError::Timeout(device, _) => MovementError::SetpointRejected(device),
+                error => error,
+            })
+    }
+
+    /// Waits until the drive reports the target of the current motion reached (status bit 10),
+    /// without starting a move. Can be used after `Servo::queue_move`, `Servo::update_target`, or
+    /// a motion commanded by other means. The motion can be aborted with
+    /// `Controller::abort_move`. Works with both foreground and background cycling.
+    ///
+    /// # Errors
+    /// Returns an error if:
+    /// - The motion is paused, so it can't reach it's target while waiting
+    /// - The motion was aborted
+    /// - The device faulted or has been emergency stopped
+    /// - The target wasn't reached within the timeout
+    pub async fn wait_target_reached(&mut self, timeout: Duration) -> Result<(), MovementError> {
+        if self.paused {
+            return Err(MovementError::NoActiveMove(self.device.id));
+        }
+        let start = Instant::now();
+        loop {
+            // Stop the motion if another task requested it to be aborted
+            if self.device.controller.take_abort(self.device.id) {
+                let position = self.abort_move().await?;
+                return Err(MovementError::Aborted(self.device.id, position));
+            }
+
+            // Check whether the target is reached, or waiting has to stop
+            match self.device.check_motion(
+                |status| status.is_set(StatusWordBit::MotionComplete),
+                start,
+                timeout,
+            ) {
+                Some(Ok(_)) => return Ok(()),
+                Some(Err(error)) => return Err(self.motion_error(error)),
+                None => self.device.controller.next_cycle().await,
+            }
+        }
+    }
+
+    /// Checks whether a profile position move is running
+    ///
+    /// # Errors
+    /// Returns an error if the inputs couldn't be read
+    fn move_running(&mut self) -> Result<bool, MovementError> {
+        let status = self.snapshot().map_err(MovementError::Ethercat)?;
+        Ok(status.mode == Some(OperationMode::ProfilePosition)
+            && !status.status.is_set(StatusWordBit::MotionComplete))
+    }
+
+    /// Sends a setpoint to the drive with the full set-point handshake, see
+    /// `handshake::send_setpoint`. Returns when the acknowledgement is low again, so the next
+    /// setpoint can be sent.
+    ///
+    /// The drive has to acknowledge the setpoint within `timeout` from `start`.
+    ///
+    /// # Errors
+    /// Returns an error if the setpoint couldn't be written, the drive faulted, or the setpoint
+    /// wasn't acknowledged in time
+    pub(super) async fn send_setpoint(
+        &mut self,
+        setpoint: Setpoint,
+        start: Instant,
+        timeout: Duration,
+    ) -> Result<(), MovementError> {
+        self.prepare_setpoint(&setpoint).await?;
+        handshake::send_setpoint(&mut self.device, setpoint, start, timeout).await
+    }
+
+    /// Sends a setpoint to the drive and returns as soon as the drive latched it, see
+    /// `handshake::latch_setpoint`.
+    ///
+    /// The drive has to acknowledge the setpoint within `timeout` from `start`.
+    ///
+    /// # Errors
+    /// Returns an error if the setpoint couldn't be written, the drive faulted, or the setpoint
+    /// wasn't acknowledged in time
+    pub(super) async fn latch_setpoint(
+        &mut self,
+        setpoint: Setpoint,
+        start: Instant,
+        timeout: Duration,
+    ) -> Result<(), MovementError> {
+        self.prepare_setpoint(&setpoint).await?;
+        handshake::latch_setpoint(&mut self.device, setpoint, start, timeout).await
+    }
+
+    /// Prepares the drive for a setpoint before it's written: targets relative to the previous
+    /// target need the drive to interpret them that way, and are tracked as the new target
+    ///
+    /// # Errors
+    /// Returns an error if the drive couldn't be configured or the previous target couldn't be
+    /// read
+    async fn prepare_setpoint(&mut self, setpoint: &Setpoint) -> Result<(), MovementError> {
+        self.relative_target = if setpoint.mode == MovementMode::RelativeToTarget {
+            self.configure_relative_to_target()
+                .await
+                .map_err(MovementError::Ethercat)?;
+            let previous = self.previous_target().map_err(MovementError::Ethercat)?;
+            Some(previous.saturating_add(setpoint.position))
+        } else {
+            None
+        };
+        Ok(())
+    }
+
+    /// Converts an error while waiting on a motion into a movement error, see
+    /// `handshake::motion_error`
+    pub(super) fn motion_error(&mut self, error: WaitTimeout) -> MovementError {
+        handshake::motion_error(&mut self.device, error)
+    }
+
+    /// Sets the halt bit and clears the new setpoint bit
+    pub(super) fn halt(&mut self) -> Result<ControlWord, EthercrabError> {
+        self.device.update_control_word(|control| {
+            control.with(ControlBit::Halt).without(ControlBit::Control4)
+        })
+    }
+
+    /// Checks whether the servo is moving in a positioning, jogging, homing or velocity mode
+    ///
+    /// # Errors
+    /// Returns an error if the inputs couldn't be read
+    fn motion_active(&mut self) -> Result<bool, MovementError> {
+        let status = self.snapshot().map_err(MovementError::Ethercat)?;
+        Ok(match status.mode {
+            Some(OperationMode::ProfilePosition | OperationMode::Jog | OperationMode::Homing) => {
+                !status.status.is_set(StatusWordBit::MotionComplete)
+            }
+            Some(OperationMode::Velocity | OperationMode::ProfileVelocity) => status.velocity != 0,
+            _ => false,
+        })
+    }
+
+    /// Moves the servo by a distance in increments at the velocity in increments per second, like
+    /// an operator nudging the axis. The servo accelerates to the velocity in one second and the
+    /// move can be interrupted with `Controller::abort_move`. The profile acceleration and
+    /// deceleration are restored afterwards.
+    ///
+    /// # Errors
+    /// Returns an error if:
+    /// - Another motion is active
+    /// - The velocity is zero
+    /// - The move couldn't be started or failed
+    /// - The previous acceleration and deceleration couldn't be read or restored
+    ///
+    /// # Returns
+    /// The distance actually travelled in increments
+    pub async fn jog_distance(&mut self, delta: i32, velocity: u32) -> Result<i32, MovementError> {
+        if self.motion_active()? {
+            return Err(MovementError::MotionActive(self.device.id));
+        }
+        if velocity == 0 {
+            return Err(MovementError::ZeroVelocity(self.device.id));
+        }
+        let start_position = self.get_position().map_err(MovementError::Ethercat)?;
+        let ramps = self
+            .current_ramps()
+            .await
+            .map_err(MovementError::Ethercat)?;
+
+        // Move relative with an acceleration and deceleration gentle enough for jogging, then
+        // restore the previous ramps, even if the move was aborted or failed
+        let options = MoveOptions::new()
+            .with_mode(MovementMode::RelativeToActual)
+            .with_velocity(velocity)
+            .with_acceleration(velocity)
+            .with_deceleration(velocity);
+        let result = self.move_with(delta, &options).await;
+        let restored = self.restore_ramps(ramps).await;
+        match result {
+            Ok(()) | Err(MovementError::Aborted(..)) => restored?,
+            Err(error) => return Err(error),
+        }
+        let end_position = self.get_position().map_err(MovementError::Ethercat)?;
+        Ok(end_position.saturating_sub(start_position))
+    }
+}
+
+impl<'servo, 'device, 'controller: 'device, const MAX_DEVICES: usize, const PDI_LENGTH: usize>
+    MotionHandle<'servo, 'device, 'controller, MAX_DEVICES, PDI_LENGTH>
+{
+    /// Returns the target position of the move
+    pub const fn target(&self) -> i32 {
+        self.target
+    }
+
+    /// Checks whether the motion has ended, either completed, aborted, or failed.
+    /// Awaiting the handle returns which one it was.
+    /// The status is only updated by the cycles of the controller.
+    pub fn is_done(&mut self) -> bool {
+        if self.ended {
+            return true;
+        }
+
+        // A paused move isn't complete, the time limit starts again when it's resumed
+        if self.servo.paused {
+            self.started = Instant::now();
+        }
+
+        // Halt the servo if another task requested the move to be aborted
+        let id = self.servo.device.id;
+        if !self.aborting && self.servo.device.controller.take_abort(id) {
+            if let Err(error) = self.servo.halt() {
+                self.error = Some(MovementError::Ethercat(error));
+                self.ended = true;
+                return true;
+            }
+            self.aborting = true;
+            self.servo.paused = false;
+        }
+
+        // Halt the servo if the following error exceeds the supervision threshold
+        self.checks = self.checks.wrapping_add(1);
+        if !self.aborting {
+            if let Some((value, threshold)) = self.servo.following_error_exceeded(self.checks) {
+                self.error = Some(MovementError::FollowingErrorExceeded { value, threshold });
+                if let Err(error) = self.servo.halt() {
+                    self.error = Some(MovementError::Ethercat(error));
+                    self.ended = true;
+                    return true;
+                }
+                self.aborting = true;
+                self.servo.paused = false;
+            }
+        }
+
+        // The position has to be within the tolerance as well, unless the move is aborted
+        let in_position = match self.in_position_tolerance {
+            Some(tolerance) if !self.aborting => {
+                match self.servo.in_position(self.end_position, tolerance) {
+                    Ok(in_position) => in_position,
+                    Err(error) => {
+                        self.error = Some(MovementError::Ethercat(error));
+                        self.ended = true;
+                        return true;
+                    }
+                }
+            }
+            _ => true,
+        };
+
+        // Check whether the motion is complete or the servo stands still, or waiting has to stop
+        match handshake::check_move_end(
+            &mut self.servo.device,
+            !self.servo.paused && in_position,
+            self.aborting,
+            self.started,
+            self.timeout,
+        ) {
+            Some(Ok(())) => {
+                if self.servo.device.controller.verbose() {
+                    log::info!("Movement completed");
+                }
+            }
+
+            // An error that halted the servo takes precedence over the abort
+            Some(Err(error @ (MovementError::Aborted(..) | MovementError::Ethercat(_)))) => {
+                self.error.get_or_insert(error);
+            }
+            Some(Err(error)) => self.error = Some(error),
+            None => return false,
+        }
+        self.ended = true;
+        true
+    }
+
+    /// Follows the position of the servo until the motion ends.
+    /// A sample is taken every `decimation` cycles, a decimation of 0 is handled as 1.
+    pub fn progress(
+        &mut self,
+        decimation: u32,
+    ) -> Progress<'_, 'servo, 'device, 'controller, MAX_DEVICES, PDI_LENGTH> {
+        Progress {
+            handle: self,
+            decimation: decimation.max(1),
+        }
+    }
+
+    /// Reads the inputs of the servo performing the move, see `Servo::snapshot`.
+    ///
+    /// # Errors
+    /// See `Servo::snapshot`
+    pub fn snapshot(&mut self) -> Result<ServoStatus, EthercrabError> {
+        self.servo.snapshot()
+    }
+
+    /// Checks whether the motion failed or is being stopped because of an error, after which
+    /// awaiting the handle returns the error
+    pub(super) const fn failed(&self) -> bool {
+        self.error.is_some()
+    }
+
+    /// Returns the servo performing the move, for reading objects while the move runs
+    pub(super) fn servo(&mut self) -> &mut Servo<'device, 'controller, MAX_DEVICES, PDI_LENGTH> {
+        self.servo
+    }
+
+    /// Waits until the next cycle has completed, finishing the pending cycle if there is one
+    pub(super) async fn wait_cycle(&mut self) {
+        match self.cycle.take() {
+            Some(cycle) => cycle.await,
+            None => self.servo.device.controller.next_cycle().await,
+        }
+    }
+
+    /// Changes the target of the motion to the requested absolute position, see
+    /// `Servo::update_target`.
+    ///
+    /// # Errors
+    /// See `Servo::update_target`
+    pub async fn update_target(&mut self, new_target: i32) -> Result<(), MovementError> {
+        self.cycle = None;
+        self.servo.update_target(new_target).await?;
+        self.target = new_target;
+        self.end_position = new_target;
+        Ok(())
+    }
+
+    /// Pauses the motion, see `Servo::pause`.
+    ///
+    /// # Errors
+    /// See `Servo::pause`
+    pub async fn pause(&mut self) -> Result<(), MovementError> {
+        self.cycle = None;
+        self.servo.pause().await
+    }
+
+    /// Resumes the paused motion, see `Servo::resume`.
+    ///
+    /// # Errors
+    /// See `Servo::resume`
+    pub async fn resume(&mut self) -> Result<(), MovementError> {
+        self.cycle = None;
+        self.servo.resume().await
+    }
+
+    /// Stops the motion and waits until the servo stands still, see `Servo::abort_move`.
+    ///
+    /// # Errors
+    /// See `Servo::abort_move`
+    ///
+    /// # Returns
+    /// The position the servo stopped at
+    pub async fn abort(mut self) -> Result<i32, MovementError> {
+        // Stop waiting on the pending cycle, the servo is used directly from here on
+        self.cycle = None;
+        self.servo.abort_move().await
+    }
+}
+
+impl<const MAX_DEVICES: usize, const PDI_LENGTH: usize> Future
+    for MotionHandle<'_, '_, '_, MAX_DEVICES, PDI_LENGTH>
+{
+    type Output = Result<(), MovementError>;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        let this = self.get_mut();
+        loop {
+            // Finish the pending cycle before checking the status word again
+            if let Some(cycle) = this.cycle.as_mut() {
+                ready!(cycle.as_mut().poll(cx));
+                this.cycle = None;
+            }
+            if this.is_done() {
+                return Poll::Ready(this.error.take().map_or(Ok(()), Err));
+            }
+            let controller = this.servo.device.controller;
+            this.cycle = Some(Box::pin(controller.next_cycle()));
+        }
+    }
+}
+
+impl<const MAX_DEVICES: usize, const PDI_LENGTH: usize>
+    Progress<'_, '_, '_, '_, MAX_DEVICES, PDI_LENGTH>
+{
+    /// Waits for the next sample.
+    /// Works with both foreground and background cycling of the controller.
+    ///
+    /// # Returns
+    /// The next sample, or `None` once the motion has ended
+    pub async fn next(&mut self) -> Option<ProgressSample> {
+        let mut cycles = 0;
+        loop {
+            // Wait for a new cycle and stop once the motion ended
+            self.handle.wait_cycle().await;
+            if self.handle.is_done() {
+                return None;
+            }
+            cycles += 1;
+            if cycles < self.decimation {
+                continue;
+            }
+
+            // Read the position and velocity of the servo in the same cycle
+            let handle = &mut *self.handle;
+            match handle.servo.snapshot() {
+                Ok(status) => {
+                    return Some(ProgressSample {
+                        position: status.position,
+                        velocity: status.velocity,
+                        fraction_complete: fraction_complete(
+                            handle.start_position,
+                            handle.end_position,
+                            status.position,
+                        ),
+                        cycle: status.cycle,
+                    });
+                }
+                Err(error) => {
+                    handle.error = Some(MovementError::Ethercat(error));
+                    handle.ended = true;
+                    return None;
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    //! Tests of the waits during a motion against a simulated drive, the set-point handshake is
+    //! tested in `handshake`
+
+    use super::*;
+    use crate::device::{
+        simulation::{SimulatedDrive, DEADLINE, ID},
+        StatusWord, WaitConditions,
+    };
+
+    /// A move that doesn't complete ends with a timeout once the deadline passed
+    #[test]
+    fn move_times_out() {
+        let mut drive = SimulatedDrive::new(100);
+        drive.set_mode(OperationMode::ProfilePosition);
+        let (result, cycles) = drive.wait_cycles(|_| false);
+        assert_eq!(cycles, DEADLINE);
+        let Err(error @ WaitTimeout::Expired(ID, _)) = result else {
+            panic!("The wait didn't expire: {result:?}");
+        };
+        assert!(matches!(
+            MovementError::from(error),
+            MovementError::Timeout(ID, _)
+        ));
+    }
+
+    /// A fault or leaving the operation enabled state ends a wait during a motion, even when
+    /// faults don't abort other waits, and takes precedence over the deadline
+    #[test]
+    fn faults_end_motion_waits() {
+        let conditions = WaitConditions {
+            expired: true,
+            ..WaitConditions::default()
+        };
+        for raw in [0x0008, 0x0007, 0x0040] {
+            let status = StatusWord::new(raw);
+            assert!(matches!(
+                conditions.motion_result(ID, status, false),
+                Some(Err(WaitTimeout::Fault(ID, _)))
+            ));
+            assert!(matches!(
+                conditions.wait_result(ID, status, false),
+                Some(Err(WaitTimeout::Expired(ID, _)))
+            ));
+        }
+    }
+
+    /// A warning while the operation is enabled doesn't end a wait during a motion
+    #[test]
+    fn warnings_keep_motion_waits() {
+        let status = StatusWord::new(0x00A7);
+        let conditions = WaitConditions::default();
+        assert!(conditions.motion_result(ID, status, false).is_none());
+        assert!(matches!(
+            conditions.motion_result(ID, status, true),
+            Some(Ok(_))
+        ));
+        let expired = WaitConditions {
+            expired: true,
+            ..conditions
+        };
+        assert!(matches!(
+            expired.motion_result(ID, status, false),
+            Some(Err(WaitTimeout::Expired(ID, _)))
+        ));
+    }
+}
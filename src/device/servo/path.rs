@@ -0,0 +1,109 @@
+//! This module contains movements along multiple waypoints.
+//!
+//! The waypoints are sent to the drive with the set-point handshake of the profile position
+//! mode: the next waypoint is sent as soon as the drive acknowledged the previous one. Waypoints
+//! with `change_immediately` set replace the current waypoint while moving, so the servo blends
+//! through without stopping. Other waypoints are started once the previous waypoint is reached.
+
+use super::{motion::Setpoint, MovementError, MovementMode, Servo, MOTION_TIMEOUT};
+use crate::device::{OperationMode, StatusWordBit};
+use core::fmt::{self, Debug, Formatter};
+use std::time::Instant;
+
+/// A point to move through
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Waypoint {
+    /// The position in increments
+    pub position: i32,
+
+    /// The velocity to move to the position with in increments per second
+    pub velocity: u32,
+
+    /// Whether the position is absolute or relative, see `MovementMode`
+    pub mode: MovementMode,
+
+    /// Whether to replace the previous waypoint immediately instead of after reaching it
+    pub change_immediately: bool,
+}
+
+/// Moving along a path failed at a waypoint
+pub struct PathError {
+    /// The index of the waypoint that failed
+    pub waypoint: usize,
+
+    /// The reason the waypoint failed
+    pub error: MovementError,
+}
+
+impl Debug for PathError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write!(f, "Waypoint {} failed: {:?}", self.waypoint, self.error)
+    }
+}
+
+impl<const MAX_DEVICES: usize, const PDI_LENGTH: usize> Servo<'_, '_, MAX_DEVICES, PDI_LENGTH> {
+    /// Moves the servo through the waypoints back-to-back and waits until the last one is
+    /// reached. Doesn't do anything if there are no waypoints.
+    ///
+    /// # Errors
+    /// Returns the index of the failing waypoint and an error if:
+    /// - The drive is not enabled or emergency stopped
+    /// - The device couldn't be set to profile position mode
+    /// - The waypoint couldn't be written
+    /// - The device faulted or didn't acknowledge or reach the waypoint in time
+    pub async fn move_path(&mut self, waypoints: &[Waypoint]) -> Result<(), PathError> {
+        if waypoints.is_empty() {
+            return Ok(());
+        }
+        if self.device.emergency_stopped() {
+            return Err(PathError {
+                waypoint: 0,
+                error: MovementError::EmergencyStopped(self.device.id),
+            });
+        }
+        if !self.device.ready_state() {
+            return Err(PathError {
+                waypoint: 0,
+                error: MovementError::DriveDisabled(self.device.id),
+            });
+        }
+        self.device
+            .set_mode(OperationMode::ProfilePosition)
+            .await
+            .map_err(|error| PathError {
+                waypoint: 0,
+                error: MovementError::SetMode(error),
+            })?;
+
+        for (index, waypoint) in waypoints.iter().enumerate() {
+            let setpoint = Setpoint {
+                position: waypoint.position,
+                velocity: Some(waypoint.velocity),
+                mode: waypoint.mode,
+                change_immediately: waypoint.change_immediately,
+            };
+            self.send_setpoint(setpoint, Instant::now(), MOTION_TIMEOUT)
+                .await
+                .map_err(|error| PathError {
+                    waypoint: index,
+                    error,
+                })?;
+        }
+
+        // Wait until the last waypoint has been reached
+        self.device
+            .wait_for(
+                |status| status.is_set(StatusWordBit::MotionComplete),
+                MOTION_TIMEOUT,
+            )
+            .await
+            .map_err(|error| PathError {
+                waypoint: waypoints.len() - 1,
+                error: MovementError::from(error),
+            })?;
+        if self.device.controller.verbose() {
+            log::info!("Path of device {} completed", self.device.id);
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,150 @@
+//! This module contains the execution of trajectories generated offline.
+//!
+//! The trajectory is validated against the limits of the drive before the first move, so an
+//! infeasible trajectory doesn't stop halfway. Each point is executed as a profile position move,
+//! followed by the dwell time of the point.
+
+use super::{motion::MoveOptions, MovementError, Servo};
+use crate::{
+    device::WaitTimeout,
+    trajectory::{Trajectory, TrajectoryError, TrajectoryLimits},
+};
+use core::{
+    fmt::{self, Debug, Formatter},
+    time::Duration,
+};
+use ethercrab::error::Error as EthercrabError;
+use std::time::Instant;
+
+/// The progress of a trajectory, reported after every point
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct TrajectoryProgress {
+    /// The index of the point that was reached
+    pub point: usize,
+
+    /// The number of points of the trajectory
+    pub points: usize,
+
+    /// The actual position after reaching the point
+    pub position: i32,
+}
+
+/// An error returned while executing a trajectory
+pub enum PlaybackError {
+    /// The trajectory can't be executed within the limits of the drive
+    Invalid(TrajectoryError),
+
+    /// The position or limits couldn't be read before starting
+    Ethercat(usize, EthercrabError),
+
+    /// Moving to a point or dwelling at it failed
+    Point {
+        /// The index of the point that failed
+        point: usize,
+
+        /// The reason the point failed
+        error: MovementError,
+    },
+}
+
+impl Debug for PlaybackError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Invalid(error) => write!(f, "Invalid trajectory: {error:?}"),
+            Self::Ethercat(device, error) => write!(
+                f,
+                "Preparing the trajectory of device {device} failed: {error:?}"
+            ),
+            Self::Point { point, error } => write!(f, "Point {point} failed: {error:?}"),
+        }
+    }
+}
+
+impl From<TrajectoryError> for PlaybackError {
+    fn from(value: TrajectoryError) -> Self {
+        Self::Invalid(value)
+    }
+}
+
+impl<const MAX_DEVICES: usize, const PDI_LENGTH: usize> Servo<'_, '_, MAX_DEVICES, PDI_LENGTH> {
+    /// Executes the trajectory point by point and calls `progress` after every reached point.
+    /// The trajectory is validated against the software position limits and the maximum profile
+    /// velocity first. The velocity and mode of each point override the options.
+    /// The trajectory can be stopped with `Controller::abort_move`.
+    ///
+    /// # Errors
+    /// Returns an error if:
+    /// - The trajectory is invalid
+    /// - The position, limits or maximum velocity couldn't be read
+    /// - A move failed, was aborted, or the device faulted while dwelling
+    pub async fn run_trajectory(
+        &mut self,
+        trajectory: &Trajectory,
+        options: &MoveOptions,
+        mut progress: impl FnMut(TrajectoryProgress),
+    ) -> Result<(), PlaybackError> {
+        let id = self.device.id;
+
+        // Validate the whole trajectory before moving
+        let start_position = self
+            .get_position()
+            .map_err(|error| PlaybackError::Ethercat(id, error))?;
+        let limits = TrajectoryLimits {
+            position: self
+                .user_software_limits()
+                .await
+                .map_err(|error| PlaybackError::Ethercat(id, error))?,
+            max_velocity: Some(
+                self.max_profile_velocity()
+                    .await
+                    .map_err(|error| PlaybackError::Ethercat(id, error))?,
+            ),
+        };
+        trajectory.validate(start_position, &limits)?;
+
+        let points = trajectory.points.len();
+        for (index, point) in trajectory.points.iter().enumerate() {
+            let point_error = |error| PlaybackError::Point {
+                point: index,
+                error,
+            };
+            let point_options = options
+                .clone()
+                .with_mode(point.mode)
+                .with_velocity(point.velocity);
+            self.move_with(point.position, &point_options)
+                .await
+                .map_err(point_error)?;
+            self.dwell(point.dwell).await.map_err(point_error)?;
+            let position = self
+                .get_position()
+                .map_err(|error| point_error(MovementError::Ethercat(error)))?;
+            progress(TrajectoryProgress {
+                point: index,
+                points,
+                position,
+            });
+        }
+        Ok(())
+    }
+
+    /// Waits at the current position for the duration, while watching for faults and abort
+    /// requests.
+    ///
+    /// # Errors
+    /// Returns an error if the device faulted or the trajectory was aborted
+    pub(super) async fn dwell(&mut self, duration: Duration) -> Result<(), MovementError> {
+        let start = Instant::now();
+        loop {
+            if self.device.controller.take_abort(self.device.id) {
+                let position = self.get_position().map_err(MovementError::Ethercat)?;
+                return Err(MovementError::Aborted(self.device.id, position));
+            }
+            match self.device.check_motion(|_| false, start, duration) {
+                Some(Err(WaitTimeout::Expired(..))) => return Ok(()),
+                Some(Err(error)) => return Err(self.motion_error(error)),
+                Some(Ok(_)) | None => self.device.controller.next_cycle().await,
+            }
+        }
+    }
+}
@@ -0,0 +1,119 @@
+//! This module contains the polarity of the servo (0x607E).
+//!
+//! Inverting the position polarity mirrors the axis: the drive negates target positions, actual
+//! positions and the homing direction. Inverting the velocity polarity negates target and actual
+//! velocities. The software position limits (0x607D) stay in the frame of the drive, targets are
+//! converted to that frame before they're checked.
+
+use super::Servo;
+use crate::device::objects;
+use ethercrab::error::Error as EthercrabError;
+
+/// The bit of the polarity object inverting positions
+const POSITION_POLARITY_BIT: u8 = 1 << 7;
+
+/// The bit of the polarity object inverting velocities
+const VELOCITY_POLARITY_BIT: u8 = 1 << 6;
+
+/// Which values the drive inverts
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct PositionPolarity {
+    /// Whether target and actual positions are negated
+    pub invert_position: bool,
+
+    /// Whether target and actual velocities are negated
+    pub invert_velocity: bool,
+}
+
+impl PositionPolarity {
+    /// Nothing is inverted
+    pub const NORMAL: Self = Self {
+        invert_position: false,
+        invert_velocity: false,
+    };
+
+    /// Positions and velocities are inverted, for mirrored axes
+    pub const INVERTED: Self = Self {
+        invert_position: true,
+        invert_velocity: true,
+    };
+
+    /// Converts the polarity to the value of the object
+    pub const fn raw(self) -> u8 {
+        let mut raw = 0;
+        if self.invert_position {
+            raw |= POSITION_POLARITY_BIT;
+        }
+        if self.invert_velocity {
+            raw |= VELOCITY_POLARITY_BIT;
+        }
+        raw
+    }
+
+    /// Converts the value of the object to a polarity
+    pub const fn from_raw(raw: u8) -> Self {
+        Self {
+            invert_position: raw & POSITION_POLARITY_BIT != 0,
+            invert_velocity: raw & VELOCITY_POLARITY_BIT != 0,
+        }
+    }
+
+    /// Converts a position between the frame of the application and the frame of the drive
+    pub const fn convert_position(self, position: i32) -> i32 {
+        if self.invert_position {
+            position.saturating_neg()
+        } else {
+            position
+        }
+    }
+}
+
+impl<const MAX_DEVICES: usize, const PDI_LENGTH: usize> Servo<'_, '_, MAX_DEVICES, PDI_LENGTH> {
+    /// Writes the polarity of the drive (0x607E).
+    /// Should be set before homing, as it also inverts the homing direction.
+    ///
+    /// # Errors
+    /// Returns an error if the polarity couldn't be written
+    pub async fn set_polarity(&mut self, polarity: PositionPolarity) -> Result<(), EthercrabError> {
+        // Forget the previous polarity, as it's unknown whether the write reached the drive
+        self.polarity = None;
+        self.device
+            .write_object(objects::POLARITY, polarity.raw())
+            .await?;
+        self.polarity = Some(polarity);
+        Ok(())
+    }
+
+    /// Reads the polarity of the drive (0x607E).
+    ///
+    /// # Errors
+    /// Returns an error if the polarity couldn't be read
+    pub async fn polarity(&mut self) -> Result<PositionPolarity, EthercrabError> {
+        let polarity = self
+            .device
+            .read_object(objects::POLARITY)
+            .await
+            .map(PositionPolarity::from_raw)?;
+        self.polarity = Some(polarity);
+        Ok(polarity)
+    }
+
+    /// Returns the polarity of the drive, only the first call communicates with the drive.
+    /// Drives without polarity object don't invert anything.
+    ///
+    /// # Errors
+    /// Returns an error if communication with the drive failed
+    pub(super) async fn cached_polarity(&mut self) -> Result<PositionPolarity, EthercrabError> {
+        if let Some(polarity) = self.polarity {
+            return Ok(polarity);
+        }
+        let polarity = self
+            .device
+            .read_optional_object(objects::POLARITY)
+            .await?
+            .map_or(PositionPolarity::NORMAL, PositionPolarity::from_raw);
+        self.polarity = Some(polarity);
+        Ok(polarity)
+    }
+}
@@ -0,0 +1,55 @@
+//! This module contains the position window, which decides when the drive considers the target
+//! of a positioning move reached.
+//!
+//! The drive sets the motion complete bit once the position stayed within the window (0x6067)
+//! for the window time (0x6068). `Servo::in_position` offers a tighter check on the crate side,
+//! which moves wait for as well if `MoveOptions::in_position_tolerance` is set.
+
+use super::Servo;
+use crate::device::objects;
+use core::time::Duration;
+use ethercrab::error::Error as EthercrabError;
+
+impl<const MAX_DEVICES: usize, const PDI_LENGTH: usize> Servo<'_, '_, MAX_DEVICES, PDI_LENGTH> {
+    /// Writes the position window in increments (0x6067) and the time the position has to stay
+    /// within it in milliseconds (0x6068).
+    ///
+    /// # Errors
+    /// Returns an error if a value couldn't be written
+    pub async fn set_position_window(
+        &mut self,
+        window: u32,
+        time_ms: u16,
+    ) -> Result<(), EthercrabError> {
+        self.device
+            .write_object(objects::POSITION_WINDOW, window)
+            .await?;
+        self.device
+            .write_object(objects::POSITION_WINDOW_TIME, time_ms)
+            .await
+    }
+
+    /// Reads the position window (0x6067) and the time the position has to stay within it
+    /// (0x6068).
+    ///
+    /// # Errors
+    /// Returns an error if a value couldn't be read
+    pub async fn position_window(&mut self) -> Result<(u32, Duration), EthercrabError> {
+        let window = self.device.read_object(objects::POSITION_WINDOW).await?;
+        let time: u16 = self
+            .device
+            .read_object(objects::POSITION_WINDOW_TIME)
+            .await?;
+        Ok((window, Duration::from_millis(u64::from(time))))
+    }
+
+    /// Checks whether the actual position is within the tolerance of the target.
+    /// Unlike the motion complete bit this doesn't depend on the position window of the drive.
+    ///
+    /// # Errors
+    /// Returns an error if the position couldn't be read
+    pub fn in_position(&mut self, target: i32, tolerance: u32) -> Result<bool, EthercrabError> {
+        let position = self.get_position()?;
+        Ok(position.abs_diff(target) <= tolerance)
+    }
+}
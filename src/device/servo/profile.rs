@@ -0,0 +1,326 @@
+//! This module contains the settings of the motion profiles of the servo.
+//!
+//! The last written acceleration and deceleration are remembered, so moves requesting the same
+//! values again don't have to write them to the drive.
+
+use super::{MovementError, Servo};
+use crate::device::objects;
+use core::fmt::{self, Debug, Formatter};
+use ethercrab::error::{Error as EthercrabError, MailboxError};
+
+/// The shape of the acceleration and deceleration ramps of profile movements (0x6086)
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum ProfileType {
+    /// Linear ramps, the acceleration changes in steps
+    Linear,
+
+    /// Sin² ramps, the acceleration changes smoothly
+    SinSquared,
+
+    /// Another profile type supported by the drive
+    Raw(i16),
+}
+
+impl ProfileType {
+    /// Converts the profile type to the value of the object
+    pub const fn raw(self) -> i16 {
+        match self {
+            Self::Linear => 0,
+            Self::SinSquared => 1,
+            Self::Raw(raw) => raw,
+        }
+    }
+
+    /// Converts the value of the object to a profile type
+    pub const fn from_raw(raw: i16) -> Self {
+        match raw {
+            0 => Self::Linear,
+            1 => Self::SinSquared,
+            raw => Self::Raw(raw),
+        }
+    }
+}
+
+/// An error returned while configuring a motion profile
+pub enum ProfileError {
+    /// The quick stop deceleration must not be zero
+    ZeroDeceleration(usize),
+
+    /// The drive doesn't support the profile type
+    UnsupportedProfileType(usize, ProfileType),
+
+    /// Communication with the drive failed
+    Ethercat(usize, EthercrabError),
+}
+
+impl Debug for ProfileError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::ZeroDeceleration(device) => {
+                write!(
+                    f,
+                    "The quick stop deceleration of device {device} can't be zero"
+                )
+            }
+            Self::UnsupportedProfileType(device, profile_type) => {
+                write!(
+                    f,
+                    "Device {device} doesn't support profile type {profile_type:?}"
+                )
+            }
+            Self::Ethercat(device, error) => {
+                write!(
+                    f,
+                    "Configuring the profile of device {device} failed: {error:?}"
+                )
+            }
+        }
+    }
+}
+
+/// The bits of the position option code selecting what relative targets are relative to,
+/// cleared for relative to the previous target
+const RELATIVE_OPTION_MASK: u16 = 0b11;
+
+impl<const MAX_DEVICES: usize, const PDI_LENGTH: usize> Servo<'_, '_, MAX_DEVICES, PDI_LENGTH> {
+    /// Writes the profile acceleration (0x6083) in increments per second squared.
+    /// Doesn't communicate with the drive if the acceleration was already written.
+    ///
+    /// # Errors
+    /// Returns an error if the acceleration couldn't be written
+    pub async fn set_profile_acceleration(
+        &mut self,
+        acceleration: u32,
+    ) -> Result<(), EthercrabError> {
+        if self.profile_acceleration == Some(acceleration) {
+            return Ok(());
+        }
+
+        // Forget the previous value, as it's unknown whether the write reached the drive
+        self.profile_acceleration = None;
+        self.device
+            .write_object(objects::PROFILE_ACCELERATION, acceleration)
+            .await?;
+        self.profile_acceleration = Some(acceleration);
+        Ok(())
+    }
+
+    /// Reads the profile acceleration (0x6083) in increments per second squared from the drive.
+    ///
+    /// # Errors
+    /// Returns an error if the acceleration couldn't be read
+    pub async fn profile_acceleration(&mut self) -> Result<u32, EthercrabError> {
+        let acceleration = self
+            .device
+            .read_object(objects::PROFILE_ACCELERATION)
+            .await?;
+        self.profile_acceleration = Some(acceleration);
+        Ok(acceleration)
+    }
+
+    /// Returns the profile velocity (0x6081) in increments per second the next move uses if it
+    /// doesn't request a velocity. The profile velocity is mapped to the outputs, so this is the
+    /// value last commanded, which is zero after enabling until a velocity has been requested.
+    ///
+    /// # Errors
+    /// Returns an error if another reference to the device exists
+    pub fn profile_velocity(&mut self) -> Result<u32, EthercrabError> {
+        self.commanded_velocity()
+    }
+
+    /// Reads the profile velocity (0x6081) in increments per second from the drive over SDO.
+    /// This is the value stored in the drive, which the outputs overwrite every cycle, see
+    /// `Servo::profile_velocity`.
+    ///
+    /// # Errors
+    /// Returns an error if the velocity couldn't be read
+    pub async fn profile_velocity_in_drive(&mut self) -> Result<u32, EthercrabError> {
+        self.device.read_object(objects::PROFILE_VELOCITY).await
+    }
+
+    /// Writes the profile deceleration (0x6084) in increments per second squared.
+    /// Doesn't communicate with the drive if the deceleration was already written.
+    ///
+    /// # Errors
+    /// Returns an error if the deceleration couldn't be written
+    pub async fn set_profile_deceleration(
+        &mut self,
+        deceleration: u32,
+    ) -> Result<(), EthercrabError> {
+        if self.profile_deceleration == Some(deceleration) {
+            return Ok(());
+        }
+
+        // Forget the previous value, as it's unknown whether the write reached the drive
+        self.profile_deceleration = None;
+        self.device
+            .write_object(objects::PROFILE_DECELERATION, deceleration)
+            .await?;
+        self.profile_deceleration = Some(deceleration);
+        Ok(())
+    }
+
+    /// Reads the profile deceleration (0x6084) in increments per second squared from the drive.
+    ///
+    /// # Errors
+    /// Returns an error if the deceleration couldn't be read
+    pub async fn profile_deceleration(&mut self) -> Result<u32, EthercrabError> {
+        let deceleration = self
+            .device
+            .read_object(objects::PROFILE_DECELERATION)
+            .await?;
+        self.profile_deceleration = Some(deceleration);
+        Ok(deceleration)
+    }
+
+    /// Returns the profile acceleration and deceleration in increments per second squared, only
+    /// reading the values from the drive that aren't known yet. Moves that write their own ramps
+    /// use this to restore the previous ramps afterwards, see `Servo::restore_ramps`.
+    ///
+    /// # Errors
+    /// Returns an error if the acceleration or deceleration couldn't be read
+    pub(super) async fn current_ramps(&mut self) -> Result<(u32, u32), EthercrabError> {
+        let acceleration = match self.profile_acceleration {
+            Some(acceleration) => acceleration,
+            None => self.profile_acceleration().await?,
+        };
+        let deceleration = match self.profile_deceleration {
+            Some(deceleration) => deceleration,
+            None => self.profile_deceleration().await?,
+        };
+        Ok((acceleration, deceleration))
+    }
+
+    /// Writes the profile acceleration and deceleration returned by `Servo::current_ramps` back.
+    /// Doesn't communicate with the drive if the ramps weren't changed.
+    ///
+    /// # Errors
+    /// Returns an error if the acceleration or deceleration couldn't be written
+    pub(super) async fn restore_ramps(
+        &mut self,
+        (acceleration, deceleration): (u32, u32),
+    ) -> Result<(), MovementError> {
+        let id = self.device.id;
+        self.set_profile_acceleration(acceleration)
+            .await
+            .map_err(|error| MovementError::WritingAcceleration(id, error))?;
+        self.set_profile_deceleration(deceleration)
+            .await
+            .map_err(|error| MovementError::WritingDeceleration(id, error))
+    }
+
+    /// Writes the deceleration of a quick stop (0x6085) in increments per second squared.
+    /// The drive only uses this deceleration if the quick stop option code (0x605A) selects a
+    /// ramp with the quick stop deceleration (option code 2 or 6), other option codes stop with
+    /// the profile deceleration or the current limit.
+    ///
+    /// # Errors
+    /// Returns an error if:
+    /// - The deceleration is zero
+    /// - The deceleration couldn't be written
+    pub async fn set_quick_stop_deceleration(
+        &mut self,
+        deceleration: u32,
+    ) -> Result<(), ProfileError> {
+        let id = self.device.id;
+        if deceleration == 0 {
+            return Err(ProfileError::ZeroDeceleration(id));
+        }
+        self.device
+            .write_object(objects::QUICK_STOP_DECELERATION, deceleration)
+            .await
+            .map_err(|error| ProfileError::Ethercat(id, error))
+    }
+
+    /// Reads the deceleration of a quick stop (0x6085) in increments per second squared.
+    ///
+    /// # Errors
+    /// Returns an error if the deceleration couldn't be read
+    pub async fn quick_stop_deceleration(&mut self) -> Result<u32, EthercrabError> {
+        self.device
+            .read_object(objects::QUICK_STOP_DECELERATION)
+            .await
+    }
+
+    /// Writes the shape of the ramps of profile movements (0x6086).
+    /// The value is read back, as drives may ignore profile types they don't support.
+    ///
+    /// # Errors
+    /// Returns an error if:
+    /// - The drive doesn't support the profile type
+    /// - The profile type couldn't be written or read back
+    pub async fn set_motion_profile_type(
+        &mut self,
+        profile_type: ProfileType,
+    ) -> Result<(), ProfileError> {
+        let id = self.device.id;
+        match self
+            .device
+            .write_object(objects::MOTION_PROFILE_TYPE, profile_type.raw())
+            .await
+        {
+            Ok(()) => {}
+            Err(EthercrabError::Mailbox(MailboxError::Aborted { .. })) => {
+                return Err(ProfileError::UnsupportedProfileType(id, profile_type));
+            }
+            Err(error) => return Err(ProfileError::Ethercat(id, error)),
+        }
+
+        // Check whether the drive accepted the profile type
+        let written = self
+            .motion_profile_type()
+            .await
+            .map_err(|error| ProfileError::Ethercat(id, error))?;
+        if written.raw() != profile_type.raw() {
+            return Err(ProfileError::UnsupportedProfileType(id, profile_type));
+        }
+        Ok(())
+    }
+
+    /// Reads the shape of the ramps of profile movements (0x6086).
+    ///
+    /// # Errors
+    /// Returns an error if the profile type couldn't be read
+    pub async fn motion_profile_type(&mut self) -> Result<ProfileType, EthercrabError> {
+        self.device
+            .read_object(objects::MOTION_PROFILE_TYPE)
+            .await
+            .map(ProfileType::from_raw)
+    }
+
+    /// Sets the relative option of the position option code (0x60F2) to relative to the
+    /// previous target, for `MovementMode::RelativeToTarget`. Only communicates with the drive
+    /// the first time, drives without a position option code keep their own interpretation.
+    ///
+    /// # Errors
+    /// Returns an error if the position option code couldn't be read or written
+    pub(super) async fn configure_relative_to_target(&mut self) -> Result<(), EthercrabError> {
+        if self.relative_option_configured {
+            return Ok(());
+        }
+        let code: Result<u16, _> = self.device.read_object(objects::POSITION_OPTION_CODE).await;
+        let result = match code {
+            Ok(code) if code & RELATIVE_OPTION_MASK == 0 => Ok(()),
+            Ok(code) => {
+                self.device
+                    .write_object(objects::POSITION_OPTION_CODE, code & !RELATIVE_OPTION_MASK)
+                    .await
+            }
+            Err(error) => Err(error),
+        };
+
+        // Drives without a (writable) position option code keep their own interpretation
+        match result {
+            Ok(()) => {}
+            Err(EthercrabError::Mailbox(MailboxError::Aborted { .. })) => log::warn!(
+                "The position option code of device {} can't be set, relative targets are \
+                 interpreted as configured in the drive",
+                self.device.id
+            ),
+            Err(error) => return Err(error),
+        }
+        self.relative_option_configured = true;
+        Ok(())
+    }
+}
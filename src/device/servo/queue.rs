@@ -0,0 +1,104 @@
+//! This module contains buffered positioning moves.
+//!
+//! The drive accepts the next profile position setpoint while the current one is still
+//! executing, and starts it as soon as the current one is reached. Queuing setpoints removes the
+//! dead time between the moves.
+
+use super::{
+    motion::Setpoint, MovementError, MovementMode, Servo, MOTION_TIMEOUT, SETPOINT_TIMEOUT,
+};
+use crate::device::{OperationMode, StatusWordBit};
+use std::time::Instant;
+
+/// The number of setpoints the drive can hold: the executing one and one buffered setpoint
+const QUEUE_CAPACITY: u8 = 2;
+
+impl<const MAX_DEVICES: usize, const PDI_LENGTH: usize> Servo<'_, '_, MAX_DEVICES, PDI_LENGTH> {
+    /// Queues a move to the requested absolute position with the requested velocity.
+    /// Returns as soon as the drive latched the setpoint, the move starts once the previous
+    /// queued moves are complete.
+    ///
+    /// # Errors
+    /// Returns an error if:
+    /// - The drive is not enabled or emergency stopped
+    /// - The device couldn't be set to profile position mode
+    /// - The drive can't accept more setpoints
+    /// - The setpoint couldn't be written
+    /// - The device faulted or didn't acknowledge the setpoint in time
+    pub async fn queue_move(&mut self, target: i32, velocity: u32) -> Result<(), MovementError> {
+        if self.device.emergency_stopped() {
+            return Err(MovementError::EmergencyStopped(self.device.id));
+        }
+        if !self.device.ready_state() {
+            return Err(MovementError::DriveDisabled(self.device.id));
+        }
+
+        // Forget the setpoints the drive has finished executing
+        let status = self.device.status_word().map_err(MovementError::Ethercat)?;
+        if status.is_set(StatusWordBit::MotionComplete) {
+            self.queued_moves = 0;
+        } else if !status.is_set(StatusWordBit::AckStartRefReached) {
+            // The buffer is free, only the executing setpoint is left
+            self.queued_moves = self.queued_moves.min(1);
+        }
+        if self.queued_moves >= QUEUE_CAPACITY {
+            return Err(MovementError::QueueFull(self.device.id));
+        }
+
+        // Only switch modes when no move is queued, as it would interrupt the queued moves
+        if self.queued_moves == 0 {
+            self.device
+                .set_mode(OperationMode::ProfilePosition)
+                .await
+                .map_err(MovementError::SetMode)?;
+        }
+        if self.device.controller.verbose() {
+            log::info!(
+                "Queuing movement to position {target} of device {}",
+                self.device.id
+            );
+        }
+
+        // Send the setpoint without replacing the executing one, a drive that doesn't release
+        // the acknowledgement has a full buffer
+        let setpoint = Setpoint {
+            position: target,
+            velocity: Some(velocity),
+            mode: MovementMode::Absolute,
+            change_immediately: false,
+        };
+        self.latch_setpoint(setpoint, Instant::now(), SETPOINT_TIMEOUT)
+            .await
+            .map_err(|error| match error {
+                MovementError::Timeout(device, status)
+                    if status.is_set(StatusWordBit::AckStartRefReached) =>
+                {
+                    MovementError::QueueFull(device)
+                }
+                MovementError::Timeout(device, _) => MovementError::SetpointRejected(device),
+                error => error,
+            })?;
+        self.queued_moves += 1;
+        Ok(())
+    }
+
+    /// Waits until all queued moves are complete.
+    /// Returns right away if no move is running.
+    ///
+    /// # Errors
+    /// Returns an error if the device faulted, has been emergency stopped, or the moves didn't
+    /// complete in time
+    pub async fn wait_all_moves_complete(&mut self) -> Result<(), MovementError> {
+        self.device
+            .wait_for(
+                |status| status.is_set(StatusWordBit::MotionComplete),
+                MOTION_TIMEOUT,
+            )
+            .await?;
+        self.queued_moves = 0;
+        if self.device.controller.verbose() {
+            log::info!("Queued movements of device {} completed", self.device.id);
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,209 @@
+//! This module contains record selection, executing motion records stored in the drive.
+//!
+//! Festo drives store pre-parameterized motion records in a record table, usually maintained
+//! with the Festo Automation Suite. Instead of sending a setpoint, the number of a record is
+//! written and the record is started with the start bit, using the same handshake as a new
+//! setpoint. This is an alternative to the setpoints of `Servo::start_move`, so both shouldn't
+//! be mixed within a single move.
+
+use super::{MovementError, Servo, MOTION_TIMEOUT};
+use crate::device::{objects::Object, ControlBit, OperationMode, StatusWordBit};
+use core::fmt::{self, Debug, Formatter};
+use ethercrab::error::{Error as EthercrabError, MailboxError};
+use std::time::Instant;
+
+/// A motion record stored in the record table of the drive
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct MotionRecord {
+    /// The number of the record
+    pub number: u8,
+
+    /// The target position of the record in increments
+    pub target: i32,
+
+    /// The velocity of the record in increments per second
+    pub velocity: u32,
+}
+
+/// An error returned while executing or reading a motion record
+pub enum RecordError {
+    /// The drive doesn't support record selection
+    Unsupported(usize),
+
+    /// The drive rejected the record number
+    InvalidRecord(usize, u8),
+
+    /// Communication with the drive failed
+    Ethercat(usize, EthercrabError),
+
+    /// The record couldn't be started or the move failed
+    Movement(MovementError),
+}
+
+impl Debug for RecordError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Unsupported(device) => {
+                write!(f, "Device {device} doesn't support record selection")
+            }
+            Self::InvalidRecord(device, record) => {
+                write!(f, "Device {device} has no motion record {record}")
+            }
+            Self::Ethercat(device, error) => write!(
+                f,
+                "Failed to communicate with device {device} during record selection: {error:?}"
+            ),
+            Self::Movement(error) => write!(f, "{error:?}"),
+        }
+    }
+}
+
+impl From<MovementError> for RecordError {
+    fn from(value: MovementError) -> Self {
+        Self::Movement(value)
+    }
+}
+
+impl<const MAX_DEVICES: usize, const PDI_LENGTH: usize> Servo<'_, '_, MAX_DEVICES, PDI_LENGTH> {
+    /// Maps an error of a record object to a record error.
+    /// Aborted transfers mean the drive doesn't know the record.
+    const fn record_error(&self, record: u8, error: EthercrabError) -> RecordError {
+        match error {
+            EthercrabError::Mailbox(MailboxError::Aborted { .. }) => {
+                RecordError::InvalidRecord(self.device.id, record)
+            }
+            error => RecordError::Ethercat(self.device.id, error),
+        }
+    }
+
+    /// Returns the requested record object or an error if the drive doesn't support it
+    const fn record_object(&self, object: Option<Object>) -> Result<Object, RecordError> {
+        match object {
+            Some(object) => Ok(object),
+            None => Err(RecordError::Unsupported(self.device.id)),
+        }
+    }
+
+    /// Starts a motion record stored in the drive.
+    /// The record number is written, after which the record is started with a rising edge of
+    /// the start bit. The start bit is dropped again once the drive acknowledged the record.
+    ///
+    /// # Parameters
+    /// `wait`: Whether to wait until the drive reports the record as completed
+    ///
+    /// # Errors
+    /// Returns an error if:
+    /// - The drive doesn't support record selection or doesn't know the record
+    /// - The drive is disabled or emergency stopped
+    /// - The drive faulted, didn't acknowledge the record, or didn't complete it in time
+    pub async fn execute_record(
+        &mut self,
+        record_number: u8,
+        wait: bool,
+    ) -> Result<(), RecordError> {
+        let select = self.record_object(self.vendor_objects.record_select)?;
+        let id = self.device.id;
+        if self.device.controller.verbose() {
+            log::info!("Executing motion record {record_number} of device {id}");
+        }
+        if self.device.emergency_stopped() {
+            return Err(MovementError::EmergencyStopped(id).into());
+        }
+        if !self.device.ready_state() {
+            return Err(MovementError::DriveDisabled(id).into());
+        }
+        self.device
+            .set_mode(OperationMode::ProfilePosition)
+            .await
+            .map_err(MovementError::SetMode)?;
+
+        // Select the record
+        self.device
+            .write_object(select, record_number)
+            .await
+            .map_err(|error| self.record_error(record_number, error))?;
+
+        // Abort requests and queued setpoints from before this record don't apply to it
+        self.device.controller.take_abort(id);
+        self.paused = false;
+        self.queued_moves = 0;
+
+        // Wait until the drive finished the previous handshake, so it sees a rising edge
+        let started = Instant::now();
+        let result = self
+            .device
+            .wait_for_motion(
+                |status| !status.is_set(StatusWordBit::AckStartRefReached),
+                started,
+                MOTION_TIMEOUT,
+            )
+            .await;
+        result.map_err(|error| self.motion_error(error))?;
+
+        // Raise the start bit until the drive acknowledged the record
+        self.device
+            .update_control_word(|control| {
+                control
+                    .without_control()
+                    .without(ControlBit::Halt)
+                    .with(ControlBit::Control4)
+            })
+            .map_err(MovementError::Ethercat)?;
+        self.device.controller.next_cycle().await;
+        let result = self
+            .device
+            .wait_for_motion(
+                |status| status.is_set(StatusWordBit::AckStartRefReached),
+                started,
+                MOTION_TIMEOUT,
+            )
+            .await;
+        result.map_err(|error| self.motion_error(error))?;
+        self.device
+            .update_control_word(|control| control.without(ControlBit::Control4))
+            .map_err(MovementError::Ethercat)?;
+        if !wait {
+            return Ok(());
+        }
+
+        // Wait until the drive reports the record as completed
+        let result = self
+            .device
+            .wait_for_motion(
+                |status| status.is_set(StatusWordBit::MotionComplete),
+                started,
+                MOTION_TIMEOUT,
+            )
+            .await;
+        result.map_err(|error| self.motion_error(error))?;
+        Ok(())
+    }
+
+    /// Reads the target position and velocity of a motion record stored in the drive.
+    ///
+    /// # Errors
+    /// Returns an error if the drive doesn't support record selection, doesn't know the record,
+    /// or communication failed
+    pub async fn read_record(&mut self, record_number: u8) -> Result<MotionRecord, RecordError> {
+        let target = self.record_object(self.vendor_objects.record_target)?;
+        let velocity = self.record_object(self.vendor_objects.record_velocity)?;
+
+        // The sub index of the record table objects selects the record
+        let target = self
+            .device
+            .read_object(Object::new(target.index, record_number))
+            .await
+            .map_err(|error| self.record_error(record_number, error))?;
+        let velocity = self
+            .device
+            .read_object(Object::new(velocity.index, record_number))
+            .await
+            .map_err(|error| self.record_error(record_number, error))?;
+        Ok(MotionRecord {
+            number: record_number,
+            target,
+            velocity,
+        })
+    }
+}
@@ -0,0 +1,169 @@
+//! This module contains registration moves, which search for a mark with a touch probe and stop
+//! a fixed distance past the latched position, like placing labels.
+//!
+//! The touch probe status is read over SDO, so the latch is noticed a few cycles after the drive
+//! captured it. The latched position itself is exact, so the final position only depends on the
+//! drive being able to stop at the offset after the latency.
+
+use super::{
+    motion::MoveOptions,
+    touch_probe::{TouchProbeConfig, TouchProbeEdge},
+    MovementError, Servo,
+};
+use core::fmt::{self, Debug, Formatter};
+use ethercrab::error::Error as EthercrabError;
+
+/// The result of a registration move
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum RegisteredMoveResult {
+    /// The touch probe latched and the servo stopped at the offset from the latched position
+    Registered {
+        /// The position latched by the touch probe
+        latched: i32,
+
+        /// The position at which the servo stopped
+        position: i32,
+    },
+
+    /// The servo reached the search target without the touch probe latching
+    NoTrigger {
+        /// The position at which the servo stopped
+        position: i32,
+    },
+}
+
+/// An error returned by a registration move
+pub enum RegistrationError {
+    /// The touch probe configuration doesn't latch on any edge
+    NoEdge(usize),
+
+    /// Arming, reading or disarming the touch probe failed
+    TouchProbe(EthercrabError),
+
+    /// The move failed
+    Movement(MovementError),
+}
+
+impl Debug for RegistrationError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::NoEdge(device) => write!(
+                f,
+                "The touch probe of device {device} has to latch on the rising or falling edge"
+            ),
+            Self::TouchProbe(error) => write!(f, "Touch probe failed: {error:?}"),
+            Self::Movement(error) => write!(f, "{error:?}"),
+        }
+    }
+}
+
+impl From<MovementError> for RegistrationError {
+    fn from(value: MovementError) -> Self {
+        Self::Movement(value)
+    }
+}
+
+impl<const MAX_DEVICES: usize, const PDI_LENGTH: usize> Servo<'_, '_, MAX_DEVICES, PDI_LENGTH> {
+    /// Moves towards the search target until the touch probe latches, then changes the target
+    /// on the fly to the latched position plus `offset_after_latch`. The sign of the offset has
+    /// to match the direction of the search, or the servo reverses to reach it. The rising edge
+    /// is used if the probe latches on both edges. The touch probe is disarmed afterwards.
+    ///
+    /// # Errors
+    /// Returns an error if:
+    /// - The touch probe doesn't latch on any edge
+    /// - The touch probe couldn't be armed, read or disarmed
+    /// - The move couldn't be started, changed or failed
+    ///
+    /// # Returns
+    /// The latched and final positions, or the final position if the search target was reached
+    /// without the touch probe latching
+    pub async fn move_registered(
+        &mut self,
+        search_target: i32,
+        velocity: u32,
+        probe: TouchProbeConfig,
+        offset_after_latch: i32,
+    ) -> Result<RegisteredMoveResult, RegistrationError> {
+        let edge = match (probe.rising_edge, probe.falling_edge) {
+            (true, _) => TouchProbeEdge::Rising,
+            (false, true) => TouchProbeEdge::Falling,
+            (false, false) => return Err(RegistrationError::NoEdge(self.device.id)),
+        };
+        self.arm_touch_probe(probe)
+            .await
+            .map_err(RegistrationError::TouchProbe)?;
+        let result = self
+            .run_registered(search_target, velocity, probe, edge, offset_after_latch)
+            .await;
+
+        // Disarm the probe, even if the move failed, so it doesn't latch outside of the search
+        let disarmed = self.disarm_touch_probe(probe.probe).await;
+        let result = result?;
+        disarmed.map_err(RegistrationError::TouchProbe)?;
+        Ok(result)
+    }
+
+    /// Runs the search move with the armed touch probe.
+    ///
+    /// # Errors
+    /// Returns an error if the touch probe couldn't be read or the move failed
+    async fn run_registered(
+        &mut self,
+        search_target: i32,
+        velocity: u32,
+        probe: TouchProbeConfig,
+        edge: TouchProbeEdge,
+        offset_after_latch: i32,
+    ) -> Result<RegisteredMoveResult, RegistrationError> {
+        let options = MoveOptions::new().with_velocity(velocity);
+        let mut handle = self.start_move(search_target, options.clone()).await?;
+
+        // Check the touch probe every cycle until it latched or the search ended
+        let latched = loop {
+            let done = handle.is_done();
+            let servo = handle.servo();
+            let status = servo
+                .touch_probe_status(probe.probe)
+                .await
+                .map_err(RegistrationError::TouchProbe)?;
+            if status.stored(edge) {
+                let latched = servo
+                    .touch_probe_position(probe.probe, edge)
+                    .await
+                    .map_err(RegistrationError::TouchProbe)?;
+                break Some((latched, done));
+            }
+            if done {
+                break None;
+            }
+            handle.wait_cycle().await;
+        };
+
+        let Some((latched, done)) = latched else {
+            handle.await?;
+            let position = self.get_position().map_err(MovementError::Ethercat)?;
+            return Ok(RegisteredMoveResult::NoTrigger { position });
+        };
+
+        // Stop at the offset from the latched position, starting a new move if the search
+        // already ended in the cycles it took to read the latched position
+        let target = latched.saturating_add(offset_after_latch);
+        let updated = if done {
+            false
+        } else {
+            match handle.update_target(target).await {
+                Ok(()) => true,
+                Err(MovementError::NoActiveMove(_)) => false,
+                Err(error) => return Err(error.into()),
+            }
+        };
+        handle.await?;
+        if !updated {
+            self.start_move(target, options).await?.await?;
+        }
+        let position = self.get_position().map_err(MovementError::Ethercat)?;
+        Ok(RegisteredMoveResult::Registered { latched, position })
+    }
+}
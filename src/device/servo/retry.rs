@@ -0,0 +1,153 @@
+//! This module contains the automatic retry of moves after a transient fault.
+//!
+//! Some faults are known to be spurious, like a following error caused by a mechanical
+//! resonance. The recovery is always the same: reset the fault, enable the drive again and
+//! repeat the move. With a `RetryPolicy` in the `MoveOptions`, `Servo::move_with` does this
+//! itself. Moves started with `Servo::start_move` aren't retried, as their handle is owned by
+//! the caller.
+
+use super::{motion::MoveOptions, MovementError, MovementMode, Servo};
+use crate::device::objects;
+use core::time::Duration;
+
+/// When and how often a failed move is retried
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct RetryPolicy {
+    /// The maximum number of attempts, including the first one
+    pub max_attempts: u32,
+
+    /// The error codes (0x603F) of the faults that are retried, every fault if empty
+    pub only_fault_codes: Vec<u16>,
+
+    /// The time to wait after recovering, before the move is repeated
+    pub backoff: Duration,
+}
+
+impl RetryPolicy {
+    /// Creates a policy retrying every fault, making at most `max_attempts` attempts
+    #[must_use]
+    pub const fn new(max_attempts: u32) -> Self {
+        Self {
+            max_attempts,
+            only_fault_codes: Vec::new(),
+            backoff: Duration::ZERO,
+        }
+    }
+
+    /// Limits the retries to faults with one of the error codes
+    #[must_use]
+    pub fn with_only_fault_codes(mut self, codes: impl Into<Vec<u16>>) -> Self {
+        self.only_fault_codes = codes.into();
+        self
+    }
+
+    /// Sets the time to wait after recovering, before the move is repeated
+    #[must_use]
+    pub const fn with_backoff(mut self, backoff: Duration) -> Self {
+        self.backoff = backoff;
+        self
+    }
+
+    /// Checks whether a fault with the error code is retried by this policy
+    fn retries(&self, code: Option<u16>) -> bool {
+        self.only_fault_codes.is_empty()
+            || code.is_some_and(|code| self.only_fault_codes.contains(&code))
+    }
+}
+
+impl<const MAX_DEVICES: usize, const PDI_LENGTH: usize> Servo<'_, '_, MAX_DEVICES, PDI_LENGTH> {
+    /// Moves to the target and waits until the motion is complete, retrying the move after a
+    /// fault matching the retry policy of the options.
+    /// Relative moves are repeated as absolute moves to the original end position, so a retry
+    /// doesn't move further than requested.
+    ///
+    /// # Errors
+    /// Returns the error of the move if it isn't retried, or the errors of all attempts if
+    /// every attempt failed or the drive couldn't be recovered
+    pub(super) async fn move_with_retry(
+        &mut self,
+        target: i32,
+        options: &MoveOptions,
+    ) -> Result<(), MovementError> {
+        let Some(policy) = &options.retry else {
+            return self.start_move(target, options.clone()).await?.await;
+        };
+        let policy = policy.clone();
+        let id = self.device.id;
+        let mut attempt_options = options.clone();
+        attempt_options.retry = None;
+
+        // Retries always move to the end position of the first attempt
+        let retry_target = match options.mode {
+            MovementMode::Absolute => target,
+            mode => mode.end_position(
+                target,
+                self.get_position().map_err(MovementError::Ethercat)?,
+                self.previous_target().map_err(MovementError::Ethercat)?,
+            ),
+        };
+        let retry_options = attempt_options
+            .clone()
+            .with_mode(MovementMode::Absolute)
+            .with_allow_unreferenced(true);
+
+        let mut attempts = Vec::new();
+        let mut attempt_target = target;
+        loop {
+            let error = match self.start_move(attempt_target, attempt_options).await {
+                Ok(handle) => match handle.await {
+                    Ok(()) => return Ok(()),
+                    Err(error) => error,
+                },
+                Err(error) => error,
+            };
+
+            // Only retry faults matching the policy, while attempts are left
+            let is_fault = matches!(error, MovementError::Fault { .. });
+            if attempts.is_empty() && !is_fault {
+                return Err(error);
+            }
+            let code = if is_fault {
+                self.device
+                    .read_optional_object(objects::ERROR_CODE)
+                    .await
+                    .ok()
+                    .flatten()
+            } else {
+                None
+            };
+            attempts.push(error);
+            let attempt_count = u32::try_from(attempts.len()).unwrap_or(u32::MAX);
+            if !is_fault || !policy.retries(code) || attempt_count >= policy.max_attempts {
+                if attempts.len() == 1 {
+                    return Err(attempts.remove(0));
+                }
+                return Err(MovementError::RetryFailed {
+                    device: id,
+                    attempts,
+                    recovery: None,
+                });
+            }
+
+            // Recover from the fault and wait before repeating the move
+            log::warn!(
+                "Move of device {id} faulted with error code {code:?}, retrying (attempt {} of {})",
+                attempt_count + 1,
+                policy.max_attempts
+            );
+            if let Err(error) = self.device.recover().await {
+                return Err(MovementError::RetryFailed {
+                    device: id,
+                    attempts,
+                    recovery: Some(error),
+                });
+            }
+            if !policy.backoff.is_zero() {
+                self.dwell(policy.backoff).await?;
+            }
+            attempt_target = retry_target;
+            attempt_options = retry_options.clone();
+        }
+    }
+}
@@ -0,0 +1,84 @@
+//! This module contains the standstill detection of the servo.
+//!
+//! The drive reports the end of a motion through the target reached bit, whose meaning depends
+//! on the mode. The standstill detection only looks at the actual velocity instead: the servo
+//! stands still once the velocity stayed within the moving deadband for the standstill time.
+//! This works in every mode, also after a quick stop or halt, or before engaging a clamp.
+
+use super::{events::MotionEventKind, MovementError, Servo, MOTION_TIMEOUT};
+use core::time::Duration;
+use ethercrab::error::Error as EthercrabError;
+use std::time::Instant;
+
+impl<const MAX_DEVICES: usize, const PDI_LENGTH: usize> Servo<'_, '_, MAX_DEVICES, PDI_LENGTH> {
+    /// Checks whether the actual velocity is within the moving deadband right now.
+    /// Use `Servo::wait_standstill` to make sure the servo stays still.
+    ///
+    /// # Errors
+    /// Returns an error if another reference to the device exists
+    pub fn is_standstill(&mut self) -> Result<bool, EthercrabError> {
+        self.is_moving().map(|moving| !moving)
+    }
+
+    /// Waits until the actual velocity stayed within the moving deadband for the standstill
+    /// time, independent of the mode and the target reached bit. Faults don't stop the wait,
+    /// as a faulted servo can still be coasting.
+    ///
+    /// # Errors
+    /// Returns an error if:
+    /// - Another reference to the device exists
+    /// - The servo didn't stand still within the timeout
+    ///
+    /// # Returns
+    /// The position the servo stands still at
+    pub async fn wait_standstill(&mut self, timeout: Duration) -> Result<i32, MovementError> {
+        let start = Instant::now();
+        let mut still_since: Option<Instant> = None;
+        loop {
+            let status = self.snapshot().map_err(MovementError::Ethercat)?;
+            if status.velocity.unsigned_abs() <= self.config.moving_deadband {
+                let since = *still_since.get_or_insert_with(Instant::now);
+                if since.elapsed() >= self.config.standstill_time {
+                    return Ok(status.position);
+                }
+            } else {
+                still_since = None;
+            }
+            if start.elapsed() >= timeout {
+                return Err(MovementError::Timeout(self.device.id, status.status));
+            }
+            self.device.controller.next_cycle().await;
+        }
+    }
+
+    /// Sets the time the velocity has to stay within the moving deadband before the servo is
+    /// considered standing still by `Servo::wait_standstill`
+    pub fn set_standstill_time(&mut self, time: Duration) {
+        self.config.standstill_time = time;
+    }
+
+    /// Returns the time the velocity has to stay within the moving deadband before the servo
+    /// is considered standing still
+    #[must_use]
+    pub const fn standstill_time(&self) -> Duration {
+        self.config.standstill_time
+    }
+
+    /// Stops the servo as fast as the drive allows with `Device::emergency_stop` and waits
+    /// until it stands still. Motion is only possible again after calling
+    /// `Device::clear_emergency` and `Device::recover`.
+    ///
+    /// # Errors
+    /// Returns an error if the position couldn't be read or the servo didn't stop in time
+    ///
+    /// # Returns
+    /// The position the servo stopped at
+    pub async fn quick_stop(&mut self) -> Result<i32, MovementError> {
+        self.device.emergency_stop();
+        self.paused = false;
+        self.queued_moves = 0;
+        let position = self.wait_standstill(MOTION_TIMEOUT).await?;
+        self.emit(MotionEventKind::Halted);
+        Ok(position)
+    }
+}
@@ -0,0 +1,328 @@
+//! This module contains the `ServoStatus`, a consistent snapshot of the process image of a servo
+//! drive.
+
+use super::Servo;
+use crate::{
+    device::{objects, OperationMode, StatusWord, StatusWordBit, UnknownMode},
+    pdo::{self, PdoValue},
+};
+use core::fmt::{self, Debug, Formatter};
+use ethercrab::error::Error as EthercrabError;
+
+/// An error returned while reading the operation mode from the process image
+pub enum ModeError {
+    /// The process image holds a byte that isn't a known operation mode
+    Unknown(usize, UnknownMode),
+
+    /// The process image couldn't be read
+    Ethercat(usize, EthercrabError),
+}
+
+impl Debug for ModeError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Unknown(device, UnknownMode(raw)) => {
+                write!(f, "Device {device} is in unknown operation mode {raw}")
+            }
+            Self::Ethercat(device, error) => write!(
+                f,
+                "Reading the operation mode of device {device} failed: {error:?}"
+            ),
+        }
+    }
+}
+
+/// The inputs and requested mode of a servo drive, all read from the same cycle
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct ServoStatus {
+    /// The status word
+    pub status: StatusWord,
+
+    /// The operation mode the drive is in, `None` if the drive reports an unknown mode
+    pub mode: Option<OperationMode>,
+
+    /// The operation mode requested from the drive, `None` if the output holds an unknown mode
+    pub commanded_mode: Option<OperationMode>,
+
+    /// The actual position in increments
+    pub position: i32,
+
+    /// The actual velocity in increments per second
+    pub velocity: i32,
+
+    /// The actual torque in thousandths of the rated torque
+    pub torque: i16,
+
+    /// The diagnosis message of the drive
+    pub diagnosis: u32,
+
+    /// Whether the drive is homed
+    pub homed: bool,
+
+    /// The cycle the values were read in, see `Controller::cycle_count`
+    pub cycle: u64,
+}
+
+impl ServoStatus {
+    /// Decodes the process image of a servo drive
+    pub(crate) fn from_process_image(inputs: &[u8], outputs: &[u8], cycle: u64) -> Self {
+        let status = StatusWord::new(u16::read(&inputs[pdo::input::STATUS_WORD..]));
+        Self {
+            status,
+            mode: OperationMode::from_raw(inputs[pdo::input::MODES_OF_OPERATION_DISPLAY]),
+            commanded_mode: OperationMode::from_raw(outputs[pdo::output::MODES_OF_OPERATION]),
+            position: i32::read(&inputs[pdo::input::POSITION_ACTUAL_VALUE..]),
+            velocity: i32::read(&inputs[pdo::input::VELOCITY_ACTUAL_VALUE..]),
+            torque: i16::read(&inputs[pdo::input::TORQUE_ACTUAL_VALUE..]),
+            diagnosis: u32::read(&inputs[pdo::input::DIAGNOSIS_MESSAGE..]),
+            homed: status.is_set(StatusWordBit::DriveHomed),
+            cycle,
+        }
+    }
+}
+
+/// The inputs of a servo drive extended with the demanded position and velocity.
+///
+/// All values are read from the same cycle. The demand values are only available if they are
+/// part of the PDO mapping, see the `demand-pdo` feature.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct ServoTelemetry {
+    /// The status, mode, position, velocity, torque and diagnosis
+    pub status: ServoStatus,
+
+    /// The demanded position in increments, `None` if it isn't mapped
+    pub position_demand: Option<i32>,
+
+    /// The demanded velocity in increments per second, `None` if it isn't mapped
+    pub velocity_demand: Option<i32>,
+}
+
+impl ServoTelemetry {
+    /// Decodes the process image of a servo drive, including the mapped demand values
+    pub(crate) fn from_process_image(inputs: &[u8], outputs: &[u8], cycle: u64) -> Self {
+        let read_mapped =
+            |object| pdo::input_offset(object).map(|offset| i32::read(&inputs[offset..]));
+        Self {
+            status: ServoStatus::from_process_image(inputs, outputs, cycle),
+            position_demand: read_mapped(objects::POSITION_DEMAND_VALUE),
+            velocity_demand: read_mapped(objects::VELOCITY_DEMAND_VALUE),
+        }
+    }
+}
+
+/// Reads the target position commanded in the output process image (0x607A)
+fn commanded_target_in(outputs: &[u8]) -> i32 {
+    i32::read(&outputs[pdo::output::TARGET_POSITION..])
+}
+
+/// Reads the profile velocity commanded in the output process image (0x6081)
+fn commanded_velocity_in(outputs: &[u8]) -> u32 {
+    u32::read(&outputs[pdo::output::PROFILE_VELOCITY..])
+}
+
+impl<const MAX_DEVICES: usize, const PDI_LENGTH: usize> Servo<'_, '_, MAX_DEVICES, PDI_LENGTH> {
+    /// Reads the status, mode, position, velocity, torque and diagnosis of the drive at once,
+    /// together with the requested mode.
+    /// All values are read from the same cycle.
+    ///
+    /// # Errors
+    /// Returns an error if another reference to the device exists
+    pub fn snapshot(&mut self) -> Result<ServoStatus, EthercrabError> {
+        let cycle = self.device.controller.cycle_count();
+        self.device.with_process_image(|inputs, outputs| {
+            ServoStatus::from_process_image(inputs, outputs, cycle)
+        })
+    }
+
+    /// Reads the status and the demanded position and velocity of the drive at once.
+    /// All values are read from the same cycle, the demand values are `None` unless mapped.
+    ///
+    /// # Errors
+    /// Returns an error if another reference to the device exists
+    pub fn telemetry(&mut self) -> Result<ServoTelemetry, EthercrabError> {
+        let cycle = self.device.controller.cycle_count();
+        self.device.with_process_image(|inputs, outputs| {
+            ServoTelemetry::from_process_image(inputs, outputs, cycle)
+        })
+    }
+
+    /// Reads the position demanded by the trajectory generator in increments.
+    /// Read from the process image if mapped, over SDO otherwise.
+    ///
+    /// # Errors
+    /// Returns an error if the value couldn't be read
+    pub async fn position_demand(&mut self) -> Result<i32, EthercrabError> {
+        self.device
+            .read_mapped_object(objects::POSITION_DEMAND_VALUE)
+            .await
+    }
+
+    /// Reads the velocity demanded by the trajectory generator in increments per second.
+    /// Read from the process image if mapped, over SDO otherwise.
+    ///
+    /// # Errors
+    /// Returns an error if the value couldn't be read
+    pub async fn velocity_demand(&mut self) -> Result<i32, EthercrabError> {
+        self.device
+            .read_mapped_object(objects::VELOCITY_DEMAND_VALUE)
+            .await
+    }
+
+    /// Reads the operation mode the drive is in from the input process image (0x6061).
+    ///
+    /// # Errors
+    /// Returns an error if the inputs couldn't be read or the drive reports an unknown mode
+    pub fn current_mode(&mut self) -> Result<OperationMode, ModeError> {
+        let id = self.device.id;
+        let raw: u8 = self
+            .device
+            .read_input(pdo::input::MODES_OF_OPERATION_DISPLAY)
+            .map_err(|error| ModeError::Ethercat(id, error))?;
+        OperationMode::try_from(raw).map_err(|error| ModeError::Unknown(id, error))
+    }
+
+    /// Reads the operation mode requested from the drive back from the output process image
+    /// (0x6060). The drive may not have switched to it yet, see `Servo::current_mode`.
+    ///
+    /// # Errors
+    /// Returns an error if the outputs couldn't be read or hold an unknown mode
+    pub fn commanded_mode(&mut self) -> Result<OperationMode, ModeError> {
+        let id = self.device.id;
+        let raw: u8 = self
+            .device
+            .read_output(pdo::output::MODES_OF_OPERATION)
+            .map_err(|error| ModeError::Ethercat(id, error))?;
+        OperationMode::try_from(raw).map_err(|error| ModeError::Unknown(id, error))
+    }
+
+    /// Reads the target position last commanded to the drive back from the output process
+    /// image (0x607A) in increments. Holds the distance instead of the absolute target after a
+    /// move relative to the previous target, see `MovementMode::RelativeToTarget`.
+    ///
+    /// # Errors
+    /// Returns an error if another reference to the device exists
+    pub fn commanded_target(&mut self) -> Result<i32, EthercrabError> {
+        self.device
+            .with_process_image(|_, outputs| commanded_target_in(outputs))
+    }
+
+    /// Returns the absolute target of the previous setpoint in increments, which the next move
+    /// relative to the previous target adds its distance to. This is the commanded target,
+    /// unless the previous setpoint was relative to the previous target itself.
+    ///
+    /// # Errors
+    /// Returns an error if another reference to the device exists
+    pub fn previous_target(&mut self) -> Result<i32, EthercrabError> {
+        self.relative_target
+            .map_or_else(|| self.commanded_target(), Ok)
+    }
+
+    /// Reads the profile velocity last commanded to the drive back from the output process
+    /// image (0x6081) in increments per second.
+    ///
+    /// # Errors
+    /// Returns an error if another reference to the device exists
+    pub fn commanded_velocity(&mut self) -> Result<u32, EthercrabError> {
+        self.device
+            .with_process_image(|_, outputs| commanded_velocity_in(outputs))
+    }
+
+    /// Checks whether the target of the current mode is reached (status bit 10).
+    /// In the positioning and homing modes this means the target position is reached, in the
+    /// velocity modes that the target velocity is reached. While halted it means the servo
+    /// stands still.
+    ///
+    /// # Errors
+    /// Returns an error if another reference to the device exists
+    pub fn target_reached(&mut self) -> Result<bool, EthercrabError> {
+        self.snapshot()
+            .map(|status| status.status.is_set(StatusWordBit::MotionComplete))
+    }
+
+    /// Checks whether the servo is moving, which is the case while the actual velocity is
+    /// outside of the deadband. Unlike `Servo::target_reached` this doesn't depend on the mode.
+    ///
+    /// # Errors
+    /// Returns an error if another reference to the device exists
+    pub fn is_moving(&mut self) -> Result<bool, EthercrabError> {
+        let deadband = self.config.moving_deadband;
+        self.snapshot()
+            .map(|status| status.velocity.unsigned_abs() > deadband)
+    }
+
+    /// Sets the velocity in increments per second below which the servo is considered standing
+    /// still by `Servo::is_moving`
+    pub fn set_moving_deadband(&mut self, deadband: u32) {
+        self.config.moving_deadband = deadband;
+    }
+
+    /// Returns the velocity in increments per second below which the servo is considered
+    /// standing still
+    #[must_use]
+    pub const fn moving_deadband(&self) -> u32 {
+        self.config.moving_deadband
+    }
+
+    /// Checks whether the drive is homed.
+    ///
+    /// # Errors
+    /// Returns an error if the status word couldn't be read
+    pub fn is_homed(&mut self) -> Result<bool, EthercrabError> {
+        self.device
+            .status_word()
+            .map(|status| status.is_set(StatusWordBit::DriveHomed))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    //! Tests of reading the commanded values back from a fake output process image
+
+    use super::*;
+    use crate::device::{
+        servo::{motion::Setpoint, MovementMode},
+        simulation::SimulatedDrive,
+        OutputImage,
+    };
+
+    /// The commanded values are read from the bytes at the offsets of the PDO mapping
+    #[test]
+    fn commanded_values_at_mapping_offsets() {
+        let drive = SimulatedDrive::new(100);
+        let outputs: Vec<u8> = (1..=drive.outputs().len())
+            .map(|byte| u8::try_from(byte).unwrap())
+            .collect();
+
+        // The target position follows the control word and the mode, then the profile velocity
+        assert_eq!(commanded_target_in(&outputs), 0x0706_0504);
+        assert_eq!(commanded_velocity_in(&outputs), 0x0B0A_0908);
+    }
+
+    /// The setpoint written for a move is read back, a setpoint without a velocity keeps the
+    /// previously commanded velocity
+    #[test]
+    fn commanded_setpoint_read_back() {
+        let mut drive = SimulatedDrive::new(100);
+        let mut setpoint = Setpoint {
+            position: -5_000,
+            velocity: Some(1_234),
+            mode: MovementMode::Absolute,
+            change_immediately: true,
+        };
+        drive
+            .apply_outputs(|outputs| setpoint.write(outputs))
+            .unwrap();
+        assert_eq!(commanded_target_in(drive.outputs()), -5_000);
+        assert_eq!(commanded_velocity_in(drive.outputs()), 1_234);
+
+        setpoint.position = i32::MAX;
+        setpoint.velocity = None;
+        drive
+            .apply_outputs(|outputs| setpoint.write(outputs))
+            .unwrap();
+        assert_eq!(commanded_target_in(drive.outputs()), i32::MAX);
+        assert_eq!(commanded_velocity_in(drive.outputs()), 1_234);
+    }
+}
@@ -0,0 +1,134 @@
+//! This module contains the limit and reference switches of the servo.
+//!
+//! The switches are decoded from the digital inputs (0x60FD), which are read from the process
+//! image when mapped and over SDO otherwise. Jogs and homing consult the switches, so an error
+//! names the switch that stopped the servo instead of only reporting a fault.
+
+use super::{JoggingDirection, JoggingError, Servo};
+use crate::{
+    device::{drive_io::DigitalInputs, objects, Drive, WaitTimeout},
+    pdo,
+};
+use ethercrab::error::Error as EthercrabError;
+
+/// The state of the limit and reference switches of a drive
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct LimitSwitches {
+    /// Whether the negative limit switch is active
+    pub negative: bool,
+
+    /// Whether the positive limit switch is active
+    pub positive: bool,
+
+    /// Whether the home (reference) switch is active
+    pub home: bool,
+
+    /// Whether safe torque off is active, if the input reporting it is configured
+    pub sto: Option<bool>,
+}
+
+impl LimitSwitches {
+    /// Decodes the switches from the digital inputs.
+    ///
+    /// # Parameters
+    /// `sto_input`: The bit of the digital inputs reporting safe torque off, if any
+    #[must_use]
+    pub const fn from_inputs(inputs: DigitalInputs, sto_input: Option<u8>) -> Self {
+        Self {
+            negative: inputs.negative_limit_switch,
+            positive: inputs.positive_limit_switch,
+            home: inputs.home_switch,
+            sto: match sto_input {
+                Some(bit) if bit < 32 => Some(inputs.raw & (1 << bit) != 0),
+                _ => None,
+            },
+        }
+    }
+
+    /// Returns whether either limit switch is active
+    #[must_use]
+    pub const fn on_limit(&self) -> bool {
+        self.negative || self.positive
+    }
+
+    /// Returns whether the limit switch in the direction is active
+    #[must_use]
+    pub const fn in_direction(&self, direction: JoggingDirection) -> bool {
+        match direction {
+            JoggingDirection::Positive => self.positive,
+            JoggingDirection::Negative => self.negative,
+        }
+    }
+
+    /// Describes the active limit switches, for use in error messages
+    #[must_use]
+    pub const fn description(&self) -> &'static str {
+        match (self.negative, self.positive) {
+            (true, true) => "both limit switches active",
+            (true, false) => "negative limit switch active",
+            (false, true) => "positive limit switch active",
+            (false, false) => "no limit switch active",
+        }
+    }
+}
+
+/// Reads the limit switches of a drive from the process image, without falling back to SDO.
+///
+/// # Errors
+/// Returns an error if the inputs couldn't be read
+///
+/// # Returns
+/// The switches, if the digital inputs are mapped
+pub(super) fn mapped_limit_switches(
+    drive: &mut impl Drive,
+    sto_input: Option<u8>,
+) -> Result<Option<LimitSwitches>, EthercrabError> {
+    let Some(offset) = pdo::input_offset(objects::DIGITAL_INPUTS) else {
+        return Ok(None);
+    };
+    let raw = drive.read_input(offset)?;
+    Ok(Some(LimitSwitches::from_inputs(
+        DigitalInputs::from_raw(raw),
+        sto_input,
+    )))
+}
+
+impl<const MAX_DEVICES: usize, const PDI_LENGTH: usize> Servo<'_, '_, MAX_DEVICES, PDI_LENGTH> {
+    /// Reads the limit and reference switches of the drive.
+    ///
+    /// # Errors
+    /// Returns an error if the digital inputs couldn't be read
+    pub async fn limit_switches(&mut self) -> Result<LimitSwitches, EthercrabError> {
+        let inputs = self.device.digital_inputs().await?;
+        Ok(LimitSwitches::from_inputs(inputs, self.config.sto_input))
+    }
+
+    /// Returns whether either limit switch of the drive is active.
+    ///
+    /// # Errors
+    /// Returns an error if the digital inputs couldn't be read
+    pub async fn on_limit(&mut self) -> Result<bool, EthercrabError> {
+        Ok(self.limit_switches().await?.on_limit())
+    }
+
+    /// Converts an error while jogging, naming the limit switch if the drive faulted because
+    /// the jog ran into it. Other faults get the fault details attached.
+    pub(super) async fn jog_error(&mut self, error: WaitTimeout) -> JoggingError {
+        let WaitTimeout::Fault(device, status) = error else {
+            return error.into();
+        };
+        if let Some(direction) = self.jog_direction {
+            if let Ok(switches) = self.limit_switches().await {
+                if switches.in_direction(direction) {
+                    return JoggingError::LimitSwitch { device, switches };
+                }
+            }
+        }
+        JoggingError::Fault {
+            device,
+            status,
+            details: self.fault_details().await.ok(),
+        }
+    }
+}
@@ -0,0 +1,223 @@
+//! This module contains teach-in: jogging an axis to a set of positions, storing them, and
+//! replaying them as a sequence of moves.
+//!
+//! `TeachRecorder` stores the positions of a single servo, `PoseRecorder` stores poses of several
+//! servos captured in the same cycle. With the `serde` feature both can be stored, for example
+//! as JSON, and loaded again.
+
+use super::{motion::MoveOptions, MovementError, MovementMode, Servo};
+use ethercrab::error::Error as EthercrabError;
+
+/// A position stored while teaching a single servo
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct TeachPoint {
+    /// The name of the point
+    pub name: String,
+
+    /// The absolute position in increments
+    pub position: i32,
+
+    /// The profile velocity used to move to the point, the velocity of the options if `None`
+    pub velocity: Option<u32>,
+}
+
+/// A pose of several servos, all positions captured in the same cycle
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct TeachPose {
+    /// The name of the pose
+    pub name: String,
+
+    /// The absolute position of each servo in increments, in the order of capturing
+    pub positions: Vec<i32>,
+
+    /// The profile velocity used to move to the pose, the velocity of the options if `None`
+    pub velocity: Option<u32>,
+}
+
+/// Records the positions of a single servo and replays them
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct TeachRecorder {
+    /// The points in the order they are replayed
+    points: Vec<TeachPoint>,
+}
+
+/// Records poses of several servos and replays them
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct PoseRecorder {
+    /// The poses in the order they are replayed
+    poses: Vec<TeachPose>,
+}
+
+/// Sets the velocity of a point to replay it with, if the velocity of the point is set
+fn point_options(options: &MoveOptions, velocity: Option<u32>) -> MoveOptions {
+    let options = options.clone().with_mode(MovementMode::Absolute);
+    match velocity {
+        Some(velocity) => options.with_velocity(velocity),
+        None => options,
+    }
+}
+
+impl TeachRecorder {
+    /// Creates a recorder without points
+    pub const fn new() -> Self {
+        Self { points: Vec::new() }
+    }
+
+    /// Appends the actual position of the servo as a point with the name.
+    ///
+    /// # Errors
+    /// Returns an error if the position couldn't be read
+    pub fn capture<const MAX_DEVICES: usize, const PDI_LENGTH: usize>(
+        &mut self,
+        servo: &mut Servo<'_, '_, MAX_DEVICES, PDI_LENGTH>,
+        name: impl Into<String>,
+    ) -> Result<(), EthercrabError> {
+        let position = servo.get_position()?;
+        self.points.push(TeachPoint {
+            name: name.into(),
+            position,
+            velocity: None,
+        });
+        Ok(())
+    }
+
+    /// Sets the velocity of the point with the name.
+    ///
+    /// # Returns
+    /// Whether a point with the name exists
+    pub fn set_velocity(&mut self, name: &str, velocity: Option<u32>) -> bool {
+        self.points
+            .iter_mut()
+            .find(|point| point.name == name)
+            .map(|point| point.velocity = velocity)
+            .is_some()
+    }
+
+    /// Returns the recorded points
+    pub fn points(&self) -> &[TeachPoint] {
+        &self.points
+    }
+
+    /// Returns the recorded points for editing, like reordering or removing points
+    pub fn points_mut(&mut self) -> &mut Vec<TeachPoint> {
+        &mut self.points
+    }
+
+    /// Moves the servo to every point in order, waiting until each move is complete.
+    /// The moves are absolute and use the velocity of the point, if set.
+    ///
+    /// # Errors
+    /// Returns an error if a move failed, see `Servo::move_with`
+    pub async fn replay<const MAX_DEVICES: usize, const PDI_LENGTH: usize>(
+        &self,
+        servo: &mut Servo<'_, '_, MAX_DEVICES, PDI_LENGTH>,
+        options: &MoveOptions,
+    ) -> Result<(), MovementError> {
+        for point in &self.points {
+            if servo.device.controller.verbose() {
+                log::info!(
+                    "Replaying point {} of device {}",
+                    point.name,
+                    servo.device.id
+                );
+            }
+            servo
+                .move_with(point.position, &point_options(options, point.velocity))
+                .await?;
+        }
+        Ok(())
+    }
+}
+
+impl PoseRecorder {
+    /// Creates a recorder without poses
+    pub const fn new() -> Self {
+        Self { poses: Vec::new() }
+    }
+
+    /// Appends the actual positions of the servos as a pose with the name.
+    /// All positions are read from the same cycle.
+    ///
+    /// # Errors
+    /// Returns an error if a position couldn't be read
+    pub fn capture<const MAX_DEVICES: usize, const PDI_LENGTH: usize>(
+        &mut self,
+        servos: &mut [&mut Servo<'_, '_, MAX_DEVICES, PDI_LENGTH>],
+        name: impl Into<String>,
+    ) -> Result<(), EthercrabError> {
+        let Some(controller) = servos.first().map(|servo| servo.device.controller) else {
+            return Ok(());
+        };
+
+        // Read again if a cycle completed while reading, so all positions are from one cycle
+        let positions = loop {
+            let cycle = controller.cycle_count();
+            let positions = servos
+                .iter_mut()
+                .map(|servo| servo.get_position())
+                .collect::<Result<Vec<_>, _>>()?;
+            if controller.cycle_count() == cycle {
+                break positions;
+            }
+        };
+        self.poses.push(TeachPose {
+            name: name.into(),
+            positions,
+            velocity: None,
+        });
+        Ok(())
+    }
+
+    /// Sets the velocity of the pose with the name.
+    ///
+    /// # Returns
+    /// Whether a pose with the name exists
+    pub fn set_velocity(&mut self, name: &str, velocity: Option<u32>) -> bool {
+        self.poses
+            .iter_mut()
+            .find(|pose| pose.name == name)
+            .map(|pose| pose.velocity = velocity)
+            .is_some()
+    }
+
+    /// Returns the recorded poses
+    pub fn poses(&self) -> &[TeachPose] {
+        &self.poses
+    }
+
+    /// Returns the recorded poses for editing, like reordering or removing poses
+    pub fn poses_mut(&mut self) -> &mut Vec<TeachPose> {
+        &mut self.poses
+    }
+
+    /// Moves the servos to every pose in order. The moves of all servos are started together,
+    /// the next pose is started once every servo reached the current one.
+    /// The servos have to be passed in the order they were captured in, servos without a
+    /// position in a pose don't move.
+    ///
+    /// # Errors
+    /// Returns an error if a move failed, see `Servo::start_move`
+    pub async fn replay<const MAX_DEVICES: usize, const PDI_LENGTH: usize>(
+        &self,
+        servos: &mut [&mut Servo<'_, '_, MAX_DEVICES, PDI_LENGTH>],
+        options: &MoveOptions,
+    ) -> Result<(), MovementError> {
+        for pose in &self.poses {
+            let options = point_options(options, pose.velocity);
+
+            // Start the moves of all servos, then wait until all are complete
+            let mut handles = Vec::with_capacity(servos.len());
+            for (servo, position) in servos.iter_mut().zip(&pose.positions) {
+                handles.push(servo.start_move(*position, options.clone()).await?);
+            }
+            for handle in handles {
+                handle.await?;
+            }
+        }
+        Ok(())
+    }
+}
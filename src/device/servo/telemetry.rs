@@ -0,0 +1,80 @@
+//! This module contains streams of status samples of a servo, for logging axis data.
+//!
+//! The samples are taken by the controller at the end of a cycle, so sampling doesn't borrow the
+//! servo and works next to motion calls. A receiver that can't keep up loses the oldest samples
+//! instead of stalling the cycle.
+
+use super::{status::ServoStatus, Servo};
+use crate::channel::{self, Receiver, Sender};
+use core::time::Duration;
+
+/// Samples the status of a device for a telemetry stream
+pub(crate) struct Sampler {
+    /// The number of cycles between samples
+    decimation: u64,
+
+    /// The sending end of the stream
+    sender: Sender<ServoStatus>,
+}
+
+impl Sampler {
+    /// Checks whether a sample has to be taken in the cycle
+    pub(crate) const fn is_due(&self, cycle: u64) -> bool {
+        cycle % self.decimation == 0
+    }
+
+    /// Sends a sample to the stream
+    pub(crate) fn send(&self, status: ServoStatus) {
+        self.sender.send(status);
+    }
+
+    /// Checks whether the stream has been dropped, so the sampler can be removed
+    pub(crate) fn is_disconnected(&self) -> bool {
+        self.sender.is_disconnected()
+    }
+}
+
+/// A stream of status samples of a servo, taken by the controller every few cycles.
+/// Sampling stops when the stream is dropped.
+///
+/// Holds the most recent samples, older samples are dropped if they aren't received in time.
+pub struct TelemetryStream {
+    /// The receiving end of the channel filled by the controller
+    receiver: Receiver<ServoStatus>,
+}
+
+impl TelemetryStream {
+    /// Waits for the next sample.
+    ///
+    /// # Returns
+    /// The next sample or `None` if the controller has been dropped
+    pub async fn recv(&self) -> Option<ServoStatus> {
+        self.receiver.recv().await
+    }
+
+    /// Returns the next sample if one was taken, without waiting
+    pub fn try_recv(&self) -> Option<ServoStatus> {
+        self.receiver.try_recv()
+    }
+
+    /// Returns the number of samples dropped because they weren't received in time
+    pub fn dropped(&self) -> u64 {
+        self.receiver.dropped()
+    }
+}
+
+impl<const MAX_DEVICES: usize, const PDI_LENGTH: usize> Servo<'_, '_, MAX_DEVICES, PDI_LENGTH> {
+    /// Creates a stream of status samples of the servo, holding at most `capacity` samples.
+    /// A sample is taken every `period` rounded down to whole cycles, with a minimum of every
+    /// cycle. Each sample is tagged with the cycle it was taken in.
+    pub fn telemetry_stream(&mut self, period: Duration, capacity: usize) -> TelemetryStream {
+        let controller = self.device.controller;
+        let cycle_time = controller.cycle_time().as_nanos().max(1);
+        let decimation = u64::try_from(period.as_nanos() / cycle_time)
+            .unwrap_or(u64::MAX)
+            .max(1);
+        let (sender, receiver) = channel::bounded(capacity);
+        controller.register_sampler(self.device.id, Sampler { decimation, sender });
+        TelemetryStream { receiver }
+    }
+}
@@ -0,0 +1,215 @@
+//! This module contains the profile torque mode of the servo.
+//!
+//! In this mode the drive produces the requested torque, ramping to it with the torque slope.
+//! The actual torque can be monitored with `Servo::get_torque` while the mode is active.
+
+use super::{Servo, MOTION_TIMEOUT};
+use crate::{
+    device::{
+        objects, ControlBit, ControlWord, OperationMode, SetModeError, StatusWordBit, WaitTimeout,
+    },
+    pdo::{self, PdoValue},
+};
+use core::fmt::{self, Debug, Formatter};
+use ethercrab::error::Error as EthercrabError;
+
+/// An error returned while controlling the torque of the servo
+pub enum TorqueError {
+    /// The drive is disabled
+    DriveDisabled(usize),
+
+    /// The device has been emergency stopped
+    EmergencyStopped(usize),
+
+    /// The requested torque exceeds the maximum torque of the drive
+    ExceedsMaxTorque(usize, i16, u16),
+
+    /// Communication failed or there was an existing reference to the device
+    Ethercat(usize, EthercrabError),
+
+    /// Failed to set the device to torque mode
+    SetMode(SetModeError),
+
+    /// The device faulted or didn't reach the torque in time
+    Wait(WaitTimeout),
+}
+
+impl From<WaitTimeout> for TorqueError {
+    fn from(error: WaitTimeout) -> Self {
+        match error {
+            WaitTimeout::EmergencyStopped(device, _) => Self::EmergencyStopped(device),
+            error => Self::Wait(error),
+        }
+    }
+}
+
+impl Debug for TorqueError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::DriveDisabled(device) => {
+                write!(f, "Drive {device} is disabled, torque control not possible")
+            }
+            Self::EmergencyStopped(device) => {
+                write!(
+                    f,
+                    "Torque control of device {device} aborted by emergency stop"
+                )
+            }
+            Self::ExceedsMaxTorque(device, requested, max) => write!(
+                f,
+                "Requested torque {requested} of device {device} exceeds the maximum torque {max}"
+            ),
+            Self::Ethercat(device, error) => {
+                write!(f, "Failed to communicate with device {device}: {error}")
+            }
+            Self::SetMode(error) => write!(f, "Error while setting torque mode: {error:?}"),
+            Self::Wait(error) => write!(f, "Torque control failed: {error:?}"),
+        }
+    }
+}
+
+impl<const MAX_DEVICES: usize, const PDI_LENGTH: usize> Servo<'_, '_, MAX_DEVICES, PDI_LENGTH> {
+    /// Reads the maximum torque of the drive (0x6072) in thousandths of the rated torque.
+    /// Only the first call communicates with the drive.
+    ///
+    /// # Errors
+    /// Returns an error if the maximum torque couldn't be read
+    pub async fn max_torque(&mut self) -> Result<u16, EthercrabError> {
+        if let Some(max_torque) = self.max_torque {
+            return Ok(max_torque);
+        }
+        let max_torque = self.device.read_object(objects::MAX_TORQUE).await?;
+        self.max_torque = Some(max_torque);
+        Ok(max_torque)
+    }
+
+    /// Checks whether the torque is within the maximum torque of the drive
+    ///
+    /// # Errors
+    /// Returns an error if the maximum torque couldn't be read or the torque exceeds it
+    async fn check_torque(&mut self, torque: i16) -> Result<(), TorqueError> {
+        let max_torque = self
+            .max_torque()
+            .await
+            .map_err(|error| TorqueError::Ethercat(self.device.id, error))?;
+        if torque.unsigned_abs() > max_torque {
+            return Err(TorqueError::ExceedsMaxTorque(
+                self.device.id,
+                torque,
+                max_torque,
+            ));
+        }
+        Ok(())
+    }
+
+    /// Produces the requested torque in thousandths of the rated torque.
+    /// The sign of the torque selects the direction.
+    /// Returns once the drive reports the torque has been reached.
+    ///
+    /// # Parameters
+    /// `target_per_mille`: The torque in thousandths of the rated torque
+    /// `slope`: The rate of change of the torque in thousandths of the rated torque per second,
+    /// the slope configured in the drive is used if `None`
+    ///
+    /// # Errors
+    /// Returns an error if:
+    /// - The drive is not enabled or emergency stopped
+    /// - The requested torque exceeds the maximum torque
+    /// - The device couldn't be set to torque mode
+    /// - The slope or target torque couldn't be written
+    /// - The device faulted or didn't reach the torque in time
+    pub async fn move_torque(
+        &mut self,
+        target_per_mille: i16,
+        slope: Option<u32>,
+    ) -> Result<(), TorqueError> {
+        if self.device.controller.verbose() {
+            log::info!(
+                "Starting torque control at {target_per_mille} of device {}",
+                self.device.id
+            );
+        }
+        if self.device.emergency_stopped() {
+            return Err(TorqueError::EmergencyStopped(self.device.id));
+        }
+        if !self.device.ready_state() {
+            return Err(TorqueError::DriveDisabled(self.device.id));
+        }
+        self.check_torque(target_per_mille).await?;
+
+        // Set the device to torque mode
+        self.device
+            .set_mode(OperationMode::Torque)
+            .await
+            .map_err(TorqueError::SetMode)?;
+
+        // Set the torque slope, if requested
+        if let Some(slope) = slope {
+            self.device
+                .write_object(objects::TORQUE_SLOPE, slope)
+                .await
+                .map_err(|error| TorqueError::Ethercat(self.device.id, error))?;
+        }
+
+        // Write the target torque and clear the halt bit at once
+        self.write_target_torque(target_per_mille, false)?;
+
+        // Give the drive a cycle to process the new target, then wait until it's reached
+        self.device.controller.next_cycle().await;
+        self.device
+            .wait_for(
+                |status| status.is_set(StatusWordBit::MotionComplete),
+                MOTION_TIMEOUT,
+            )
+            .await?;
+        Ok(())
+    }
+
+    /// Ramps the torque down to zero with the torque slope and halts the servo.
+    ///
+    /// # Errors
+    /// Returns an error if:
+    /// - The target torque couldn't be written
+    /// - The device faulted or didn't reach zero torque in time
+    pub async fn stop_torque(&mut self) -> Result<(), TorqueError> {
+        if self.device.controller.verbose() {
+            log::info!("Stopping torque control of device {}", self.device.id);
+        }
+
+        // Ramp the torque down to zero
+        self.write_target_torque(0, false)?;
+        self.device.controller.next_cycle().await;
+        self.device
+            .wait_for(
+                |status| status.is_set(StatusWordBit::MotionComplete),
+                MOTION_TIMEOUT,
+            )
+            .await?;
+
+        // Halt the servo
+        self.write_target_torque(0, true)?;
+        self.device.controller.next_cycle().await;
+        Ok(())
+    }
+
+    /// Writes the target torque and the halt bit with a single access to the outputs
+    ///
+    /// # Errors
+    /// Returns an error if another reference to the device exists
+    fn write_target_torque(&mut self, torque: i16, halt: bool) -> Result<(), TorqueError> {
+        self.device
+            .apply_outputs(|outputs| {
+                torque.write(&mut outputs[pdo::output::TARGET_TORQUE..]);
+                let control_word = &mut outputs[pdo::output::CONTROL_WORD..];
+                let control = ControlWord::new(u16::read(control_word));
+                if halt {
+                    control.with(ControlBit::Halt)
+                } else {
+                    control.without(ControlBit::Halt)
+                }
+                .raw()
+                .write(control_word);
+            })
+            .map_err(|error| TorqueError::Ethercat(self.device.id, error))
+    }
+}
@@ -0,0 +1,285 @@
+//! This module contains the torque limits of the servo, capping the torque the servo may apply.
+//!
+//! The maximum torque (0x6072) limits both directions, the positive (0x60E0) and negative
+//! (0x60E1) torque limits each limit one direction. Every written limit is read back, so a limit
+//! the drive didn't accept isn't silently ignored.
+//!
+//! `Servo::with_torque_limit` lowers the limits for the duration of a future. The previous limits
+//! are written over SDO afterwards, which can't be done while a cancelled future is dropped. In
+//! that case the limits are restored by the next call or `Servo::restore_torque_limits`.
+
+use super::Servo;
+use crate::device::objects::{self, Object};
+use core::{
+    fmt::{self, Debug, Formatter},
+    future::Future,
+    pin::Pin,
+};
+use ethercrab::error::Error as EthercrabError;
+
+/// The torque limits of the drive, restored after the limits were lowered temporarily
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(super) struct TorqueLimits {
+    /// The maximum torque in thousandths of the rated torque
+    max: u16,
+
+    /// The positive torque limit, if the drive supports it
+    positive: Option<u16>,
+
+    /// The negative torque limit, if the drive supports it
+    negative: Option<u16>,
+}
+
+/// An error returned while reading or writing a torque limit
+pub enum TorqueLimitError {
+    /// The drive reports a different value than was written
+    VerificationFailed {
+        /// The device number
+        device: usize,
+
+        /// The object that was written
+        object: Object,
+
+        /// The value that was written
+        written: u16,
+
+        /// The value read back from the drive
+        read: u16,
+    },
+
+    /// Reading or writing a torque limit failed
+    Ethercat {
+        /// The device number
+        device: usize,
+
+        /// The object that was accessed
+        object: Object,
+
+        /// The error returned by `EtherCrab`
+        error: EthercrabError,
+    },
+}
+
+impl Debug for TorqueLimitError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::VerificationFailed {
+                device,
+                object,
+                written,
+                read,
+            } => write!(
+                f,
+                "Torque limit {:#06x}:{} of device {device} reads {read} after writing {written}",
+                object.index, object.sub_index
+            ),
+            Self::Ethercat {
+                device,
+                object,
+                error,
+            } => write!(
+                f,
+                "Accessing torque limit {:#06x}:{} of device {device} failed: {error:?}",
+                object.index, object.sub_index
+            ),
+        }
+    }
+}
+
+/// A future borrowing the servo, returned by the closure passed to `Servo::with_torque_limit`
+pub type ServoFuture<'servo, T> = Pin<Box<dyn Future<Output = T> + Send + 'servo>>;
+
+impl<const MAX_DEVICES: usize, const PDI_LENGTH: usize> Servo<'_, '_, MAX_DEVICES, PDI_LENGTH> {
+    /// Reads a torque limit
+    ///
+    /// # Errors
+    /// Returns an error if the object couldn't be read
+    async fn read_torque_limit(&mut self, object: Object) -> Result<u16, TorqueLimitError> {
+        let device = self.device.id;
+        self.device
+            .read_object(object)
+            .await
+            .map_err(|error| TorqueLimitError::Ethercat {
+                device,
+                object,
+                error,
+            })
+    }
+
+    /// Reads a torque limit, drives without the object report `None`.
+    ///
+    /// # Errors
+    /// Returns an error if the object couldn't be read
+    async fn read_optional_torque_limit(
+        &mut self,
+        object: Object,
+    ) -> Result<Option<u16>, TorqueLimitError> {
+        let device = self.device.id;
+        self.device
+            .read_optional_object(object)
+            .await
+            .map_err(|error| TorqueLimitError::Ethercat {
+                device,
+                object,
+                error,
+            })
+    }
+
+    /// Writes a torque limit and reads it back.
+    ///
+    /// # Errors
+    /// Returns an error if the object couldn't be written or read, or reads a different value
+    async fn write_torque_limit(
+        &mut self,
+        object: Object,
+        limit: u16,
+    ) -> Result<(), TorqueLimitError> {
+        let device = self.device.id;
+        self.device
+            .write_object(object, limit)
+            .await
+            .map_err(|error| TorqueLimitError::Ethercat {
+                device,
+                object,
+                error,
+            })?;
+        let read = self.read_torque_limit(object).await?;
+        if read != limit {
+            return Err(TorqueLimitError::VerificationFailed {
+                device,
+                object,
+                written: limit,
+                read,
+            });
+        }
+        Ok(())
+    }
+
+    /// Writes all torque limits, skipping the limits the drive doesn't support.
+    /// The cached maximum torque is read again on next use.
+    ///
+    /// # Errors
+    /// Returns an error if a limit couldn't be written
+    async fn write_torque_limits(&mut self, limits: TorqueLimits) -> Result<(), TorqueLimitError> {
+        self.max_torque = None;
+        self.write_torque_limit(objects::MAX_TORQUE, limits.max)
+            .await?;
+        if let Some(positive) = limits.positive {
+            self.write_torque_limit(objects::POSITIVE_TORQUE_LIMIT, positive)
+                .await?;
+        }
+        if let Some(negative) = limits.negative {
+            self.write_torque_limit(objects::NEGATIVE_TORQUE_LIMIT, negative)
+                .await?;
+        }
+        Ok(())
+    }
+
+    /// Writes the maximum torque of the drive (0x6072) in thousandths of the rated torque.
+    ///
+    /// # Errors
+    /// Returns an error if the maximum torque couldn't be written or wasn't accepted
+    pub async fn set_max_torque(&mut self, per_mille: u16) -> Result<(), TorqueLimitError> {
+        self.max_torque = None;
+        self.write_torque_limit(objects::MAX_TORQUE, per_mille)
+            .await?;
+        self.max_torque = Some(per_mille);
+        Ok(())
+    }
+
+    /// Writes the positive (0x60E0) and negative (0x60E1) torque limits in thousandths of the
+    /// rated torque.
+    ///
+    /// # Errors
+    /// Returns an error if a limit couldn't be written or wasn't accepted
+    pub async fn set_torque_limits(
+        &mut self,
+        positive: u16,
+        negative: u16,
+    ) -> Result<(), TorqueLimitError> {
+        self.write_torque_limit(objects::POSITIVE_TORQUE_LIMIT, positive)
+            .await?;
+        self.write_torque_limit(objects::NEGATIVE_TORQUE_LIMIT, negative)
+            .await
+    }
+
+    /// Reads the positive (0x60E0) and negative (0x60E1) torque limits in thousandths of the
+    /// rated torque.
+    ///
+    /// # Errors
+    /// Returns an error if a limit couldn't be read
+    pub async fn torque_limits(&mut self) -> Result<(u16, u16), TorqueLimitError> {
+        let positive = self
+            .read_torque_limit(objects::POSITIVE_TORQUE_LIMIT)
+            .await?;
+        let negative = self
+            .read_torque_limit(objects::NEGATIVE_TORQUE_LIMIT)
+            .await?;
+        Ok((positive, negative))
+    }
+
+    /// Restores the torque limits from before a temporary limit that was cancelled.
+    /// Does nothing if there are no limits to restore.
+    ///
+    /// # Errors
+    /// Returns an error if a limit couldn't be written
+    pub async fn restore_torque_limits(&mut self) -> Result<(), TorqueLimitError> {
+        if let Some(limits) = self.pending_torque_limits {
+            self.write_torque_limits(limits).await?;
+            self.pending_torque_limits = None;
+        }
+        Ok(())
+    }
+
+    /// Lowers all torque limits to the limit while the future returned by `run` is awaited and
+    /// restores the previous limits afterwards. Limits that are already lower aren't raised.
+    /// If the returned future is cancelled, the limits are restored by the next call of this
+    /// function or `Servo::restore_torque_limits`.
+    ///
+    /// # Parameters
+    /// `limit`: The torque in thousandths of the rated torque
+    ///
+    /// # Errors
+    /// Returns an error if the limits couldn't be read, lowered or restored
+    ///
+    /// # Returns
+    /// The output of the future
+    pub async fn with_torque_limit<T>(
+        &mut self,
+        limit: u16,
+        run: impl FnOnce(&mut Self) -> ServoFuture<'_, T>,
+    ) -> Result<T, TorqueLimitError> {
+        // Restore the limits of a cancelled call first, so they aren't saved as the original
+        self.restore_torque_limits().await?;
+        let previous = TorqueLimits {
+            max: self
+                .read_optional_torque_limit(objects::MAX_TORQUE)
+                .await?
+                .unwrap_or(u16::MAX),
+            positive: self
+                .read_optional_torque_limit(objects::POSITIVE_TORQUE_LIMIT)
+                .await?,
+            negative: self
+                .read_optional_torque_limit(objects::NEGATIVE_TORQUE_LIMIT)
+                .await?,
+        };
+        self.pending_torque_limits = Some(previous);
+
+        // Lower the limits, without raising a limit that's already lower
+        let lowered = TorqueLimits {
+            max: previous.max.min(limit),
+            positive: previous.positive.map(|positive| positive.min(limit)),
+            negative: previous.negative.map(|negative| negative.min(limit)),
+        };
+        let result = match self.write_torque_limits(lowered).await {
+            Ok(()) => Ok(run(self).await),
+            Err(error) => Err(error),
+        };
+
+        // Restore the limits, even if lowering them failed
+        let restored = self.restore_torque_limits().await;
+        let result = result?;
+        restored?;
+        Ok(result)
+    }
+}
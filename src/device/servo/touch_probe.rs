@@ -0,0 +1,217 @@
+//! This module contains everything related to the touch probe function of the drive.
+//! A touch probe latches the position of the servo when an input or the encoder index pulse fires.
+//!
+//! The touch probe objects aren't part of the default PDO mapping, so they are accessed over SDO.
+//! This adds the latency of a mailbox transfer (multiple cycles) to arming the probe and to
+//! noticing that a position was latched. The latched position itself is captured by the drive
+//! and is exact regardless of that latency.
+
+use super::Servo;
+use crate::device::objects;
+use ethercrab::error::Error as EthercrabError;
+
+/// One of the two touch probes of the drive
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TouchProbe {
+    /// Touch probe 1
+    First,
+
+    /// Touch probe 2
+    Second,
+}
+
+impl TouchProbe {
+    /// Returns the position of the bits of this touch probe in the function and status words
+    const fn shift(self) -> u16 {
+        match self {
+            Self::First => 0,
+            Self::Second => 8,
+        }
+    }
+}
+
+/// How often the touch probe latches a position
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TouchProbeMode {
+    /// Only the first event after arming is latched
+    SingleShot,
+
+    /// Every event is latched, overwriting the previous position
+    Continuous,
+}
+
+/// The signal triggering the touch probe
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TouchProbeTrigger {
+    /// The touch probe input of the drive
+    Input,
+
+    /// The index (zero) pulse of the encoder
+    IndexPulse,
+}
+
+/// The edge of the trigger signal
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TouchProbeEdge {
+    /// The signal becomes active
+    Rising,
+
+    /// The signal becomes inactive
+    Falling,
+}
+
+/// The configuration used to arm a touch probe
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct TouchProbeConfig {
+    /// The touch probe to arm
+    pub probe: TouchProbe,
+
+    /// Whether to latch once or continuously
+    pub mode: TouchProbeMode,
+
+    /// The signal triggering the touch probe
+    pub trigger: TouchProbeTrigger,
+
+    /// Latch the position on the rising edge
+    pub rising_edge: bool,
+
+    /// Latch the position on the falling edge
+    pub falling_edge: bool,
+}
+
+impl TouchProbeConfig {
+    /// Encodes the configuration as bits of the touch probe function (0x60B8).
+    /// The bits of the other touch probe are left cleared.
+    pub const fn function_bits(&self) -> u16 {
+        let mut bits = 1;
+        if matches!(self.mode, TouchProbeMode::Continuous) {
+            bits |= 1 << 1;
+        }
+        if matches!(self.trigger, TouchProbeTrigger::IndexPulse) {
+            bits |= 1 << 2;
+        }
+        if self.rising_edge {
+            bits |= 1 << 4;
+        }
+        if self.falling_edge {
+            bits |= 1 << 5;
+        }
+        bits << self.probe.shift()
+    }
+}
+
+/// The decoded status of a touch probe (0x60B9)
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct TouchProbeStatus {
+    /// Whether the touch probe is armed
+    pub enabled: bool,
+
+    /// Whether a position was latched on the rising edge
+    pub rising_edge_stored: bool,
+
+    /// Whether a position was latched on the falling edge
+    pub falling_edge_stored: bool,
+}
+
+impl TouchProbeStatus {
+    /// Decodes the status of the requested touch probe from the touch probe status word
+    pub const fn from_raw(raw: u16, probe: TouchProbe) -> Self {
+        let bits = raw >> probe.shift();
+        Self {
+            enabled: bits & 1 != 0,
+            rising_edge_stored: bits & (1 << 1) != 0,
+            falling_edge_stored: bits & (1 << 2) != 0,
+        }
+    }
+
+    /// Returns whether a position was latched on the requested edge
+    pub const fn stored(&self, edge: TouchProbeEdge) -> bool {
+        match edge {
+            TouchProbeEdge::Rising => self.rising_edge_stored,
+            TouchProbeEdge::Falling => self.falling_edge_stored,
+        }
+    }
+}
+
+impl<const MAX_DEVICES: usize, const PDI_LENGTH: usize> Servo<'_, '_, MAX_DEVICES, PDI_LENGTH> {
+    /// Arms a touch probe, the configuration of the other touch probe is kept.
+    /// Arming an already armed touch probe clears the latched positions.
+    ///
+    /// # Errors
+    /// Returns an error if the touch probe function couldn't be read or written
+    pub async fn arm_touch_probe(
+        &mut self,
+        config: TouchProbeConfig,
+    ) -> Result<(), EthercrabError> {
+        let probe_bits = 0xFF << config.probe.shift();
+        let function: u16 = self
+            .device
+            .read_mapped_object(objects::TOUCH_PROBE_FUNCTION)
+            .await?;
+
+        // Disable the touch probe first, so the drive sees a new enable edge
+        self.device
+            .write_mapped_object(objects::TOUCH_PROBE_FUNCTION, function & !probe_bits)
+            .await?;
+        self.device.controller.next_cycle().await;
+        self.device
+            .write_mapped_object(
+                objects::TOUCH_PROBE_FUNCTION,
+                (function & !probe_bits) | config.function_bits(),
+            )
+            .await
+    }
+
+    /// Disarms a touch probe.
+    ///
+    /// # Errors
+    /// Returns an error if the touch probe function couldn't be read or written
+    pub async fn disarm_touch_probe(&mut self, probe: TouchProbe) -> Result<(), EthercrabError> {
+        let function: u16 = self
+            .device
+            .read_mapped_object(objects::TOUCH_PROBE_FUNCTION)
+            .await?;
+        self.device
+            .write_mapped_object(
+                objects::TOUCH_PROBE_FUNCTION,
+                function & !(0xFF << probe.shift()),
+            )
+            .await
+    }
+
+    /// Reads the status of a touch probe.
+    ///
+    /// # Errors
+    /// Returns an error if the touch probe status couldn't be read
+    pub async fn touch_probe_status(
+        &mut self,
+        probe: TouchProbe,
+    ) -> Result<TouchProbeStatus, EthercrabError> {
+        let status = self
+            .device
+            .read_mapped_object(objects::TOUCH_PROBE_STATUS)
+            .await?;
+        Ok(TouchProbeStatus::from_raw(status, probe))
+    }
+
+    /// Reads the position latched by a touch probe on the requested edge.
+    /// Only meaningful when the status reports a stored position for that edge.
+    ///
+    /// # Errors
+    /// Returns an error if the latched position couldn't be read
+    pub async fn touch_probe_position(
+        &mut self,
+        probe: TouchProbe,
+        edge: TouchProbeEdge,
+    ) -> Result<i32, EthercrabError> {
+        let object = match (probe, edge) {
+            (TouchProbe::First, TouchProbeEdge::Rising) => objects::TOUCH_PROBE_1_POSITION_RISING,
+            (TouchProbe::First, TouchProbeEdge::Falling) => objects::TOUCH_PROBE_1_POSITION_FALLING,
+            (TouchProbe::Second, TouchProbeEdge::Rising) => objects::TOUCH_PROBE_2_POSITION_RISING,
+            (TouchProbe::Second, TouchProbeEdge::Falling) => {
+                objects::TOUCH_PROBE_2_POSITION_FALLING
+            }
+        };
+        self.device.read_mapped_object(object).await
+    }
+}
@@ -0,0 +1,416 @@
+//! This module contains movements in engineering units, like millimetres or degrees.
+//!
+//! The drive only knows increments, so every value is converted with the configured
+//! `UnitScaling` before it's passed to the increment based functions of the servo.
+
+use super::{mechanics::Mechanics, motion::MoveOptions, MovementError, Servo};
+use core::fmt::{self, Debug, Formatter};
+use ethercrab::error::Error as EthercrabError;
+
+/// The conversion between the increments of the drive and an engineering unit
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct UnitScaling {
+    /// The number of increments in a single unit
+    pub increments_per_unit: f64,
+
+    /// The name of the unit, used for logging
+    pub unit_name: &'static str,
+}
+
+/// A profile velocity in increments per second, as written to the drive
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct RawVelocity(pub u32);
+
+/// An error returned by a movement in engineering units
+pub enum UnitError {
+    /// No unit scaling was configured for the device
+    NotConfigured(usize),
+
+    /// The value in units can't be represented in increments
+    OutOfRange {
+        /// The device number
+        device: usize,
+
+        /// The value that should have been converted
+        value: f64,
+
+        /// The name of the unit
+        unit_name: &'static str,
+    },
+
+    /// The position couldn't be read
+    Ethercat(usize, EthercrabError),
+
+    /// The movement failed
+    Movement(MovementError),
+}
+
+impl Debug for UnitError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::NotConfigured(device) => {
+                write!(f, "No unit scaling was configured for device {device}")
+            }
+            Self::OutOfRange {
+                device,
+                value,
+                unit_name,
+            } => write!(
+                f,
+                "{value} {unit_name} is out of the range of increments of device {device}"
+            ),
+            Self::Ethercat(device, error) => write!(
+                f,
+                "Reading the position of device {device} failed: {error:?}"
+            ),
+            Self::Movement(error) => write!(f, "{error:?}"),
+        }
+    }
+}
+
+impl From<MovementError> for UnitError {
+    fn from(value: MovementError) -> Self {
+        Self::Movement(value)
+    }
+}
+
+impl From<u32> for RawVelocity {
+    fn from(value: u32) -> Self {
+        Self(value)
+    }
+}
+
+impl From<RawVelocity> for u32 {
+    fn from(value: RawVelocity) -> Self {
+        value.0
+    }
+}
+
+/// Rounds a velocity in increments per second to the nearest increment.
+///
+/// # Returns
+/// The velocity or `None` if it's negative, not finite or doesn't fit in an `u32`
+#[expect(
+    clippy::cast_possible_truncation,
+    clippy::cast_sign_loss,
+    reason = "The value is rounded and checked against the range of u32 before the cast"
+)]
+fn round_velocity(increments_per_second: f64) -> Option<RawVelocity> {
+    let increments_per_second = increments_per_second.round();
+    (increments_per_second.is_finite()
+        && increments_per_second >= 0.0
+        && increments_per_second <= f64::from(u32::MAX))
+    .then_some(RawVelocity(increments_per_second as u32))
+}
+
+impl RawVelocity {
+    /// Converts a velocity in revolutions per minute, rounding to the nearest increment per second.
+    ///
+    /// # Returns
+    /// The velocity or `None` if it's negative or doesn't fit in an `u32`
+    #[must_use]
+    pub fn from_rpm(rpm: f64, increments_per_revolution: u32) -> Option<Self> {
+        round_velocity(rpm * f64::from(increments_per_revolution) / 60.0)
+    }
+
+    /// Converts a velocity in units per second, rounding to the nearest increment per second.
+    ///
+    /// # Returns
+    /// The velocity or `None` if it's negative or doesn't fit in an `u32`
+    #[must_use]
+    pub fn from_units_per_second(units_per_second: f64, scaling: &UnitScaling) -> Option<Self> {
+        round_velocity(units_per_second * scaling.increments_per_unit)
+    }
+
+    /// Converts the velocity to revolutions per minute
+    #[must_use]
+    pub fn to_rpm(self, increments_per_revolution: u32) -> f64 {
+        f64::from(self.0) * 60.0 / f64::from(increments_per_revolution)
+    }
+
+    /// Converts the velocity to units per second
+    #[must_use]
+    pub fn to_units_per_second(self, scaling: &UnitScaling) -> f64 {
+        f64::from(self.0) / scaling.increments_per_unit
+    }
+}
+
+impl UnitScaling {
+    /// Creates a scaling from the number of increments in a single unit
+    #[must_use]
+    pub const fn new(increments_per_unit: f64, unit_name: &'static str) -> Self {
+        Self {
+            increments_per_unit,
+            unit_name,
+        }
+    }
+
+    /// Derives the scaling of a drive reporting positions in encoder increments.
+    /// The feed constant of the mechanics is expected to be in the unit of the scaling.
+    ///
+    /// # Returns
+    /// The scaling or `None` if the mechanics contain zero
+    #[must_use]
+    pub fn from_mechanics(
+        mechanics: &Mechanics,
+        increments_per_revolution: u32,
+        unit_name: &'static str,
+    ) -> Option<Self> {
+        if mechanics.gear_denominator == 0 || mechanics.feed_constant_numerator == 0 {
+            return None;
+        }
+
+        // Increments per motor revolution, times motor revolutions per unit of feed
+        let increments_per_unit = f64::from(increments_per_revolution)
+            * f64::from(mechanics.gear_numerator)
+            * f64::from(mechanics.feed_constant_denominator)
+            / (f64::from(mechanics.gear_denominator)
+                * f64::from(mechanics.feed_constant_numerator));
+        Some(Self::new(increments_per_unit, unit_name))
+    }
+
+    /// Converts a value in units to increments, rounding to the nearest increment.
+    ///
+    /// # Returns
+    /// The number of increments or `None` if it doesn't fit in an `i32`
+    #[must_use]
+    #[expect(
+        clippy::cast_possible_truncation,
+        reason = "The value is rounded and checked against the range of i32 before the cast"
+    )]
+    pub fn to_increments(&self, units: f64) -> Option<i32> {
+        let increments = (units * self.increments_per_unit).round();
+        (increments.is_finite()
+            && increments >= f64::from(i32::MIN)
+            && increments <= f64::from(i32::MAX))
+        .then_some(increments as i32)
+    }
+
+    /// Converts a number of increments to units
+    #[must_use]
+    pub fn to_units(&self, increments: i32) -> f64 {
+        f64::from(increments) / self.increments_per_unit
+    }
+}
+
+impl<const MAX_DEVICES: usize, const PDI_LENGTH: usize> Servo<'_, '_, MAX_DEVICES, PDI_LENGTH> {
+    /// Sets the scaling used by the movements in units
+    pub fn set_unit_scaling(&mut self, scaling: UnitScaling) {
+        self.unit_scaling = Some(scaling);
+    }
+
+    /// Returns the scaling used by the movements in units, if configured
+    #[must_use]
+    pub const fn unit_scaling(&self) -> Option<UnitScaling> {
+        self.unit_scaling
+    }
+
+    /// Returns the configured scaling
+    ///
+    /// # Errors
+    /// Returns an error if no scaling was configured
+    const fn configured_scaling(&self) -> Result<UnitScaling, UnitError> {
+        match self.unit_scaling {
+            Some(scaling) => Ok(scaling),
+            None => Err(UnitError::NotConfigured(self.device.id)),
+        }
+    }
+
+    /// Converts a value in units to increments with the configured scaling
+    ///
+    /// # Errors
+    /// Returns an error if no scaling was configured or the value doesn't fit in an `i32`
+    fn units_to_increments(&self, value: f64) -> Result<i32, UnitError> {
+        let scaling = self.configured_scaling()?;
+        scaling.to_increments(value).ok_or(UnitError::OutOfRange {
+            device: self.device.id,
+            value,
+            unit_name: scaling.unit_name,
+        })
+    }
+
+    /// Moves to an absolute position in units and waits until the target is reached.
+    ///
+    /// # Errors
+    /// Returns an error if:
+    /// - No scaling was configured
+    /// - The target doesn't fit in increments
+    /// - The movement failed
+    pub async fn move_to_units(&mut self, target: f64) -> Result<(), UnitError> {
+        let increments = self.units_to_increments(target)?;
+        if self.device.controller.verbose() {
+            log::info!(
+                "Moving device {} to {target} {}",
+                self.device.id,
+                self.configured_scaling()?.unit_name
+            );
+        }
+        self.move_with(increments, &MoveOptions::new()).await?;
+        Ok(())
+    }
+
+    /// Reads the actual position in units.
+    ///
+    /// # Errors
+    /// Returns an error if no scaling was configured or the position couldn't be read
+    pub fn position_units(&mut self) -> Result<f64, UnitError> {
+        let scaling = self.configured_scaling()?;
+        let position = self
+            .get_position()
+            .map_err(|error| UnitError::Ethercat(self.device.id, error))?;
+        Ok(scaling.to_units(position))
+    }
+
+    /// Starts a velocity movement with a velocity in units per second.
+    /// The movement is stopped with `stop_velocity`.
+    ///
+    /// # Errors
+    /// Returns an error if:
+    /// - No scaling was configured
+    /// - The velocity doesn't fit in increments per second
+    /// - The movement couldn't be started
+    pub async fn jog_units(&mut self, velocity: f64) -> Result<(), UnitError> {
+        let increments = self.units_to_increments(velocity)?;
+        self.move_velocity(increments).await?;
+        Ok(())
+    }
+
+    /// Moves the servo by a distance in units at a velocity in units per second.
+    /// See `jog_distance`.
+    ///
+    /// # Errors
+    /// Returns an error if:
+    /// - No scaling was configured
+    /// - The distance or velocity doesn't fit in increments
+    /// - The move couldn't be started or failed
+    ///
+    /// # Returns
+    /// The distance actually travelled in units
+    pub async fn jog_distance_units(
+        &mut self,
+        delta: f64,
+        velocity: f64,
+    ) -> Result<f64, UnitError> {
+        let scaling = self.configured_scaling()?;
+        let increments = self.units_to_increments(delta)?;
+        let RawVelocity(raw_velocity) = RawVelocity::from_units_per_second(velocity, &scaling)
+            .ok_or(UnitError::OutOfRange {
+                device: self.device.id,
+                value: velocity,
+                unit_name: scaling.unit_name,
+            })?;
+        let travelled = self.jog_distance(increments, raw_velocity).await?;
+        Ok(scaling.to_units(travelled))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    //! Tests of the velocity conversions with a 20-bit encoder
+
+    use super::*;
+
+    /// The increments per revolution of a 20-bit encoder
+    const ENCODER: u32 = 1 << 20;
+
+    /// Revolutions per minute are converted to increments per second
+    #[test]
+    fn from_rpm() {
+        assert_eq!(RawVelocity::from_rpm(0.0, ENCODER), Some(RawVelocity(0)));
+        assert_eq!(
+            RawVelocity::from_rpm(60.0, ENCODER),
+            Some(RawVelocity(ENCODER))
+        );
+        assert_eq!(
+            RawVelocity::from_rpm(3000.0, ENCODER),
+            Some(RawVelocity(52_428_800))
+        );
+
+        // 17476.27 increments per second
+        assert_eq!(
+            RawVelocity::from_rpm(1.0, ENCODER),
+            Some(RawVelocity(17_476))
+        );
+    }
+
+    /// Velocities are rounded to the nearest increment, halfway values away from zero
+    #[test]
+    fn rounding() {
+        let increment = 60.0 / f64::from(ENCODER);
+        assert_eq!(
+            RawVelocity::from_rpm(increment * 0.5, ENCODER),
+            Some(RawVelocity(1))
+        );
+        assert_eq!(
+            RawVelocity::from_rpm(increment * 0.499, ENCODER),
+            Some(RawVelocity(0))
+        );
+        assert_eq!(
+            RawVelocity::from_rpm(increment * 1000.5, ENCODER),
+            Some(RawVelocity(1001))
+        );
+        assert_eq!(
+            RawVelocity::from_rpm(increment * 1000.499, ENCODER),
+            Some(RawVelocity(1000))
+        );
+    }
+
+    /// Velocities that don't fit in an `u32` are rejected
+    #[test]
+    fn out_of_range() {
+        let max = f64::from(u32::MAX) * 60.0 / f64::from(ENCODER);
+        assert_eq!(
+            RawVelocity::from_rpm(max, ENCODER),
+            Some(RawVelocity(u32::MAX))
+        );
+        assert_eq!(RawVelocity::from_rpm(max + 1.0, ENCODER), None);
+        assert_eq!(RawVelocity::from_rpm(-1.0, ENCODER), None);
+        assert_eq!(RawVelocity::from_rpm(f64::NAN, ENCODER), None);
+        assert_eq!(RawVelocity::from_rpm(f64::INFINITY, ENCODER), None);
+    }
+
+    /// Converting to revolutions per minute and back returns the same velocity
+    #[test]
+    #[expect(
+        clippy::float_cmp,
+        reason = "Whole revolutions convert exactly with a power of two increments per revolution"
+    )]
+    fn rpm_round_trip() {
+        assert_eq!(RawVelocity(ENCODER).to_rpm(ENCODER), 60.0);
+        for velocity in [0, 1, 17_476, 17_477, ENCODER - 1, 52_428_800, u32::MAX] {
+            let rpm = RawVelocity(velocity).to_rpm(ENCODER);
+            assert_eq!(
+                RawVelocity::from_rpm(rpm, ENCODER),
+                Some(RawVelocity(velocity))
+            );
+        }
+    }
+
+    /// Units per second are converted with the scaling of the mechanics
+    #[test]
+    #[expect(
+        clippy::float_cmp,
+        reason = "Whole revolutions convert exactly with a power of two increments per revolution"
+    )]
+    fn units_per_second() {
+        // A spindle with a pitch of 10 mm, 104857.6 increments per millimetre
+        let mechanics = Mechanics {
+            gear_numerator: 1,
+            gear_denominator: 1,
+            feed_constant_numerator: 10,
+            feed_constant_denominator: 1,
+        };
+        let scaling = UnitScaling::from_mechanics(&mechanics, ENCODER, "mm").unwrap();
+        assert_eq!(
+            RawVelocity::from_units_per_second(10.0, &scaling),
+            Some(RawVelocity(ENCODER))
+        );
+        assert_eq!(
+            RawVelocity::from_units_per_second(1.0, &scaling),
+            Some(RawVelocity(104_858))
+        );
+        assert_eq!(RawVelocity(ENCODER).to_units_per_second(&scaling), 10.0);
+        assert_eq!(RawVelocity::from_units_per_second(-0.1, &scaling), None);
+    }
+}
@@ -0,0 +1,162 @@
+//! This module contains the profile velocity mode of the servo.
+//!
+//! In this mode the servo keeps turning at the requested velocity, ramping up and down with the
+//! profile acceleration and deceleration of the drive.
+//!
+//! The drive reports the target velocity reached (status bit 10) once the velocity stayed within
+//! the velocity window (0x606D) for the velocity window time (0x606E). It reports standstill
+//! (status bit 12) once the velocity stayed below the velocity threshold (0x606F) for the
+//! velocity threshold time (0x6070).
+
+use super::{events::MotionEventKind, MovementError, Servo, MOTION_TIMEOUT};
+use crate::{
+    device::{objects, ControlBit, ControlWord, OperationMode, StatusWordBit},
+    pdo::{self, PdoValue},
+};
+use core::time::Duration;
+use ethercrab::error::Error as EthercrabError;
+
+impl<const MAX_DEVICES: usize, const PDI_LENGTH: usize> Servo<'_, '_, MAX_DEVICES, PDI_LENGTH> {
+    /// Turns the servo at the requested velocity in increments per second.
+    /// The sign of the velocity selects the direction.
+    /// Returns once the drive reports the velocity has been reached, see
+    /// `Servo::set_velocity_window`.
+    ///
+    /// # Errors
+    /// Returns an error if:
+    /// - The drive is not enabled or emergency stopped
+    /// - The device couldn't be set to profile velocity mode
+    /// - The target velocity couldn't be written
+    /// - The device faulted or didn't reach the velocity in time
+    pub async fn move_velocity(&mut self, target: i32) -> Result<(), MovementError> {
+        if self.device.controller.verbose() {
+            log::info!(
+                "Starting velocity movement at {target} of device {}",
+                self.device.id
+            );
+        }
+        if self.device.emergency_stopped() {
+            return Err(MovementError::EmergencyStopped(self.device.id));
+        }
+        if !self.device.ready_state() {
+            return Err(MovementError::DriveDisabled(self.device.id));
+        }
+
+        // Set the device to profile velocity mode
+        self.device
+            .set_mode(OperationMode::ProfileVelocity)
+            .await
+            .map_err(MovementError::SetMode)?;
+
+        // Write the target velocity and clear the halt bit at once
+        self.device
+            .apply_outputs(|outputs| {
+                target.write(&mut outputs[pdo::output::TARGET_VELOCITY..]);
+                let control_word = &mut outputs[pdo::output::CONTROL_WORD..];
+                ControlWord::new(u16::read(control_word))
+                    .without(ControlBit::Halt)
+                    .raw()
+                    .write(control_word);
+            })
+            .map_err(MovementError::Ethercat)?;
+
+        // Give the drive a cycle to process the new target, then wait until it's reached
+        self.device.controller.next_cycle().await;
+        self.device
+            .wait_for(
+                |status| status.is_set(StatusWordBit::MotionComplete),
+                MOTION_TIMEOUT,
+            )
+            .await
+            .map_err(MovementError::from)?;
+        Ok(())
+    }
+
+    /// Stops a velocity movement and waits until the servo stands still, see
+    /// `Servo::wait_standstill`.
+    ///
+    /// # Errors
+    /// Returns an error if:
+    /// - The halt bit couldn't be set
+    /// - The servo didn't stop in time
+    pub async fn stop_velocity(&mut self) -> Result<(), MovementError> {
+        if self.device.controller.verbose() {
+            log::info!("Stopping velocity movement of device {}", self.device.id);
+        }
+
+        // Halt the servo, the drive decelerates with the profile deceleration
+        self.device
+            .update_control_word(|control| control.with(ControlBit::Halt))
+            .map_err(MovementError::Ethercat)?;
+
+        // Wait until the servo stands still
+        self.device.controller.next_cycle().await;
+        self.wait_standstill(MOTION_TIMEOUT).await?;
+        self.emit(MotionEventKind::Halted);
+        Ok(())
+    }
+
+    /// Writes the velocity window in increments per second (0x606D) and the time the velocity
+    /// has to stay within it in milliseconds (0x606E).
+    ///
+    /// # Errors
+    /// Returns an error if a value couldn't be written
+    pub async fn set_velocity_window(
+        &mut self,
+        window: u16,
+        time_ms: u16,
+    ) -> Result<(), EthercrabError> {
+        self.device
+            .write_object(objects::VELOCITY_WINDOW, window)
+            .await?;
+        self.device
+            .write_object(objects::VELOCITY_WINDOW_TIME, time_ms)
+            .await
+    }
+
+    /// Reads the velocity window (0x606D) and the time the velocity has to stay within it
+    /// (0x606E).
+    ///
+    /// # Errors
+    /// Returns an error if a value couldn't be read
+    pub async fn velocity_window(&mut self) -> Result<(u16, Duration), EthercrabError> {
+        let window = self.device.read_object(objects::VELOCITY_WINDOW).await?;
+        let time: u16 = self
+            .device
+            .read_object(objects::VELOCITY_WINDOW_TIME)
+            .await?;
+        Ok((window, Duration::from_millis(u64::from(time))))
+    }
+
+    /// Writes the velocity threshold in increments per second (0x606F) and the time the
+    /// velocity has to stay below it in milliseconds (0x6070).
+    ///
+    /// # Errors
+    /// Returns an error if a value couldn't be written
+    pub async fn set_velocity_threshold(
+        &mut self,
+        threshold: u16,
+        time_ms: u16,
+    ) -> Result<(), EthercrabError> {
+        self.device
+            .write_object(objects::VELOCITY_THRESHOLD, threshold)
+            .await?;
+        self.device
+            .write_object(objects::VELOCITY_THRESHOLD_TIME, time_ms)
+            .await
+    }
+
+    /// Reads the velocity threshold (0x606F) and the time the velocity has to stay below it
+    /// (0x6070).
+    ///
+    /// # Errors
+    /// Returns an error if a value couldn't be read
+    pub async fn velocity_threshold(&mut self) -> Result<(u16, Duration), EthercrabError> {
+        let threshold = self.device.read_object(objects::VELOCITY_THRESHOLD).await?;
+        let time: u16 = self
+            .device
+            .read_object(objects::VELOCITY_THRESHOLD_TIME)
+            .await?;
+        Ok((threshold, Duration::from_millis(u64::from(time))))
+    }
+}
@@ -0,0 +1,454 @@
+//! This module contains a simulated servo drive for the tests of the motion functions.
+//!
+//! The drive holds input and output process images laid out like the PDO mapping. Every cycle it
+//! reacts to the outputs like a drive in the profile position, jog or homing mode: setpoints are
+//! latched on a rising edge of the new setpoint bit while the acknowledgement is low, a setpoint
+//! latched during a move is buffered until the move completed, unless it has to be changed
+//! immediately. The drive moves towards the target and reports when it has been reached, and
+//! jogs at the profile velocity.
+//! Faults and homing errors can be injected. The emitted motion events are recorded, and the
+//! status word is scanned for motion events every cycle like the controller does.
+//!
+//! The drive implements `Drive` with a simulated clock advancing one cycle time per cycle, so
+//! the motion sequences of the servo run against it deterministically with `block_on`.
+
+use super::{
+    servo::{
+        events::{status_events, MotionEvent, MotionEventKind},
+        status::ServoStatus,
+    },
+    ControlBit, ControlWord, Drive, OperationMode, OutputImage, SetModeError, StatusWord,
+    StatusWordBit, WaitConditions, WaitTimeout,
+};
+use crate::pdo::{self, PdoValue};
+use core::{
+    future::Future,
+    pin::pin,
+    task::{Context, Poll, Waker},
+    time::Duration,
+};
+use ethercrab::error::Error as EthercrabError;
+use std::{sync::Arc, task::Wake, time::Instant};
+
+/// The device number of the simulated drive
+pub const ID: usize = 0;
+
+/// The cycle time of the simulated clock
+pub const CYCLE_TIME: Duration = Duration::from_millis(10);
+
+/// The number of cycles after which waits on the simulated drive expire
+pub const DEADLINE: usize = 1_000;
+
+/// The timeout of waits on the simulated drive, `DEADLINE` cycles
+pub const TIMEOUT: Duration = Duration::from_secs(10);
+
+/// The status word of a drive that is enabled for operation
+const OPERATION_ENABLED: u16 = 0x0027;
+
+/// The status word of a drive that is in the fault state
+const FAULT: u16 = 0x0008;
+
+/// The number of cycles the drive takes to home
+const HOMING_CYCLES: u32 = 3;
+
+/// Calculates the size in bytes of a process image with the mapping
+fn image_size(mapping: &[u32]) -> usize {
+    mapping
+        .iter()
+        .map(|entry| (entry & 0xFF) as usize / 8)
+        .sum()
+}
+
+/// The progress of homing of the simulated drive
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Homing {
+    /// Homing hasn't been started
+    Idle,
+
+    /// Homing is running, holds the cycles left until the home position is reached
+    Running(u32),
+
+    /// The home position has been reached, the drive is homed
+    Attained,
+
+    /// Homing failed with a homing error
+    Failed,
+}
+
+/// Returns the mask of a status word bit
+const fn mask(bit: StatusWordBit) -> u16 {
+    1 << bit as u16
+}
+
+/// A waker that does nothing, the simulated drive never lets a future wait
+struct NoopWaker;
+
+impl Wake for NoopWaker {
+    fn wake(self: Arc<Self>) {}
+}
+
+/// Runs a future driving the simulated drive to completion.
+/// The simulated drive cycles synchronously, so the future completes when it's polled once.
+pub fn block_on<F: Future>(future: F) -> F::Output {
+    let waker = Waker::from(Arc::new(NoopWaker));
+    let mut context = Context::from_waker(&waker);
+    match pin!(future).poll(&mut context) {
+        Poll::Ready(output) => output,
+        Poll::Pending => panic!("The future waited on something besides the simulated drive"),
+    }
+}
+
+/// A simulated servo drive that is enabled for operation
+pub struct SimulatedDrive {
+    /// The start of the simulated clock
+    epoch: Instant,
+
+    /// The input process image, as reported after the last cycle
+    inputs: Vec<u8>,
+
+    /// The output process image, sent with the next cycle
+    outputs: Vec<u8>,
+
+    /// The control word received in the previous cycle, to detect rising edges
+    previous_control: ControlWord,
+
+    /// The actual position in increments
+    position: i32,
+
+    /// The distance the drive moves per cycle in increments
+    step: i32,
+
+    /// The target the drive is moving to, `None` while it stands still
+    target: Option<i32>,
+
+    /// The buffered target, started once the current target has been reached
+    queued: Option<i32>,
+
+    /// Whether the drive acknowledges the latched setpoint
+    acknowledged: bool,
+
+    /// The progress of the last homing
+    homing: Homing,
+
+    /// Whether the next homing fails
+    fail_homing: bool,
+
+    /// Whether the drive faulted
+    faulted: bool,
+
+    /// The cycle in which the drive faults
+    fault_at: Option<usize>,
+
+    /// The status word found during the previous scan for motion events
+    scanned: StatusWord,
+
+    /// The output process images received in every cycle
+    pub sent: Vec<Vec<u8>>,
+
+    /// The targets latched by the drive, in order
+    pub latched: Vec<i32>,
+
+    /// The targets reached by the drive, in order
+    pub reached: Vec<i32>,
+
+    /// The emitted motion events, in order
+    pub events: Vec<MotionEvent>,
+}
+
+impl SimulatedDrive {
+    /// Creates a drive standing still at position 0, enabled for operation without a mode
+    ///
+    /// # Parameters
+    /// `step`: The distance the drive moves per cycle in increments
+    pub fn new(step: i32) -> Self {
+        let control = ControlWord::new(0)
+            .with(ControlBit::SwitchOn)
+            .with(ControlBit::EnableVoltage)
+            .with(ControlBit::QuickStop)
+            .with(ControlBit::EnableOperation);
+        let mut outputs = vec![0; image_size(&pdo::OUTPUTS)];
+        control
+            .raw()
+            .write(&mut outputs[pdo::output::CONTROL_WORD..]);
+        let mut drive = Self {
+            epoch: Instant::now(),
+            inputs: vec![0; image_size(&pdo::INPUTS)],
+            outputs,
+            previous_control: control,
+            position: 0,
+            step,
+            target: None,
+            queued: None,
+            acknowledged: false,
+            homing: Homing::Idle,
+            fail_homing: false,
+            faulted: false,
+            fault_at: None,
+            scanned: StatusWord::new(0),
+            sent: Vec::new(),
+            latched: Vec::new(),
+            reached: Vec::new(),
+            events: Vec::new(),
+        };
+        drive.update_inputs();
+        drive.scanned = drive.status();
+        drive
+    }
+
+    /// Returns the output process image
+    pub fn outputs(&self) -> &[u8] {
+        &self.outputs
+    }
+
+    /// Returns the status word reported after the last cycle
+    pub fn status(&self) -> StatusWord {
+        StatusWord::new(u16::read(&self.inputs[pdo::input::STATUS_WORD..]))
+    }
+
+    /// Returns the actual position reported after the last cycle
+    pub fn position(&self) -> i32 {
+        i32::read(&self.inputs[pdo::input::POSITION_ACTUAL_VALUE..])
+    }
+
+    /// Requests the operation mode in the outputs, like `Device::set_mode`
+    pub fn set_mode(&mut self, mode: OperationMode) {
+        self.outputs[pdo::output::MODES_OF_OPERATION] = mode as u8;
+    }
+
+    /// Makes the drive fault in the next cycle
+    pub fn fault(&mut self) {
+        self.faulted = true;
+    }
+
+    /// Makes the drive fault in a later cycle, so it reports the fault once it ran `cycle` cycles
+    pub fn fault_at(&mut self, cycle: usize) {
+        self.fault_at = Some(cycle);
+    }
+
+    /// Makes the next homing fail with a homing error instead of reaching the home position
+    pub fn fail_homing(&mut self) {
+        self.fail_homing = true;
+    }
+
+    /// Cycles the drive until the wait during a motion ends, with `Drive::wait_for_motion` and a
+    /// deadline of `DEADLINE` cycles
+    ///
+    /// # Returns
+    /// The result of the wait and the number of cycles it took
+    pub fn wait_cycles(
+        &mut self,
+        predicate: impl Fn(StatusWord) -> bool + Send,
+    ) -> (Result<StatusWord, WaitTimeout>, usize) {
+        let cycles = self.sent.len();
+        let start = self.now();
+        let result = block_on(self.wait_for_motion(predicate, start, TIMEOUT));
+        (result, self.sent.len() - cycles)
+    }
+
+    /// Exchanges the process images: the drive receives the outputs, reacts to them and reports
+    /// its state in the inputs, which are scanned for motion events
+    pub fn cycle(&mut self) {
+        self.sent.push(self.outputs.clone());
+        let control = ControlWord::new(u16::read(&self.outputs[pdo::output::CONTROL_WORD..]));
+        let rising = control.is_set(ControlBit::Control4)
+            && !self.previous_control.is_set(ControlBit::Control4);
+        self.previous_control = control;
+        self.faulted |= self.fault_at == Some(self.sent.len());
+        if !self.faulted {
+            match self.mode() {
+                Some(OperationMode::ProfilePosition) => self.profile_position(control, rising),
+                Some(OperationMode::Jog) => self.jog(control),
+                Some(OperationMode::Homing) => self.homing(rising),
+                _ => {}
+            }
+        }
+        self.update_inputs();
+        self.scan_events();
+    }
+
+    /// Emits the events of the status bits raised since the previous scan, like
+    /// `Controller::scan_motion_events`
+    fn scan_events(&mut self) {
+        let status =
+            ServoStatus::from_process_image(&self.inputs, &self.outputs, self.cycle_count());
+        for kind in status_events(ID, self.scanned, &status) {
+            self.emit(kind);
+        }
+        self.scanned = status.status;
+    }
+
+    /// Returns the operation mode requested in the outputs
+    fn mode(&self) -> Option<OperationMode> {
+        OperationMode::from_raw(self.outputs[pdo::output::MODES_OF_OPERATION])
+    }
+
+    /// Performs the set-point handshake and moves towards the latched target
+    fn profile_position(&mut self, control: ControlWord, rising: bool) {
+        // A rising edge is only seen once the previous handshake finished
+        if rising && !self.acknowledged {
+            let target = i32::read(&self.outputs[pdo::output::TARGET_POSITION..]);
+            self.latched.push(target);
+            if self.target.is_some() && !control.is_set(ControlBit::Control5) {
+                self.queued = Some(target);
+            } else {
+                self.target = Some(target);
+            }
+            self.acknowledged = true;
+        } else if !control.is_set(ControlBit::Control4) {
+            self.acknowledged = false;
+        }
+
+        // The halt bit stops the drive, otherwise it moves a step towards the target
+        if control.is_set(ControlBit::Halt) {
+            self.target = None;
+            self.queued = None;
+        } else if let Some(target) = self.target {
+            let distance = target.saturating_sub(self.position);
+            self.position = self
+                .position
+                .saturating_add(distance.clamp(-self.step, self.step));
+            if self.position == target {
+                self.reached.push(target);
+                self.target = self.queued.take();
+            }
+        }
+    }
+
+    /// Moves for a cycle at the profile velocity in the direction of the jog bits
+    fn jog(&mut self, control: ControlWord) {
+        let velocity = u32::read(&self.outputs[pdo::output::PROFILE_VELOCITY..]);
+        let distance =
+            i64::from(velocity) * i64::try_from(CYCLE_TIME.as_millis()).unwrap_or(0) / 1_000;
+        let distance = i32::try_from(distance).unwrap_or(i32::MAX);
+        if control.is_set(ControlBit::Control4) {
+            self.position = self.position.saturating_add(distance);
+        } else if control.is_set(ControlBit::Control5) {
+            self.position = self.position.saturating_sub(distance);
+        }
+    }
+
+    /// Homes on the current position a few cycles after a rising edge of the homing start bit,
+    /// or reports a homing error
+    fn homing(&mut self, rising: bool) {
+        if rising && !matches!(self.homing, Homing::Running(_)) {
+            self.homing = Homing::Running(HOMING_CYCLES);
+        }
+        if let Homing::Running(left) = self.homing {
+            self.homing = if self.fail_homing {
+                self.fail_homing = false;
+                Homing::Failed
+            } else if left == 0 {
+                Homing::Attained
+            } else {
+                Homing::Running(left - 1)
+            };
+        }
+    }
+
+    /// Writes the state of the drive to the inputs
+    fn update_inputs(&mut self) {
+        let mut status = if self.faulted {
+            FAULT
+        } else {
+            OPERATION_ENABLED
+        };
+        if !self.faulted {
+            if self.mode() == Some(OperationMode::Homing) {
+                status |= match self.homing {
+                    Homing::Idle => mask(StatusWordBit::MotionComplete),
+                    Homing::Running(_) => 0,
+                    Homing::Attained => {
+                        mask(StatusWordBit::MotionComplete)
+                            | mask(StatusWordBit::AckStartRefReached)
+                    }
+                    Homing::Failed => {
+                        mask(StatusWordBit::MotionComplete) | mask(StatusWordBit::ModeSpecificError)
+                    }
+                };
+            } else {
+                if self.target.is_none() {
+                    status |= mask(StatusWordBit::MotionComplete);
+                }
+                if self.acknowledged {
+                    status |= mask(StatusWordBit::AckStartRefReached);
+                }
+            }
+        }
+        if self.homing == Homing::Attained {
+            status |= mask(StatusWordBit::DriveHomed);
+        }
+        status.write(&mut self.inputs[pdo::input::STATUS_WORD..]);
+        self.inputs[pdo::input::MODES_OF_OPERATION_DISPLAY] =
+            self.outputs[pdo::output::MODES_OF_OPERATION];
+        self.position
+            .write(&mut self.inputs[pdo::input::POSITION_ACTUAL_VALUE..]);
+        let velocity = if self.target.is_some() { self.step } else { 0 };
+        velocity.write(&mut self.inputs[pdo::input::VELOCITY_ACTUAL_VALUE..]);
+    }
+}
+
+impl OutputImage for SimulatedDrive {
+    fn apply_outputs<R>(
+        &mut self,
+        update: impl FnOnce(&mut [u8]) -> R,
+    ) -> Result<R, EthercrabError> {
+        Ok(update(&mut self.outputs))
+    }
+}
+
+impl Drive for SimulatedDrive {
+    fn id(&self) -> usize {
+        ID
+    }
+
+    /// Returns the time of the simulated clock, one cycle time after the epoch per cycle
+    fn now(&self) -> Instant {
+        self.epoch + CYCLE_TIME * u32::try_from(self.sent.len()).unwrap_or(u32::MAX)
+    }
+
+    fn cycle_count(&self) -> u64 {
+        self.sent.len() as u64
+    }
+
+    fn status_word(&mut self) -> Result<StatusWord, EthercrabError> {
+        Ok(self.status())
+    }
+
+    fn with_process_image<R>(
+        &mut self,
+        read: impl FnOnce(&[u8], &[u8]) -> R,
+    ) -> Result<R, EthercrabError> {
+        Ok(read(&self.inputs, &self.outputs))
+    }
+
+    fn check_motion(
+        &mut self,
+        predicate: impl Fn(StatusWord) -> bool,
+        start: Instant,
+        timeout: Duration,
+    ) -> Option<Result<StatusWord, WaitTimeout>> {
+        let status = self.status();
+        let conditions = WaitConditions {
+            expired: self.now().saturating_duration_since(start) >= timeout,
+            ..WaitConditions::default()
+        };
+        conditions.motion_result(ID, status, predicate(status))
+    }
+
+    async fn next_cycle(&mut self) {
+        self.cycle();
+    }
+
+    /// Requests the mode like `SimulatedDrive::set_mode`, the drive displays it after the next
+    /// cycle
+    async fn set_mode(&mut self, mode: OperationMode) -> Result<(), SetModeError> {
+        Self::set_mode(self, mode);
+        self.cycle();
+        Ok(())
+    }
+
+    fn emit(&mut self, kind: MotionEventKind) {
+        let cycle = self.cycle_count();
+        self.events.push(MotionEvent { cycle, kind });
+    }
+}
@@ -0,0 +1,489 @@
+//! This module contains the `Stepper` drive struct for Festo CMMT-ST stepper drives.
+//!
+//! A stepper drive is controlled through the same `CiA402` objects as a servo drive, so `Stepper`
+//! wraps a `Servo` and forwards the common calls to it. It adds the stepper specific settings,
+//! the current reduction at standstill and the loop mode, and uses defaults suited for
+//! steppers: jogs are slower and velocities are capped at a lower ceiling, as steppers lose
+//! torque at high speeds. Homing methods searching the index pulse or a block need the encoder,
+//! so they are rejected while the stepper runs in open loop.
+//!
+//! `DeviceInfo::drive_type` tells whether a device should be controlled with a `Servo` or a
+//! `Stepper`.
+
+use super::{
+    festo::VendorObjects,
+    objects::{self, Object},
+    servo::{
+        config::{ConfigError, ServoConfig},
+        homing::{method_requires_encoder, HomingConfig, HomingConfigError, HomingPolicy},
+        motion::MoveOptions,
+        HomingError, JoggingError, MovementError, MovementMode, Servo,
+    },
+    DeviceInfo, DisableError, DisableReport, DriveType,
+};
+use crate::controller::Controller;
+use core::fmt::{self, Debug, Formatter};
+use ethercrab::error::{Error as EthercrabError, MailboxError};
+
+/// The velocity ceiling of a stepper in increments per second, if none was set
+const DEFAULT_MAX_VELOCITY: u32 = 200_000;
+
+/// The velocity of jogs without an explicit velocity in increments per second
+const DEFAULT_JOG_VELOCITY: u32 = 20_000;
+
+/// Whether the stepper uses its encoder to control the motor current
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum LoopMode {
+    /// The motor is driven with a constant current without feedback, like a classic stepper
+    OpenLoop,
+
+    /// The current is controlled using the encoder, like a servo
+    ClosedLoop,
+}
+
+impl LoopMode {
+    /// Returns the value of the loop mode object
+    const fn raw(self) -> u8 {
+        match self {
+            Self::OpenLoop => 0,
+            Self::ClosedLoop => 1,
+        }
+    }
+}
+
+/// An error returned while configuring the stepper specific settings
+pub enum StepperError {
+    /// The drive doesn't support the setting
+    Unsupported(usize),
+
+    /// The current reduction is larger than 100 percent
+    InvalidCurrentReduction(usize, u8),
+
+    /// The drive reports an unknown loop mode
+    UnknownLoopMode(usize, u8),
+
+    /// Communication with the drive failed
+    Ethercat(usize, EthercrabError),
+}
+
+impl Debug for StepperError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Unsupported(device) => {
+                write!(f, "Device {device} doesn't support this stepper setting")
+            }
+            Self::InvalidCurrentReduction(device, percent) => write!(
+                f,
+                "Current reduction of {percent}% for device {device} is larger than 100%"
+            ),
+            Self::UnknownLoopMode(device, mode) => {
+                write!(f, "Device {device} reports unknown loop mode {mode}")
+            }
+            Self::Ethercat(device, error) => {
+                write!(f, "Failed to communicate with device {device}: {error:?}")
+            }
+        }
+    }
+}
+
+/// The struct responsible for controlling a stepper motor driven by a Festo CMMT-ST drive
+pub struct Stepper<'device, 'controller: 'device, const MAX_DEVICES: usize, const PDI_LENGTH: usize>
+{
+    /// The servo controlling the drive through the common `CiA402` objects
+    servo: Servo<'device, 'controller, MAX_DEVICES, PDI_LENGTH>,
+
+    /// The highest velocity commanded by the stepper in increments per second
+    max_velocity: u32,
+
+    /// The loop mode of the drive, read on first use
+    loop_mode: Option<LoopMode>,
+}
+
+impl<'device, 'controller: 'device, const MAX_DEVICES: usize, const PDI_LENGTH: usize>
+    Stepper<'device, 'controller, MAX_DEVICES, PDI_LENGTH>
+{
+    /// Creates a new device that can be used to control a stepper motor, with the default jog
+    /// velocity of a stepper.
+    ///
+    /// # Errors
+    /// Returns an error if the device failed to reset or enable
+    pub async fn new(
+        controller: &'controller Controller<'_, MAX_DEVICES, PDI_LENGTH>,
+        device_number: usize,
+    ) -> Result<Self, ConfigError> {
+        let config = ServoConfig {
+            default_jog_velocity: Some(DEFAULT_JOG_VELOCITY),
+            ..ServoConfig::new()
+        };
+        let servo = Servo::new_with_config(controller, device_number, config).await?;
+        Ok(Self::from_servo(servo))
+    }
+
+    /// Wraps a servo controlling a stepper drive.
+    /// Logs a warning if the drive doesn't look like a stepper drive.
+    pub fn from_servo(mut servo: Servo<'device, 'controller, MAX_DEVICES, PDI_LENGTH>) -> Self {
+        let device = servo.device_mut();
+        if let Ok(drive_type @ (DriveType::Servo | DriveType::Unknown)) = device.drive_type() {
+            log::warn!(
+                "Device {} is detected as {drive_type:?}, not as a stepper drive",
+                device.id
+            );
+        }
+        Self {
+            servo,
+            max_velocity: DEFAULT_MAX_VELOCITY,
+            loop_mode: None,
+        }
+    }
+
+    /// Unwraps the inner servo, bypassing the checks of the stepper
+    pub fn into_servo(self) -> Servo<'device, 'controller, MAX_DEVICES, PDI_LENGTH> {
+        self.servo
+    }
+
+    /// Returns a reference to the inner servo for more specific control
+    pub const fn servo(&self) -> &Servo<'device, 'controller, MAX_DEVICES, PDI_LENGTH> {
+        &self.servo
+    }
+
+    /// Returns a mutable reference to the inner servo for more specific control.
+    /// Calls on the servo bypass the velocity ceiling and the homing checks of the stepper.
+    pub fn servo_mut(&mut self) -> &mut Servo<'device, 'controller, MAX_DEVICES, PDI_LENGTH> {
+        &mut self.servo
+    }
+
+    /// Returns the highest velocity commanded by the stepper in increments per second
+    pub const fn max_velocity(&self) -> u32 {
+        self.max_velocity
+    }
+
+    /// Sets the highest velocity commanded by the stepper in increments per second.
+    /// Higher velocities requested by moves and jogs are reduced to it.
+    pub fn set_max_velocity(&mut self, max_velocity: u32) {
+        self.max_velocity = max_velocity;
+    }
+
+    /// Reads all identifying information of the drive.
+    ///
+    /// # Errors
+    /// Returns an error if another reference to the device exists
+    pub async fn info(&mut self) -> Result<DeviceInfo, EthercrabError> {
+        self.servo.info().await
+    }
+
+    /// Retrieves the current position of the stepper in increments.
+    ///
+    /// # Errors
+    /// Returns an error if another reference to the device exists
+    pub fn get_position(&mut self) -> Result<i32, EthercrabError> {
+        self.servo.get_position()
+    }
+
+    /// Retrieves the current velocity of the stepper in increments per second.
+    ///
+    /// # Errors
+    /// Returns an error if another reference to the device exists
+    pub fn get_velocity(&mut self) -> Result<i32, EthercrabError> {
+        self.servo.get_velocity()
+    }
+
+    /// Returns the object of the vendor objects, if the drive supports it
+    ///
+    /// # Errors
+    /// Returns an error if the drive doesn't support the object
+    fn stepper_object(
+        &self,
+        object: fn(&VendorObjects) -> Option<Object>,
+    ) -> Result<Object, StepperError> {
+        object(self.servo.vendor_objects())
+            .ok_or_else(|| StepperError::Unsupported(self.servo.device().id))
+    }
+
+    /// Converts a failed SDO transfer, aborted transfers mean the drive doesn't support the
+    /// object
+    const fn sdo_error(&self, error: EthercrabError) -> StepperError {
+        let id = self.servo.device().id;
+        match error {
+            EthercrabError::Mailbox(MailboxError::Aborted { .. }) => StepperError::Unsupported(id),
+            error => StepperError::Ethercat(id, error),
+        }
+    }
+
+    /// Sets the reduction of the motor current at standstill in percent of the rated current,
+    /// which limits the heating of a stepper holding its position. The holding torque drops
+    /// accordingly.
+    ///
+    /// # Errors
+    /// Returns an error if:
+    /// - The reduction is larger than 100 percent
+    /// - The drive doesn't support the setting
+    /// - The reduction couldn't be written
+    pub async fn set_standstill_current_reduction(
+        &mut self,
+        percent: u8,
+    ) -> Result<(), StepperError> {
+        if percent > 100 {
+            return Err(StepperError::InvalidCurrentReduction(
+                self.servo.device().id,
+                percent,
+            ));
+        }
+        let object = self.stepper_object(|objects| objects.standstill_current_reduction)?;
+        if let Err(error) = self.servo.device_mut().write_object(object, percent).await {
+            return Err(self.sdo_error(error));
+        }
+        Ok(())
+    }
+
+    /// Reads the reduction of the motor current at standstill in percent of the rated current.
+    ///
+    /// # Errors
+    /// Returns an error if the drive doesn't support the setting or it couldn't be read
+    pub async fn standstill_current_reduction(&mut self) -> Result<u8, StepperError> {
+        let object = self.stepper_object(|objects| objects.standstill_current_reduction)?;
+        let result = self.servo.device_mut().read_object(object).await;
+        result.map_err(|error| self.sdo_error(error))
+    }
+
+    /// Selects whether the stepper runs in open or closed loop.
+    /// The drive has to be disabled for the change to take effect on some firmware versions.
+    ///
+    /// # Errors
+    /// Returns an error if the drive doesn't support the setting or it couldn't be written
+    pub async fn set_loop_mode(&mut self, mode: LoopMode) -> Result<(), StepperError> {
+        let object = self.stepper_object(|objects| objects.loop_mode)?;
+        if let Err(error) = self
+            .servo
+            .device_mut()
+            .write_object(object, mode.raw())
+            .await
+        {
+            return Err(self.sdo_error(error));
+        }
+        self.loop_mode = Some(mode);
+        if self.servo.device().controller.verbose() {
+            log::info!(
+                "Set loop mode of device {} to {mode:?}",
+                self.servo.device().id
+            );
+        }
+        Ok(())
+    }
+
+    /// Returns whether the stepper runs in open or closed loop, only the first call
+    /// communicates with the drive.
+    ///
+    /// # Errors
+    /// Returns an error if:
+    /// - The drive doesn't support the setting
+    /// - The loop mode couldn't be read
+    /// - The drive reports an unknown loop mode
+    pub async fn loop_mode(&mut self) -> Result<LoopMode, StepperError> {
+        if let Some(mode) = self.loop_mode {
+            return Ok(mode);
+        }
+        let object = self.stepper_object(|objects| objects.loop_mode)?;
+        let raw: u8 = match self.servo.device_mut().read_object(object).await {
+            Ok(raw) => raw,
+            Err(error) => return Err(self.sdo_error(error)),
+        };
+        let mode = match raw {
+            0 => LoopMode::OpenLoop,
+            1 => LoopMode::ClosedLoop,
+            raw => return Err(StepperError::UnknownLoopMode(self.servo.device().id, raw)),
+        };
+        self.loop_mode = Some(mode);
+        Ok(mode)
+    }
+
+    /// Checks whether the homing method can be used in the current loop mode.
+    /// Drives without a readable loop mode decide themselves.
+    ///
+    /// # Errors
+    /// Returns an error if the method needs the encoder while running in open loop
+    async fn check_homing_method(&mut self, method: i8) -> Result<(), HomingError> {
+        if method_requires_encoder(method)
+            && matches!(self.loop_mode().await, Ok(LoopMode::OpenLoop))
+        {
+            return Err(HomingConfigError::RequiresEncoder(self.servo.device().id, method).into());
+        }
+        Ok(())
+    }
+
+    /// Moves the stepper to home according to the policy, see `Servo::home`.
+    /// Steppers often lack a reference switch, so the homing method configured in the drive is
+    /// checked first.
+    ///
+    /// # Errors
+    /// Returns an error if:
+    /// - The homing method couldn't be read
+    /// - The homing method needs the encoder while running in open loop
+    /// - Homing failed, see `Servo::home`
+    ///
+    /// # Returns
+    /// Whether homing was performed
+    pub async fn home(&mut self, policy: HomingPolicy) -> Result<bool, HomingError> {
+        let device = self.servo.device().id;
+        let object = objects::HOMING_METHOD;
+        let method = self
+            .servo
+            .device_mut()
+            .read_object(object)
+            .await
+            .map_err(|error| HomingConfigError::Ethercat {
+                device,
+                object,
+                error,
+            })?;
+        self.check_homing_method(method).await?;
+        self.servo.home(policy).await
+    }
+
+    /// Writes the homing parameters to the drive and moves the stepper to home according to
+    /// the policy, see `Servo::home_with_config`.
+    ///
+    /// # Errors
+    /// Returns an error if:
+    /// - The homing method needs the encoder while running in open loop
+    /// - The parameters couldn't be configured or homing failed
+    ///
+    /// # Returns
+    /// Whether homing was performed
+    pub async fn home_with_config(
+        &mut self,
+        policy: HomingPolicy,
+        config: &HomingConfig,
+    ) -> Result<bool, HomingError> {
+        self.check_homing_method(config.method).await?;
+        self.servo.home_with_config(policy, config).await
+    }
+
+    /// Reduces the velocity to the velocity ceiling of the stepper
+    fn limit_velocity(&self, velocity: u32) -> u32 {
+        if velocity > self.max_velocity {
+            log::warn!(
+                "Velocity {velocity} of device {} exceeds the stepper ceiling, using {}",
+                self.servo.device().id,
+                self.max_velocity
+            );
+        }
+        velocity.min(self.max_velocity)
+    }
+
+    /// Moves the stepper to the requested position, see `Servo::move_position`.
+    ///
+    /// # Errors
+    /// See `Servo::move_with`
+    pub async fn move_position(
+        &mut self,
+        target: i32,
+        movement: MovementMode,
+    ) -> Result<(), MovementError> {
+        self.move_with(target, &MoveOptions::new().with_mode(movement))
+            .await
+    }
+
+    /// Moves the stepper to the requested position with the requested velocity in increments
+    /// per second, reduced to the velocity ceiling.
+    ///
+    /// # Errors
+    /// See `Servo::move_with`
+    pub async fn move_position_velocity(
+        &mut self,
+        target: i32,
+        velocity: u32,
+        movement: MovementMode,
+    ) -> Result<(), MovementError> {
+        let options = MoveOptions::new()
+            .with_mode(movement)
+            .with_velocity(velocity);
+        self.move_with(target, &options).await
+    }
+
+    /// Moves the stepper to the requested position with the requested options, see
+    /// `Servo::move_with`. The velocity is reduced to the velocity ceiling, moves without a
+    /// velocity use the default velocity of the servo configuration or the ceiling.
+    ///
+    /// # Errors
+    /// See `Servo::move_with`
+    pub async fn move_with(
+        &mut self,
+        target: i32,
+        options: &MoveOptions,
+    ) -> Result<(), MovementError> {
+        let velocity = options
+            .velocity
+            .or_else(|| self.servo.config().default_velocity)
+            .unwrap_or(self.max_velocity);
+        let options = MoveOptions {
+            velocity: Some(self.limit_velocity(velocity)),
+            ..options.clone()
+        };
+        self.servo.move_with(target, &options).await
+    }
+
+    /// Moves the stepper in positive direction at the default jog velocity.
+    ///
+    /// # Errors
+    /// See `Servo::jog_positive_at`
+    pub async fn jog_positive(&mut self) -> Result<(), JoggingError> {
+        let velocity = self.jog_velocity();
+        self.servo.jog_positive_at(velocity).await
+    }
+
+    /// Moves the stepper in negative direction at the default jog velocity.
+    ///
+    /// # Errors
+    /// See `Servo::jog_negative_at`
+    pub async fn jog_negative(&mut self) -> Result<(), JoggingError> {
+        let velocity = self.jog_velocity();
+        self.servo.jog_negative_at(velocity).await
+    }
+
+    /// Moves the stepper in positive direction at the velocity in increments per second,
+    /// reduced to the velocity ceiling.
+    ///
+    /// # Errors
+    /// See `Servo::jog_positive_at`
+    pub async fn jog_positive_at(&mut self, velocity: u32) -> Result<(), JoggingError> {
+        let velocity = self.limit_velocity(velocity);
+        self.servo.jog_positive_at(velocity).await
+    }
+
+    /// Moves the stepper in negative direction at the velocity in increments per second,
+    /// reduced to the velocity ceiling.
+    ///
+    /// # Errors
+    /// See `Servo::jog_negative_at`
+    pub async fn jog_negative_at(&mut self, velocity: u32) -> Result<(), JoggingError> {
+        let velocity = self.limit_velocity(velocity);
+        self.servo.jog_negative_at(velocity).await
+    }
+
+    /// Returns the default jog velocity reduced to the velocity ceiling
+    fn jog_velocity(&self) -> u32 {
+        let velocity = self
+            .servo
+            .config()
+            .default_jog_velocity
+            .unwrap_or(DEFAULT_JOG_VELOCITY);
+        self.limit_velocity(velocity)
+    }
+
+    /// Stops jogging the stepper.
+    ///
+    /// # Errors
+    /// Returns an error if the device faulted or didn't stop in time
+    pub async fn jog_stop(&mut self) -> Result<(), JoggingError> {
+        self.servo.jog_stop().await
+    }
+
+    /// Disables the device after use, see `Device::disable`.
+    ///
+    /// # Errors
+    /// Returns an error if the device didn't get disabled.
+    pub async fn disable(self) -> Result<DisableReport, DisableError> {
+        self.servo.disable().await
+    }
+}
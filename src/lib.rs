@@ -10,5 +10,15 @@
 #[cfg(not(any(feature = "tokio", feature = "smol")))]
 const _: () = panic!("Either tokio or smol needs to be used as async runtime");
 
+#[cfg(all(feature = "tokio", feature = "smol"))]
+const _: () = panic!(
+    "Only one of tokio and smol can be used as async runtime, disable the default features to \
+     use smol"
+);
+
+mod channel;
 pub mod controller;
+mod cycle;
 pub mod device;
+pub mod pdo;
+pub mod trajectory;
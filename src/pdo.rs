@@ -0,0 +1,187 @@
+//! This module contains the PDO mapping written to the drives while configuring the network.
+//!
+//! The byte offsets of the mapped objects in the process image are derived from these tables,
+//! so objects added to the mapping are automatically read from the process image.
+
+use crate::device::{festo, objects::Object};
+
+/// The index to write the output PDO's to
+pub const OUTPUT_INDEX: u16 = 0x1600;
+
+/// The values for the output PDO's (index, sub-index, and bit length of each object)
+pub const OUTPUTS: [u32; 9] = [
+    0x6040_0010,
+    0x6060_0008,
+    0x607a_0020,
+    0x6081_0020,
+    0x60ff_0020,
+    0x6071_0010,
+    0x60b1_0020,
+    0x60b2_0010,
+    0x0000_0008,
+];
+
+/// The index to write the input PDO's to
+pub const INPUT_INDEX: u16 = 0x1A00;
+
+/// The values for the input PDO's (index, sub-index, and bit length of each object)
+#[cfg(not(feature = "demand-pdo"))]
+pub const INPUTS: [u32; 8] = [
+    0x6041_0010,
+    0x6061_0008,
+    0x6064_0020,
+    0x606c_0020,
+    0x6077_0010,
+    0x2194_0520,
+    0x60f4_0020,
+    0x0000_0008,
+];
+
+/// The values for the input PDO's (index, sub-index, and bit length of each object).
+/// The position demand (0x6062) and velocity demand (0x606B) are mapped as well.
+#[cfg(feature = "demand-pdo")]
+pub const INPUTS: [u32; 10] = [
+    0x6041_0010,
+    0x6061_0008,
+    0x6064_0020,
+    0x606c_0020,
+    0x6077_0010,
+    0x2194_0520,
+    0x60f4_0020,
+    0x6062_0020,
+    0x606b_0020,
+    0x0000_0008,
+];
+
+/// Searches the mapping for the object.
+///
+/// # Returns
+/// The byte offset of the object in the process image or `None` if the object isn't mapped
+pub const fn offset(mapping: &[u32], object: Object) -> Option<usize> {
+    let mut offset = 0;
+    let mut entry = 0;
+    while entry < mapping.len() {
+        // Return the current offset if the index and sub-index match
+        let value = mapping[entry];
+        if value >> 16 == object.index as u32 && (value >> 8) & 0xFF == object.sub_index as u32 {
+            return Some(offset);
+        }
+
+        // Skip the bytes of the current object
+        offset += (value & 0xFF) as usize / 8;
+        entry += 1;
+    }
+    None
+}
+
+/// Returns the byte offset of the object in the input process image, if mapped
+pub const fn input_offset(object: Object) -> Option<usize> {
+    offset(&INPUTS, object)
+}
+
+/// Returns the byte offset of the object in the output process image, if mapped
+pub const fn output_offset(object: Object) -> Option<usize> {
+    offset(&OUTPUTS, object)
+}
+
+/// Returns the offset of an object that has to be mapped, fails to compile otherwise
+const fn mapped(offset: Option<usize>) -> usize {
+    match offset {
+        Some(offset) => offset,
+        None => panic!("Object isn't part of the PDO mapping"),
+    }
+}
+
+/// The byte offsets of the objects in the output process image
+pub mod output {
+    use super::{mapped, output_offset};
+    use crate::device::objects;
+
+    /// The offset of the control word
+    pub const CONTROL_WORD: usize = mapped(output_offset(objects::CONTROL_WORD));
+
+    /// The offset of the requested operation mode
+    pub const MODES_OF_OPERATION: usize = mapped(output_offset(objects::MODES_OF_OPERATION));
+
+    /// The offset of the target position
+    pub const TARGET_POSITION: usize = mapped(output_offset(objects::TARGET_POSITION));
+
+    /// The offset of the profile velocity
+    pub const PROFILE_VELOCITY: usize = mapped(output_offset(objects::PROFILE_VELOCITY));
+
+    /// The offset of the target velocity
+    pub const TARGET_VELOCITY: usize = mapped(output_offset(objects::TARGET_VELOCITY));
+
+    /// The offset of the target torque
+    pub const TARGET_TORQUE: usize = mapped(output_offset(objects::TARGET_TORQUE));
+
+    /// The offset of the velocity offset
+    pub const VELOCITY_OFFSET: usize = mapped(output_offset(objects::VELOCITY_OFFSET));
+
+    /// The offset of the torque offset
+    pub const TORQUE_OFFSET: usize = mapped(output_offset(objects::TORQUE_OFFSET));
+}
+
+/// The byte offsets of the objects in the input process image
+pub mod input {
+    use super::{festo, input_offset, mapped};
+    use crate::device::objects;
+
+    /// The offset of the status word
+    pub const STATUS_WORD: usize = mapped(input_offset(objects::STATUS_WORD));
+
+    /// The offset of the operation mode display
+    pub const MODES_OF_OPERATION_DISPLAY: usize =
+        mapped(input_offset(objects::MODES_OF_OPERATION_DISPLAY));
+
+    /// The offset of the actual position
+    pub const POSITION_ACTUAL_VALUE: usize = mapped(input_offset(objects::POSITION_ACTUAL_VALUE));
+
+    /// The offset of the actual velocity
+    pub const VELOCITY_ACTUAL_VALUE: usize = mapped(input_offset(objects::VELOCITY_ACTUAL_VALUE));
+
+    /// The offset of the actual torque
+    pub const TORQUE_ACTUAL_VALUE: usize = mapped(input_offset(objects::TORQUE_ACTUAL_VALUE));
+
+    /// The offset of the diagnosis message
+    pub const DIAGNOSIS_MESSAGE: usize = mapped(input_offset(festo::DIAGNOSIS_MESSAGE));
+
+    /// The offset of the following error
+    pub const FOLLOWING_ERROR_ACTUAL_VALUE: usize =
+        mapped(input_offset(objects::FOLLOWING_ERROR_ACTUAL_VALUE));
+}
+
+/// A value that can be read from and written to a process image in little endian byte order
+pub trait PdoValue: Sized + Copy {
+    /// The number of bytes the value takes in the process image
+    const SIZE: usize;
+
+    /// Reads the value from the start of the bytes
+    fn read(bytes: &[u8]) -> Self;
+
+    /// Writes the value to the start of the bytes
+    fn write(self, bytes: &mut [u8]);
+}
+
+/// Implements `PdoValue` for integer types
+macro_rules! impl_pdo_value {
+    ($($value:ty),*) => {
+        $(
+            impl PdoValue for $value {
+                const SIZE: usize = size_of::<$value>();
+
+                fn read(bytes: &[u8]) -> Self {
+                    let mut raw = [0; size_of::<$value>()];
+                    raw.copy_from_slice(&bytes[..Self::SIZE]);
+                    Self::from_le_bytes(raw)
+                }
+
+                fn write(self, bytes: &mut [u8]) {
+                    bytes[..Self::SIZE].copy_from_slice(&self.to_le_bytes());
+                }
+            }
+        )*
+    };
+}
+
+impl_pdo_value!(u8, i8, u16, i16, u32, i32);
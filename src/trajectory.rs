@@ -0,0 +1,1271 @@
+//! This module contains trajectory generators, which calculate setpoints for the cyclic modes.
+//!
+//! The generators are pure calculations and don't communicate with any device. Positions are in
+//! increments, velocities in increments per second and accelerations in increments per second
+//! squared.
+
+use crate::device::servo::{limits::SoftwareLimits, MovementMode};
+use core::{
+    fmt::{self, Debug, Formatter},
+    time::Duration,
+};
+
+/// The state of a trajectory at a point in time
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Sample {
+    /// The position in increments
+    pub position: f64,
+
+    /// The velocity in increments per second
+    pub velocity: f64,
+
+    /// The acceleration in increments per second squared
+    pub acceleration: f64,
+}
+
+/// A trajectory from a start to an end position
+pub trait Profile {
+    /// Returns the time it takes to complete the trajectory
+    fn duration(&self) -> Duration;
+
+    /// Returns the state of the trajectory at the requested time since the start.
+    /// Times after the end of the trajectory return the end position at standstill.
+    fn sample(&self, time: Duration) -> Sample;
+
+    /// Returns an iterator over the samples of the trajectory, stepping by the requested period.
+    /// The last sample is always the end of the trajectory.
+    fn samples(&self, period: Duration) -> Samples<'_, Self>
+    where
+        Self: Sized,
+    {
+        Samples {
+            profile: self,
+            period,
+            step: 0,
+            done: false,
+        }
+    }
+}
+
+/// An iterator over the samples of a trajectory, see `Profile::samples`
+pub struct Samples<'profile, P: Profile> {
+    /// The sampled trajectory
+    profile: &'profile P,
+
+    /// The time between samples
+    period: Duration,
+
+    /// The number of the next sample
+    step: u32,
+
+    /// Whether the end of the trajectory has been returned
+    done: bool,
+}
+
+impl<P: Profile> Iterator for Samples<'_, P> {
+    type Item = Sample;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.done {
+            return None;
+        }
+
+        // Return the end of the trajectory once the time passed the duration
+        let time = self.period.saturating_mul(self.step);
+        if time >= self.profile.duration() || self.period.is_zero() {
+            self.done = true;
+            return Some(self.profile.sample(self.profile.duration()));
+        }
+        self.step += 1;
+        Some(self.profile.sample(time))
+    }
+}
+
+/// Returns whether all values are finite and larger than zero
+fn all_positive(values: &[f64]) -> bool {
+    values.iter().all(|value| value.is_finite() && *value > 0.0)
+}
+
+/// A trajectory with constant acceleration, constant velocity and constant deceleration phases.
+/// Moves too short to reach the maximum velocity have a triangular velocity profile.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct TrapezoidalProfile {
+    /// The start position
+    start: f64,
+
+    /// The direction of the movement, 1 or -1
+    direction: f64,
+
+    /// The distance to travel
+    distance: f64,
+
+    /// The highest velocity reached
+    peak_velocity: f64,
+
+    /// The acceleration and deceleration
+    acceleration: f64,
+
+    /// The duration of the acceleration and the deceleration phase
+    acceleration_time: f64,
+
+    /// The duration of the constant velocity phase
+    cruise_time: f64,
+
+    /// The duration of the whole trajectory
+    duration: Duration,
+}
+
+impl TrapezoidalProfile {
+    /// Creates a trapezoidal trajectory from `start` to `end`.
+    ///
+    /// # Returns
+    /// The trajectory or `None` if the velocity or acceleration isn't a positive number, or the
+    /// trajectory would take too long to be represented as a `Duration`
+    pub fn new(start: i32, end: i32, max_velocity: f64, max_acceleration: f64) -> Option<Self> {
+        let profile = Self::along(
+            (f64::from(end) - f64::from(start)).abs(),
+            max_velocity,
+            max_acceleration,
+        )?;
+        Some(Self {
+            start: f64::from(start),
+            direction: if end < start { -1.0 } else { 1.0 },
+            ..profile
+        })
+    }
+
+    /// Creates a trapezoidal trajectory from 0 over the distance in the positive direction.
+    ///
+    /// # Returns
+    /// The trajectory or `None` if the velocity or acceleration isn't a positive number, or the
+    /// trajectory would take too long to be represented as a `Duration`
+    fn along(distance: f64, max_velocity: f64, max_acceleration: f64) -> Option<Self> {
+        if !all_positive(&[max_velocity, max_acceleration]) {
+            return None;
+        }
+
+        // Use a triangular profile if the maximum velocity can't be reached
+        let acceleration_distance = max_velocity * max_velocity / (2.0 * max_acceleration);
+        let (peak_velocity, cruise_time) = if 2.0 * acceleration_distance >= distance {
+            ((distance * max_acceleration).sqrt(), 0.0)
+        } else {
+            (
+                max_velocity,
+                2.0f64.mul_add(-acceleration_distance, distance) / max_velocity,
+            )
+        };
+
+        // Tiny velocities or accelerations result in durations that don't fit a `Duration`
+        let acceleration_time = peak_velocity / max_acceleration;
+        let duration =
+            Duration::try_from_secs_f64(2.0f64.mul_add(acceleration_time, cruise_time)).ok()?;
+        Some(Self {
+            start: 0.0,
+            direction: 1.0,
+            distance,
+            peak_velocity,
+            acceleration: max_acceleration,
+            acceleration_time,
+            cruise_time,
+            duration,
+        })
+    }
+
+    /// Returns the highest velocity reached during the trajectory
+    pub const fn peak_velocity(&self) -> f64 {
+        self.peak_velocity
+    }
+}
+
+impl Profile for TrapezoidalProfile {
+    fn duration(&self) -> Duration {
+        self.duration
+    }
+
+    fn sample(&self, time: Duration) -> Sample {
+        // Compare with the rounded duration, so sampling at the duration returns the exact end
+        let finished = time >= self.duration;
+        let time = time.as_secs_f64();
+        let deceleration_start = self.acceleration_time + self.cruise_time;
+        let end_time = deceleration_start + self.acceleration_time;
+
+        // Calculate the distance, velocity and acceleration in the current phase
+        let (distance, velocity, acceleration) = if finished || time >= end_time {
+            (self.distance, 0.0, 0.0)
+        } else if time < self.acceleration_time {
+            (
+                0.5 * self.acceleration * time * time,
+                self.acceleration * time,
+                self.acceleration,
+            )
+        } else if time < deceleration_start {
+            let acceleration_distance = 0.5 * self.peak_velocity * self.acceleration_time;
+            (
+                self.peak_velocity
+                    .mul_add(time - self.acceleration_time, acceleration_distance),
+                self.peak_velocity,
+                0.0,
+            )
+        } else {
+            let remaining = end_time - time;
+            (
+                (0.5 * self.acceleration * remaining).mul_add(-remaining, self.distance),
+                self.acceleration * remaining,
+                -self.acceleration,
+            )
+        };
+
+        Sample {
+            position: self.direction.mul_add(distance, self.start),
+            velocity: self.direction * velocity,
+            acceleration: self.direction * acceleration,
+        }
+    }
+}
+
+/// A straight line from a start to an end pose of several axes, traveled with a trapezoidal
+/// velocity profile along the line.
+///
+/// Every axis covers the same part of its distance at the same time. The samples of the path are
+/// the distance traveled along the line, `LinearPath::point` converts them to axis positions.
+#[derive(Debug, Clone, PartialEq)]
+pub struct LinearPath {
+    /// The start position of every axis
+    start: Vec<f64>,
+
+    /// The distance every axis travels, negative when moving in the negative direction
+    delta: Vec<f64>,
+
+    /// The length of the line
+    length: f64,
+
+    /// The trajectory of the distance traveled along the line
+    profile: TrapezoidalProfile,
+}
+
+impl LinearPath {
+    /// Creates a line from the start to the end pose.
+    ///
+    /// # Parameters
+    /// `velocity`: The maximum velocity along the line in increments per second
+    ///
+    /// `acceleration`: The acceleration along the line in increments per second squared
+    ///
+    /// # Returns
+    /// The path or `None` if the poses have a different number of axes, the velocity or
+    /// acceleration isn't a positive number, or traveling the line would take too long to be
+    /// represented as a `Duration`
+    pub fn new(start: &[i32], end: &[i32], velocity: f64, acceleration: f64) -> Option<Self> {
+        if start.len() != end.len() {
+            return None;
+        }
+        let delta: Vec<_> = start
+            .iter()
+            .zip(end)
+            .map(|(start, end)| f64::from(*end) - f64::from(*start))
+            .collect();
+        let length = delta.iter().map(|delta| delta * delta).sum::<f64>().sqrt();
+        Some(Self {
+            start: start.iter().copied().map(f64::from).collect(),
+            delta,
+            length,
+            profile: TrapezoidalProfile::along(length, velocity, acceleration)?,
+        })
+    }
+
+    /// Returns the length of the line
+    pub const fn length(&self) -> f64 {
+        self.length
+    }
+
+    /// Calculates the positions of the axes after traveling the distance along the line.
+    /// Distances outside of the line are clamped to its ends.
+    pub fn point(&self, distance: f64) -> Vec<f64> {
+        let fraction = if self.length > 0.0 {
+            (distance / self.length).clamp(0.0, 1.0)
+        } else {
+            1.0
+        };
+        self.start
+            .iter()
+            .zip(&self.delta)
+            .map(|(start, delta)| delta.mul_add(fraction, *start))
+            .collect()
+    }
+
+    /// Calculates how far the positions are away from the line.
+    /// The positions are compared to the point of the line at the progress of the axis with the
+    /// longest distance, so axes lagging behind the others are detected.
+    ///
+    /// # Returns
+    /// The index of the axis furthest away from the line and its distance, or `None` if the
+    /// number of positions doesn't match the number of axes or no axis moves
+    pub fn deviation(&self, positions: &[i32]) -> Option<(usize, f64)> {
+        if positions.len() != self.start.len() {
+            return None;
+        }
+
+        // Follow the progress of the axis covering the longest distance
+        let (lead, lead_delta) = self
+            .delta
+            .iter()
+            .enumerate()
+            .max_by(|(_, a), (_, b)| a.abs().total_cmp(&b.abs()))?;
+        if *lead_delta == 0.0 {
+            return None;
+        }
+        let fraction = (f64::from(positions[lead]) - self.start[lead]) / lead_delta;
+        positions
+            .iter()
+            .zip(self.start.iter().zip(&self.delta))
+            .map(|(position, (start, delta))| {
+                (f64::from(*position) - delta.mul_add(fraction, *start)).abs()
+            })
+            .enumerate()
+            .max_by(|(_, a), (_, b)| a.total_cmp(b))
+    }
+
+    /// Calculates the distances along the line while decelerating to standstill, sampled by
+    /// the period. Used to stop in the middle of the line without leaving it.
+    ///
+    /// # Parameters
+    /// `distance`: The distance along the line when starting to decelerate
+    ///
+    /// `velocity`: The velocity along the line when starting to decelerate
+    pub fn stopping_distances(&self, distance: f64, velocity: f64, period: Duration) -> Vec<f64> {
+        let acceleration = self.profile.acceleration;
+        let period = period.as_secs_f64();
+        if velocity <= 0.0 || period <= 0.0 {
+            return Vec::new();
+        }
+
+        // Count the periods instead of adding them up, so rounding errors don't accumulate
+        let stop_time = velocity / acceleration;
+        let mut distances: Vec<_> = (1..=u32::MAX)
+            .map(|step| f64::from(step) * period)
+            .take_while(|time| *time < stop_time)
+            .map(|time| velocity.mul_add(time, -0.5 * acceleration * time * time) + distance)
+            .collect();
+        distances.push(velocity * velocity / (2.0 * acceleration) + distance);
+        distances
+    }
+}
+
+impl Profile for LinearPath {
+    fn duration(&self) -> Duration {
+        self.profile.duration()
+    }
+
+    fn sample(&self, time: Duration) -> Sample {
+        self.profile.sample(time)
+    }
+}
+
+/// The profile of a positioning move taking a requested time, see `synchronized_move`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SynchronizedMove {
+    /// The profile velocity in increments per second
+    pub velocity: u32,
+
+    /// The profile acceleration and deceleration in increments per second squared
+    pub acceleration: u32,
+}
+
+/// Calculates the shortest time a trapezoidal move over the distance takes within the limits.
+///
+/// # Returns
+/// The duration or `None` if a limit isn't larger than zero, or the move would take too long to
+/// be represented as a `Duration`
+pub fn minimum_move_duration(
+    distance: u32,
+    max_velocity: u32,
+    max_acceleration: u32,
+) -> Option<Duration> {
+    let distance = f64::from(distance);
+    let (velocity, acceleration) = (f64::from(max_velocity), f64::from(max_acceleration));
+    if !all_positive(&[velocity, acceleration]) {
+        return None;
+    }
+
+    // Moves too short to reach the maximum velocity have a triangular velocity profile
+    let seconds = if velocity * velocity / acceleration >= distance {
+        2.0 * (distance / acceleration).sqrt()
+    } else {
+        distance / velocity + velocity / acceleration
+    };
+    Duration::try_from_secs_f64(seconds).ok()
+}
+
+/// Calculates the velocity of a trapezoidal move over the distance, which takes the requested
+/// time when accelerating and decelerating at the maximum acceleration.
+///
+/// Moves of several axes calculated for the same duration start and arrive together.
+///
+/// # Returns
+/// The profile, or `None` if a limit isn't larger than zero or the move can't be completed
+/// within the duration, see `minimum_move_duration`
+#[expect(
+    clippy::cast_possible_truncation,
+    clippy::cast_sign_loss,
+    reason = "The velocity is between zero and the maximum velocity, which is a u32"
+)]
+pub fn synchronized_move(
+    distance: u32,
+    duration: Duration,
+    max_velocity: u32,
+    max_acceleration: u32,
+) -> Option<SynchronizedMove> {
+    if duration < minimum_move_duration(distance, max_velocity, max_acceleration)? {
+        return None;
+    }
+
+    // Solve distance = velocity * (duration - velocity / acceleration) for the lowest velocity,
+    // the other solution would need a longer acceleration than the whole move
+    let distance = f64::from(distance);
+    let acceleration = f64::from(max_acceleration);
+    let seconds = duration.as_secs_f64();
+    let discriminant = (acceleration * seconds)
+        .mul_add(acceleration * seconds, -4.0 * acceleration * distance)
+        .max(0.0);
+    let velocity = (acceleration.mul_add(seconds, -discriminant.sqrt()) / 2.0).round();
+    Some(SynchronizedMove {
+        velocity: (velocity as u32).clamp(1, max_velocity),
+        acceleration: max_acceleration,
+    })
+}
+
+/// A phase of a jerk-limited trajectory with constant jerk
+#[derive(Debug, Default, Clone, Copy, PartialEq)]
+struct Segment {
+    /// The time since the start of the trajectory at which the phase starts
+    start_time: f64,
+
+    /// The duration of the phase
+    duration: f64,
+
+    /// The distance travelled at the start of the phase
+    distance: f64,
+
+    /// The velocity at the start of the phase
+    velocity: f64,
+
+    /// The acceleration at the start of the phase
+    acceleration: f64,
+
+    /// The jerk during the phase
+    jerk: f64,
+}
+
+impl Segment {
+    /// Returns the distance, velocity and acceleration after the time since the start of the
+    /// phase
+    fn at(&self, time: f64) -> (f64, f64, f64) {
+        let distance = (self.jerk / 6.0 * time).mul_add(
+            time * time,
+            (0.5 * self.acceleration * time)
+                .mul_add(time, self.velocity.mul_add(time, self.distance)),
+        );
+        let velocity =
+            (0.5 * self.jerk * time).mul_add(time, self.acceleration.mul_add(time, self.velocity));
+        let acceleration = self.jerk.mul_add(time, self.acceleration);
+        (distance, velocity, acceleration)
+    }
+}
+
+/// A jerk-limited (S-curve) trajectory.
+///
+/// The trajectory has seven phases: increasing, constant and decreasing acceleration, constant
+/// velocity, and increasing, constant and decreasing deceleration. Phases are skipped when the
+/// move is too short to reach the maximum acceleration or velocity.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct SCurveProfile {
+    /// The start position
+    start: f64,
+
+    /// The end position
+    end: f64,
+
+    /// The direction of the movement, 1 or -1
+    direction: f64,
+
+    /// The highest velocity reached
+    peak_velocity: f64,
+
+    /// The phases of the trajectory
+    segments: [Segment; 7],
+
+    /// The duration of the whole trajectory
+    duration: Duration,
+}
+
+/// Returns the duration of the jerk phases and of the whole acceleration phase needed to reach
+/// the velocity from standstill
+fn acceleration_times(velocity: f64, max_acceleration: f64, max_jerk: f64) -> (f64, f64) {
+    if velocity * max_jerk < max_acceleration.powi(2) {
+        // The maximum acceleration isn't reached
+        let jerk_time = (velocity / max_jerk).sqrt();
+        (jerk_time, 2.0 * jerk_time)
+    } else {
+        let jerk_time = max_acceleration / max_jerk;
+        (jerk_time, jerk_time + velocity / max_acceleration)
+    }
+}
+
+impl SCurveProfile {
+    /// Creates a jerk-limited trajectory from `start` to `end`.
+    ///
+    /// # Returns
+    /// The trajectory or `None` if the velocity, acceleration or jerk isn't a positive number,
+    /// or the trajectory would take too long to be represented as a `Duration`
+    pub fn new(
+        start: i32,
+        end: i32,
+        max_velocity: f64,
+        max_acceleration: f64,
+        max_jerk: f64,
+    ) -> Option<Self> {
+        if !all_positive(&[max_velocity, max_acceleration, max_jerk]) {
+            return None;
+        }
+        let distance = (f64::from(end) - f64::from(start)).abs();
+
+        // Accelerating to a velocity and back to standstill takes `velocity * acceleration time`,
+        // lower the peak velocity with bisection if the maximum velocity needs more distance
+        let distance_to_stop =
+            |velocity: f64| velocity * acceleration_times(velocity, max_acceleration, max_jerk).1;
+        let peak_velocity = if distance_to_stop(max_velocity) > distance {
+            let (mut low, mut high) = (0.0, max_velocity);
+            for _ in 0..100 {
+                let middle = 0.5 * (low + high);
+                if distance_to_stop(middle) > distance {
+                    high = middle;
+                } else {
+                    low = middle;
+                }
+            }
+            low
+        } else {
+            max_velocity
+        };
+
+        // Calculate the durations of the phases
+        let (jerk_time, acceleration_time) =
+            acceleration_times(peak_velocity, max_acceleration, max_jerk);
+        let cruise_time = if peak_velocity > 0.0 {
+            (distance - distance_to_stop(peak_velocity)).max(0.0) / peak_velocity
+        } else {
+            0.0
+        };
+        let constant_time = 2.0f64.mul_add(-jerk_time, acceleration_time);
+        let phases = [
+            (jerk_time, max_jerk),
+            (constant_time, 0.0),
+            (jerk_time, -max_jerk),
+            (cruise_time, 0.0),
+            (jerk_time, -max_jerk),
+            (constant_time, 0.0),
+            (jerk_time, max_jerk),
+        ];
+
+        // Integrate the phases to find the state at the start of each phase
+        let mut segments = [Segment::default(); 7];
+        let mut current = Segment::default();
+        for (segment, (duration, jerk)) in segments.iter_mut().zip(phases) {
+            current.duration = duration.max(0.0);
+            current.jerk = jerk;
+            *segment = current;
+
+            let (distance, velocity, acceleration) = current.at(current.duration);
+            current = Segment {
+                start_time: current.start_time + current.duration,
+                distance,
+                velocity,
+                acceleration,
+                ..Segment::default()
+            };
+        }
+
+        // Tiny velocities, accelerations or jerks result in durations that don't fit a `Duration`
+        let last = segments[6];
+        let duration = Duration::try_from_secs_f64(last.start_time + last.duration).ok()?;
+        Some(Self {
+            start: f64::from(start),
+            end: f64::from(end),
+            direction: if end < start { -1.0 } else { 1.0 },
+            peak_velocity,
+            segments,
+            duration,
+        })
+    }
+
+    /// Returns the highest velocity reached during the trajectory
+    pub const fn peak_velocity(&self) -> f64 {
+        self.peak_velocity
+    }
+}
+
+impl Profile for SCurveProfile {
+    fn duration(&self) -> Duration {
+        self.duration
+    }
+
+    fn sample(&self, time: Duration) -> Sample {
+        // Return the exact end position after the last phase
+        if time >= self.duration {
+            return Sample {
+                position: self.end,
+                velocity: 0.0,
+                acceleration: 0.0,
+            };
+        }
+        let time = time.as_secs_f64();
+
+        // Find the current phase and calculate the state in it
+        let segment = self
+            .segments
+            .iter()
+            .rev()
+            .find(|segment| segment.start_time <= time)
+            .unwrap_or(&self.segments[0]);
+        let (distance, velocity, acceleration) = segment.at(time - segment.start_time);
+        Sample {
+            position: self.direction.mul_add(distance, self.start),
+            velocity: self.direction * velocity,
+            acceleration: self.direction * acceleration,
+        }
+    }
+}
+
+/// A point of a trajectory executed as a profile position move
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct TrajectoryPoint {
+    /// The position in increments
+    pub position: i32,
+
+    /// The velocity to move to the position with in increments per second
+    pub velocity: u32,
+
+    /// The time to wait at the position before moving to the next point
+    pub dwell: Duration,
+
+    /// Whether the position is absolute or relative to the previous point
+    pub mode: MovementMode,
+}
+
+/// A sequence of points generated offline, executed with `Servo::run_trajectory`
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Trajectory {
+    /// The points in the order they are executed
+    pub points: Vec<TrajectoryPoint>,
+}
+
+/// The limits a trajectory is validated against
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct TrajectoryLimits {
+    /// The range of positions the points have to be in, not checked if `None`
+    pub position: Option<SoftwareLimits>,
+
+    /// The highest velocity in increments per second, not checked if `None`
+    pub max_velocity: Option<u32>,
+}
+
+/// A trajectory that can't be executed
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub enum TrajectoryError {
+    /// The trajectory doesn't contain any points
+    Empty,
+
+    /// The velocity of the point is zero
+    ZeroVelocity(usize),
+
+    /// The point lies outside of the position limits
+    OutOfLimits {
+        /// The index of the point
+        point: usize,
+
+        /// The absolute position of the point
+        position: i32,
+    },
+
+    /// The velocity of the point exceeds the maximum velocity
+    VelocityTooHigh {
+        /// The index of the point
+        point: usize,
+
+        /// The velocity of the point
+        velocity: u32,
+
+        /// The maximum velocity
+        max: u32,
+    },
+}
+
+impl Debug for TrajectoryError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Empty => write!(f, "The trajectory doesn't contain any points"),
+            Self::ZeroVelocity(point) => write!(f, "Point {point} has a velocity of zero"),
+            Self::OutOfLimits { point, position } => write!(
+                f,
+                "Point {point} at position {position} is outside of the position limits"
+            ),
+            Self::VelocityTooHigh {
+                point,
+                velocity,
+                max,
+            } => write!(
+                f,
+                "The velocity {velocity} of point {point} exceeds the maximum of {max}"
+            ),
+        }
+    }
+}
+
+impl Trajectory {
+    /// Creates a trajectory from the points
+    pub const fn new(points: Vec<TrajectoryPoint>) -> Self {
+        Self { points }
+    }
+
+    /// Checks whether every point can be reached in order, starting at the start position.
+    /// Relative points are added to the position of the previous point.
+    ///
+    /// # Errors
+    /// Returns an error if:
+    /// - The trajectory is empty
+    /// - A point has a velocity of zero or above the maximum velocity
+    /// - A point lies outside of the position limits
+    pub fn validate(
+        &self,
+        start_position: i32,
+        limits: &TrajectoryLimits,
+    ) -> Result<(), TrajectoryError> {
+        if self.points.is_empty() {
+            return Err(TrajectoryError::Empty);
+        }
+        let mut position = start_position;
+        for (index, point) in self.points.iter().enumerate() {
+            if point.velocity == 0 {
+                return Err(TrajectoryError::ZeroVelocity(index));
+            }
+            if let Some(max) = limits.max_velocity {
+                if point.velocity > max {
+                    return Err(TrajectoryError::VelocityTooHigh {
+                        point: index,
+                        velocity: point.velocity,
+                        max,
+                    });
+                }
+            }
+
+            // Follow the absolute position along the points, each point is the previous target
+            // of the next one
+            position = point.mode.end_position(point.position, position, position);
+            if limits
+                .position
+                .is_some_and(|limits| !limits.contains(position))
+            {
+                return Err(TrajectoryError::OutOfLimits {
+                    point: index,
+                    position,
+                });
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    //! Tests of the trajectory generators against closed-form values
+
+    use super::*;
+
+    /// The tolerance of comparisons between calculated and expected values
+    const EPSILON: f64 = 1e-9;
+
+    /// Checks the position, velocity and acceleration of a sample
+    fn assert_sample(sample: Sample, position: f64, velocity: f64, acceleration: f64) {
+        assert!(
+            (sample.position - position).abs() < EPSILON
+                && (sample.velocity - velocity).abs() < EPSILON
+                && (sample.acceleration - acceleration).abs() < EPSILON,
+            "{sample:?} != ({position}, {velocity}, {acceleration})"
+        );
+    }
+
+    /// A move long enough to cruise at the maximum velocity follows the closed-form phases
+    #[test]
+    fn trapezoidal_sample() {
+        // 2 s accelerating over 100, 8 s cruising over 800, 2 s decelerating over 100
+        let profile = TrapezoidalProfile::new(0, 1000, 100.0, 50.0).unwrap();
+        assert_eq!(profile.duration(), Duration::from_secs(12));
+        assert!((profile.peak_velocity() - 100.0).abs() < EPSILON);
+        assert_sample(profile.sample(Duration::ZERO), 0.0, 0.0, 50.0);
+        assert_sample(profile.sample(Duration::from_secs(1)), 25.0, 50.0, 50.0);
+        assert_sample(profile.sample(Duration::from_secs(2)), 100.0, 100.0, 0.0);
+        assert_sample(profile.sample(Duration::from_secs(6)), 500.0, 100.0, 0.0);
+        assert_sample(profile.sample(Duration::from_secs(11)), 975.0, 50.0, -50.0);
+        assert_sample(profile.sample(Duration::from_secs(12)), 1000.0, 0.0, 0.0);
+        assert_sample(profile.sample(Duration::from_secs(20)), 1000.0, 0.0, 0.0);
+    }
+
+    /// A move in the negative direction mirrors the samples
+    #[test]
+    fn trapezoidal_sample_negative() {
+        let profile = TrapezoidalProfile::new(1000, 0, 100.0, 50.0).unwrap();
+        assert_sample(profile.sample(Duration::from_secs(1)), 975.0, -50.0, -50.0);
+        assert_sample(profile.sample(Duration::from_secs(6)), 500.0, -100.0, 0.0);
+        assert_sample(profile.sample(Duration::from_secs(11)), 25.0, -50.0, 50.0);
+        assert_sample(profile.sample(Duration::from_secs(12)), 0.0, 0.0, 0.0);
+    }
+
+    /// A move too short to reach the maximum velocity has a triangular velocity profile
+    #[test]
+    fn triangular_sample() {
+        // Peak velocity sqrt(distance * acceleration) = 100 after 1 s
+        let profile = TrapezoidalProfile::new(0, 100, 1000.0, 100.0).unwrap();
+        let at = |millis| profile.sample(Duration::from_millis(millis));
+        assert_eq!(profile.duration(), Duration::from_secs(2));
+        assert!((profile.peak_velocity() - 100.0).abs() < EPSILON);
+        assert_sample(at(500), 12.5, 50.0, 100.0);
+        assert_sample(at(1000), 50.0, 100.0, -100.0);
+        assert_sample(at(1500), 87.5, 50.0, -100.0);
+        assert_sample(at(2000), 100.0, 0.0, 0.0);
+    }
+
+    /// A move without distance ends immediately
+    #[test]
+    fn trapezoidal_zero_distance() {
+        let profile = TrapezoidalProfile::new(42, 42, 100.0, 50.0).unwrap();
+        assert_eq!(profile.duration(), Duration::ZERO);
+        assert_sample(profile.sample(Duration::ZERO), 42.0, 0.0, 0.0);
+    }
+
+    /// Limits that aren't positive numbers, or too small to finish in a representable time,
+    /// don't create a trajectory
+    #[test]
+    fn trapezoidal_invalid_limits() {
+        assert!(TrapezoidalProfile::new(0, 100, 0.0, 50.0).is_none());
+        assert!(TrapezoidalProfile::new(0, 100, 100.0, -1.0).is_none());
+        assert!(TrapezoidalProfile::new(0, 100, f64::NAN, 50.0).is_none());
+        assert!(TrapezoidalProfile::new(0, 1_000_000, 1e-300, 1.0).is_none());
+        assert!(TrapezoidalProfile::new(0, 1_000_000, 1.0, 1e-300).is_none());
+    }
+
+    /// The samples step by the period and end exactly at the end position
+    #[test]
+    fn samples_end_at_end() {
+        let profile = TrapezoidalProfile::new(0, 1000, 100.0, 50.0).unwrap();
+        let samples: Vec<_> = profile.samples(Duration::from_millis(250)).collect();
+        assert_eq!(samples.len(), 49);
+        assert_sample(samples[4], 25.0, 50.0, 50.0);
+        assert_sample(*samples.last().unwrap(), 1000.0, 0.0, 0.0);
+    }
+
+    /// The end is reached exactly, even when the duration was rounded to whole nanoseconds
+    #[test]
+    fn trapezoidal_exact_end() {
+        let profile = TrapezoidalProfile::new(-7, 1234, 333.0, 77.0).unwrap();
+        assert_eq!(
+            profile.sample(profile.duration()),
+            Sample {
+                position: 1234.0,
+                velocity: 0.0,
+                acceleration: 0.0,
+            }
+        );
+    }
+
+    /// The shortest duration of trapezoidal and triangular moves
+    #[test]
+    fn minimum_duration() {
+        assert_eq!(
+            minimum_move_duration(1000, 100, 50),
+            Some(Duration::from_secs(12))
+        );
+        assert_eq!(
+            minimum_move_duration(100, 1000, 100),
+            Some(Duration::from_secs(2))
+        );
+        assert_eq!(minimum_move_duration(0, 100, 50), Some(Duration::ZERO));
+        assert_eq!(minimum_move_duration(1000, 0, 50), None);
+        assert_eq!(minimum_move_duration(1000, 100, 0), None);
+    }
+
+    /// Synchronized moves take the requested duration at the maximum acceleration
+    #[test]
+    fn synchronized_moves() {
+        // The shortest duration requires the maximum velocity
+        assert_eq!(
+            synchronized_move(1000, Duration::from_secs(12), 100, 50),
+            Some(SynchronizedMove {
+                velocity: 100,
+                acceleration: 50,
+            })
+        );
+
+        // v * (20 s - v / 50) = 1000 has the lower solution 52.79
+        assert_eq!(
+            synchronized_move(1000, Duration::from_secs(20), 100, 50),
+            Some(SynchronizedMove {
+                velocity: 53,
+                acceleration: 50,
+            })
+        );
+
+        // Every axis moving for the same duration arrives together, within the rounding of the
+        // velocity to whole increments per second
+        let duration = Duration::from_secs(2);
+        for distance in [1_000, 30_000, 99_999, 150_000] {
+            let profile = synchronized_move(distance, duration, 100_000, 200_000).unwrap();
+            let trajectory = TrapezoidalProfile::new(
+                0,
+                i32::try_from(distance).unwrap(),
+                f64::from(profile.velocity),
+                f64::from(profile.acceleration),
+            )
+            .unwrap();
+            let difference = trajectory.duration().abs_diff(duration);
+            assert!(
+                difference < Duration::from_millis(100),
+                "{distance}: {difference:?}"
+            );
+        }
+    }
+
+    /// Synchronized moves can't be faster than the limits allow
+    #[test]
+    fn synchronized_move_infeasible() {
+        assert_eq!(
+            synchronized_move(1000, Duration::from_secs(11), 100, 50),
+            None
+        );
+        assert_eq!(
+            synchronized_move(1000, Duration::from_secs(20), 0, 50),
+            None
+        );
+        assert_eq!(
+            synchronized_move(1000, Duration::from_secs(20), 100, 0),
+            None
+        );
+    }
+
+    /// Points of a line are on the line and clamped to its ends
+    #[test]
+    fn linear_path_points() {
+        let path = LinearPath::new(&[0, 100, -50], &[300, 100, 350], 1000.0, 1000.0).unwrap();
+        assert!((path.length() - 500.0).abs() < EPSILON);
+        assert_eq!(path.point(0.0), vec![0.0, 100.0, -50.0]);
+        assert_eq!(path.point(250.0), vec![150.0, 100.0, 150.0]);
+        assert_eq!(path.point(500.0), vec![300.0, 100.0, 350.0]);
+        assert_eq!(path.point(-10.0), vec![0.0, 100.0, -50.0]);
+        assert_eq!(path.point(1e9), vec![300.0, 100.0, 350.0]);
+
+        // A line without length is at its end right away
+        let path = LinearPath::new(&[5, 6], &[5, 6], 1000.0, 1000.0).unwrap();
+        assert_eq!(path.duration(), Duration::ZERO);
+        assert_eq!(path.point(0.0), vec![5.0, 6.0]);
+        assert!(LinearPath::new(&[0, 0], &[1], 1000.0, 1000.0).is_none());
+    }
+
+    /// Every sample of a line, rounded to increments, stays within rounding of the line
+    #[test]
+    fn linear_path_samples_on_line() {
+        let path =
+            LinearPath::new(&[0, 1000, 7], &[30_000, -9_000, 7], 20_000.0, 50_000.0).unwrap();
+        for sample in path.samples(Duration::from_millis(1)) {
+            #[expect(
+                clippy::cast_possible_truncation,
+                reason = "The positions are between the start and end positions"
+            )]
+            let positions: Vec<_> = path
+                .point(sample.position)
+                .into_iter()
+                .map(|position| position.round() as i32)
+                .collect();
+            let (_, deviation) = path.deviation(&positions).unwrap();
+            assert!(deviation <= 1.0, "{positions:?} deviates {deviation}");
+        }
+    }
+
+    /// The deviation is measured against the progress of the axis with the longest distance
+    #[test]
+    fn linear_path_deviation() {
+        let path = LinearPath::new(&[0, 0], &[1000, 500], 1000.0, 1000.0).unwrap();
+        assert_eq!(
+            path.deviation(&[400, 200]).map(|(_, deviation)| deviation),
+            Some(0.0)
+        );
+        assert_eq!(path.deviation(&[400, 150]), Some((1, 50.0)));
+        assert_eq!(path.deviation(&[400]), None);
+
+        let path = LinearPath::new(&[3, 4], &[3, 4], 1000.0, 1000.0).unwrap();
+        assert_eq!(path.deviation(&[3, 4]), None);
+    }
+
+    /// Stopping decelerates to standstill at the acceleration of the line, without moving back
+    #[test]
+    fn linear_path_stopping() {
+        let path = LinearPath::new(&[0], &[100_000], 1000.0, 500.0).unwrap();
+
+        // Stopping from 1000 increments per second takes 2 seconds and 1000 increments
+        let distances = path.stopping_distances(200.0, 1000.0, Duration::from_millis(100));
+        assert_eq!(distances.len(), 20);
+        assert!((distances[0] - 297.5).abs() < EPSILON);
+        assert!((distances[19] - 1200.0).abs() < EPSILON);
+        assert!(distances.windows(2).all(|pair| pair[0] <= pair[1]));
+        assert!(path
+            .stopping_distances(200.0, 0.0, Duration::from_millis(100))
+            .is_empty());
+        assert!(path
+            .stopping_distances(200.0, 1000.0, Duration::ZERO)
+            .is_empty());
+    }
+
+    /// Samples an S-curve every millisecond and checks the limits, the continuity and the end
+    fn check_s_curve(start: i32, end: i32, velocity: f64, acceleration: f64, jerk: f64) {
+        let profile = SCurveProfile::new(start, end, velocity, acceleration, jerk).unwrap();
+        let period = 0.001;
+        let tolerance = 1e-6;
+        let samples: Vec<_> = profile.samples(Duration::from_millis(1)).collect();
+
+        // The last sample is exactly the end position at standstill
+        assert_eq!(
+            *samples.last().unwrap(),
+            Sample {
+                position: f64::from(end),
+                velocity: 0.0,
+                acceleration: 0.0,
+            }
+        );
+        assert_sample(profile.sample(Duration::ZERO), f64::from(start), 0.0, 0.0);
+
+        for sample in &samples {
+            assert!(
+                sample.velocity.abs() <= velocity * (1.0 + tolerance),
+                "{sample:?}"
+            );
+            assert!(
+                sample.acceleration.abs() <= acceleration * (1.0 + tolerance),
+                "{sample:?}"
+            );
+        }
+
+        // Consecutive samples can only differ by what the limits allow within one period,
+        // including the step to the exact end position
+        for pair in samples.windows(2) {
+            let (previous, next) = (pair[0], pair[1]);
+            assert!(
+                (next.position - previous.position).abs() <= velocity.mul_add(period, tolerance),
+                "{previous:?} -> {next:?}"
+            );
+            assert!(
+                (next.velocity - previous.velocity).abs()
+                    <= acceleration.mul_add(period, tolerance),
+                "{previous:?} -> {next:?}"
+            );
+            assert!(
+                (next.acceleration - previous.acceleration).abs()
+                    <= jerk.mul_add(period, tolerance),
+                "{previous:?} -> {next:?}"
+            );
+        }
+    }
+
+    /// A long move reaches the maximum acceleration and velocity
+    #[test]
+    fn s_curve_long_move() {
+        check_s_curve(0, 100_000, 10_000.0, 50_000.0, 1_000_000.0);
+        check_s_curve(100_000, 0, 10_000.0, 50_000.0, 1_000_000.0);
+        let profile = SCurveProfile::new(0, 100_000, 10_000.0, 50_000.0, 1_000_000.0).unwrap();
+        assert!((profile.peak_velocity() - 10_000.0).abs() < EPSILON);
+    }
+
+    /// A move reaching the maximum acceleration, but not the maximum velocity
+    #[test]
+    fn s_curve_without_cruise() {
+        check_s_curve(0, 1_500, 10_000.0, 50_000.0, 1_000_000.0);
+        let profile = SCurveProfile::new(0, 1_500, 10_000.0, 50_000.0, 1_000_000.0).unwrap();
+        assert!(profile.peak_velocity() < 10_000.0);
+    }
+
+    /// A move too short to reach the maximum acceleration
+    #[test]
+    fn s_curve_short_move() {
+        check_s_curve(0, 10, 10_000.0, 50_000.0, 1_000_000.0);
+        check_s_curve(-3, -20, 10_000.0, 50_000.0, 1_000_000.0);
+        let profile = SCurveProfile::new(0, 10, 10_000.0, 50_000.0, 1_000_000.0).unwrap();
+        let peak_acceleration = profile
+            .samples(Duration::from_micros(100))
+            .map(|sample| sample.acceleration.abs())
+            .fold(0.0, f64::max);
+        assert!(peak_acceleration < 50_000.0);
+    }
+
+    /// A move without distance ends immediately at the start position
+    #[test]
+    fn s_curve_zero_distance() {
+        let profile = SCurveProfile::new(7, 7, 10_000.0, 50_000.0, 1_000_000.0).unwrap();
+        assert_eq!(profile.duration(), Duration::ZERO);
+        assert_sample(profile.sample(Duration::ZERO), 7.0, 0.0, 0.0);
+        check_s_curve(7, 7, 10_000.0, 50_000.0, 1_000_000.0);
+    }
+
+    /// Limits that aren't positive numbers, or too small to finish in a representable time,
+    /// don't create a trajectory
+    #[test]
+    fn s_curve_invalid_limits() {
+        assert!(SCurveProfile::new(0, 100, 100.0, 100.0, 0.0).is_none());
+        assert!(SCurveProfile::new(0, 100, f64::INFINITY, 100.0, 100.0).is_none());
+        assert!(SCurveProfile::new(0, 1_000_000, 1e-300, 1.0, 1.0).is_none());
+    }
+
+    /// Creates a point without dwell time
+    const fn point(position: i32, velocity: u32, mode: MovementMode) -> TrajectoryPoint {
+        TrajectoryPoint {
+            position,
+            velocity,
+            dwell: Duration::ZERO,
+            mode,
+        }
+    }
+
+    /// The limits of the validation tests
+    const LIMITS: TrajectoryLimits = TrajectoryLimits {
+        position: Some(SoftwareLimits {
+            min: -1_000,
+            max: 1_000,
+        }),
+        max_velocity: Some(5_000),
+    };
+
+    /// A trajectory within the limits is valid, a trajectory without points isn't
+    #[test]
+    fn validate_valid_and_empty() {
+        let trajectory = Trajectory::new(vec![
+            point(1_000, 5_000, MovementMode::Absolute),
+            point(-2_000, 1, MovementMode::RelativeToTarget),
+        ]);
+        assert_eq!(trajectory.validate(0, &LIMITS), Ok(()));
+        assert_eq!(
+            Trajectory::default().validate(0, &LIMITS),
+            Err(TrajectoryError::Empty)
+        );
+        assert_eq!(
+            Trajectory::default().validate(0, &TrajectoryLimits::default()),
+            Err(TrajectoryError::Empty)
+        );
+    }
+
+    /// Every point needs a velocity, regardless of the limits
+    #[test]
+    fn validate_zero_velocity() {
+        let trajectory = Trajectory::new(vec![
+            point(100, 1_000, MovementMode::Absolute),
+            point(200, 0, MovementMode::Absolute),
+        ]);
+        assert_eq!(
+            trajectory.validate(0, &TrajectoryLimits::default()),
+            Err(TrajectoryError::ZeroVelocity(1))
+        );
+    }
+
+    /// Relative points are added to the previous point, the first one to the start position
+    #[test]
+    fn validate_out_of_limits() {
+        let trajectory = Trajectory::new(vec![
+            point(600, 1_000, MovementMode::RelativeToActual),
+            point(300, 1_000, MovementMode::RelativeToTarget),
+            point(-600, 1_000, MovementMode::RelativeToTarget),
+        ]);
+        assert_eq!(trajectory.validate(-500, &LIMITS), Ok(()));
+        assert_eq!(
+            trajectory.validate(200, &LIMITS),
+            Err(TrajectoryError::OutOfLimits {
+                point: 1,
+                position: 1_100,
+            })
+        );
+
+        // An absolute point resets the position the following relative points are added to
+        let trajectory = Trajectory::new(vec![
+            point(-900, 1_000, MovementMode::Absolute),
+            point(-200, 1_000, MovementMode::RelativeToTarget),
+        ]);
+        assert_eq!(
+            trajectory.validate(900, &LIMITS),
+            Err(TrajectoryError::OutOfLimits {
+                point: 1,
+                position: -1_100,
+            })
+        );
+        assert_eq!(
+            trajectory.validate(900, &TrajectoryLimits::default()),
+            Ok(())
+        );
+    }
+
+    /// Points faster than the maximum velocity are rejected
+    #[test]
+    fn validate_velocity_too_high() {
+        let trajectory = Trajectory::new(vec![
+            point(100, 5_000, MovementMode::Absolute),
+            point(200, 5_001, MovementMode::Absolute),
+        ]);
+        assert_eq!(
+            trajectory.validate(0, &LIMITS),
+            Err(TrajectoryError::VelocityTooHigh {
+                point: 1,
+                velocity: 5_001,
+                max: 5_000,
+            })
+        );
+    }
+}
+
+#[cfg(all(test, feature = "serde"))]
+mod serde_tests {
+    //! Tests of the serialization of trajectories generated offline
+
+    use super::*;
+
+    /// A trajectory is read back unchanged after being serialized
+    #[test]
+    fn trajectory_round_trip() {
+        let trajectory = Trajectory::new(vec![
+            TrajectoryPoint {
+                position: -12_345,
+                velocity: 5_000,
+                dwell: Duration::from_millis(250),
+                mode: MovementMode::Absolute,
+            },
+            TrajectoryPoint {
+                position: 1_000,
+                velocity: u32::MAX,
+                dwell: Duration::new(3, 7),
+                mode: MovementMode::RelativeToTarget,
+            },
+            TrajectoryPoint {
+                position: i32::MIN,
+                velocity: 1,
+                dwell: Duration::ZERO,
+                mode: MovementMode::RelativeToActual,
+            },
+        ]);
+        let json = serde_json::to_string(&trajectory).unwrap();
+        assert_eq!(
+            serde_json::from_str::<Trajectory>(&json).unwrap(),
+            trajectory
+        );
+        assert_eq!(
+            serde_json::from_str::<Trajectory>(
+                &serde_json::to_string(&Trajectory::default()).unwrap()
+            )
+            .unwrap(),
+            Trajectory::default()
+        );
+    }
+}